@@ -0,0 +1,159 @@
+//! Orderbook aggregator throughput at 1k markets.
+//!
+//! Run with `cargo bench --bench orderbook`.
+
+use std::hint::black_box;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use kalshi_trade_rs::models::Side;
+use kalshi_trade_rs::orderbook::OrderbookAggregator;
+use kalshi_trade_rs::ws::{OrderbookDeltaData, OrderbookSnapshotData, StreamMessage, StreamUpdate};
+
+const MARKETS: usize = 1_000;
+const LEVELS: usize = 20;
+const READER_THREADS: usize = 4;
+
+fn ticker(i: usize) -> String {
+    format!("BENCH-{i:04}")
+}
+
+fn snapshot(ticker: &str) -> StreamUpdate {
+    let levels = |start: usize| -> Vec<(String, String)> {
+        (0..LEVELS)
+            .map(|n| (format!("0.{:02}", start - n), "100.00".to_string()))
+            .collect()
+    };
+    StreamUpdate {
+        channel: "orderbook_snapshot".to_string(),
+        sid: 1,
+        seq: None,
+        msg: StreamMessage::OrderbookSnapshot(OrderbookSnapshotData {
+            market_ticker: ticker.to_string(),
+            market_id: String::new(),
+            yes_dollars_fp: Some(levels(45)),
+            no_dollars_fp: Some(levels(53)),
+        }),
+    }
+}
+
+fn delta(ticker: &str, delta_fp: &str) -> StreamUpdate {
+    StreamUpdate {
+        channel: "orderbook_delta".to_string(),
+        sid: 1,
+        seq: None,
+        msg: StreamMessage::OrderbookDelta(OrderbookDeltaData {
+            market_ticker: ticker.to_string(),
+            market_id: String::new(),
+            price_dollars: "0.45".to_string(),
+            delta_fp: delta_fp.to_string(),
+            side: Side::Yes,
+            client_order_id: None,
+            subaccount: None,
+            ts: None,
+        }),
+    }
+}
+
+fn seeded() -> (OrderbookAggregator, Vec<String>) {
+    let aggregator = OrderbookAggregator::new();
+    let tickers: Vec<String> = (0..MARKETS).map(ticker).collect();
+    for ticker in &tickers {
+        aggregator.apply_update(&snapshot(ticker));
+    }
+    (aggregator, tickers)
+}
+
+/// One delta up and one down per market, so books stay the same size.
+fn delta_round(tickers: &[String]) -> Vec<StreamUpdate> {
+    tickers
+        .iter()
+        .flat_map(|t| [delta(t, "5"), delta(t, "-5")])
+        .collect()
+}
+
+fn bench_deltas(c: &mut Criterion) {
+    let (aggregator, tickers) = seeded();
+    let deltas = delta_round(&tickers);
+
+    let mut group = c.benchmark_group("orderbook_deltas");
+    group.throughput(Throughput::Elements(deltas.len() as u64));
+
+    group.bench_function("1k_markets", |b| {
+        b.iter(|| {
+            for update in &deltas {
+                black_box(aggregator.apply_update(update));
+            }
+        })
+    });
+
+    // Same writer workload while readers poll every market
+    let stop = Arc::new(AtomicBool::new(false));
+    let readers: Vec<_> = (0..READER_THREADS)
+        .map(|_| {
+            let aggregator = aggregator.clone();
+            let tickers = tickers.clone();
+            let stop = stop.clone();
+            thread::spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    for ticker in &tickers {
+                        black_box(aggregator.summary(ticker));
+                        black_box(aggregator.best_bid(ticker));
+                    }
+                }
+            })
+        })
+        .collect();
+
+    group.bench_function("1k_markets_with_readers", |b| {
+        b.iter(|| {
+            for update in &deltas {
+                black_box(aggregator.apply_update(update));
+            }
+        })
+    });
+
+    stop.store(true, Ordering::Relaxed);
+    for reader in readers {
+        reader.join().expect("reader thread panicked");
+    }
+    group.finish();
+}
+
+fn bench_reads(c: &mut Criterion) {
+    let (aggregator, tickers) = seeded();
+
+    let mut group = c.benchmark_group("orderbook_reads");
+    group.throughput(Throughput::Elements(tickers.len() as u64));
+
+    group.bench_function("summary_1k_markets", |b| {
+        b.iter(|| {
+            for ticker in &tickers {
+                black_box(aggregator.summary(ticker));
+            }
+        })
+    });
+
+    group.bench_function("cost_to_buy_1k_markets", |b| {
+        b.iter(|| {
+            for ticker in &tickers {
+                black_box(aggregator.cost_to_buy(ticker, Side::Yes, 500));
+            }
+        })
+    });
+
+    group.bench_function("depth_10_1k_markets", |b| {
+        b.iter(|| {
+            for ticker in &tickers {
+                black_box(aggregator.depth(ticker, 10));
+            }
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_deltas, bench_reads);
+criterion_main!(benches);
@@ -191,18 +191,24 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("  - Chunking orders into batches of 20 (API limit)");
     println!("  - Rate limiting based on your account tier");
     println!("  - Retry logic with exponential backoff");
+    println!("  - Concurrent chunks under one shared write budget");
     println!();
 
     println!("Example: Creating 100 orders with Premier tier:");
     println!("  let manager = BatchManager::builder(&client)");
     println!("      .tier(RateLimitTier::Premier)");
     println!("      .retry_config(RetryConfig::default())");
+    println!("      .concurrency(4)");
     println!("      .build();");
     println!();
     println!("  // Orders automatically chunked into 5 batches of 20");
-    println!("  // Rate limited to 100 writes/sec");
+    println!("  // Up to 4 batches in flight, rate limited to 100 writes/sec");
     println!("  let result = manager.create_orders(orders_100).await;");
     println!();
+    println!("  // Clones share the rate limiter and can move into tasks");
+    println!("  let worker = manager.clone();");
+    println!("  tokio::spawn(async move {{ worker.cancel_orders(ids).await }});");
+    println!();
 
     // 8. Cost Calculation
     println!("=== Operation Costs ===");
@@ -0,0 +1,883 @@
+//! The algo execution loop.
+
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::{Notify, broadcast};
+use tokio::time::MissedTickBehavior;
+use tracing::{debug, warn};
+
+use super::parent::{AlgoEvent, AlgoProgress, AlgoState, AlgoStrategy, ChildOrder, ParentOrder};
+use crate::client::KalshiClient;
+use crate::error::{Error, Result};
+use crate::models::{Action, AmendOrderRequest, CreateOrderRequest, Order, OrderStatus, Side};
+use crate::orderbook::{OrderbookAggregator, OrderbookUpdate};
+use crate::orders::RiskGuard;
+use crate::units::contracts;
+use crate::ws::{FillData, KalshiStreamHandle, StreamMessage, UserOrderData};
+
+/// Default capacity of the event broadcast channel.
+const DEFAULT_EVENT_CAPACITY: usize = 256;
+
+/// How often iceberg and peg algos re-check their children between
+/// stream messages.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Consecutive failed placements after which an algo gives up.
+const MAX_CONSECUTIVE_FAILURES: u32 = 3;
+
+/// Contracts due by the end of slice `slice` (1-based) of a TWAP.
+fn twap_target(quantity: i64, slices: u32, slice: u32) -> i64 {
+    let slices = i64::from(slices.max(1));
+    let slice = i64::from(slice).min(slices);
+    (quantity * slice + slices - 1) / slices
+}
+
+/// Contracts that may still be placed under a participation cap, or `None`
+/// without one.
+fn participation_allowance(
+    fraction: Option<f64>,
+    market_volume: i64,
+    filled: i64,
+    working: i64,
+) -> Option<i64> {
+    let fraction = fraction?.clamp(0.0, 1.0);
+    let allowed = (fraction * market_volume as f64).floor() as i64;
+    Some((allowed - filled - working).max(0))
+}
+
+/// Price `offset` cents behind the touch on the order's side, never
+/// crossing the opposite side.
+///
+/// `yes_bid` and `yes_ask` are YES prices in cents. Returns `None` when the
+/// side being joined is empty.
+fn touch_price(
+    side: Side,
+    action: Action,
+    yes_bid: Option<i64>,
+    yes_ask: Option<i64>,
+    offset: i64,
+) -> Option<i64> {
+    let (bid, ask) = match side {
+        Side::Yes => (yes_bid, yes_ask),
+        Side::No => (yes_ask.map(|p| 100 - p), yes_bid.map(|p| 100 - p)),
+    };
+    let price = match action {
+        Action::Buy => {
+            let price = bid? - offset;
+            ask.map_or(price, |ask| price.min(ask - 1))
+        }
+        Action::Sell => {
+            let price = ask? + offset;
+            bid.map_or(price, |bid| price.max(bid + 1))
+        }
+    };
+    Some(price.clamp(1, 99))
+}
+
+/// `price`, or the limit if `price` is worse than it.
+fn cap_price(action: Action, price: i64, limit: i64) -> i64 {
+    match action {
+        Action::Buy => price.min(limit),
+        Action::Sell => price.max(limit),
+    }
+}
+
+/// A child order with its fills counted from both sources.
+///
+/// Fills arrive on the `fill` channel and as cumulative counts on
+/// `user_orders` and REST responses, in any order; the larger of the two
+/// wins.
+#[derive(Debug)]
+struct Child {
+    order: ChildOrder,
+    streamed: i64,
+    reported: i64,
+}
+
+impl Child {
+    fn from_order(order: &Order, price: i64) -> Self {
+        let reported = contracts(&order.fill_count_fp);
+        let mut child = Self {
+            order: ChildOrder {
+                order_id: order.order_id.clone(),
+                client_order_id: (!order.client_order_id.is_empty())
+                    .then(|| order.client_order_id.clone()),
+                price,
+                count: contracts(&order.initial_count_fp),
+                filled: 0,
+                open: order.status == OrderStatus::Resting,
+            },
+            streamed: 0,
+            reported,
+        };
+        child.merge_fills();
+        child
+    }
+
+    fn merge_fills(&mut self) {
+        self.order.filled = self.streamed.max(self.reported);
+        if self.order.filled >= self.order.count {
+            self.order.open = false;
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Core {
+    state: AlgoState,
+    children: Vec<Child>,
+    seen_trades: HashSet<String>,
+    market_volume: i64,
+    failures: u32,
+}
+
+impl Core {
+    fn filled(&self) -> i64 {
+        self.children.iter().map(|c| c.order.filled).sum()
+    }
+
+    fn working(&self) -> i64 {
+        self.children.iter().map(|c| c.order.working()).sum()
+    }
+
+    fn child_mut(&mut self, order_id: &str) -> Option<&mut Child> {
+        self.children
+            .iter_mut()
+            .find(|c| c.order.order_id == order_id)
+    }
+
+    fn open_children(&self) -> Vec<ChildOrder> {
+        self.children
+            .iter()
+            .filter(|c| c.order.open)
+            .map(|c| c.order.clone())
+            .collect()
+    }
+
+    fn progress(&self, quantity: i64) -> AlgoProgress {
+        let filled = self.filled();
+        AlgoProgress {
+            state: self.state.clone(),
+            filled,
+            remaining: (quantity - filled).max(0),
+            working: self.working(),
+            market_volume: self.market_volume,
+            children: self.children.iter().map(|c| c.order.clone()).collect(),
+        }
+    }
+
+    /// Count a fill, returning the event to publish.
+    fn apply_fill(&mut self, fill: &FillData) -> Option<AlgoEvent> {
+        let child = self
+            .children
+            .iter_mut()
+            .find(|c| c.order.order_id == fill.order_id)?;
+        if !self.seen_trades.insert(fill.trade_id.clone()) {
+            return None;
+        }
+        let count = contracts(&fill.count_fp);
+        child.streamed += count;
+        child.merge_fills();
+        Some(AlgoEvent::Fill {
+            order_id: fill.order_id.clone(),
+            count,
+        })
+    }
+
+    /// Merge an order update, returning the child if it left the book.
+    fn apply_user_order(&mut self, data: &UserOrderData) -> Option<ChildOrder> {
+        let child = self.child_mut(&data.order_id)?;
+        let was_open = child.order.open;
+        child.reported = child.reported.max(contracts(&data.fill_count_fp));
+        // A late update must not reopen a child already seen closed
+        child.order.open &= data.status == OrderStatus::Resting;
+        child.merge_fills();
+        (was_open && !child.order.open).then(|| child.order.clone())
+    }
+}
+
+/// Works a [`ParentOrder`] with an [`AlgoStrategy`].
+///
+/// Build one with [`new`](Self::new), optionally attach an orderbook with
+/// [`with_orderbook`](Self::with_orderbook) and risk checks with
+/// [`with_risk_guard`](Self::with_risk_guard), then drive it with
+/// [`run`](Self::run). Progress can be read at any time from
+/// [`progress`](Self::progress) or followed on
+/// [`event_receiver`](Self::event_receiver).
+///
+/// Children are placed as good-till-canceled limit orders and are canceled
+/// when the algo finishes without completing, unless the parent opted out
+/// with [`cancel_on_stop`](ParentOrder::cancel_on_stop).
+///
+/// Clones share state, so a clone can [`stop`](Self::stop) a running algo.
+#[derive(Clone)]
+pub struct AlgoOrder {
+    client: KalshiClient,
+    parent: ParentOrder,
+    strategy: AlgoStrategy,
+    orderbook: Option<OrderbookAggregator>,
+    risk: Option<RiskGuard>,
+    core: Arc<Mutex<Core>>,
+    event_sender: broadcast::Sender<AlgoEvent>,
+    stop: Arc<Notify>,
+}
+
+impl AlgoOrder {
+    /// Create an algo for `parent`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidQuantity`] if the parent quantity, TWAP slice
+    /// count or iceberg display size is not positive, and
+    /// [`Error::InvalidPrice`] if the limit price is outside 1-99.
+    pub fn new(client: KalshiClient, parent: ParentOrder, strategy: AlgoStrategy) -> Result<Self> {
+        if parent.quantity <= 0 {
+            return Err(Error::InvalidQuantity(parent.quantity));
+        }
+        if !(1..=99).contains(&parent.limit_price) {
+            return Err(Error::InvalidPrice(parent.limit_price));
+        }
+        match strategy {
+            AlgoStrategy::Twap { slices: 0, .. } => return Err(Error::InvalidQuantity(0)),
+            AlgoStrategy::Iceberg { display } if display <= 0 => {
+                return Err(Error::InvalidQuantity(display));
+            }
+            _ => {}
+        }
+
+        let (event_sender, _) = broadcast::channel(DEFAULT_EVENT_CAPACITY);
+        Ok(Self {
+            client,
+            parent,
+            strategy,
+            orderbook: None,
+            risk: None,
+            core: Arc::new(Mutex::new(Core {
+                state: AlgoState::Pending,
+                children: Vec::new(),
+                seen_trades: HashSet::new(),
+                market_volume: 0,
+                failures: 0,
+            })),
+            event_sender,
+            stop: Arc::new(Notify::new()),
+        })
+    }
+
+    /// Price children off a live orderbook.
+    ///
+    /// Required for [`AlgoStrategy::Peg`]. TWAP children join the touch
+    /// instead of resting at the limit. The book should be tracking the
+    /// parent's market, ideally with own orders registered so the algo does
+    /// not peg to itself.
+    #[must_use]
+    pub fn with_orderbook(mut self, orderbook: OrderbookAggregator) -> Self {
+        self.orderbook = Some(orderbook);
+        self
+    }
+
+    /// Check children against `guard` before they are placed or amended.
+    ///
+    /// A child the guard rejects counts as a failed placement. Feed the
+    /// guard the same `fill` and `user_orders` updates as the algo so it
+    /// sees the children's positions.
+    #[must_use]
+    pub fn with_risk_guard(mut self, guard: RiskGuard) -> Self {
+        self.risk = Some(guard);
+        self
+    }
+
+    /// The parent order being worked.
+    pub fn parent(&self) -> &ParentOrder {
+        &self.parent
+    }
+
+    /// Current execution progress.
+    pub fn progress(&self) -> AlgoProgress {
+        self.lock().progress(self.parent.quantity)
+    }
+
+    /// Get a receiver for algo events.
+    pub fn event_receiver(&self) -> broadcast::Receiver<AlgoEvent> {
+        self.event_sender.subscribe()
+    }
+
+    /// Stop the algo.
+    ///
+    /// [`run`](Self::run) cancels resting children if the parent asks for it
+    /// and returns with [`AlgoState::Stopped`]. Stopping before `run` starts
+    /// makes it return immediately.
+    pub fn stop(&self) {
+        {
+            let mut core = self.lock();
+            if core.state.is_finished() {
+                return;
+            }
+            core.state = AlgoState::Stopped;
+        }
+        self.stop.notify_one();
+    }
+
+    // =========================================================================
+    // Execution
+    // =========================================================================
+
+    /// Work the parent until it completes, expires, fails or is stopped.
+    ///
+    /// Subscribe the handle to [`Channel::Fill`] and
+    /// [`Channel::UserOrders`] to follow children, and to
+    /// [`Channel::Trade`] for the parent's market if it has a participation
+    /// cap. A stream that closes or loses its connection fails the algo.
+    ///
+    /// [`Channel::Fill`]: crate::ws::Channel::Fill
+    /// [`Channel::UserOrders`]: crate::ws::Channel::UserOrders
+    /// [`Channel::Trade`]: crate::ws::Channel::Trade
+    pub async fn run(&self, mut handle: KalshiStreamHandle) -> AlgoProgress {
+        {
+            let mut core = self.lock();
+            if core.state == AlgoState::Pending {
+                core.state = AlgoState::Running;
+            }
+        }
+        if matches!(self.strategy, AlgoStrategy::Peg { .. }) && self.orderbook.is_none() {
+            self.fail("peg needs an orderbook".to_string());
+        }
+
+        let (period, mut slice) = match self.strategy {
+            AlgoStrategy::Twap { duration, slices } => {
+                let period = duration / slices;
+                (period.max(Duration::from_millis(1)), 0)
+            }
+            _ => (REFRESH_INTERVAL, 0),
+        };
+        let mut tick = tokio::time::interval(period);
+        tick.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut book = self.orderbook.as_ref().map(|b| b.update_receiver());
+
+        while !self.is_finished() {
+            tokio::select! {
+                _ = self.stop.notified() => {}
+                received = handle.update_receiver.recv() => match received {
+                    Ok(update) => self.apply_message(&update.msg).await,
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        debug!(missed = n, "algo updates lagged");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => {
+                        self.fail("stream closed".to_string());
+                    }
+                },
+                _ = book_changed(&mut book, &self.parent.ticker) => {
+                    if matches!(self.strategy, AlgoStrategy::Peg { .. }) {
+                        self.work().await;
+                    }
+                }
+                _ = tick.tick() => match self.strategy {
+                    AlgoStrategy::Twap { slices, .. } => {
+                        slice += 1;
+                        self.next_slice(slices, slice).await;
+                    }
+                    _ => self.work().await,
+                },
+            }
+            self.check_complete();
+        }
+
+        if self.parent.cancel_on_stop {
+            self.cancel_open().await;
+        }
+        let progress = self.progress();
+        self.publish(AlgoEvent::Finished(progress.clone()));
+        progress
+    }
+
+    async fn apply_message(&self, msg: &StreamMessage) {
+        match msg {
+            StreamMessage::Fill(fill) => {
+                let event = self.lock().apply_fill(fill);
+                if let Some(event) = event {
+                    self.publish(event);
+                    self.refill().await;
+                }
+            }
+            StreamMessage::UserOrder(data) => {
+                let closed = self.lock().apply_user_order(data);
+                if let Some(child) = closed {
+                    self.publish(AlgoEvent::ChildClosed(child));
+                    self.refill().await;
+                }
+            }
+            StreamMessage::Trade(trade) if trade.market_ticker == self.parent.ticker => {
+                self.lock().market_volume += contracts(&trade.count_fp);
+            }
+            StreamMessage::Closed { reason } | StreamMessage::ConnectionLost { reason, .. } => {
+                self.fail(format!("stream disconnected: {reason}"));
+            }
+            _ => {}
+        }
+    }
+
+    /// Replace filled children right away instead of waiting for a tick.
+    async fn refill(&self) {
+        self.check_complete();
+        if !matches!(self.strategy, AlgoStrategy::Twap { .. }) && !self.is_finished() {
+            self.work().await;
+        }
+    }
+
+    /// Start TWAP slice `slice`, rolling what is left of the last one.
+    async fn next_slice(&self, slices: u32, slice: u32) {
+        if slice > slices {
+            let mut core = self.lock();
+            if core.state == AlgoState::Running {
+                core.state = AlgoState::Expired;
+            }
+            return;
+        }
+        self.cancel_open().await;
+        let due = twap_target(self.parent.quantity, slices, slice) - self.lock().filled();
+        let price = self.touch().unwrap_or(self.parent.limit_price);
+        self.place(due, price).await;
+    }
+
+    /// Top up an iceberg or reprice a peg.
+    async fn work(&self) {
+        if self.is_finished() {
+            return;
+        }
+        match self.strategy {
+            AlgoStrategy::Twap { .. } => {}
+            AlgoStrategy::Iceberg { display } => {
+                let (working, remaining) = {
+                    let core = self.lock();
+                    (core.working(), self.parent.quantity - core.filled())
+                };
+                if working == 0 {
+                    self.place(display.min(remaining), self.parent.limit_price)
+                        .await;
+                }
+            }
+            AlgoStrategy::Peg { .. } => {
+                let Some(price) = self.touch() else {
+                    return;
+                };
+                let (open, remaining) = {
+                    let core = self.lock();
+                    (
+                        core.open_children(),
+                        self.parent.quantity - core.filled() - core.working(),
+                    )
+                };
+                match open.first() {
+                    Some(child) if child.price != price => self.amend(child, price).await,
+                    Some(_) => {}
+                    None => self.place(remaining, price).await,
+                }
+            }
+        }
+    }
+
+    /// Price at the touch for the current strategy, capped by the limit.
+    fn touch(&self) -> Option<i64> {
+        let book = self.orderbook.as_ref()?;
+        let offset = match self.strategy {
+            AlgoStrategy::Peg { offset } => offset,
+            _ => 0,
+        };
+        let ticker = &self.parent.ticker;
+        let price = touch_price(
+            self.parent.side,
+            self.parent.action,
+            book.best_external_bid(ticker).map(|(p, _)| p),
+            book.best_external_ask(ticker).map(|(p, _)| p),
+            offset,
+        )?;
+        Some(cap_price(
+            self.parent.action,
+            price,
+            self.parent.limit_price,
+        ))
+    }
+
+    /// Place a child for up to `count` contracts, within the participation
+    /// cap.
+    async fn place(&self, count: i64, price: i64) {
+        let count = {
+            let core = self.lock();
+            let remaining = self.parent.quantity - core.filled() - core.working();
+            let allowance = participation_allowance(
+                self.parent.max_participation,
+                core.market_volume,
+                core.filled(),
+                core.working(),
+            );
+            count.min(remaining).min(allowance.unwrap_or(i64::MAX))
+        };
+        if count <= 0 {
+            return;
+        }
+
+        let mut request = CreateOrderRequest::new(
+            &self.parent.ticker,
+            self.parent.side,
+            self.parent.action,
+            count,
+        );
+        request = match self.parent.side {
+            Side::Yes => request.yes_price(price),
+            Side::No => request.no_price(price),
+        };
+        if let Some(subaccount) = self.parent.subaccount {
+            request = request.subaccount(subaccount);
+        }
+
+        let created = match &self.risk {
+            Some(guard) => guard.create_order(request).await,
+            None => self.client.create_order(request).await,
+        };
+        match created {
+            Ok(response) => {
+                let child = Child::from_order(&response.order, price);
+                let placed = child.order.clone();
+                {
+                    let mut core = self.lock();
+                    core.failures = 0;
+                    core.children.push(child);
+                }
+                debug!(order_id = %placed.order_id, count, price, "algo child placed");
+                self.publish(AlgoEvent::ChildPlaced(placed));
+            }
+            Err(e) => {
+                warn!(ticker = %self.parent.ticker, error = %e, "algo child placement failed");
+                let failures = {
+                    let mut core = self.lock();
+                    core.failures += 1;
+                    core.failures
+                };
+                self.publish(AlgoEvent::ChildRejected {
+                    error: e.to_string(),
+                });
+                if failures >= MAX_CONSECUTIVE_FAILURES {
+                    self.fail(format!("{failures} placements failed, last: {e}"));
+                }
+            }
+        }
+    }
+
+    async fn amend(&self, child: &ChildOrder, price: i64) {
+        let mut request =
+            AmendOrderRequest::new(&self.parent.ticker, self.parent.side, self.parent.action);
+        request = match self.parent.side {
+            Side::Yes => request.yes_price(price),
+            Side::No => request.no_price(price),
+        };
+        if let Some(subaccount) = self.parent.subaccount {
+            request = request.subaccount(subaccount);
+        }
+
+        let amended = match &self.risk {
+            Some(guard) => guard.amend_order(&child.order_id, request).await,
+            None => self.client.amend_order(&child.order_id, request).await,
+        };
+        match amended {
+            Ok(response) => {
+                let amended = {
+                    let mut core = self.lock();
+                    let Some(tracked) = core.child_mut(&child.order_id) else {
+                        return;
+                    };
+                    tracked.order.order_id = response.order.order_id.clone();
+                    tracked.order.price = price;
+                    tracked.order.open = response.order.status == OrderStatus::Resting;
+                    tracked.reported = tracked
+                        .reported
+                        .max(contracts(&response.order.fill_count_fp));
+                    tracked.merge_fills();
+                    tracked.order.clone()
+                };
+                self.publish(AlgoEvent::ChildAmended(amended));
+            }
+            Err(e) => {
+                // The child may have filled or been canceled meanwhile;
+                // user_orders will say which, and the next tick retries.
+                warn!(order_id = %child.order_id, error = %e, "algo child amend failed");
+                self.publish(AlgoEvent::ChildRejected {
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+
+    async fn cancel_open(&self) {
+        let open = self.lock().open_children();
+        for child in open {
+            let canceled = match self.parent.subaccount {
+                Some(subaccount) if subaccount != 0 => {
+                    self.client
+                        .cancel_order_for_subaccount(&child.order_id, subaccount)
+                        .await
+                }
+                _ => self.client.cancel_order(&child.order_id).await,
+            };
+            match canceled {
+                Ok(response) => {
+                    let closed = {
+                        let mut core = self.lock();
+                        core.child_mut(&child.order_id).map(|tracked| {
+                            tracked.order.open = false;
+                            tracked.reported = tracked
+                                .reported
+                                .max(contracts(&response.order.fill_count_fp));
+                            tracked.merge_fills();
+                            tracked.order.clone()
+                        })
+                    };
+                    if let Some(closed) = closed {
+                        self.publish(AlgoEvent::ChildClosed(closed));
+                    }
+                }
+                Err(e) => {
+                    warn!(order_id = %child.order_id, error = %e, "algo child cancel failed");
+                }
+            }
+        }
+    }
+
+    fn check_complete(&self) {
+        let mut core = self.lock();
+        if !core.state.is_finished() && core.filled() >= self.parent.quantity {
+            core.state = AlgoState::Completed;
+        }
+    }
+
+    fn fail(&self, reason: String) {
+        let mut core = self.lock();
+        if !core.state.is_finished() {
+            warn!(ticker = %self.parent.ticker, %reason, "algo failed");
+            core.state = AlgoState::Failed(reason);
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        self.lock().state.is_finished()
+    }
+
+    fn publish(&self, event: AlgoEvent) {
+        // Ignore send errors - no receivers is fine
+        let _ = self.event_sender.send(event);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Core> {
+        self.core.lock().expect("algo lock poisoned")
+    }
+}
+
+impl std::fmt::Debug for AlgoOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AlgoOrder")
+            .field("parent", &self.parent)
+            .field("strategy", &self.strategy)
+            .field("state", &self.lock().state)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Wait for a book update on `ticker`, or forever without a book.
+///
+/// A lagged receiver counts as an update, since the book may have moved.
+async fn book_changed(receiver: &mut Option<broadcast::Receiver<OrderbookUpdate>>, ticker: &str) {
+    if let Some(rx) = receiver.as_mut() {
+        loop {
+            match rx.recv().await {
+                Ok(update) if update.ticker == ticker => return,
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(_)) => return,
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+        *receiver = None;
+    }
+    std::future::pending().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    use crate::client::mock_http::MockHttpServer;
+    use crate::orders::RiskLimits;
+    use crate::test_fixtures as fixtures;
+
+    fn user_order(order_id: &str, status: &str, filled: &str) -> UserOrderData {
+        fixtures::user_order(json!({
+            "order_id": order_id,
+            "status": status,
+            "fill_count_fp": filled,
+        }))
+    }
+
+    fn fill(trade_id: &str, order_id: &str, count: &str) -> FillData {
+        fixtures::fill(json!({
+            "trade_id": trade_id,
+            "order_id": order_id,
+            "count_fp": count,
+            "post_position_fp": count,
+        }))
+    }
+
+    fn core_with_child(order_id: &str, count: i64) -> Core {
+        Core {
+            state: AlgoState::Running,
+            children: vec![Child {
+                order: ChildOrder {
+                    order_id: order_id.to_string(),
+                    client_order_id: None,
+                    price: 45,
+                    count,
+                    filled: 0,
+                    open: true,
+                },
+                streamed: 0,
+                reported: 0,
+            }],
+            seen_trades: HashSet::new(),
+            market_volume: 0,
+            failures: 0,
+        }
+    }
+
+    #[test]
+    fn test_twap_target_spreads_remainder() {
+        let due: Vec<i64> = (1..=3).map(|k| twap_target(10, 3, k)).collect();
+        assert_eq!(due, vec![4, 7, 10]);
+        assert_eq!(twap_target(10, 3, 5), 10);
+        assert_eq!(twap_target(1, 4, 1), 1);
+    }
+
+    #[test]
+    fn test_participation_allowance() {
+        assert_eq!(participation_allowance(None, 0, 0, 0), None);
+        assert_eq!(participation_allowance(Some(0.1), 255, 10, 5), Some(10));
+        assert_eq!(participation_allowance(Some(0.1), 50, 10, 5), Some(0));
+    }
+
+    #[test]
+    fn test_touch_price_yes_side() {
+        // Join the bid without crossing the ask
+        assert_eq!(
+            touch_price(Side::Yes, Action::Buy, Some(40), Some(45), 0),
+            Some(40)
+        );
+        assert_eq!(
+            touch_price(Side::Yes, Action::Buy, Some(40), Some(45), 2),
+            Some(38)
+        );
+        assert_eq!(
+            touch_price(Side::Yes, Action::Sell, Some(40), Some(45), 1),
+            Some(46)
+        );
+        assert_eq!(
+            touch_price(Side::Yes, Action::Sell, Some(40), Some(41), -5),
+            Some(41)
+        );
+        assert_eq!(touch_price(Side::Yes, Action::Buy, None, Some(45), 0), None);
+    }
+
+    #[test]
+    fn test_touch_price_no_side() {
+        // YES 40/45 is NO 55/60
+        assert_eq!(
+            touch_price(Side::No, Action::Buy, Some(40), Some(45), 0),
+            Some(55)
+        );
+        assert_eq!(
+            touch_price(Side::No, Action::Sell, Some(40), Some(45), 0),
+            Some(60)
+        );
+        assert_eq!(touch_price(Side::No, Action::Buy, Some(40), None, 0), None);
+    }
+
+    #[test]
+    fn test_cap_price() {
+        assert_eq!(cap_price(Action::Buy, 50, 45), 45);
+        assert_eq!(cap_price(Action::Buy, 40, 45), 40);
+        assert_eq!(cap_price(Action::Sell, 40, 45), 45);
+        assert_eq!(cap_price(Action::Sell, 50, 45), 50);
+    }
+
+    #[test]
+    fn test_fills_merge_stream_and_reported() {
+        let mut core = core_with_child("o1", 10);
+
+        assert!(core.apply_fill(&fill("t1", "o1", "3.00")).is_some());
+        // Duplicate and foreign fills are ignored
+        assert!(core.apply_fill(&fill("t1", "o1", "3.00")).is_none());
+        assert!(core.apply_fill(&fill("t2", "other", "3.00")).is_none());
+        assert_eq!(core.filled(), 3);
+        assert_eq!(core.working(), 7);
+
+        // A later cumulative count covers fills the stream missed
+        assert!(
+            core.apply_user_order(&user_order("o1", "resting", "5.00"))
+                .is_none()
+        );
+        assert_eq!(core.filled(), 5);
+
+        // A stale count does not roll back streamed fills
+        core.apply_fill(&fill("t3", "o1", "2.00"));
+        core.apply_user_order(&user_order("o1", "resting", "5.00"));
+        assert_eq!(core.filled(), 5);
+
+        let closed = core
+            .apply_user_order(&user_order("o1", "canceled", "6.00"))
+            .unwrap();
+        assert!(!closed.open);
+        assert_eq!(closed.filled, 6);
+        assert_eq!(core.working(), 0);
+    }
+
+    #[test]
+    fn test_full_fill_closes_child() {
+        let mut core = core_with_child("o1", 4);
+        core.apply_fill(&fill("t1", "o1", "4.00"));
+        assert_eq!(core.filled(), 4);
+        assert!(core.open_children().is_empty());
+
+        let progress = core.progress(10);
+        assert_eq!(progress.remaining, 6);
+        assert_eq!(progress.working, 0);
+    }
+
+    #[tokio::test]
+    async fn test_children_checked_by_risk_guard() {
+        let server = MockHttpServer::start().await;
+        server.respond(
+            "POST",
+            "/portfolio/orders",
+            201,
+            json!({ "order": fixtures::order(json!({ "initial_count_fp": "2.00" })) }),
+        );
+        let limits = RiskLimits {
+            max_order_size: Some(2),
+            ..RiskLimits::default()
+        };
+        let parent = ParentOrder::new("TEST", Side::Yes, Action::Buy, 10).limit_price(45);
+        let algo = AlgoOrder::new(server.client(), parent, AlgoStrategy::iceberg(5))
+            .unwrap()
+            .with_risk_guard(RiskGuard::new(server.client(), limits));
+        let mut events = algo.event_receiver();
+
+        algo.place(5, 45).await;
+        assert!(matches!(
+            events.try_recv(),
+            Ok(AlgoEvent::ChildRejected { .. })
+        ));
+        assert!(server.requests_to("POST", "/portfolio/orders").is_empty());
+
+        algo.place(2, 45).await;
+        assert!(matches!(events.try_recv(), Ok(AlgoEvent::ChildPlaced(_))));
+        assert_eq!(server.requests_to("POST", "/portfolio/orders").len(), 1);
+    }
+}
@@ -0,0 +1,63 @@
+//! Client-side execution algorithms.
+//!
+//! Kalshi only accepts plain limit orders, so working a large order over
+//! time is done here: an [`AlgoOrder`] runs a [`ParentOrder`] by placing,
+//! repricing and canceling child limit orders through a
+//! [`KalshiClient`](crate::KalshiClient).
+//!
+//! Three strategies are available:
+//!
+//! - [`AlgoStrategy::Twap`] spreads the quantity evenly over a time window,
+//!   rolling any unfilled part of a slice into the next one.
+//! - [`AlgoStrategy::Iceberg`] shows a fixed display size and refills it
+//!   each time it fills.
+//! - [`AlgoStrategy::Peg`] keeps one order at the best bid or ask on its
+//!   side, amending it as the book moves. It needs an
+//!   [`OrderbookAggregator`](crate::orderbook::OrderbookAggregator).
+//!
+//! Every parent has a limit price that children never cross, an optional
+//! cap on its share of market volume, and a choice of whether stopping
+//! cancels resting children. Fills and market volume come from the
+//! `fill`, `user_orders` and `trade` WebSocket channels.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use kalshi_trade_rs::{
+//!     Action, KalshiClient, KalshiConfig, Side,
+//!     algo::{AlgoOrder, AlgoStrategy, ParentOrder},
+//!     ws::{Channel, KalshiStreamClient},
+//! };
+//!
+//! # async fn example() -> kalshi_trade_rs::Result<()> {
+//! let config = KalshiConfig::from_env()?;
+//! let client = KalshiClient::new(config.clone())?;
+//!
+//! let stream = KalshiStreamClient::connect(&config).await?;
+//! let mut handle = stream.handle();
+//! handle.subscribe(Channel::Fill, &[]).await?;
+//! handle.subscribe(Channel::UserOrders, &[]).await?;
+//! handle.subscribe(Channel::Trade, &["KXBTC-25JAN"]).await?;
+//!
+//! let parent = ParentOrder::new("KXBTC-25JAN", Side::Yes, Action::Buy, 500)
+//!     .limit_price(45)
+//!     .max_participation(0.1);
+//! let twap = AlgoOrder::new(
+//!     client,
+//!     parent,
+//!     AlgoStrategy::twap(Duration::from_secs(600), 20),
+//! )?;
+//!
+//! let progress = twap.run(handle).await;
+//! println!("{:?}: filled {} of 500", progress.state, progress.filled);
+//! # Ok(())
+//! # }
+//! ```
+
+mod engine;
+mod parent;
+
+pub use engine::AlgoOrder;
+pub use parent::{AlgoEvent, AlgoProgress, AlgoState, AlgoStrategy, ChildOrder, ParentOrder};
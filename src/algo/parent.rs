@@ -0,0 +1,229 @@
+//! Parent order specifications and execution progress.
+
+use std::time::Duration;
+
+use crate::models::{Action, Side};
+
+/// An order to be worked by an [`AlgoOrder`](super::AlgoOrder).
+///
+/// Prices are in cents on the order's own side: a NO order's limit is a NO
+/// price.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParentOrder {
+    pub(super) ticker: String,
+    pub(super) side: Side,
+    pub(super) action: Action,
+    pub(super) quantity: i64,
+    pub(super) limit_price: i64,
+    pub(super) max_participation: Option<f64>,
+    pub(super) cancel_on_stop: bool,
+    pub(super) subaccount: Option<i32>,
+}
+
+impl ParentOrder {
+    /// Work `quantity` contracts at prices no worse than `limit_price`.
+    ///
+    /// The limit defaults to the most aggressive price (99 to buy, 1 to
+    /// sell); set it with [`limit_price`](Self::limit_price).
+    #[must_use]
+    pub fn new(ticker: impl Into<String>, side: Side, action: Action, quantity: i64) -> Self {
+        Self {
+            ticker: ticker.into(),
+            side,
+            action,
+            quantity,
+            limit_price: match action {
+                Action::Buy => 99,
+                Action::Sell => 1,
+            },
+            max_participation: None,
+            cancel_on_stop: true,
+            subaccount: None,
+        }
+    }
+
+    /// Never buy above or sell below this price, in cents.
+    #[must_use]
+    pub fn limit_price(mut self, price: i64) -> Self {
+        self.limit_price = price;
+        self
+    }
+
+    /// Cap fills at this fraction of the market volume traded since the
+    /// algo started, between 0 and 1.
+    ///
+    /// Volume is counted from the `trade` channel, so nothing is placed
+    /// until the market trades. Default: no cap.
+    #[must_use]
+    pub fn max_participation(mut self, fraction: f64) -> Self {
+        self.max_participation = Some(fraction);
+        self
+    }
+
+    /// Whether stopping the algo cancels its resting children.
+    ///
+    /// Default: `true`.
+    #[must_use]
+    pub fn cancel_on_stop(mut self, cancel: bool) -> Self {
+        self.cancel_on_stop = cancel;
+        self
+    }
+
+    /// Place children in this subaccount.
+    #[must_use]
+    pub fn subaccount(mut self, subaccount: i32) -> Self {
+        self.subaccount = Some(subaccount);
+        self
+    }
+
+    /// Market ticker.
+    pub fn ticker(&self) -> &str {
+        &self.ticker
+    }
+
+    /// Total contracts to fill.
+    pub fn quantity(&self) -> i64 {
+        self.quantity
+    }
+}
+
+/// How an [`AlgoOrder`](super::AlgoOrder) works its parent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlgoStrategy {
+    /// Fill evenly over `duration`, in `slices` equal steps.
+    ///
+    /// At the start of each slice the unfilled child of the previous slice
+    /// is canceled and one child is placed for everything due by the end
+    /// of the new slice. Children join the touch when an orderbook is
+    /// attached and rest at the limit otherwise.
+    Twap {
+        /// Length of the window.
+        duration: Duration,
+        /// Number of slices.
+        slices: u32,
+    },
+    /// Show at most `display` contracts at the limit, refilling as they
+    /// fill.
+    Iceberg {
+        /// Contracts visible at a time.
+        display: i64,
+    },
+    /// Rest at the best bid (buys) or ask (sells) on the order's side,
+    /// `offset` cents less aggressive, amending as the touch moves.
+    Peg {
+        /// Cents behind the touch; 0 joins it.
+        offset: i64,
+    },
+}
+
+impl AlgoStrategy {
+    /// A TWAP over `duration` in `slices` steps.
+    pub fn twap(duration: Duration, slices: u32) -> Self {
+        Self::Twap { duration, slices }
+    }
+
+    /// An iceberg showing `display` contracts.
+    pub fn iceberg(display: i64) -> Self {
+        Self::Iceberg { display }
+    }
+
+    /// A peg `offset` cents behind the touch.
+    pub fn peg(offset: i64) -> Self {
+        Self::Peg { offset }
+    }
+}
+
+/// Lifecycle of an [`AlgoOrder`](super::AlgoOrder).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlgoState {
+    /// Not started yet.
+    Pending,
+    /// Working the parent.
+    Running,
+    /// The full quantity filled.
+    Completed,
+    /// A TWAP window ended before the quantity filled.
+    Expired,
+    /// [`stop`](super::AlgoOrder::stop) was called.
+    Stopped,
+    /// The algo gave up, for example because the stream disconnected or
+    /// child orders kept being rejected.
+    Failed(String),
+}
+
+impl AlgoState {
+    /// Whether the algo has finished.
+    pub fn is_finished(&self) -> bool {
+        !matches!(self, Self::Pending | Self::Running)
+    }
+}
+
+/// A child order placed by an algo.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChildOrder {
+    /// Order ID.
+    pub order_id: String,
+    /// Client order ID, if the client stamps them.
+    pub client_order_id: Option<String>,
+    /// Limit price in cents on the parent's side.
+    pub price: i64,
+    /// Contracts ordered.
+    pub count: i64,
+    /// Contracts filled.
+    pub filled: i64,
+    /// Whether the order may still be resting.
+    pub open: bool,
+}
+
+impl ChildOrder {
+    /// Contracts still resting.
+    pub fn working(&self) -> i64 {
+        if self.open {
+            (self.count - self.filled).max(0)
+        } else {
+            0
+        }
+    }
+}
+
+/// Snapshot of an algo's execution.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlgoProgress {
+    /// Current state.
+    pub state: AlgoState,
+    /// Contracts filled across all children.
+    pub filled: i64,
+    /// Contracts not yet filled.
+    pub remaining: i64,
+    /// Contracts resting in open children.
+    pub working: i64,
+    /// Market volume counted toward the participation cap.
+    pub market_volume: i64,
+    /// Every child placed, oldest first.
+    pub children: Vec<ChildOrder>,
+}
+
+/// Events published by an [`AlgoOrder`](super::AlgoOrder).
+#[derive(Debug, Clone)]
+pub enum AlgoEvent {
+    /// A child order was placed.
+    ChildPlaced(ChildOrder),
+    /// A child order was moved to a new price.
+    ChildAmended(ChildOrder),
+    /// A child order was canceled or otherwise left the book.
+    ChildClosed(ChildOrder),
+    /// A child order filled.
+    Fill {
+        /// Child order ID.
+        order_id: String,
+        /// Contracts filled.
+        count: i64,
+    },
+    /// Placing or amending a child failed.
+    ChildRejected {
+        /// The error reported.
+        error: String,
+    },
+    /// The algo finished.
+    Finished(AlgoProgress),
+}
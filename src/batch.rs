@@ -1,7 +1,9 @@
 //! Batch order management with rate limiting.
 //!
 //! This module provides a [`BatchManager`] that handles automatic chunking
-//! and rate-limited submission of orders to the Kalshi API.
+//! and rate-limited submission of orders to the Kalshi API. The manager owns
+//! a clone of the client, so it can be stored alongside it or moved into
+//! spawned tasks; clones share one rate limiter.
 //!
 //! # Example
 //!
@@ -36,20 +38,31 @@
 //! let manager = BatchManager::builder(&client)
 //!     .tier(RateLimitTier::Advanced)
 //!     .retry_config(RetryConfig::default())
+//!     .concurrency(4)
 //!     .build();
 //! ```
 
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::{Duration, Instant};
 
+use chrono::Utc;
+use futures_util::stream::{self, StreamExt};
 use tokio::sync::Mutex;
 
 use crate::{
     KalshiClient,
     error::{Error, MAX_BATCH_SIZE, Result},
     models::{
-        BatchCancelOrderItem, BatchCancelOrderResult, BatchCancelOrdersRequest,
-        BatchCreateOrdersRequest, BatchOrderResult, CreateOrderRequest, Order,
+        AmendOrderRequest, BatchCancelOrderItem, BatchCancelOrderResult, BatchCancelOrdersRequest,
+        BatchCreateOrdersRequest, BatchOrderError, BatchOrderErrorKind, BatchOrderResult,
+        CreateOrderRequest, DecreaseOrderRequest, Order,
     },
+    orders::{self, CancelAllFilter, CancelAllReport, ClientOrderIdGenerator},
+    units::contracts,
 };
 
 /// Write cost for each order in a batch create request.
@@ -58,6 +71,12 @@ const CREATE_ORDER_COST: f64 = 1.0;
 /// Write cost for each order in a batch cancel request.
 const CANCEL_ORDER_COST: f64 = 0.2;
 
+/// Write cost of a single amend request.
+const AMEND_ORDER_COST: f64 = 1.0;
+
+/// Write cost of a single decrease request, charged like any other write.
+const DECREASE_ORDER_COST: f64 = 1.0;
+
 /// Default maximum retry attempts for transient errors.
 const DEFAULT_MAX_RETRIES: u32 = 3;
 
@@ -67,6 +86,9 @@ const DEFAULT_BASE_DELAY: Duration = Duration::from_millis(100);
 /// Default maximum delay between retries.
 const DEFAULT_MAX_DELAY: Duration = Duration::from_secs(10);
 
+/// Default number of chunks in flight at once.
+const DEFAULT_CONCURRENCY: usize = 1;
+
 /// Rate limit tiers for the Kalshi API.
 ///
 /// Each tier defines the number of read and write operations allowed per second.
@@ -156,6 +178,17 @@ impl RetryConfig {
     }
 }
 
+/// What a [`BatchManager`] does when a request fails after its retries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BatchErrorPolicy {
+    /// Send nothing after the first failed request.
+    #[default]
+    StopOnError,
+    /// Keep sending the remaining requests. The result's `error` holds the
+    /// first failure and `completed` everything that succeeded.
+    ContinueOnError,
+}
+
 /// Token bucket rate limiter.
 ///
 /// Implements a token bucket algorithm where tokens are consumed for each
@@ -223,18 +256,24 @@ impl TokenBucket {
 ///     .retry_config(RetryConfig::with_max_retries(5))
 ///     .build();
 /// ```
-pub struct BatchManagerBuilder<'a> {
-    client: &'a KalshiClient,
+pub struct BatchManagerBuilder {
+    client: KalshiClient,
     tier: RateLimitTier,
     retry_config: RetryConfig,
+    client_order_ids: Option<ClientOrderIdGenerator>,
+    concurrency: usize,
+    error_policy: BatchErrorPolicy,
 }
 
-impl<'a> BatchManagerBuilder<'a> {
-    fn new(client: &'a KalshiClient) -> Self {
+impl BatchManagerBuilder {
+    fn new(client: &KalshiClient) -> Self {
         Self {
-            client,
+            client: client.clone(),
             tier: RateLimitTier::default(),
             retry_config: RetryConfig::no_retries(),
+            client_order_ids: None,
+            concurrency: DEFAULT_CONCURRENCY,
+            error_policy: BatchErrorPolicy::default(),
         }
     }
 
@@ -250,12 +289,48 @@ impl<'a> BatchManagerBuilder<'a> {
         self
     }
 
+    /// Set the generator for client order IDs.
+    ///
+    /// Defaults to the client's [generator](KalshiClient::with_client_order_ids),
+    /// or one without a prefix if the client has none.
+    pub fn client_order_ids(mut self, generator: ClientOrderIdGenerator) -> Self {
+        self.client_order_ids = Some(generator);
+        self
+    }
+
+    /// Set how many requests may be in flight at once: batch chunks for
+    /// create and cancel, single orders for amend and decrease.
+    ///
+    /// Requests still wait for write tokens before they are sent, so higher
+    /// concurrency lowers latency without exceeding the tier's budget.
+    /// Values below 1 are treated as 1.
+    ///
+    /// Default: 1 (requests are sent one after another).
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Set whether to keep sending after a request fails.
+    ///
+    /// Default: [`BatchErrorPolicy::StopOnError`].
+    pub fn error_policy(mut self, policy: BatchErrorPolicy) -> Self {
+        self.error_policy = policy;
+        self
+    }
+
     /// Build the batch manager.
-    pub fn build(self) -> BatchManager<'a> {
+    pub fn build(self) -> BatchManager {
+        let client_order_ids = self
+            .client_order_ids
+            .unwrap_or_else(|| default_client_order_ids(&self.client));
         BatchManager {
             client: self.client,
-            rate_limiter: Mutex::new(TokenBucket::new(self.tier.writes_per_second())),
+            rate_limiter: Arc::new(Mutex::new(TokenBucket::new(self.tier.writes_per_second()))),
             retry_config: self.retry_config,
+            client_order_ids,
+            concurrency: self.concurrency,
+            error_policy: self.error_policy,
         }
     }
 }
@@ -273,18 +348,32 @@ impl<'a> BatchManagerBuilder<'a> {
 ///
 /// - **Batch Create**: Submit multiple orders at once
 /// - **Batch Cancel**: Cancel multiple orders at once
-///
-/// Note: The Kalshi API does not support batch amend operations.
-/// Individual order amendments must use [`KalshiClient::amend_order`].
+/// - **Amend and Decrease**: The Kalshi API has no batch endpoint for these,
+///   so each order is its own request, paced and retried like a chunk
+/// - **Replace**: Cancel orders and create their replacements, optionally
+///   amending in place first
 ///
 /// # Rate Limiting
 ///
 /// The manager uses a token bucket algorithm to pace requests:
 /// - Each order in a batch create costs 1 write token
 /// - Each order in a batch cancel costs 0.2 write tokens
+/// - Each amend or decrease costs 1 write token
 /// - Tokens refill at the tier's writes-per-second rate
 /// - Initial bucket is full, allowing burst capacity of 1 second
 ///
+/// The manager holds its own clone of the client and is `Clone`, `Send` and
+/// `Sync`. Clones share the token bucket, so hand a clone to each task that
+/// writes instead of building a manager per task; separately built managers
+/// each get a full budget and together can exceed the account's limit.
+///
+/// # Concurrency
+///
+/// By default chunks are sent one after another. With
+/// [`concurrency`](BatchManagerBuilder::concurrency) above 1, up to that many
+/// chunks are in flight at once, each still paced by the shared bucket.
+/// Results keep the order of the input either way.
+///
 /// # Retry Behavior
 ///
 /// By default, retries are disabled. Enable them via the builder:
@@ -298,6 +387,13 @@ impl<'a> BatchManagerBuilder<'a> {
 /// Retries use exponential backoff and only apply to transient errors
 /// (network timeouts, rate limit responses, server errors).
 ///
+/// Every order is given a client order ID before its chunk is first sent.
+/// When a chunk fails in a way that may have reached the exchange (a
+/// network error or a 5xx response), the orders are looked up by client
+/// order ID before the retry and only the ones that were not created are
+/// resent, so retries never duplicate orders. If the lookup itself fails
+/// the chunk is not retried.
+///
 /// # Empty Input Handling
 ///
 /// Passing an empty vector to `create_orders` or `cancel_orders` returns
@@ -309,6 +405,12 @@ impl<'a> BatchManagerBuilder<'a> {
 /// the manager preserves successfully completed work. Check both the
 /// `completed` field and `error` field of the result.
 ///
+/// By default no chunk is sent after one fails. Chunks already in flight
+/// when it fails are allowed to finish, and their results are kept in
+/// `completed`. With [`BatchErrorPolicy::ContinueOnError`] every chunk is
+/// sent regardless. [`create_orders_with_outcomes`](Self::create_orders_with_outcomes)
+/// reports what happened to each input order, including those never sent.
+///
 /// # Example
 ///
 /// ```ignore
@@ -330,41 +432,150 @@ impl<'a> BatchManagerBuilder<'a> {
 /// // Or convert to Result if you don't need partial results
 /// let response = manager.create_orders(orders).await.into_result()?;
 /// ```
-pub struct BatchManager<'a> {
-    client: &'a KalshiClient,
-    rate_limiter: Mutex<TokenBucket>,
+#[derive(Clone)]
+pub struct BatchManager {
+    client: KalshiClient,
+    rate_limiter: Arc<Mutex<TokenBucket>>,
     retry_config: RetryConfig,
+    client_order_ids: ClientOrderIdGenerator,
+    concurrency: usize,
+    error_policy: BatchErrorPolicy,
 }
 
-impl<'a> BatchManager<'a> {
+impl BatchManager {
     /// Create a new batch manager with the specified rate limit tier.
     ///
-    /// For advanced configuration (retries), use [`BatchManager::builder`].
-    pub fn new(client: &'a KalshiClient, tier: RateLimitTier) -> Self {
-        Self {
-            client,
-            rate_limiter: Mutex::new(TokenBucket::new(tier.writes_per_second())),
-            retry_config: RetryConfig::no_retries(),
-        }
+    /// The manager keeps a clone of `client`. For advanced configuration
+    /// (retries, concurrency), use [`BatchManager::builder`].
+    pub fn new(client: &KalshiClient, tier: RateLimitTier) -> Self {
+        BatchManagerBuilder::new(client).tier(tier).build()
     }
 
     /// Create a builder for advanced configuration.
-    pub fn builder(client: &'a KalshiClient) -> BatchManagerBuilder<'a> {
+    pub fn builder(client: &KalshiClient) -> BatchManagerBuilder {
         BatchManagerBuilder::new(client)
     }
 
+    /// The client requests are sent through.
+    pub(crate) fn client(&self) -> &KalshiClient {
+        &self.client
+    }
+
+    /// Wait until `cost` write tokens are available and take them.
+    async fn acquire(&self, cost: f64) {
+        // Get wait time and consume tokens, then release lock before sleeping
+        let wait_time = {
+            let mut limiter = self.rate_limiter.lock().await;
+            limiter.consume(cost)
+        };
+
+        if !wait_time.is_zero() {
+            tokio::time::sleep(wait_time).await;
+        }
+    }
+
+    /// Send `items` in rate-limited chunks of `chunk_size`, up to
+    /// `concurrency` at a time.
+    ///
+    /// Returns the results of every chunk that succeeded, in input order,
+    /// and the error of the first chunk that failed.
+    async fn run_chunks<T, R, F, Fut>(
+        &self,
+        items: Vec<T>,
+        chunk_size: usize,
+        cost_per_item: f64,
+        send: F,
+    ) -> (Vec<R>, Option<Error>)
+    where
+        T: Clone,
+        F: Fn(Vec<T>) -> Fut,
+        Fut: Future<Output = Result<Vec<R>>>,
+    {
+        let mut completed = Vec::with_capacity(items.len());
+        let mut error = None;
+        let chunks = self
+            .run_chunks_detailed(items, chunk_size, cost_per_item, send)
+            .await;
+        for (_, outcome) in chunks {
+            match outcome {
+                Some(Ok(results)) => completed.extend(results),
+                Some(Err(e)) => {
+                    error.get_or_insert(e);
+                }
+                None => {}
+            }
+        }
+        (completed, error)
+    }
+
+    /// Like [`run_chunks`](Self::run_chunks), but returns each chunk with
+    /// its outcome, `None` for chunks not sent.
+    ///
+    /// Under [`BatchErrorPolicy::StopOnError`], chunks are not sent once one
+    /// has failed.
+    async fn run_chunks_detailed<T, R, F, Fut>(
+        &self,
+        items: Vec<T>,
+        chunk_size: usize,
+        cost_per_item: f64,
+        send: F,
+    ) -> Vec<(Vec<T>, Option<Result<Vec<R>>>)>
+    where
+        T: Clone,
+        F: Fn(Vec<T>) -> Fut,
+        Fut: Future<Output = Result<Vec<R>>>,
+    {
+        let stop_on_error = self.error_policy == BatchErrorPolicy::StopOnError;
+        let stopped = AtomicBool::new(false);
+        let (stopped, send) = (&stopped, &send);
+        let chunks: Vec<_> = items
+            .chunks(chunk_size)
+            .map(|chunk| async move {
+                let chunk = chunk.to_vec();
+                if stopped.load(Ordering::Acquire) {
+                    return (chunk, None);
+                }
+                self.acquire(chunk.len() as f64 * cost_per_item).await;
+                // Another chunk may have failed while this one waited
+                if stopped.load(Ordering::Acquire) {
+                    return (chunk, None);
+                }
+                let result = send(chunk.clone()).await;
+                if result.is_err() && stop_on_error {
+                    stopped.store(true, Ordering::Release);
+                }
+                (chunk, Some(result))
+            })
+            .collect();
+        stream::iter(chunks)
+            .buffered(self.concurrency)
+            .collect()
+            .await
+    }
+
     /// Execute a batch operation with retry logic.
-    async fn execute_with_retry<T, F, Fut>(&self, mut operation: F) -> Result<T>
+    async fn execute_with_retry<T, F, Fut>(&self, operation: F) -> Result<T>
     where
         F: FnMut() -> Fut,
         Fut: std::future::Future<Output = Result<T>>,
+    {
+        self.execute_with_retry_if(|_| true, operation).await
+    }
+
+    /// Execute an operation with retry logic, retrying only errors that
+    /// `retryable` accepts as well as [`should_retry`](Self::should_retry).
+    async fn execute_with_retry_if<T, P, F, Fut>(&self, retryable: P, mut operation: F) -> Result<T>
+    where
+        P: Fn(&Error) -> bool,
+        F: FnMut() -> Fut,
+        Fut: std::future::Future<Output = Result<T>>,
     {
         let mut attempt = 0;
 
         loop {
             match operation().await {
                 Ok(result) => return Ok(result),
-                Err(e) if self.should_retry(&e, attempt) => {
+                Err(e) if self.should_retry(&e, attempt) && retryable(&e) => {
                     let delay = self.retry_config.delay_for_attempt(attempt);
                     tracing::debug!(
                         attempt = attempt + 1,
@@ -423,48 +634,124 @@ impl<'a> BatchManager<'a> {
             };
         }
 
-        let mut all_results = Vec::with_capacity(orders.len());
-
-        for chunk in orders.chunks(MAX_BATCH_SIZE) {
-            let cost = chunk.len() as f64 * CREATE_ORDER_COST;
-
-            // Get wait time and consume tokens, then release lock before sleeping
-            let wait_time = {
-                let mut limiter = self.rate_limiter.lock().await;
-                limiter.consume(cost)
-            };
+        let (orders, error) = self
+            .run_chunks(
+                orders,
+                MAX_BATCH_SIZE,
+                CREATE_ORDER_COST,
+                |mut chunk| async move {
+                    self.client_order_ids.stamp(&mut chunk);
+                    self.create_chunk(&chunk).await
+                },
+            )
+            .await;
 
-            if !wait_time.is_zero() {
-                tokio::time::sleep(wait_time).await;
-            }
+        BatchOperationResult {
+            completed: AggregatedCreateResponse { orders },
+            error,
+        }
+    }
 
-            // Send the batch with retry logic
-            let request = BatchCreateOrdersRequest::new(chunk.to_vec());
-            let client = self.client;
-            match self
-                .execute_with_retry(|| {
-                    let req = request.clone();
-                    async move { client.batch_create_orders(req).await }
-                })
-                .await
-            {
-                Ok(response) => all_results.extend(response.orders),
-                Err(e) => {
-                    return BatchOperationResult {
-                        completed: AggregatedCreateResponse {
-                            orders: all_results,
+    /// Create multiple orders and report what happened to each one.
+    ///
+    /// Orders are stamped with client order IDs and sent like
+    /// [`create_orders`](Self::create_orders), following the manager's
+    /// [`BatchErrorPolicy`]. The report has one outcome per input order, in
+    /// input order, including orders that were never sent. Use
+    /// [`CreateOrdersReport::resubmittable`] to collect the orders that can
+    /// be sent again without risking a duplicate.
+    pub async fn create_orders_with_outcomes(
+        &self,
+        mut orders: Vec<CreateOrderRequest>,
+    ) -> CreateOrdersReport {
+        self.client_order_ids.stamp(&mut orders);
+        let chunks = self
+            .run_chunks_detailed(
+                orders,
+                MAX_BATCH_SIZE,
+                CREATE_ORDER_COST,
+                |chunk| async move { self.create_chunk(&chunk).await },
+            )
+            .await;
+
+        let mut outcomes = Vec::new();
+        for (chunk, result) in chunks {
+            match result {
+                None => outcomes.extend(chunk.into_iter().map(|request| CreateOrderOutcome {
+                    request,
+                    status: CreateOrderStatus::NotAttempted,
+                })),
+                Some(Ok(results)) => outcomes.extend(match_results(chunk, results)),
+                Some(Err(e)) => {
+                    let status = match order_rejection(&e) {
+                        Some(error) => CreateOrderStatus::Rejected(error),
+                        None => CreateOrderStatus::TransportFailed {
+                            error: e.to_string(),
+                            may_exist: orders::is_ambiguous(&e),
                         },
-                        error: Some(e),
                     };
+                    outcomes.extend(chunk.into_iter().map(|request| CreateOrderOutcome {
+                        request,
+                        status: status.clone(),
+                    }));
                 }
             }
         }
+        CreateOrdersReport { outcomes }
+    }
 
-        BatchOperationResult {
-            completed: AggregatedCreateResponse {
-                orders: all_results,
-            },
-            error: None,
+    /// Create one chunk of stamped orders, retrying without duplicates.
+    async fn create_chunk(&self, chunk: &[CreateOrderRequest]) -> Result<Vec<BatchOrderResult>> {
+        let since = Utc::now().timestamp() - orders::LOOKUP_SLACK_SECS;
+        let mut found: HashMap<String, Order> = HashMap::new();
+        let mut attempt = 0;
+
+        loop {
+            let pending = orders::missing(chunk, &found);
+            let sent = if pending.is_empty() {
+                Ok(Vec::new())
+            } else {
+                self.client
+                    .batch_create_orders(BatchCreateOrdersRequest::new(pending.clone()))
+                    .await
+                    .map(|response| response.orders)
+            };
+
+            match sent {
+                Ok(mut sent) => {
+                    if attempt > 0 {
+                        sent =
+                            orders::resolve_duplicates(&self.client, &pending, since, sent).await;
+                    }
+                    return Ok(orders::merge_batch_results(chunk, &found, sent));
+                }
+                Err(e) if self.should_retry(&e, attempt) => {
+                    if orders::is_ambiguous(&e) {
+                        match orders::find_created(&self.client, chunk, since).await {
+                            Ok(created) => found.extend(created),
+                            Err(lookup) => {
+                                tracing::warn!(
+                                    error = %lookup,
+                                    "Could not look up orders after ambiguous batch failure"
+                                );
+                                return Err(e);
+                            }
+                        }
+                    }
+                    let delay = self.retry_config.delay_for_attempt(attempt);
+                    tracing::debug!(
+                        attempt = attempt + 1,
+                        max_retries = self.retry_config.max_retries,
+                        delay_ms = delay.as_millis(),
+                        already_created = found.len(),
+                        error = %e,
+                        "Retrying batch create"
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
         }
     }
 
@@ -494,49 +781,17 @@ impl<'a> BatchManager<'a> {
             };
         }
 
-        let mut all_results = Vec::with_capacity(order_ids.len());
-
-        for chunk in order_ids.chunks(MAX_BATCH_SIZE) {
-            let cost = chunk.len() as f64 * CANCEL_ORDER_COST;
-
-            // Get wait time and consume tokens, then release lock before sleeping
-            let wait_time = {
-                let mut limiter = self.rate_limiter.lock().await;
-                limiter.consume(cost)
-            };
-
-            if !wait_time.is_zero() {
-                tokio::time::sleep(wait_time).await;
-            }
-
-            // Send the batch with retry logic
-            #[allow(deprecated)]
-            let request = BatchCancelOrdersRequest::new(chunk.to_vec());
-            let client = self.client;
-            match self
-                .execute_with_retry(|| {
-                    let req = request.clone();
-                    async move { client.batch_cancel_orders(req).await }
-                })
-                .await
-            {
-                Ok(response) => all_results.extend(response.orders),
-                Err(e) => {
-                    return BatchOperationResult {
-                        completed: AggregatedCancelResponse {
-                            orders: all_results,
-                        },
-                        error: Some(e),
-                    };
-                }
-            }
-        }
+        let (orders, error) = self
+            .run_chunks(order_ids, MAX_BATCH_SIZE, CANCEL_ORDER_COST, |chunk| {
+                #[allow(deprecated)]
+                let request = BatchCancelOrdersRequest::new(chunk);
+                self.cancel_chunk(request)
+            })
+            .await;
 
         BatchOperationResult {
-            completed: AggregatedCancelResponse {
-                orders: all_results,
-            },
-            error: None,
+            completed: AggregatedCancelResponse { orders },
+            error,
         }
     }
 
@@ -556,50 +811,434 @@ impl<'a> BatchManager<'a> {
             };
         }
 
-        let mut all_results = Vec::with_capacity(items.len());
+        let (orders, error) = self
+            .run_chunks(items, MAX_BATCH_SIZE, CANCEL_ORDER_COST, |chunk| {
+                self.cancel_chunk(BatchCancelOrdersRequest::with_orders(chunk))
+            })
+            .await;
 
-        for chunk in items.chunks(MAX_BATCH_SIZE) {
-            let cost = chunk.len() as f64 * CANCEL_ORDER_COST;
+        BatchOperationResult {
+            completed: AggregatedCancelResponse { orders },
+            error,
+        }
+    }
 
-            let wait_time = {
-                let mut limiter = self.rate_limiter.lock().await;
-                limiter.consume(cost)
-            };
+    /// Cancel every resting order matching `filter`, paced by this manager.
+    ///
+    /// Works like [`KalshiClient::cancel_all`], but cancels share this
+    /// manager's rate limiter with its other requests, so the filter's
+    /// [tier](CancelAllFilter::tier) is ignored.
+    pub async fn cancel_all(&self, filter: CancelAllFilter) -> Result<CancelAllReport> {
+        orders::cancel_all_with(self, &filter).await
+    }
 
-            if !wait_time.is_zero() {
-                tokio::time::sleep(wait_time).await;
+    /// Amend multiple orders, one request per order.
+    ///
+    /// Each entry is an order ID and its amendment. Requests are paced by the
+    /// shared rate limiter and up to
+    /// [`concurrency`](BatchManagerBuilder::concurrency) are in flight at
+    /// once. An amendment sets absolute prices and quantities, so transient
+    /// failures are retried even when the first attempt may have been
+    /// applied.
+    ///
+    /// An order the API rejects (for example because it already filled) gets
+    /// a per-order error and the rest continue. Transport, rate limit,
+    /// authentication and server errors that outlast the retries stop
+    /// processing, and the error is returned alongside the completed results.
+    pub async fn amend_orders(
+        &self,
+        amendments: Vec<(String, AmendOrderRequest)>,
+    ) -> BatchOperationResult<AggregatedAmendResponse> {
+        let (orders, error) = self
+            .run_chunks(amendments, 1, AMEND_ORDER_COST, |chunk| async move {
+                let mut results = Vec::with_capacity(chunk.len());
+                for (order_id, request) in chunk {
+                    results.push(self.amend_one(order_id, request).await?);
+                }
+                Ok(results)
+            })
+            .await;
+
+        BatchOperationResult {
+            completed: AggregatedAmendResponse { orders },
+            error,
+        }
+    }
+
+    async fn amend_one(
+        &self,
+        order_id: String,
+        request: AmendOrderRequest,
+    ) -> Result<AmendOrderResult> {
+        let client = &self.client;
+        let result = self
+            .execute_with_retry(|| {
+                let req = request.clone();
+                let order_id = order_id.as_str();
+                async move { client.amend_order(order_id, req).await }
+            })
+            .await;
+        match result {
+            Ok(response) => Ok(AmendOrderResult {
+                order_id,
+                old_order: Some(response.old_order),
+                order: Some(response.order),
+                error: None,
+            }),
+            Err(e) => {
+                let error = order_rejection(&e).ok_or(e)?;
+                Ok(AmendOrderResult {
+                    order_id,
+                    old_order: None,
+                    order: None,
+                    error: Some(error),
+                })
             }
+        }
+    }
 
-            let request = BatchCancelOrdersRequest::with_orders(chunk.to_vec());
-            let client = self.client;
-            match self
-                .execute_with_retry(|| {
+    /// Decrease multiple orders, one request per order.
+    ///
+    /// Pacing, concurrency and error handling follow
+    /// [`amend_orders`](Self::amend_orders). A decrease by `reduce_by` is
+    /// not idempotent, so it is only retried after errors that prove the
+    /// request was not applied (such as rate limiting); `reduce_to`
+    /// decreases are retried after any transient error.
+    ///
+    /// A `reduce_by` decrease that fails ambiguously (a transport error or
+    /// 5xx) is not retried: it may or may not have been applied, and the
+    /// error stops processing. Check the order before decreasing it again,
+    /// or use `reduce_to`.
+    pub async fn decrease_orders(
+        &self,
+        decreases: Vec<(String, DecreaseOrderRequest)>,
+    ) -> BatchOperationResult<AggregatedDecreaseResponse> {
+        let (orders, error) = self
+            .run_chunks(decreases, 1, DECREASE_ORDER_COST, |chunk| async move {
+                let mut results = Vec::with_capacity(chunk.len());
+                for (order_id, request) in chunk {
+                    results.push(self.decrease_one(order_id, request).await?);
+                }
+                Ok(results)
+            })
+            .await;
+
+        BatchOperationResult {
+            completed: AggregatedDecreaseResponse { orders },
+            error,
+        }
+    }
+
+    async fn decrease_one(
+        &self,
+        order_id: String,
+        request: DecreaseOrderRequest,
+    ) -> Result<DecreaseOrderResult> {
+        let client = &self.client;
+        let idempotent = request.reduce_to.is_some() || request.reduce_to_fp.is_some();
+        let result = self
+            .execute_with_retry_if(
+                |e| idempotent || !orders::is_ambiguous(e),
+                || {
                     let req = request.clone();
-                    async move { client.batch_cancel_orders(req).await }
+                    let order_id = order_id.as_str();
+                    async move { client.decrease_order(order_id, req).await }
+                },
+            )
+            .await;
+        match result {
+            Ok(response) => Ok(DecreaseOrderResult {
+                order_id,
+                order: Some(response.order),
+                error: None,
+            }),
+            Err(e) => {
+                let error = order_rejection(&e).ok_or(e)?;
+                Ok(DecreaseOrderResult {
+                    order_id,
+                    order: None,
+                    error: Some(error),
                 })
-                .await
-            {
-                Ok(response) => all_results.extend(response.orders),
-                Err(e) => {
-                    return BatchOperationResult {
-                        completed: AggregatedCancelResponse {
-                            orders: all_results,
-                        },
-                        error: Some(e),
-                    };
+            }
+        }
+    }
+
+    /// Replace resting orders with new ones.
+    ///
+    /// With [`ReplaceMode::CancelThenCreate`] every original is canceled in
+    /// batches and a replacement is created for each cancel that succeeded.
+    /// With [`ReplaceMode::AmendFirst`] each original is amended to the
+    /// replacement's side, price and quantity; originals the API refuses to
+    /// amend, or whose amendment fails outright under
+    /// [`BatchErrorPolicy::ContinueOnError`], fall back to cancel-then-create. A replacement is never created
+    /// for an original that could not be canceled, so an order that filled
+    /// in the meantime is not doubled.
+    ///
+    /// As with an amendment, a replacement's count is the order's total
+    /// size: a recreated replacement is sent for the count less whatever the
+    /// original filled before it was canceled. If the cancel response does
+    /// not include the original, the replacement is capped at the contracts
+    /// the cancel removed. An original that filled completely is canceled
+    /// without a replacement.
+    ///
+    /// Results keep the order of `replacements`. If processing stops with an
+    /// error, originals that were canceled but whose replacement was not
+    /// sent are still reported, with `canceled` set and no `order`.
+    pub async fn replace_orders(
+        &self,
+        replacements: Vec<OrderReplacement>,
+        mode: ReplaceMode,
+    ) -> BatchOperationResult<AggregatedReplaceResponse> {
+        let mut results: Vec<Option<ReplaceOrderResult>> = vec![None; replacements.len()];
+        let mut error = None;
+
+        // Amend in place where possible
+        let fallback: Vec<usize> = match mode {
+            ReplaceMode::CancelThenCreate => (0..replacements.len()).collect(),
+            ReplaceMode::AmendFirst => {
+                let amendments = replacements
+                    .iter()
+                    .enumerate()
+                    .map(|(index, replacement)| (index, replacement.amendment()))
+                    .collect();
+                let replacements = &replacements;
+                let (amended, amend_error) = self
+                    .run_chunks(amendments, 1, AMEND_ORDER_COST, |chunk| async move {
+                        let mut results = Vec::with_capacity(chunk.len());
+                        for (index, request) in chunk {
+                            let order_id = replacements[index].order_id.clone();
+                            results.push((index, self.amend_one(order_id, request).await?));
+                        }
+                        Ok(results)
+                    })
+                    .await;
+
+                let mut fallback = Vec::new();
+                let mut answered = vec![false; replacements.len()];
+                for (index, amend) in amended {
+                    answered[index] = true;
+                    match amend.order {
+                        Some(order) => {
+                            results[index] = Some(ReplaceOrderResult {
+                                order_id: amend.order_id,
+                                method: ReplaceMethod::Amended,
+                                canceled: amend.old_order,
+                                order: Some(order),
+                                error: None,
+                            });
+                        }
+                        None => fallback.push(index),
+                    }
+                }
+                if amend_error.is_some() {
+                    error = amend_error;
+                    match self.error_policy {
+                        BatchErrorPolicy::StopOnError => fallback.clear(),
+                        // Canceling first keeps an amendment that did land
+                        // from being doubled
+                        BatchErrorPolicy::ContinueOnError => {
+                            fallback.extend((0..replacements.len()).filter(|&i| !answered[i]));
+                            fallback.sort_unstable();
+                        }
+                    }
                 }
+                fallback
+            }
+        };
+
+        // Cancel the rest
+        let items = fallback
+            .iter()
+            .map(|&index| replacements[index].cancel_item())
+            .collect();
+        let canceled = self.cancel_orders_with_items(items).await;
+        error = error.or(canceled.error);
+        let mut cancels: HashMap<String, BatchCancelOrderResult> = canceled
+            .completed
+            .orders
+            .into_iter()
+            .map(|cancel| (cancel.order_id.clone(), cancel))
+            .collect();
+        let mut to_create = Vec::new();
+        for index in fallback {
+            let replacement = &replacements[index];
+            let Some(cancel) = cancels.remove(&replacement.order_id) else {
+                continue;
+            };
+            if cancel.error.is_none() {
+                let count = replacement.remaining_after(&cancel);
+                if count > 0 {
+                    to_create.push((index, count));
+                }
+            }
+            results[index] = Some(ReplaceOrderResult {
+                order_id: replacement.order_id.clone(),
+                method: ReplaceMethod::Recreated,
+                canceled: cancel.order,
+                order: None,
+                error: cancel.error,
+            });
+        }
+
+        // Create replacements for everything canceled, even if canceling
+        // stopped early, so no canceled order is left without one
+        let mut orders: Vec<CreateOrderRequest> = to_create
+            .iter()
+            .map(|&(index, count)| {
+                let mut order = replacements[index].order.clone();
+                order.count = count;
+                order.count_fp = None;
+                order
+            })
+            .collect();
+        self.client_order_ids.stamp(&mut orders);
+        let indices: HashMap<String, usize> = orders
+            .iter()
+            .zip(&to_create)
+            .filter_map(|(order, &(index, _))| Some((order.client_order_id.clone()?, index)))
+            .collect();
+        let created = self.create_orders(orders).await;
+        error = error.or(created.error);
+        for create in created.completed.orders {
+            let index = create
+                .client_order_id
+                .as_ref()
+                .and_then(|id| indices.get(id));
+            if let Some(result) = index.and_then(|&index| results[index].as_mut()) {
+                result.order = create.order;
+                result.error = create.error;
             }
         }
 
         BatchOperationResult {
-            completed: AggregatedCancelResponse {
-                orders: all_results,
+            completed: AggregatedReplaceResponse {
+                orders: results.into_iter().flatten().collect(),
             },
-            error: None,
+            error,
+        }
+    }
+
+    /// Send one cancel chunk with retry logic.
+    async fn cancel_chunk(
+        &self,
+        request: BatchCancelOrdersRequest,
+    ) -> Result<Vec<BatchCancelOrderResult>> {
+        let client = &self.client;
+        self.execute_with_retry(|| {
+            let req = request.clone();
+            async move { client.batch_cancel_orders(req).await }
+        })
+        .await
+        .map(|response| response.orders)
+    }
+}
+
+impl fmt::Debug for BatchManager {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BatchManager")
+            .field("retry_config", &self.retry_config)
+            .field("concurrency", &self.concurrency)
+            .field("error_policy", &self.error_policy)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The client's client order ID generator, or one without a prefix.
+fn default_client_order_ids(client: &KalshiClient) -> ClientOrderIdGenerator {
+    client.client_order_ids().cloned().unwrap_or_default()
+}
+
+/// Pair each stamped request of a chunk with its result, by client order
+/// ID where the response echoes it and by position otherwise.
+fn match_results(
+    chunk: Vec<CreateOrderRequest>,
+    results: Vec<BatchOrderResult>,
+) -> Vec<CreateOrderOutcome> {
+    let mut results: Vec<Option<BatchOrderResult>> = results.into_iter().map(Some).collect();
+    chunk
+        .into_iter()
+        .enumerate()
+        .map(|(position, request)| {
+            let index = results
+                .iter()
+                .position(|result| {
+                    result.as_ref().is_some_and(|result| {
+                        result.client_order_id.is_some()
+                            && result.client_order_id == request.client_order_id
+                    })
+                })
+                .unwrap_or(position);
+            let status = match results.get_mut(index).and_then(Option::take) {
+                Some(BatchOrderResult {
+                    order: Some(order), ..
+                }) => CreateOrderStatus::Created(Box::new(order)),
+                Some(BatchOrderResult {
+                    error: Some(error), ..
+                }) => CreateOrderStatus::Rejected(error),
+                _ => CreateOrderStatus::TransportFailed {
+                    error: "no result returned for order".to_string(),
+                    may_exist: true,
+                },
+            };
+            CreateOrderOutcome { request, status }
+        })
+        .collect()
+}
+
+/// The per-order error for a request the API rejected, or `None` if the
+/// failure is not specific to the order: transport, rate limit,
+/// authentication and server errors would fail any other order too.
+pub(crate) fn order_rejection(error: &Error) -> Option<BatchOrderError> {
+    match error {
+        Error::Api(msg) => {
+            let status = api_status(error)?;
+            if !(400..500).contains(&status) || matches!(status, 401 | 403 | 429) {
+                return None;
+            }
+            let (_, body) = msg.split_once(": ")?;
+            Some(parse_api_error(status, body))
         }
+        Error::InvalidPrice(_)
+        | Error::InvalidQuantity(_)
+        | Error::InvalidContracts(_)
+        | Error::InvalidSubaccountId(_) => Some(BatchOrderError {
+            code: "invalid_request".to_string(),
+            message: error.to_string(),
+            details: None,
+            service: None,
+        }),
+        _ => None,
     }
 }
 
+/// The HTTP status of an API error, if it has one.
+pub(crate) fn api_status(error: &Error) -> Option<u16> {
+    let Error::Api(msg) = error else {
+        return None;
+    };
+    let (status, _) = msg.split_once(": ")?;
+    status.split_whitespace().next()?.parse().ok()
+}
+
+/// Parse an API error body, `{"error": {"code": ..., "message": ...}}`,
+/// falling back to the HTTP status and raw body.
+fn parse_api_error(status: u16, body: &str) -> BatchOrderError {
+    #[derive(serde::Deserialize)]
+    struct Envelope {
+        error: BatchOrderError,
+    }
+
+    serde_json::from_str::<Envelope>(body)
+        .map(|envelope| envelope.error)
+        .or_else(|_| serde_json::from_str::<BatchOrderError>(body))
+        .unwrap_or_else(|_| BatchOrderError {
+            code: status.to_string(),
+            message: body.to_string(),
+            details: None,
+            service: None,
+        })
+}
+
 /// Check if an API error message indicates a transient/retryable error.
 fn is_transient_api_error(msg: &str) -> bool {
     let msg_lower = msg.to_lowercase();
@@ -743,9 +1382,330 @@ impl AggregatedCancelResponse {
     }
 }
 
+/// What happened to one order in [`BatchManager::create_orders_with_outcomes`].
+#[derive(Debug, Clone)]
+pub enum CreateOrderStatus {
+    /// The order was created.
+    Created(Box<Order>),
+    /// The API rejected the order, or the whole chunk it was sent in.
+    Rejected(BatchOrderError),
+    /// The order was never sent because processing stopped first.
+    NotAttempted,
+    /// The request carrying the order failed without a per-order answer,
+    /// for example a network error or rate limiting that outlasted retries.
+    TransportFailed {
+        /// The error reported.
+        error: String,
+        /// Whether the request may have reached the exchange, so the order
+        /// may exist even though no result came back.
+        may_exist: bool,
+    },
+}
+
+/// An input order of [`BatchManager::create_orders_with_outcomes`] and what
+/// happened to it.
+#[derive(Debug, Clone)]
+pub struct CreateOrderOutcome {
+    /// The order as submitted, stamped with its client order ID.
+    pub request: CreateOrderRequest,
+    /// What happened to it.
+    pub status: CreateOrderStatus,
+}
+
+impl CreateOrderOutcome {
+    /// The client order ID the order was submitted with.
+    pub fn client_order_id(&self) -> Option<&str> {
+        self.request.client_order_id.as_deref()
+    }
+
+    /// Whether the order can be sent again without risking a duplicate.
+    ///
+    /// True for orders never sent, rejections that say nothing about the
+    /// order ([`BatchOrderErrorKind::is_retryable`](crate::models::BatchOrderErrorKind::is_retryable)),
+    /// and failures that cannot have reached the exchange. A server error
+    /// may come after the order was created, so it is only safe with a
+    /// client order ID, which makes the exchange reject a duplicate.
+    pub fn is_safe_to_resubmit(&self) -> bool {
+        match &self.status {
+            CreateOrderStatus::Created(_) => false,
+            CreateOrderStatus::Rejected(error) => match error.kind() {
+                BatchOrderErrorKind::ServerError => self.request.client_order_id.is_some(),
+                kind => kind.is_retryable(),
+            },
+            CreateOrderStatus::NotAttempted => true,
+            CreateOrderStatus::TransportFailed { may_exist, .. } => !may_exist,
+        }
+    }
+}
+
+/// Per-order outcomes of [`BatchManager::create_orders_with_outcomes`].
+#[derive(Debug, Clone, Default)]
+pub struct CreateOrdersReport {
+    /// One outcome per input order, in input order.
+    pub outcomes: Vec<CreateOrderOutcome>,
+}
+
+impl CreateOrdersReport {
+    /// Returns true if every order was created.
+    pub fn is_complete(&self) -> bool {
+        self.outcomes
+            .iter()
+            .all(|outcome| matches!(outcome.status, CreateOrderStatus::Created(_)))
+    }
+
+    /// Returns an iterator over successfully created orders.
+    pub fn created(&self) -> impl Iterator<Item = &Order> {
+        self.outcomes
+            .iter()
+            .filter_map(|outcome| match &outcome.status {
+                CreateOrderStatus::Created(order) => Some(order.as_ref()),
+                _ => None,
+            })
+    }
+
+    /// Orders that can be sent again without risking a duplicate, keeping
+    /// their client order IDs.
+    pub fn resubmittable(&self) -> Vec<CreateOrderRequest> {
+        self.outcomes
+            .iter()
+            .filter(|outcome| outcome.is_safe_to_resubmit())
+            .map(|outcome| outcome.request.clone())
+            .collect()
+    }
+}
+
+/// How [`BatchManager::replace_orders`] replaces an order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReplaceMode {
+    /// Cancel the original, then create the replacement.
+    #[default]
+    CancelThenCreate,
+    /// Amend the original in place, falling back to cancel-then-create if
+    /// the API refuses the amendment.
+    AmendFirst,
+}
+
+/// How an order was replaced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplaceMethod {
+    /// The original was amended in place.
+    Amended,
+    /// The original was canceled and a new order created.
+    Recreated,
+}
+
+/// A resting order and the order that should replace it.
+#[derive(Debug, Clone)]
+pub struct OrderReplacement {
+    /// ID of the order to replace.
+    pub order_id: String,
+    /// The replacement. Its subaccount also locates the original.
+    pub order: CreateOrderRequest,
+}
+
+impl OrderReplacement {
+    /// Replace `order_id` with `order`.
+    pub fn new(order_id: impl Into<String>, order: CreateOrderRequest) -> Self {
+        Self {
+            order_id: order_id.into(),
+            order,
+        }
+    }
+
+    /// The amendment that turns the original into the replacement.
+    fn amendment(&self) -> AmendOrderRequest {
+        let order = &self.order;
+        AmendOrderRequest {
+            ticker: order.ticker.clone(),
+            side: order.side,
+            action: order.action,
+            client_order_id: None,
+            updated_client_order_id: order.client_order_id.clone(),
+            yes_price: order.yes_price,
+            no_price: order.no_price,
+            yes_price_dollars: order.yes_price_dollars.clone(),
+            no_price_dollars: order.no_price_dollars.clone(),
+            count: Some(order.count),
+            count_fp: order.count_fp.clone(),
+            subaccount: order.subaccount,
+        }
+    }
+
+    /// Contracts to create after the original was canceled.
+    fn remaining_after(&self, cancel: &BatchCancelOrderResult) -> i64 {
+        let requested = self
+            .order
+            .count_fp
+            .as_deref()
+            .map_or(self.order.count, contracts);
+        let remaining = match &cancel.order {
+            Some(original) => requested - contracts(&original.fill_count_fp),
+            None => requested.min(contracts(&cancel.reduced_by_fp)),
+        };
+        remaining.max(0)
+    }
+
+    fn cancel_item(&self) -> BatchCancelOrderItem {
+        let item = BatchCancelOrderItem::new(&self.order_id);
+        match self.order.subaccount {
+            Some(subaccount) => item.subaccount(subaccount),
+            None => item,
+        }
+    }
+}
+
+/// Outcome of one amendment in [`BatchManager::amend_orders`].
+#[derive(Debug, Clone)]
+pub struct AmendOrderResult {
+    /// ID of the amended order.
+    pub order_id: String,
+    /// Order state before amendment (present on success).
+    pub old_order: Option<Order>,
+    /// Order state after amendment (present on success).
+    pub order: Option<Order>,
+    /// Error details (present on failure).
+    pub error: Option<BatchOrderError>,
+}
+
+/// Outcome of one decrease in [`BatchManager::decrease_orders`].
+#[derive(Debug, Clone)]
+pub struct DecreaseOrderResult {
+    /// ID of the decreased order.
+    pub order_id: String,
+    /// Order state after the decrease (present on success).
+    pub order: Option<Order>,
+    /// Error details (present on failure).
+    pub error: Option<BatchOrderError>,
+}
+
+/// Outcome of one replacement in [`BatchManager::replace_orders`].
+#[derive(Debug, Clone)]
+pub struct ReplaceOrderResult {
+    /// ID of the original order.
+    pub order_id: String,
+    /// How the order was replaced, or attempted to be.
+    pub method: ReplaceMethod,
+    /// The original as amended away or canceled, if that succeeded.
+    pub canceled: Option<Order>,
+    /// The replacement order (present on success).
+    pub order: Option<Order>,
+    /// Error details from whichever step failed.
+    pub error: Option<BatchOrderError>,
+}
+
+/// Aggregated response from [`BatchManager::amend_orders`].
+#[derive(Debug, Clone)]
+pub struct AggregatedAmendResponse {
+    /// All amend results, in input order.
+    pub orders: Vec<AmendOrderResult>,
+}
+
+impl AggregatedAmendResponse {
+    /// Returns an iterator over successfully amended orders.
+    pub fn successful_orders(&self) -> impl Iterator<Item = &Order> {
+        self.orders.iter().filter_map(|r| r.order.as_ref())
+    }
+
+    /// Returns an iterator over failed amendments with their order_id and error.
+    pub fn failed_orders(&self) -> impl Iterator<Item = (&str, &BatchOrderError)> {
+        self.orders
+            .iter()
+            .filter_map(|r| r.error.as_ref().map(|e| (r.order_id.as_str(), e)))
+    }
+
+    /// Returns the number of successfully amended orders.
+    pub fn success_count(&self) -> usize {
+        self.orders.iter().filter(|r| r.order.is_some()).count()
+    }
+
+    /// Returns the number of failed amendments.
+    pub fn failure_count(&self) -> usize {
+        self.orders.iter().filter(|r| r.error.is_some()).count()
+    }
+
+    /// Returns the total number of orders processed.
+    pub fn total_count(&self) -> usize {
+        self.orders.len()
+    }
+}
+
+/// Aggregated response from [`BatchManager::decrease_orders`].
+#[derive(Debug, Clone)]
+pub struct AggregatedDecreaseResponse {
+    /// All decrease results, in input order.
+    pub orders: Vec<DecreaseOrderResult>,
+}
+
+impl AggregatedDecreaseResponse {
+    /// Returns an iterator over successfully decreased orders.
+    pub fn successful_orders(&self) -> impl Iterator<Item = &Order> {
+        self.orders.iter().filter_map(|r| r.order.as_ref())
+    }
+
+    /// Returns an iterator over failed decreases with their order_id and error.
+    pub fn failed_orders(&self) -> impl Iterator<Item = (&str, &BatchOrderError)> {
+        self.orders
+            .iter()
+            .filter_map(|r| r.error.as_ref().map(|e| (r.order_id.as_str(), e)))
+    }
+
+    /// Returns the number of successfully decreased orders.
+    pub fn success_count(&self) -> usize {
+        self.orders.iter().filter(|r| r.order.is_some()).count()
+    }
+
+    /// Returns the number of failed decreases.
+    pub fn failure_count(&self) -> usize {
+        self.orders.iter().filter(|r| r.error.is_some()).count()
+    }
+
+    /// Returns the total number of orders processed.
+    pub fn total_count(&self) -> usize {
+        self.orders.len()
+    }
+}
+
+/// Aggregated response from [`BatchManager::replace_orders`].
+#[derive(Debug, Clone)]
+pub struct AggregatedReplaceResponse {
+    /// All replace results, in input order.
+    pub orders: Vec<ReplaceOrderResult>,
+}
+
+impl AggregatedReplaceResponse {
+    /// Returns an iterator over the replacement orders now resting or filled.
+    pub fn successful_orders(&self) -> impl Iterator<Item = &Order> {
+        self.orders.iter().filter_map(|r| r.order.as_ref())
+    }
+
+    /// Returns an iterator over failed replacements with the original
+    /// order_id and error.
+    pub fn failed_orders(&self) -> impl Iterator<Item = (&str, &BatchOrderError)> {
+        self.orders
+            .iter()
+            .filter_map(|r| r.error.as_ref().map(|e| (r.order_id.as_str(), e)))
+    }
+
+    /// Returns the number of orders replaced.
+    pub fn success_count(&self) -> usize {
+        self.orders.iter().filter(|r| r.order.is_some()).count()
+    }
+
+    /// Returns the number of failed replacements.
+    pub fn failure_count(&self) -> usize {
+        self.orders.iter().filter(|r| r.error.is_some()).count()
+    }
+
+    /// Returns the total number of orders processed.
+    pub fn total_count(&self) -> usize {
+        self.orders.len()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::client::mock_http::MockHttpServer;
 
     #[test]
     fn test_retry_config_delay_calculation() {
@@ -795,6 +1755,256 @@ mod tests {
         assert!((wait3.as_secs_f64() - 1.0).abs() < 0.1);
     }
 
+    fn test_manager(concurrency: usize) -> BatchManager {
+        let client = KalshiClient::new(crate::ws::mock_server::test_config()).unwrap();
+        BatchManager::builder(&client)
+            .tier(RateLimitTier::Prime)
+            .concurrency(concurrency)
+            .build()
+    }
+
+    #[tokio::test]
+    async fn test_continue_on_error_sends_every_chunk() {
+        let client = KalshiClient::new(crate::ws::mock_server::test_config()).unwrap();
+        let manager = BatchManager::builder(&client)
+            .tier(RateLimitTier::Prime)
+            .error_policy(BatchErrorPolicy::ContinueOnError)
+            .build();
+
+        let chunks = manager
+            .run_chunks_detailed(
+                (0..60).collect(),
+                MAX_BATCH_SIZE,
+                CANCEL_ORDER_COST,
+                |chunk: Vec<usize>| async move {
+                    if chunk[0] == 0 {
+                        return Err(Error::Api("400 Bad Request: invalid".to_string()));
+                    }
+                    Ok(chunk)
+                },
+            )
+            .await;
+
+        let sent: Vec<bool> = chunks
+            .iter()
+            .map(|(_, outcome)| matches!(outcome, Some(Ok(_))))
+            .collect();
+        assert_eq!(sent, vec![false, true, true]);
+    }
+
+    #[test]
+    fn test_outcomes_matched_and_resubmittable() {
+        use crate::models::{Action, Side};
+
+        let request = |id: &str| {
+            CreateOrderRequest::new("TEST", Side::Yes, Action::Buy, 1).client_order_id(id)
+        };
+        let rejected = |code: &str| BatchOrderError {
+            code: code.to_string(),
+            message: String::new(),
+            details: None,
+            service: None,
+        };
+        // Results out of order, matched back by client order ID
+        let results = vec![
+            BatchOrderResult {
+                client_order_id: Some("b".to_string()),
+                order: None,
+                error: Some(rejected("internal_server_error")),
+            },
+            BatchOrderResult {
+                client_order_id: Some("a".to_string()),
+                order: None,
+                error: Some(rejected("insufficient_balance")),
+            },
+        ];
+        let mut outcomes = match_results(vec![request("a"), request("b")], results);
+        outcomes.push(CreateOrderOutcome {
+            request: request("c"),
+            status: CreateOrderStatus::NotAttempted,
+        });
+        outcomes.push(CreateOrderOutcome {
+            request: request("d"),
+            status: CreateOrderStatus::TransportFailed {
+                error: "timed out".to_string(),
+                may_exist: true,
+            },
+        });
+
+        // A server error may have created the order; only a client order ID
+        // makes sending it again safe
+        let server_error = |request: CreateOrderRequest| CreateOrderOutcome {
+            request,
+            status: CreateOrderStatus::Rejected(rejected("internal_server_error")),
+        };
+        outcomes.push(server_error(request("e")));
+        assert!(
+            !server_error(CreateOrderRequest::new("TEST", Side::Yes, Action::Buy, 1))
+                .is_safe_to_resubmit()
+        );
+
+        let report = CreateOrdersReport { outcomes };
+        assert_eq!(report.outcomes[0].client_order_id(), Some("a"));
+        assert!(matches!(
+            &report.outcomes[0].status,
+            CreateOrderStatus::Rejected(e) if e.code == "insufficient_balance"
+        ));
+        assert!(!report.is_complete());
+        let resubmit: Vec<_> = report
+            .resubmittable()
+            .into_iter()
+            .map(|r| r.client_order_id.unwrap())
+            .collect();
+        assert_eq!(resubmit, vec!["b", "c", "e"]);
+    }
+
+    #[tokio::test]
+    async fn test_manager_moves_into_spawned_tasks() {
+        let manager = test_manager(1);
+        let task = tokio::spawn({
+            let manager = manager.clone();
+            async move {
+                manager.create_orders(Vec::new()).await.is_complete()
+                    && manager.cancel_orders(Vec::new()).await.is_complete()
+                    && manager
+                        .cancel_orders_with_items(Vec::new())
+                        .await
+                        .is_complete()
+                    && manager
+                        .create_orders_with_outcomes(Vec::new())
+                        .await
+                        .is_complete()
+                    && manager.amend_orders(Vec::new()).await.is_complete()
+                    && manager.decrease_orders(Vec::new()).await.is_complete()
+                    && manager
+                        .replace_orders(Vec::new(), ReplaceMode::AmendFirst)
+                        .await
+                        .is_complete()
+            }
+        });
+        assert!(task.await.unwrap());
+        assert!(Arc::ptr_eq(
+            &manager.rate_limiter,
+            &manager.clone().rate_limiter
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_chunks_keep_order() {
+        use std::sync::atomic::AtomicUsize;
+
+        let manager = test_manager(2);
+        let in_flight = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let items: Vec<usize> = (0..100).collect();
+
+        let (completed, error) = manager
+            .run_chunks(items.clone(), MAX_BATCH_SIZE, CANCEL_ORDER_COST, |chunk| {
+                let (in_flight, peak) = (&in_flight, &peak);
+                async move {
+                    let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                    Ok(chunk)
+                }
+            })
+            .await;
+
+        assert!(error.is_none());
+        assert_eq!(completed, items);
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_no_chunks_sent_after_failure() {
+        use std::sync::atomic::AtomicUsize;
+
+        let manager = test_manager(1);
+        let sent = AtomicUsize::new(0);
+
+        let (completed, error) = manager
+            .run_chunks(
+                (0..100).collect(),
+                MAX_BATCH_SIZE,
+                CANCEL_ORDER_COST,
+                |chunk: Vec<usize>| {
+                    let sent = &sent;
+                    async move {
+                        sent.fetch_add(1, Ordering::SeqCst);
+                        if chunk[0] == 20 {
+                            return Err(Error::Api("400 Bad Request: invalid".to_string()));
+                        }
+                        Ok(chunk)
+                    }
+                },
+            )
+            .await;
+
+        assert!(matches!(error, Some(Error::Api(_))));
+        assert_eq!(completed, (0..20).collect::<Vec<_>>());
+        assert_eq!(sent.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_order_rejection_classification() {
+        let rejected = order_rejection(&Error::Api(
+            r#"404 Not Found: {"error":{"code":"not_found","message":"order not found"}}"#
+                .to_string(),
+        ))
+        .unwrap();
+        assert_eq!(rejected.code, "not_found");
+        assert_eq!(rejected.message, "order not found");
+
+        let plain = order_rejection(&Error::Api("400 Bad Request: bad".to_string())).unwrap();
+        assert_eq!(
+            (plain.code.as_str(), plain.message.as_str()),
+            ("400", "bad")
+        );
+
+        // Failures that would hit every order stop processing instead
+        for msg in [
+            "429 Too Many Requests: slow down",
+            "401 Unauthorized: bad signature",
+            "503 Service Unavailable: down",
+            "JSON decode error: eof. Response: ",
+        ] {
+            assert!(
+                order_rejection(&Error::Api(msg.to_string())).is_none(),
+                "{msg}"
+            );
+        }
+        assert_eq!(
+            order_rejection(&Error::InvalidPrice(0)).unwrap().code,
+            "invalid_request"
+        );
+    }
+
+    #[test]
+    fn test_replacement_amendment() {
+        use crate::models::{Action, Side};
+
+        let replacement = OrderReplacement::new(
+            "order-1",
+            CreateOrderRequest::new("TEST", Side::No, Action::Buy, 7)
+                .no_price(40)
+                .client_order_id("new-id")
+                .subaccount(2),
+        );
+
+        let amendment = replacement.amendment();
+        assert_eq!(amendment.ticker, "TEST");
+        assert_eq!(amendment.side, Side::No);
+        assert_eq!(amendment.no_price, Some(40));
+        assert_eq!(amendment.count, Some(7));
+        assert_eq!(amendment.updated_client_order_id.as_deref(), Some("new-id"));
+        assert_eq!(amendment.subaccount, Some(2));
+
+        let item = replacement.cancel_item();
+        assert_eq!(item.order_id, "order-1");
+        assert_eq!(item.subaccount, Some(2));
+    }
+
     #[test]
     fn test_is_transient_api_error() {
         // Transient errors should be retried
@@ -880,4 +2090,203 @@ mod tests {
         assert_eq!(response.failure_count(), 1);
         assert!((response.total_reduced() - 15.0).abs() < f64::EPSILON);
     }
+    // =========================================================================
+    // Replacing orders against a mock server
+    // =========================================================================
+
+    fn canceled_order(order_id: &str, filled: &str) -> serde_json::Value {
+        serde_json::json!({
+            "order_id": order_id,
+            "user_id": "user",
+            "client_order_id": "",
+            "ticker": "TEST",
+            "side": "yes",
+            "action": "buy",
+            "type": "limit",
+            "status": "canceled",
+            "yes_price_dollars": "0.45",
+            "no_price_dollars": "0.55",
+            "fill_count_fp": filled,
+            "remaining_count_fp": "0.00",
+            "initial_count_fp": "10.00",
+            "taker_fill_cost_dollars": "0.00",
+            "maker_fill_cost_dollars": "0.00",
+        })
+    }
+
+    fn replacement() -> OrderReplacement {
+        use crate::models::{Action, Side};
+
+        OrderReplacement::new(
+            "a",
+            CreateOrderRequest::new("TEST", Side::Yes, Action::Buy, 10)
+                .yes_price(46)
+                .client_order_id("r1"),
+        )
+    }
+
+    async fn replace_against(
+        server: &MockHttpServer,
+    ) -> BatchOperationResult<AggregatedReplaceResponse> {
+        let manager = BatchManager::builder(&server.client())
+            .tier(RateLimitTier::Prime)
+            .build();
+        manager
+            .replace_orders(vec![replacement()], ReplaceMode::CancelThenCreate)
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_replace_after_partial_fill() {
+        let server = MockHttpServer::start().await;
+        server.respond(
+            "DELETE",
+            "/portfolio/orders/batched",
+            200,
+            serde_json::json!({ "orders": [{
+                "order_id": "a",
+                "reduced_by_fp": "6.00",
+                "order": canceled_order("a", "4.00"),
+            }] }),
+        );
+        let mut created = canceled_order("b", "0.00");
+        created["status"] = "resting".into();
+        server.respond(
+            "POST",
+            "/portfolio/orders/batched",
+            201,
+            serde_json::json!({ "orders": [{ "client_order_id": "r1", "order": created }] }),
+        );
+
+        let result = replace_against(&server).await;
+        assert!(result.error.is_none());
+        let replaced = &result.completed.orders[0];
+        assert!(replaced.canceled.is_some());
+        assert_eq!(replaced.order.as_ref().unwrap().order_id, "b");
+
+        // Only the part of the original that did not fill is replaced
+        let sent = server.requests_to("POST", "/portfolio/orders/batched");
+        assert_eq!(sent[0].body["orders"][0]["count"], 6);
+    }
+
+    #[tokio::test]
+    async fn test_replace_skips_create_when_cancel_fails() {
+        let server = MockHttpServer::start().await;
+        server.respond(
+            "DELETE",
+            "/portfolio/orders/batched",
+            200,
+            serde_json::json!({ "orders": [{
+                "order_id": "a",
+                "reduced_by_fp": "0.00",
+                "error": { "code": "not_found", "message": "order already executed" },
+            }] }),
+        );
+
+        let result = replace_against(&server).await;
+        let replaced = &result.completed.orders[0];
+        assert!(replaced.canceled.is_none());
+        assert!(replaced.order.is_none());
+        assert_eq!(replaced.error.as_ref().unwrap().code, "not_found");
+        assert!(
+            server
+                .requests_to("POST", "/portfolio/orders/batched")
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_replace_reports_canceled_when_create_fails() {
+        let server = MockHttpServer::start().await;
+        server.respond(
+            "DELETE",
+            "/portfolio/orders/batched",
+            200,
+            serde_json::json!({ "orders": [{
+                "order_id": "a",
+                "reduced_by_fp": "10.00",
+                "order": canceled_order("a", "0.00"),
+            }] }),
+        );
+        server.respond(
+            "POST",
+            "/portfolio/orders/batched",
+            400,
+            serde_json::json!({ "error": { "code": "insufficient_balance", "message": "" } }),
+        );
+
+        let result = replace_against(&server).await;
+        assert!(result.error.is_some());
+        let replaced = &result.completed.orders[0];
+        assert_eq!(replaced.canceled.as_ref().unwrap().order_id, "a");
+        assert!(replaced.order.is_none());
+        let sent = server.requests_to("POST", "/portfolio/orders/batched");
+        assert_eq!(sent[0].body["orders"][0]["count"], 10);
+    }
+
+    #[tokio::test]
+    async fn test_amend_first_falls_back_after_failed_amend() {
+        use crate::models::{Action, Side};
+
+        let server = MockHttpServer::start().await;
+        server.respond(
+            "POST",
+            "/portfolio/orders/a/amend",
+            500,
+            serde_json::json!({ "error": { "code": "internal_server_error", "message": "" } }),
+        );
+        let mut amended = canceled_order("c", "0.00");
+        amended["status"] = "resting".into();
+        server.respond(
+            "POST",
+            "/portfolio/orders/c/amend",
+            200,
+            serde_json::json!({ "old_order": canceled_order("c", "0.00"), "order": amended }),
+        );
+        server.respond(
+            "DELETE",
+            "/portfolio/orders/batched",
+            200,
+            serde_json::json!({ "orders": [{
+                "order_id": "a",
+                "reduced_by_fp": "10.00",
+                "order": canceled_order("a", "0.00"),
+            }] }),
+        );
+        let mut created = canceled_order("b", "0.00");
+        created["status"] = "resting".into();
+        server.respond(
+            "POST",
+            "/portfolio/orders/batched",
+            201,
+            serde_json::json!({ "orders": [{ "client_order_id": "r1", "order": created }] }),
+        );
+
+        let manager = BatchManager::builder(&server.client())
+            .tier(RateLimitTier::Prime)
+            .retry_config(RetryConfig::no_retries())
+            .error_policy(BatchErrorPolicy::ContinueOnError)
+            .build();
+        let other = OrderReplacement::new(
+            "c",
+            CreateOrderRequest::new("TEST", Side::Yes, Action::Buy, 10)
+                .yes_price(46)
+                .client_order_id("r2"),
+        );
+        let result = manager
+            .replace_orders(vec![replacement(), other], ReplaceMode::AmendFirst)
+            .await;
+
+        assert!(result.error.is_some());
+        let orders = &result.completed.orders;
+        assert_eq!(orders.len(), 2);
+        assert_eq!(orders[0].order_id, "a");
+        assert_eq!(orders[0].method, ReplaceMethod::Recreated);
+        assert_eq!(orders[0].order.as_ref().unwrap().order_id, "b");
+        assert_eq!(orders[1].order_id, "c");
+        assert_eq!(orders[1].method, ReplaceMethod::Amended);
+        let canceled = server.requests_to("DELETE", "/portfolio/orders/batched");
+        assert_eq!(canceled.len(), 1);
+        assert_eq!(canceled[0].body["orders"][0]["order_id"], "a");
+    }
 }
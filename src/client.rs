@@ -1,4 +1,6 @@
 mod http;
+#[cfg(test)]
+pub(crate) mod mock_http;
 mod websocket;
 
 pub use http::HttpClient;
@@ -48,6 +50,7 @@ use crate::{
         TradesResponse, TransferBetweenSubaccountsRequest, TransferResponse,
         UpdateOrderGroupLimitRequest, UpdateSubaccountNettingRequest, UserDataTimestampResponse,
     },
+    orders::{CancelAllFilter, CancelAllReport, ClientOrderIdGenerator},
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -106,6 +109,7 @@ impl Environment {
 #[derive(Debug, Clone)]
 pub struct KalshiClient {
     http: HttpClient,
+    client_order_ids: Option<ClientOrderIdGenerator>,
 }
 
 impl KalshiClient {
@@ -120,7 +124,47 @@ impl KalshiClient {
     /// Returns an error if the HTTP client cannot be created.
     pub fn new(config: KalshiConfig) -> Result<Self> {
         let http = HttpClient::new(config)?;
-        Ok(Self { http })
+        Ok(Self {
+            http,
+            client_order_ids: None,
+        })
+    }
+
+    /// Send REST requests to `base_url` instead of the environment's.
+    #[cfg(test)]
+    pub(crate) fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.http = self.http.with_base_url(base_url);
+        self
+    }
+
+    /// Stamp orders with client order IDs and recover ambiguous submissions.
+    ///
+    /// With a generator set, [`create_order`](Self::create_order) and
+    /// [`batch_create_orders`](Self::batch_create_orders) give every order
+    /// without a `client_order_id` one from `generator`. If a submission then
+    /// fails in a way that leaves its outcome unknown (a transport error or a
+    /// 5xx response), the orders are looked up by client order ID and only
+    /// those that were not created are resubmitted, once.
+    ///
+    /// Clone the client with a different generator per strategy to tell
+    /// their orders apart:
+    ///
+    /// ```ignore
+    /// use kalshi_trade_rs::ClientOrderIdGenerator;
+    ///
+    /// let market_maker = client
+    ///     .clone()
+    ///     .with_client_order_ids(ClientOrderIdGenerator::new("mm")?);
+    /// ```
+    #[must_use]
+    pub fn with_client_order_ids(mut self, generator: ClientOrderIdGenerator) -> Self {
+        self.client_order_ids = Some(generator);
+        self
+    }
+
+    /// The client order ID generator, if one is set.
+    pub fn client_order_ids(&self) -> Option<&ClientOrderIdGenerator> {
+        self.client_order_ids.as_ref()
     }
 
     /// Get the underlying HTTP client for advanced usage.
@@ -763,8 +807,45 @@ impl KalshiClient {
     /// let response = client.create_order(request).await?;
     /// println!("Order created: {}", response.order.order_id);
     /// ```
-    pub async fn create_order(&self, request: CreateOrderRequest) -> Result<OrderResponse> {
-        orders::create_order(&self.http, request).await
+    ///
+    /// With [client order IDs](Self::with_client_order_ids) enabled, a failed
+    /// submission that may have reached the exchange is looked up before it
+    /// is resent. If the resend is rejected because the first attempt turned
+    /// up after the lookup, the existing order is returned.
+    pub async fn create_order(&self, mut request: CreateOrderRequest) -> Result<OrderResponse> {
+        let Some(generator) = &self.client_order_ids else {
+            return orders::create_order(&self.http, request).await;
+        };
+        generator.stamp(std::slice::from_mut(&mut request));
+        let since = chrono::Utc::now().timestamp() - crate::orders::LOOKUP_SLACK_SECS;
+
+        match orders::create_order(&self.http, request.clone()).await {
+            Err(e) if crate::orders::is_ambiguous(&e) => {
+                let Ok(mut found) =
+                    crate::orders::find_created(self, std::slice::from_ref(&request), since).await
+                else {
+                    return Err(e);
+                };
+                let id = request.client_order_id.clone().unwrap_or_default();
+                if let Some(order) = found.remove(&id) {
+                    return Ok(OrderResponse { order });
+                }
+                tracing::debug!(client_order_id = %id, error = %e, "order not created, resubmitting");
+                match orders::create_order(&self.http, request.clone()).await {
+                    // The first attempt was created after the lookup ran
+                    Err(duplicate) if crate::orders::is_duplicate(&duplicate) => {
+                        crate::orders::find_created(self, std::slice::from_ref(&request), since)
+                            .await
+                            .ok()
+                            .and_then(|mut found| found.remove(&id))
+                            .map(|order| OrderResponse { order })
+                            .ok_or(duplicate)
+                    }
+                    result => result,
+                }
+            }
+            result => result,
+        }
     }
 
     /// Get a specific order by ID.
@@ -902,11 +983,51 @@ impl KalshiClient {
     ///     }
     /// }
     /// ```
+    ///
+    /// With [client order IDs](Self::with_client_order_ids) enabled, a failed
+    /// batch that may have reached the exchange is looked up and only the
+    /// orders that were not created are resent. Resent orders rejected as
+    /// duplicates are looked up again and reported as created.
     pub async fn batch_create_orders(
         &self,
-        request: BatchCreateOrdersRequest,
+        mut request: BatchCreateOrdersRequest,
     ) -> Result<BatchCreateOrdersResponse> {
-        orders::batch_create_orders(&self.http, request).await
+        let Some(generator) = &self.client_order_ids else {
+            return orders::batch_create_orders(&self.http, request).await;
+        };
+        generator.stamp(&mut request.orders);
+        let since = chrono::Utc::now().timestamp() - crate::orders::LOOKUP_SLACK_SECS;
+
+        match orders::batch_create_orders(&self.http, request.clone()).await {
+            Err(e) if crate::orders::is_ambiguous(&e) => {
+                let Ok(found) = crate::orders::find_created(self, &request.orders, since).await
+                else {
+                    return Err(e);
+                };
+                let missing = crate::orders::missing(&request.orders, &found);
+                tracing::debug!(
+                    created = found.len(),
+                    resubmitting = missing.len(),
+                    error = %e,
+                    "recovering batch create"
+                );
+                let sent = if missing.is_empty() {
+                    Vec::new()
+                } else {
+                    let sent = orders::batch_create_orders(
+                        &self.http,
+                        BatchCreateOrdersRequest::new(missing.clone()),
+                    )
+                    .await?
+                    .orders;
+                    crate::orders::resolve_duplicates(self, &missing, since, sent).await
+                };
+                Ok(BatchCreateOrdersResponse {
+                    orders: crate::orders::merge_batch_results(&request.orders, &found, sent),
+                })
+            }
+            result => result,
+        }
     }
 
     /// Cancel multiple orders in a single request.
@@ -938,6 +1059,48 @@ impl KalshiClient {
         orders::batch_cancel_orders(&self.http, request).await
     }
 
+    /// Cancel every resting order matching `filter`.
+    ///
+    /// A kill switch for incidents. Resting orders are listed with paging and
+    /// canceled in batches as fast as the filter's
+    /// [rate limit tier](CancelAllFilter::tier) allows, each in its own
+    /// subaccount. When the filter does not restrict markets, the order
+    /// groups of the listed orders are triggered as well, which cancels
+    /// their orders in one request, unless
+    /// [`trigger_groups(false)`](CancelAllFilter::trigger_groups) is set.
+    /// After each round the orders are listed again, and anything still
+    /// resting is retried, up to three rounds.
+    ///
+    /// Triggered groups are left triggered, so they refuse new orders until
+    /// [`reset_order_group`](Self::reset_order_group) is called for each
+    /// group in the report's `triggered_groups`.
+    ///
+    /// Errors from individual cancels do not stop the call; orders that
+    /// could not be canceled are listed in the report's `failed`. An error is
+    /// only returned if the orders cannot be listed.
+    ///
+    /// Each call paces its cancels on its own. To share the rate limit with
+    /// other batch requests, use [`BatchManager::cancel_all`](crate::BatchManager::cancel_all)
+    /// instead.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use kalshi_trade_rs::{CancelAllFilter, RateLimitTier};
+    ///
+    /// let report = client
+    ///     .cancel_all(CancelAllFilter::new().tier(RateLimitTier::Advanced))
+    ///     .await?;
+    /// if !report.is_complete() {
+    ///     for failure in &report.failed {
+    ///         eprintln!("still resting: {} ({:?})", failure.order_id, failure.reason);
+    ///     }
+    /// }
+    /// ```
+    pub async fn cancel_all(&self, filter: CancelAllFilter) -> Result<CancelAllReport> {
+        crate::orders::cancel_all(self, &filter).await
+    }
+
     /// Get queue positions for all resting orders.
     ///
     /// Queue position represents the number of contracts that need to be matched
@@ -31,6 +31,13 @@ impl HttpClient {
         })
     }
 
+    /// Send requests to `base_url` instead of the environment's.
+    #[cfg(test)]
+    pub(crate) fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
     /// Get the current timestamp in milliseconds.
     fn current_timestamp_ms() -> u64 {
         SystemTime::now()
@@ -0,0 +1,219 @@
+//! In-process mock Kalshi REST server for tests.
+//!
+//! [`MockHttpServer`] listens on a local TCP port and answers each request
+//! from scripted responses keyed by method and path. Every request is
+//! recorded so tests can assert what the client sent.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let server = MockHttpServer::start().await;
+//! server.respond("GET", "/portfolio/balance", 200, json!({ "balance": 100 }));
+//!
+//! let client = server.client();
+//! let balance = client.get_balance().await?;
+//! assert_eq!(server.requests_to("GET", "/portfolio/balance").len(), 1);
+//! ```
+
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use serde_json::{Value as JsonValue, json};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    task::JoinHandle,
+};
+
+use super::KalshiClient;
+use crate::ws::mock_server::test_config;
+
+/// A request received by the mock server.
+#[derive(Debug, Clone)]
+pub(crate) struct MockRequest {
+    pub method: String,
+    /// Path without the query string.
+    pub path: String,
+    /// JSON body, or null if there was none.
+    pub body: JsonValue,
+}
+
+#[derive(Debug, Clone)]
+struct MockResponse {
+    status: u16,
+    body: JsonValue,
+}
+
+#[derive(Debug, Default)]
+struct Routes {
+    once: HashMap<(String, String), VecDeque<MockResponse>>,
+    always: HashMap<(String, String), MockResponse>,
+    requests: Vec<MockRequest>,
+}
+
+impl Routes {
+    fn respond(&mut self, request: MockRequest) -> MockResponse {
+        let key = (request.method.clone(), request.path.clone());
+        self.requests.push(request);
+        if let Some(response) = self.once.get_mut(&key).and_then(VecDeque::pop_front) {
+            return response;
+        }
+        self.always.get(&key).cloned().unwrap_or(MockResponse {
+            status: 404,
+            body: json!({ "error": { "code": "not_found", "message": "no mock route" } }),
+        })
+    }
+}
+
+/// A local HTTP server answering Kalshi REST requests from a script.
+pub(crate) struct MockHttpServer {
+    addr: SocketAddr,
+    routes: Arc<Mutex<Routes>>,
+    accept_task: JoinHandle<()>,
+}
+
+impl MockHttpServer {
+    /// Bind to an ephemeral local port and start serving.
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind mock http server");
+        let addr = listener.local_addr().expect("mock http server address");
+        let routes = Arc::new(Mutex::new(Routes::default()));
+
+        let accept_routes = routes.clone();
+        let accept_task = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve(stream, accept_routes.clone()));
+            }
+        });
+
+        Self {
+            addr,
+            routes,
+            accept_task,
+        }
+    }
+
+    /// A client whose REST requests go to this server.
+    pub fn client(&self) -> KalshiClient {
+        KalshiClient::new(test_config())
+            .expect("test client")
+            .with_base_url(format!("http://{}", self.addr))
+    }
+
+    /// Answer every `method` request to `path` with `status` and `body`.
+    ///
+    /// Replaces an earlier response for the same route.
+    pub fn respond(&self, method: &str, path: &str, status: u16, body: JsonValue) {
+        self.lock().always.insert(
+            (method.to_string(), path.to_string()),
+            MockResponse { status, body },
+        );
+    }
+
+    /// Answer the next `method` request to `path` with `status` and `body`.
+    ///
+    /// One-shot responses are used in the order they were added, before
+    /// any response set with [`respond`](Self::respond).
+    pub fn respond_once(&self, method: &str, path: &str, status: u16, body: JsonValue) {
+        self.lock()
+            .once
+            .entry((method.to_string(), path.to_string()))
+            .or_default()
+            .push_back(MockResponse { status, body });
+    }
+
+    /// Requests received for one route.
+    pub fn requests_to(&self, method: &str, path: &str) -> Vec<MockRequest> {
+        self.lock()
+            .requests
+            .iter()
+            .filter(|r| r.method == method && r.path == path)
+            .cloned()
+            .collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Routes> {
+        self.routes.lock().expect("mock http lock poisoned")
+    }
+}
+
+impl Drop for MockHttpServer {
+    fn drop(&mut self) {
+        self.accept_task.abort();
+    }
+}
+
+/// Serve requests on one connection until the client closes it.
+async fn serve(mut stream: TcpStream, routes: Arc<Mutex<Routes>>) {
+    let mut buf = Vec::new();
+    loop {
+        let Some(request) = read_request(&mut stream, &mut buf).await else {
+            return;
+        };
+        let response = routes
+            .lock()
+            .expect("mock http lock poisoned")
+            .respond(request);
+
+        let body = response.body.to_string();
+        let head = format!(
+            "HTTP/1.1 {} Mock\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n",
+            response.status,
+            body.len()
+        );
+        if stream.write_all(head.as_bytes()).await.is_err()
+            || stream.write_all(body.as_bytes()).await.is_err()
+        {
+            return;
+        }
+    }
+}
+
+/// Read one request, keeping any bytes past it in `buf`.
+async fn read_request(stream: &mut TcpStream, buf: &mut Vec<u8>) -> Option<MockRequest> {
+    let header_end = loop {
+        if let Some(end) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break end + 4;
+        }
+        read_more(stream, buf).await?;
+    };
+
+    let head = String::from_utf8_lossy(&buf[..header_end]).into_owned();
+    let mut lines = head.lines();
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let target = request_line.next()?.to_string();
+    let content_length = lines
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+        .unwrap_or(0);
+
+    while buf.len() < header_end + content_length {
+        read_more(stream, buf).await?;
+    }
+    let body = serde_json::from_slice(&buf[header_end..header_end + content_length])
+        .unwrap_or(JsonValue::Null);
+    buf.drain(..header_end + content_length);
+
+    let path = target.split('?').next().unwrap_or(&target);
+    Some(MockRequest {
+        method,
+        path: path.to_string(),
+        body,
+    })
+}
+
+async fn read_more(stream: &mut TcpStream, buf: &mut Vec<u8>) -> Option<()> {
+    let mut chunk = [0u8; 4096];
+    match stream.read(&mut chunk).await {
+        Ok(0) | Err(_) => None,
+        Ok(n) => {
+            buf.extend_from_slice(&chunk[..n]);
+            Some(())
+        }
+    }
+}
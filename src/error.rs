@@ -152,6 +152,21 @@ pub enum Error {
     #[error("Failed to read private key file '{0}': {1}")]
     PrivateKeyFileError(String, String),
 
+    #[error("Orderbook checkpoint file '{0}': {1}")]
+    CheckpointFileError(String, String),
+
+    #[error("Contingent order store '{0}': {1}")]
+    ContingentStoreError(String, String),
+
+    #[error("Invalid client order ID prefix '{0}': use up to 16 ASCII letters, digits, '-' or '_'")]
+    InvalidClientOrderIdPrefix(String),
+
+    #[error("Order rejected by risk check: {0}")]
+    RiskRejected(crate::orders::RiskViolation),
+
+    #[error("Dead man's switch tripped: {0}")]
+    SwitchTripped(crate::orders::TripReason),
+
     #[error("Invalid header value: {0}")]
     InvalidHeaderValue(String),
 
@@ -208,6 +223,15 @@ pub enum Error {
 
     #[error("Invalid target cost dollars {0}: must be positive")]
     InvalidTargetCostDollars(f64),
+
+    #[error("Series {0} uses a fee type this client cannot price")]
+    UnsupportedFeeType(String),
+
+    #[error("Fee schedule of series {0} before its first fee change is not known")]
+    UnknownFeeSchedule(String),
+
+    #[error("Order has no limit price")]
+    MissingOrderPrice,
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
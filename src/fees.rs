@@ -0,0 +1,679 @@
+//! Trading fee calculation.
+//!
+//! Kalshi charges fees per order based on the series' fee schedule:
+//!
+//! - [`FeeType::Quadratic`]: takers pay `ceil(0.07 * C * P * (1 - P))`
+//!   dollars, where `C` is the number of contracts and `P` the price in
+//!   dollars. Makers pay nothing.
+//! - [`FeeType::QuadraticWithMakerFees`]: as above, and makers pay
+//!   `ceil(0.0175 * C * P * (1 - P))`.
+//! - [`FeeType::Flat`]: every fill pays a fixed rate per contract. The API
+//!   does not publish the rate, so it must be set in [`FeeRates::flat`].
+//!
+//! Rates are scaled by the series' `fee_multiplier` and rounded up to the
+//! next cent. [`FeeRates`] holds the base rates if they ever need
+//! overriding.
+//!
+//! [`MarketFees`] combines a market's fee waiver, its series' schedule and
+//! the series' scheduled fee changes, so a fee can be computed for any
+//! point in time. [`FeeCalculator`] resolves markets to their series over
+//! REST and caches the result.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use chrono::Utc;
+//! use kalshi_trade_rs::{
+//!     KalshiClient, KalshiConfig,
+//!     fees::{FeeCalculator, FeeRole},
+//! };
+//!
+//! # async fn example() -> kalshi_trade_rs::Result<()> {
+//! let client = KalshiClient::new(KalshiConfig::from_env()?)?;
+//! let fees = FeeCalculator::new(client);
+//!
+//! // Taker fee for 100 contracts at 45 cents
+//! let fee = fees
+//!     .fee_cents("KXBTC-25JAN", 45, 100, FeeRole::Taker, Utc::now())
+//!     .await?;
+//!
+//! // Lowest price at which selling them again covers both fees
+//! let market = fees.market("KXBTC-25JAN").await?;
+//! let exit = market.break_even_exit(45, 100, FeeRole::Taker, FeeRole::Maker, Utc::now());
+//! println!("fee {fee}c, break-even exit {exit:?}");
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+
+use crate::client::KalshiClient;
+use crate::error::{Error, Result};
+use crate::models::{
+    Action, CreateOrderRequest, FeeType, GetFeeChangesParams, Market, Series, SeriesFeeChange, Side,
+};
+use crate::units::{contracts, whole_cents};
+
+/// Base fee rates, before the series multiplier.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeeRates {
+    /// Quadratic taker rate.
+    ///
+    /// Default: 0.07.
+    pub taker: f64,
+
+    /// Quadratic maker rate, charged only by
+    /// [`FeeType::QuadraticWithMakerFees`] series.
+    ///
+    /// Default: 0.0175.
+    pub maker: f64,
+
+    /// Dollars per contract for [`FeeType::Flat`] series, from Kalshi's
+    /// published fee schedule.
+    ///
+    /// Default: unset, so fees for flat-fee series cannot be computed.
+    pub flat: Option<f64>,
+}
+
+impl Default for FeeRates {
+    fn default() -> Self {
+        Self {
+            taker: 0.07,
+            maker: 0.0175,
+            flat: None,
+        }
+    }
+}
+
+/// Whether an order adds liquidity or takes it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeeRole {
+    /// The order rested on the book and was matched later.
+    Maker,
+    /// The order matched a resting order on arrival.
+    Taker,
+}
+
+/// Round a fee in dollars up to the next cent.
+fn round_up_cents(fee_dollars: f64) -> i64 {
+    // Guard against float noise pushing an exact cent amount up a cent
+    (fee_dollars * 100.0 - 1e-9).ceil().max(0.0) as i64
+}
+
+/// Quadratic fee `ceil(rate * contracts * P * (1 - P))` in cents, with `P`
+/// the price in dollars.
+///
+/// The formula is symmetric in `P`, so the price may be on either side.
+pub fn quadratic_fee_cents(rate: f64, price_cents: i64, contracts: i64) -> i64 {
+    let price = price_cents as f64 / 100.0;
+    round_up_cents(rate * contracts as f64 * price * (1.0 - price))
+}
+
+/// A series' fee type and multiplier.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeeSchedule {
+    /// How fees are computed.
+    pub fee_type: FeeType,
+    /// Multiplier applied to the base rates.
+    pub multiplier: f64,
+}
+
+impl FeeSchedule {
+    /// A schedule of `fee_type` scaled by `multiplier`.
+    pub fn new(fee_type: FeeType, multiplier: f64) -> Self {
+        Self {
+            fee_type,
+            multiplier,
+        }
+    }
+
+    /// The schedule a series currently charges.
+    ///
+    /// Fee types this crate does not know become [`FeeType::Unknown`].
+    pub fn from_series(series: &Series) -> Self {
+        let fee_type = serde_json::from_value(serde_json::Value::String(series.fee_type.clone()))
+            .unwrap_or(FeeType::Unknown);
+        Self::new(fee_type, series.fee_multiplier)
+    }
+
+    /// The schedule a fee change switches to.
+    pub fn from_change(change: &SeriesFeeChange) -> Self {
+        Self::new(change.fee_type, change.fee_multiplier)
+    }
+
+    /// Fee in cents for one order, or `None` for an unknown fee type or a
+    /// flat fee without a configured rate.
+    ///
+    /// `price_cents` is the fill price on either side.
+    pub fn fee_cents(
+        &self,
+        rates: &FeeRates,
+        role: FeeRole,
+        price_cents: i64,
+        contracts: i64,
+    ) -> Option<i64> {
+        let fee = match (self.fee_type, role) {
+            (FeeType::Quadratic | FeeType::QuadraticWithMakerFees, FeeRole::Taker) => {
+                quadratic_fee_cents(rates.taker * self.multiplier, price_cents, contracts)
+            }
+            (FeeType::QuadraticWithMakerFees, FeeRole::Maker) => {
+                quadratic_fee_cents(rates.maker * self.multiplier, price_cents, contracts)
+            }
+            (FeeType::Quadratic, FeeRole::Maker) => 0,
+            (FeeType::Flat, _) => round_up_cents(rates.flat? * self.multiplier * contracts as f64),
+            _ => return None,
+        };
+        Some(fee)
+    }
+}
+
+/// Everything needed to price fees in one market.
+#[derive(Debug, Clone)]
+pub struct MarketFees {
+    /// Market ticker.
+    pub ticker: String,
+    /// Ticker of the market's series.
+    pub series_ticker: String,
+    /// Fees are waived for trades before this time.
+    pub fee_waiver_expiration: Option<DateTime<Utc>>,
+    /// The schedule in force when the series was fetched.
+    pub schedule: FeeSchedule,
+    /// The schedule in force before the first change.
+    ///
+    /// The series' schedule if no change had taken effect when it was
+    /// fetched. Otherwise the series no longer reports it, so it is `None`
+    /// unless set with [`with_base_schedule`](Self::with_base_schedule).
+    pub base_schedule: Option<FeeSchedule>,
+    /// Scheduled changes as (effective time, new schedule), oldest first.
+    pub changes: Vec<(DateTime<Utc>, FeeSchedule)>,
+    /// Base rates.
+    pub rates: FeeRates,
+}
+
+impl MarketFees {
+    /// Combine a market, its series and the series' fee changes.
+    ///
+    /// Changes for other series and with unparseable times are ignored.
+    pub fn new(market: &Market, series: &Series, changes: &[SeriesFeeChange]) -> Self {
+        let mut changes: Vec<(DateTime<Utc>, FeeSchedule)> = changes
+            .iter()
+            .filter(|change| change.series_ticker == series.ticker)
+            .filter_map(|change| {
+                Some((
+                    parse_time(&change.scheduled_ts)?,
+                    FeeSchedule::from_change(change),
+                ))
+            })
+            .collect();
+        changes.sort_by_key(|(at, _)| *at);
+
+        let schedule = FeeSchedule::from_series(series);
+        Self {
+            ticker: market.ticker.clone(),
+            series_ticker: series.ticker.clone(),
+            fee_waiver_expiration: market
+                .fee_waiver_expiration_time
+                .as_deref()
+                .and_then(parse_time),
+            schedule,
+            base_schedule: base_schedule(schedule, &changes, Utc::now()),
+            changes,
+            rates: FeeRates::default(),
+        }
+    }
+
+    /// Use different base rates.
+    #[must_use]
+    pub fn with_rates(mut self, rates: FeeRates) -> Self {
+        self.rates = rates;
+        self
+    }
+
+    /// Set the schedule in force before the first change.
+    #[must_use]
+    pub fn with_base_schedule(mut self, schedule: FeeSchedule) -> Self {
+        self.base_schedule = Some(schedule);
+        self
+    }
+
+    /// The schedule in force at `at`: the latest change effective by then,
+    /// or the [base schedule](Self::base_schedule) before the first change.
+    pub fn schedule_at(&self, at: DateTime<Utc>) -> Option<FeeSchedule> {
+        self.changes
+            .iter()
+            .rev()
+            .find(|(effective, _)| *effective <= at)
+            .map_or(self.base_schedule, |(_, schedule)| Some(*schedule))
+    }
+
+    /// Whether fees are waived at `at`.
+    pub fn is_waived(&self, at: DateTime<Utc>) -> bool {
+        self.fee_waiver_expiration
+            .is_some_and(|expiration| at < expiration)
+    }
+
+    /// Fee in cents for an order of `contracts` filled at `price_cents` at
+    /// time `at`, or `None` for an unknown fee type or schedule.
+    pub fn fee_cents(
+        &self,
+        price_cents: i64,
+        contracts: i64,
+        role: FeeRole,
+        at: DateTime<Utc>,
+    ) -> Option<i64> {
+        if self.is_waived(at) {
+            return Some(0);
+        }
+        self.schedule_at(at)?
+            .fee_cents(&self.rates, role, price_cents, contracts)
+    }
+
+    /// Lowest price at which selling `contracts` bought at `entry_price`
+    /// returns at least the cost plus both fees.
+    ///
+    /// Prices are on the side held. Returns `None` if no price up to 99
+    /// breaks even or the fee type is unknown.
+    pub fn break_even_exit(
+        &self,
+        entry_price: i64,
+        contracts: i64,
+        entry_role: FeeRole,
+        exit_role: FeeRole,
+        at: DateTime<Utc>,
+    ) -> Option<i64> {
+        let cost =
+            entry_price * contracts + self.fee_cents(entry_price, contracts, entry_role, at)?;
+        (entry_price.max(1)..=99).find_map(|exit| {
+            let proceeds = exit * contracts - self.fee_cents(exit, contracts, exit_role, at)?;
+            (proceeds >= cost).then_some(exit)
+        })
+    }
+
+    /// Expected profit in cents of trading `contracts` at `price_cents`
+    /// against a fair value, after fees.
+    ///
+    /// `fair_value_cents` is the value of one contract on the order's side,
+    /// for example 100 times the probability it settles in the money.
+    /// Returns `None` for an unknown fee type.
+    pub fn net_edge_cents(
+        &self,
+        action: Action,
+        price_cents: i64,
+        contracts: i64,
+        fair_value_cents: f64,
+        role: FeeRole,
+        at: DateTime<Utc>,
+    ) -> Option<f64> {
+        let per_contract = match action {
+            Action::Buy => fair_value_cents - price_cents as f64,
+            Action::Sell => price_cents as f64 - fair_value_cents,
+        };
+        let fee = self.fee_cents(price_cents, contracts, role, at)?;
+        Some(per_contract * contracts as f64 - fee as f64)
+    }
+}
+
+/// The schedule before the first of `changes`, given the series' schedule
+/// at `now`, or `None` if a change had already replaced it.
+fn base_schedule(
+    schedule: FeeSchedule,
+    changes: &[(DateTime<Utc>, FeeSchedule)],
+    now: DateTime<Utc>,
+) -> Option<FeeSchedule> {
+    match changes.first() {
+        Some((effective, _)) if *effective <= now => None,
+        _ => Some(schedule),
+    }
+}
+
+/// The price on the order's side in cents and the number of contracts.
+fn order_fill(order: &CreateOrderRequest) -> Result<(i64, i64)> {
+    let yes = order
+        .yes_price
+        .or_else(|| order.yes_price_dollars.as_deref().and_then(whole_cents));
+    let no = order
+        .no_price
+        .or_else(|| order.no_price_dollars.as_deref().and_then(whole_cents));
+    let price = match order.side {
+        Side::Yes => yes.or_else(|| no.map(|p| 100 - p)),
+        Side::No => no.or_else(|| yes.map(|p| 100 - p)),
+    }
+    .ok_or(Error::MissingOrderPrice)?;
+    let count = order.count_fp.as_deref().map_or(order.count, contracts);
+    Ok((price, count))
+}
+
+fn parse_time(ts: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(ts)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// Computes fees for any market, fetching and caching fee data as needed.
+///
+/// The first lookup for a market fetches the market, its event, its series
+/// and the series' fee changes, including past ones. Results are cached
+/// until [`clear`](Self::clear) is called.
+///
+/// Clones share the cache.
+#[derive(Clone)]
+pub struct FeeCalculator {
+    client: KalshiClient,
+    rates: FeeRates,
+    base_schedules: HashMap<String, FeeSchedule>,
+    markets: Arc<Mutex<HashMap<String, MarketFees>>>,
+}
+
+impl FeeCalculator {
+    /// Create a calculator with the default rates.
+    pub fn new(client: KalshiClient) -> Self {
+        Self {
+            client,
+            rates: FeeRates::default(),
+            base_schedules: HashMap::new(),
+            markets: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Use different base rates.
+    #[must_use]
+    pub fn with_rates(mut self, rates: FeeRates) -> Self {
+        self.rates = rates;
+        self
+    }
+
+    /// Price fees before a series' first fee change with `schedule`.
+    ///
+    /// Needed only for times before a change that had already taken effect
+    /// when the series was fetched; the series no longer reports the
+    /// schedule it replaced.
+    #[must_use]
+    pub fn with_base_schedule(
+        mut self,
+        series_ticker: impl Into<String>,
+        schedule: FeeSchedule,
+    ) -> Self {
+        self.base_schedules.insert(series_ticker.into(), schedule);
+        self
+    }
+
+    /// Fee data for a market, fetched on first use.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the market, event, series or fee changes cannot
+    /// be fetched.
+    pub async fn market(&self, ticker: &str) -> Result<MarketFees> {
+        if let Some(fees) = self.lock().get(ticker) {
+            return Ok(fees.clone());
+        }
+
+        let market = self.client.get_market(ticker).await?.market;
+        let event = self.client.get_event(&market.event_ticker).await?.event;
+        let series = self.client.get_series(&event.series_ticker).await?.series;
+        let changes = self
+            .client
+            .get_fee_changes_with_params(
+                GetFeeChangesParams::new()
+                    .series_ticker(&series.ticker)
+                    .show_historical(true),
+            )
+            .await?
+            .series_fee_change_arr;
+
+        let mut fees = MarketFees::new(&market, &series, &changes).with_rates(self.rates);
+        if fees.base_schedule.is_none()
+            && let Some(&schedule) = self.base_schedules.get(&series.ticker)
+        {
+            fees = fees.with_base_schedule(schedule);
+        }
+        self.lock().insert(ticker.to_string(), fees.clone());
+        Ok(fees)
+    }
+
+    /// Fee in cents for `contracts` of `ticker` filled at `price_cents` at
+    /// time `at`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the market's fee data cannot be fetched,
+    /// [`Error::UnknownFeeSchedule`] if `at` is before a change that
+    /// replaced a schedule the series no longer reports, or
+    /// [`Error::UnsupportedFeeType`] if the series uses a fee type this
+    /// crate does not know.
+    pub async fn fee_cents(
+        &self,
+        ticker: &str,
+        price_cents: i64,
+        contracts: i64,
+        role: FeeRole,
+        at: DateTime<Utc>,
+    ) -> Result<i64> {
+        let fees = self.market(ticker).await?;
+        if !fees.is_waived(at) && fees.schedule_at(at).is_none() {
+            return Err(Error::UnknownFeeSchedule(fees.series_ticker));
+        }
+        fees.fee_cents(price_cents, contracts, role, at)
+            .ok_or(Error::UnsupportedFeeType(fees.series_ticker))
+    }
+
+    /// Fee in cents for an order if it fills completely at its limit price.
+    ///
+    /// The order's size is read from `count_fp` when set, otherwise from
+    /// `count`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::MissingOrderPrice`] if the order has no limit price,
+    /// or any error from [`fee_cents`](Self::fee_cents).
+    pub async fn order_fee_cents(
+        &self,
+        order: &CreateOrderRequest,
+        role: FeeRole,
+        at: DateTime<Utc>,
+    ) -> Result<i64> {
+        let (price, count) = order_fill(order)?;
+        self.fee_cents(&order.ticker, price, count, role, at).await
+    }
+
+    /// Forget cached fee data, for example after a fee change is announced.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, MarketFees>> {
+        self.markets.lock().expect("fee cache lock poisoned")
+    }
+}
+
+impl std::fmt::Debug for FeeCalculator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FeeCalculator")
+            .field("rates", &self.rates)
+            .field("cached", &self.lock().len())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn series(fee_type: &str, multiplier: f64) -> Series {
+        serde_json::from_value(serde_json::json!({
+            "ticker": "KXTEST",
+            "frequency": "daily",
+            "title": "Test",
+            "category": "Test",
+            "fee_type": fee_type,
+            "fee_multiplier": multiplier,
+        }))
+        .unwrap()
+    }
+
+    fn fees(fee_type: &str, multiplier: f64) -> MarketFees {
+        let schedule = FeeSchedule::from_series(&series(fee_type, multiplier));
+        MarketFees {
+            ticker: "KXTEST-1".to_string(),
+            series_ticker: "KXTEST".to_string(),
+            fee_waiver_expiration: None,
+            schedule,
+            base_schedule: Some(schedule),
+            changes: Vec::new(),
+            rates: FeeRates::default(),
+        }
+    }
+
+    fn change(fee_type: FeeType, multiplier: f64, scheduled_ts: &str) -> SeriesFeeChange {
+        SeriesFeeChange {
+            id: "1".to_string(),
+            series_ticker: "KXTEST".to_string(),
+            fee_type,
+            fee_multiplier: multiplier,
+            scheduled_ts: scheduled_ts.to_string(),
+        }
+    }
+
+    fn at(ts: &str) -> DateTime<Utc> {
+        parse_time(ts).unwrap()
+    }
+
+    #[test]
+    fn test_quadratic_fee_cents() {
+        // 0.07 * 0.50 * 0.50 = $0.0175 -> 2 cents
+        assert_eq!(quadratic_fee_cents(0.07, 50, 1), 2);
+        // 0.07 * 100 * 0.50 * 0.50 = $1.75 exactly
+        assert_eq!(quadratic_fee_cents(0.07, 50, 100), 175);
+        assert_eq!(quadratic_fee_cents(0.07, 1, 1), 1);
+        assert_eq!(quadratic_fee_cents(0.0, 50, 1), 0);
+    }
+
+    #[test]
+    fn test_fee_by_type_and_role() {
+        let now = Utc::now();
+        let quadratic = fees("quadratic", 1.0);
+        assert_eq!(quadratic.fee_cents(50, 100, FeeRole::Taker, now), Some(175));
+        assert_eq!(quadratic.fee_cents(50, 100, FeeRole::Maker, now), Some(0));
+
+        // 0.0175 * 100 * 0.25 = $0.4375 -> 44 cents
+        let maker = fees("quadratic_with_maker_fees", 1.0);
+        assert_eq!(maker.fee_cents(50, 100, FeeRole::Maker, now), Some(44));
+
+        let half = fees("quadratic", 0.5);
+        assert_eq!(half.fee_cents(50, 100, FeeRole::Taker, now), Some(88));
+
+        // Flat fees need a configured rate
+        let flat = fees("flat", 1.0);
+        assert_eq!(flat.fee_cents(90, 10, FeeRole::Maker, now), None);
+        let flat = flat.with_rates(FeeRates {
+            flat: Some(0.02),
+            ..FeeRates::default()
+        });
+        assert_eq!(flat.fee_cents(90, 10, FeeRole::Maker, now), Some(20));
+
+        assert_eq!(
+            fees("tiered", 1.0).fee_cents(50, 1, FeeRole::Taker, now),
+            None
+        );
+    }
+
+    #[test]
+    fn test_waiver_and_scheduled_changes() {
+        let mut market = fees("quadratic", 1.0);
+        market.fee_waiver_expiration = Some(at("2026-03-01T00:00:00Z"));
+        let change = change(FeeType::QuadraticWithMakerFees, 2.0, "2026-06-01T00:00:00Z");
+        market.changes = vec![(at(&change.scheduled_ts), FeeSchedule::from_change(&change))];
+
+        let waived = at("2026-02-01T00:00:00Z");
+        let before = at("2026-04-01T00:00:00Z");
+        let after = at("2026-07-01T00:00:00Z");
+        assert_eq!(market.fee_cents(50, 100, FeeRole::Taker, waived), Some(0));
+        assert_eq!(market.fee_cents(50, 100, FeeRole::Taker, before), Some(175));
+        assert_eq!(market.fee_cents(50, 100, FeeRole::Maker, before), Some(0));
+        assert_eq!(market.fee_cents(50, 100, FeeRole::Taker, after), Some(350));
+        assert_eq!(market.fee_cents(50, 100, FeeRole::Maker, after), Some(88));
+    }
+
+    #[test]
+    fn test_schedule_before_first_change() {
+        let mut market = fees("quadratic_with_maker_fees", 2.0);
+        let current = market.schedule;
+        let change = change(FeeType::QuadraticWithMakerFees, 2.0, "2026-06-01T00:00:00Z");
+        market.changes = vec![(at(&change.scheduled_ts), FeeSchedule::from_change(&change))];
+
+        // A change already in force: the earlier schedule is not reported
+        market.base_schedule = base_schedule(current, &market.changes, at("2026-07-01T00:00:00Z"));
+        let early = at("2026-01-01T00:00:00Z");
+        assert_eq!(market.schedule_at(early), None);
+        assert_eq!(market.fee_cents(50, 100, FeeRole::Maker, early), None);
+        assert_eq!(
+            market.schedule_at(at("2026-07-01T00:00:00Z")),
+            Some(current)
+        );
+
+        let quadratic = FeeSchedule::new(FeeType::Quadratic, 1.0);
+        let market = market.with_base_schedule(quadratic);
+        assert_eq!(market.fee_cents(50, 100, FeeRole::Maker, early), Some(0));
+
+        // Only upcoming changes: the series' schedule applies until then
+        let base = base_schedule(current, &market.changes, at("2026-05-01T00:00:00Z"));
+        assert_eq!(base, Some(current));
+    }
+
+    #[test]
+    fn test_order_fill() {
+        let order = CreateOrderRequest::new("KXTEST-1", Side::No, Action::Buy, 1).yes_price(45);
+        assert_eq!(order_fill(&order).unwrap(), (55, 1));
+
+        let order = order.count_fp("100.00");
+        assert_eq!(order_fill(&order).unwrap(), (55, 100));
+
+        let order =
+            CreateOrderRequest::new("KXTEST-1", Side::Yes, Action::Buy, 1).no_price_dollars("0.40");
+        assert_eq!(order_fill(&order).unwrap(), (60, 1));
+
+        let order = CreateOrderRequest::new("KXTEST-1", Side::Yes, Action::Buy, 1);
+        assert!(matches!(order_fill(&order), Err(Error::MissingOrderPrice)));
+    }
+
+    #[test]
+    fn test_break_even_exit() {
+        let market = fees("quadratic", 1.0);
+        let now = Utc::now();
+
+        // Cost 4500 + 174 fee = 4674; a taker exit at 48 nets only
+        // 4800 - 175 = 4625, at 49 it nets 4900 - 175 = 4725
+        assert_eq!(
+            market.break_even_exit(45, 100, FeeRole::Taker, FeeRole::Taker, now),
+            Some(49)
+        );
+        assert_eq!(
+            market.break_even_exit(45, 100, FeeRole::Taker, FeeRole::Maker, now),
+            Some(47)
+        );
+        assert_eq!(
+            market.break_even_exit(99, 100, FeeRole::Taker, FeeRole::Taker, now),
+            None
+        );
+    }
+
+    #[test]
+    fn test_net_edge() {
+        let market = fees("quadratic", 1.0);
+        let now = Utc::now();
+
+        // 5 cents of edge on 100 contracts less a 175 cent fee
+        let edge = market
+            .net_edge_cents(Action::Buy, 50, 100, 55.0, FeeRole::Taker, now)
+            .unwrap();
+        assert!((edge - 325.0).abs() < 1e-9);
+
+        let edge = market
+            .net_edge_cents(Action::Sell, 50, 100, 55.0, FeeRole::Maker, now)
+            .unwrap();
+        assert!((edge + 500.0).abs() < 1e-9);
+    }
+}
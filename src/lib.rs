@@ -38,13 +38,20 @@
 //! - `KALSHI_API_KEY_ID`: Your API key ID
 //! - `KALSHI_PRIVATE_KEY_PATH`: Path to your RSA private key PEM file
 
+pub mod algo;
 mod api;
 pub mod auth;
 pub mod batch;
 pub mod client;
 pub mod error;
+pub mod fees;
 pub mod models;
 pub mod orderbook;
+pub mod orders;
+pub mod portfolio;
+#[cfg(test)]
+mod test_fixtures;
+mod units;
 pub mod ws;
 
 // Re-export commonly used types at the crate root
@@ -56,8 +63,8 @@ pub use models::{
     AnnouncementStatus, AnnouncementType, ApiKey, ApiKeysResponse, ApiTierLimitsResponse,
     BalanceResponse, BatchCancelOrderItem, BatchCancelOrderResult, BatchCancelOrdersRequest,
     BatchCancelOrdersResponse, BatchCandlesticksResponse, BatchCreateOrdersRequest,
-    BatchCreateOrdersResponse, BatchLiveDataResponse, BatchOrderError, BatchOrderResult,
-    CancelOrderResponse, Candlestick, CandlestickPeriod, CandlesticksResponse,
+    BatchCreateOrdersResponse, BatchLiveDataResponse, BatchOrderError, BatchOrderErrorKind,
+    BatchOrderResult, CancelOrderResponse, Candlestick, CandlestickPeriod, CandlesticksResponse,
     CommunicationsIdResponse, CompetitionFilter, CreateApiKeyRequest, CreateApiKeyResponse,
     CreateOrderGroupRequest, CreateOrderGroupResponse, CreateOrderRequest, CreateQuoteRequest,
     CreateRfqRequest, DecreaseOrderRequest, Event, EventPosition, EventResponse, EventStatus,
@@ -95,10 +102,27 @@ pub use ws::{
     SubscribeResult,
 };
 
+// Re-export execution algorithm types
+pub use algo::{
+    AlgoEvent, AlgoOrder, AlgoProgress, AlgoState, AlgoStrategy, ChildOrder, ParentOrder,
+};
+
 // Re-export batch management types
 pub use batch::{
-    AggregatedCancelResponse, AggregatedCreateResponse, BatchManager, BatchManagerBuilder,
-    BatchOperationResult, RateLimitTier, RetryConfig,
+    AggregatedAmendResponse, AggregatedCancelResponse, AggregatedCreateResponse,
+    AggregatedDecreaseResponse, AggregatedReplaceResponse, AmendOrderResult, BatchErrorPolicy,
+    BatchManager, BatchManagerBuilder, BatchOperationResult, CreateOrderOutcome, CreateOrderStatus,
+    CreateOrdersReport, DecreaseOrderResult, OrderReplacement, RateLimitTier, ReplaceMethod,
+    ReplaceMode, ReplaceOrderResult, RetryConfig,
+};
+
+// Re-export fee calculation types
+pub use fees::{FeeCalculator, FeeRates, FeeRole, FeeSchedule, MarketFees};
+
+// Re-export portfolio tracking types
+pub use portfolio::{
+    PnlSummary, PortfolioEvent, PortfolioTracker, PortfolioTrackerConfig, PositionDiscrepancy,
+    PositionSnapshot,
 };
 
 // Re-export orderbook aggregation types
@@ -106,3 +130,11 @@ pub use orderbook::{
     OrderbookAggregator, OrderbookDelta, OrderbookLadder, OrderbookSummary, OrderbookUpdate,
     SequenceGap,
 };
+
+pub use orders::{
+    CancelAllFilter, CancelAllReport, CancelFailure, ClientOrderIdGenerator, Contingency,
+    ContingentEvent, ContingentManager, ContingentOrder, DeadMansSwitch, DeadMansSwitchConfig,
+    Firing, OrderFilter, OrderManager, OrderTransition, PriceSource, PriceTrigger, RiskGuard,
+    RiskLimits, RiskRule, RiskViolation, SwitchEvent, TrackedOrder, TransitionSource,
+    TriggerDirection, TriggerLeg, TripReason,
+};
@@ -82,10 +82,10 @@ pub use multivariate::{
 pub use order::{
     AmendOrderRequest, AmendOrderResponse, BatchCancelOrderItem, BatchCancelOrderResult,
     BatchCancelOrdersRequest, BatchCancelOrdersResponse, BatchCreateOrdersRequest,
-    BatchCreateOrdersResponse, BatchOrderError, BatchOrderResult, CancelOrderResponse,
-    CreateOrderRequest, DecreaseOrderRequest, GetOrdersParams, GetQueuePositionsParams, Order,
-    OrderQueuePositionResponse, OrderResponse, OrdersResponse, QueuePosition,
-    QueuePositionsResponse, TimeInForce,
+    BatchCreateOrdersResponse, BatchOrderError, BatchOrderErrorKind, BatchOrderResult,
+    CancelOrderResponse, CreateOrderRequest, DecreaseOrderRequest, GetOrdersParams,
+    GetQueuePositionsParams, Order, OrderQueuePositionResponse, OrderResponse, OrdersResponse,
+    QueuePosition, QueuePositionsResponse, TimeInForce,
 };
 pub use order_group::{
     CreateOrderGroupRequest, CreateOrderGroupResponse, GetOrderGroupResponse, GetOrderGroupsParams,
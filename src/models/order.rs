@@ -732,6 +732,69 @@ pub struct BatchOrderError {
     pub service: Option<String>,
 }
 
+impl BatchOrderError {
+    /// Classify the error by its code.
+    ///
+    /// Only the code is used; messages are free text and too easily
+    /// misread.
+    pub fn kind(&self) -> BatchOrderErrorKind {
+        BatchOrderErrorKind::from_code(&self.code)
+    }
+}
+
+/// Broad category of a [`BatchOrderError`].
+///
+/// Derived from the error code. Kalshi does not publish a full list of
+/// codes, so only the ones it is known to return and the HTTP statuses
+/// used in place of a missing error body are classified; everything else
+/// is [`Unknown`](Self::Unknown). More categories may be added.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum BatchOrderErrorKind {
+    /// Too many requests; the order was not placed.
+    RateLimited,
+    /// The exchange failed to process the order.
+    ServerError,
+    /// Not enough balance for the order.
+    InsufficientBalance,
+    /// The market is closed.
+    MarketClosed,
+    /// An order with the same client order ID already exists.
+    DuplicateOrder,
+    /// The order or market was not found.
+    NotFound,
+    /// The order's parameters were rejected.
+    InvalidOrder,
+    /// Any other error.
+    Unknown,
+}
+
+impl BatchOrderErrorKind {
+    /// Classify an API error code, or the HTTP status used in its place
+    /// when the response had no error body. Unrecognized codes are
+    /// [`Unknown`](Self::Unknown).
+    fn from_code(code: &str) -> Self {
+        match code.to_ascii_lowercase().as_str() {
+            "429" => Self::RateLimited,
+            "internal_server_error" | "500" | "502" | "503" | "504" => Self::ServerError,
+            "insufficient_balance" => Self::InsufficientBalance,
+            "market_closed" => Self::MarketClosed,
+            "order_already_exists" | "409" => Self::DuplicateOrder,
+            "not_found" | "404" => Self::NotFound,
+            "invalid_parameters" | "invalid_request" | "400" => Self::InvalidOrder,
+            _ => Self::Unknown,
+        }
+    }
+
+    /// Whether sending the same order again later may succeed.
+    ///
+    /// True for rate limiting and server errors, which say nothing about
+    /// the order itself.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::RateLimited | Self::ServerError)
+    }
+}
+
 /// Response from POST /portfolio/orders/batched.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BatchCreateOrdersResponse {
@@ -1012,6 +1075,46 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_batch_order_error_kind() {
+        let error = |code: &str, message: &str| BatchOrderError {
+            code: code.to_string(),
+            message: message.to_string(),
+            details: None,
+            service: None,
+        };
+
+        assert_eq!(
+            error("insufficient_balance", "").kind(),
+            BatchOrderErrorKind::InsufficientBalance
+        );
+        assert_eq!(error("NOT_FOUND", "").kind(), BatchOrderErrorKind::NotFound);
+        assert_eq!(
+            error("429", "rate limit exceeded").kind(),
+            BatchOrderErrorKind::RateLimited
+        );
+        assert_eq!(
+            error("something_new", "").kind(),
+            BatchOrderErrorKind::Unknown
+        );
+        // Codes are matched whole, and messages are not read
+        assert_eq!(
+            error("market_closed_position_limit", "").kind(),
+            BatchOrderErrorKind::Unknown
+        );
+        assert_eq!(
+            error("unknown", "market is closed").kind(),
+            BatchOrderErrorKind::Unknown
+        );
+        assert_eq!(
+            error("order_already_exists", "").kind(),
+            BatchOrderErrorKind::DuplicateOrder
+        );
+        assert_eq!(error("409", "").kind(), BatchOrderErrorKind::DuplicateOrder);
+        assert!(error("internal_server_error", "").kind().is_retryable());
+        assert!(!error("post_only_cross", "").kind().is_retryable());
+    }
+
     #[test]
     fn test_batch_cancel_with_orders() {
         let orders = vec![
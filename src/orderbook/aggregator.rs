@@ -1,14 +1,28 @@
 //! Orderbook aggregator for maintaining live orderbook state.
 
 use std::collections::{BTreeMap, HashMap};
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex};
 
+use chrono::Utc;
 use tokio::sync::broadcast;
-
-use crate::models::Side;
-use crate::ws::{KalshiStreamHandle, StreamMessage};
-
+use tokio::time::{Instant, Interval, sleep_until};
+use tracing::{debug, warn};
+
+use crate::client::KalshiClient;
+use crate::error::Result;
+use crate::models::{GetOrderbookParams, QueuePosition, Side};
+use crate::ws::{
+    Channel, KalshiStreamHandle, OrderbookDeltaData, OrderbookSnapshotData, StreamMessage,
+    StreamUpdate,
+};
+
+use super::checkpoint::{BookCheckpoint, BookDrift, OrderbookCheckpoint, SeedReport};
+use super::depth::{Admission, DepthConfig, DepthPublisher, DepthSnapshot, DepthUpdate};
+use super::health::{self, BookHealth, BookHealthConfig, HealthMonitor};
+use super::impact::{self, Sweep};
+use super::own::{LevelOverlay, OwnOrder, OwnOrders};
 use super::state::OrderbookState;
+use super::store::BookStore;
 
 /// Summary of an orderbook's current state.
 #[derive(Debug, Clone)]
@@ -53,6 +67,10 @@ pub struct OrderbookUpdate {
     pub summary: OrderbookSummary,
     /// What changed (only present for delta updates, not snapshots).
     pub delta: Option<OrderbookDelta>,
+    /// Whether this snapshot replaced a stale book, after a sequence gap with
+    /// [`with_resync`](OrderbookAggregator::with_resync) enabled or a
+    /// [`restore`](OrderbookAggregator::restore) from a checkpoint.
+    pub resynced: bool,
 }
 
 /// Notification of a sequence gap.
@@ -82,9 +100,15 @@ pub struct OrderbookLadder {
 /// Default channel capacity for update broadcasts.
 const DEFAULT_UPDATE_CAPACITY: usize = 1024;
 
+/// Default channel capacity for depth updates.
+const DEFAULT_DEPTH_CAPACITY: usize = 1024;
+
 /// Default channel capacity for gap notifications.
 const DEFAULT_GAP_CAPACITY: usize = 64;
 
+/// Default channel capacity for health events.
+const DEFAULT_HEALTH_CAPACITY: usize = 256;
+
 /// Aggregator that maintains live orderbook state from WebSocket updates.
 ///
 /// The aggregator processes orderbook snapshots and deltas to maintain
@@ -116,6 +140,79 @@ const DEFAULT_GAP_CAPACITY: usize = 64;
 /// # }
 /// ```
 ///
+/// # Resync on Sequence Gaps
+///
+/// Kalshi numbers messages per subscription, so sequence numbers are checked
+/// per `sid` and a gap is reported against the delta that revealed it. By
+/// default a [`SequenceGap`] is only reported; deltas keep being applied to
+/// a book that may no longer match the exchange. With
+/// [`with_resync`](Self::with_resync) enabled, a gap instead marks every
+/// market on the subscription stale, query methods return `None` for it, and the aggregator removes and
+/// re-adds the market on its `orderbook_delta` subscription so Kalshi sends a
+/// fresh snapshot. That snapshot is published with
+/// [`OrderbookUpdate::resynced`] set.
+///
+/// # Warm Starts
+///
+/// [`checkpoint`](Self::checkpoint) captures every live book, which
+/// [`OrderbookCheckpoint::save`] writes to a file. After a restart,
+/// [`restore`](Self::restore) loads those books marked stale, so they stay
+/// hidden until a live snapshot replaces them. [`seed_from_rest`](Self::seed_from_rest)
+/// fills books from the REST orderbook endpoint before the stream is up, and
+/// [`check_drift`](Self::check_drift) compares a live book against REST.
+///
+/// ```no_run
+/// use kalshi_trade_rs::orderbook::{OrderbookAggregator, OrderbookCheckpoint};
+///
+/// # async fn example(client: &kalshi_trade_rs::KalshiClient) -> kalshi_trade_rs::Result<()> {
+/// let aggregator = OrderbookAggregator::new();
+/// if let Ok(checkpoint) = OrderbookCheckpoint::load("books.json") {
+///     aggregator.restore(&checkpoint);
+/// }
+/// let report = aggregator.seed_from_rest(client, &["TICKER-1", "TICKER-2"]).await;
+/// for (ticker, error) in &report.failed {
+///     eprintln!("could not seed {ticker}: {error}");
+/// }
+///
+/// // ... subscribe and run process_updates ...
+///
+/// aggregator.checkpoint().save("books.json")?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Own Orders
+///
+/// When the handle passed to [`process_updates`](Self::process_updates) is
+/// also subscribed to [`Channel::UserOrders`], the aggregator tracks our
+/// resting orders. [`external_depth_at_price`](Self::external_depth_at_price),
+/// [`best_external_bid`](Self::best_external_bid) and
+/// [`best_external_ask`](Self::best_external_ask) then exclude our own size,
+/// and [`own_overlay`](Self::own_overlay) annotates each level with our
+/// quantity and estimated queue position. Queue estimates can be tightened
+/// with [`refresh_queue_positions`](Self::refresh_queue_positions).
+///
+/// # Book Health
+///
+/// A missed delta can leave a book crossed or holding levels that no longer
+/// exist. [`with_health`](Self::with_health) validates each update and
+/// publishes [`BookHealth`] events for crossed books, negative quantities,
+/// off-grid prices, quiet markets and reinitializations.
+/// [`is_healthy`](Self::is_healthy) summarizes whether a market's book can be
+/// traded against.
+///
+/// # Concurrency
+///
+/// Each market's book has its own lock, and the set of markets is published
+/// as an immutable index that is replaced only when a market is added or
+/// removed. Applying a delta therefore takes a single per-market lock, and
+/// updates to different markets never contend. Top-of-book queries
+/// ([`summary`](Self::summary), [`best_bid`](Self::best_bid),
+/// [`best_ask`](Self::best_ask), [`spread`](Self::spread),
+/// [`midpoint`](Self::midpoint)) read a snapshot republished after every
+/// change and never block the writer. Queries that walk the ladder take the
+/// market's read lock for the duration of the walk.
+///
 /// # Example - Push-based (streaming)
 ///
 /// ```no_run
@@ -142,9 +239,23 @@ const DEFAULT_GAP_CAPACITY: usize = 64;
 /// ```
 #[derive(Clone)]
 pub struct OrderbookAggregator {
-    state: Arc<RwLock<HashMap<String, OrderbookState>>>,
+    store: Arc<BookStore>,
+    own: Arc<OwnOrders>,
     update_sender: broadcast::Sender<OrderbookUpdate>,
     gap_sender: broadcast::Sender<SequenceGap>,
+    depth_sender: broadcast::Sender<DepthUpdate>,
+    depth: Option<Arc<DepthPublisher>>,
+    health_sender: broadcast::Sender<BookHealth>,
+    health: Option<Arc<HealthMonitor>>,
+    sequences: Arc<Mutex<Sequences>>,
+    resync: bool,
+}
+
+/// Last sequence number per subscription and the subscription of each book.
+#[derive(Debug, Default)]
+struct Sequences {
+    last: HashMap<i64, i64>,
+    markets: HashMap<String, i64>,
 }
 
 impl Default for OrderbookAggregator {
@@ -163,14 +274,139 @@ impl OrderbookAggregator {
     pub fn with_capacity(update_capacity: usize, gap_capacity: usize) -> Self {
         let (update_sender, _) = broadcast::channel(update_capacity);
         let (gap_sender, _) = broadcast::channel(gap_capacity);
+        let (depth_sender, _) = broadcast::channel(DEFAULT_DEPTH_CAPACITY);
+        let (health_sender, _) = broadcast::channel(DEFAULT_HEALTH_CAPACITY);
 
         Self {
-            state: Arc::new(RwLock::new(HashMap::new())),
+            store: Arc::new(BookStore::default()),
+            own: Arc::new(OwnOrders::default()),
             update_sender,
             gap_sender,
+            depth_sender,
+            depth: None,
+            health_sender,
+            health: None,
+            sequences: Arc::new(Mutex::new(Sequences::default())),
+            resync: false,
         }
     }
 
+    /// Enable automatic resync when a sequence gap is detected.
+    ///
+    /// Resync re-subscribes through the handle passed to
+    /// [`process_updates`](Self::process_updates), so it only takes effect
+    /// there. Call this before cloning the aggregator; clones copy the setting.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use kalshi_trade_rs::orderbook::OrderbookAggregator;
+    /// # async fn example(handle: kalshi_trade_rs::ws::KalshiStreamHandle) {
+    /// let aggregator = OrderbookAggregator::new().with_resync();
+    /// let mut updates = aggregator.update_receiver();
+    /// let agg_clone = aggregator.clone();
+    ///
+    /// tokio::spawn(async move {
+    ///     agg_clone.process_updates(handle).await;
+    /// });
+    ///
+    /// while let Ok(update) = updates.recv().await {
+    ///     if update.resynced {
+    ///         println!("{} recovered from a sequence gap", update.ticker);
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn with_resync(mut self) -> Self {
+        self.resync = true;
+        self
+    }
+
+    /// Publish a top-N depth view on [`depth_receiver`](Self::depth_receiver).
+    ///
+    /// Each change to a market's top `levels` per side is sent as a
+    /// [`DepthUpdate`] diff against the previous update for that market, tagged
+    /// with the book version so consumers can tell when they missed one. With a
+    /// throttle, changes within the window are conflated per market. Call this
+    /// before cloning the aggregator; clones share the feed.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use kalshi_trade_rs::orderbook::{DepthConfig, OrderbookAggregator};
+    /// # use std::time::Duration;
+    /// # async fn example(handle: kalshi_trade_rs::ws::KalshiStreamHandle) {
+    /// let aggregator = OrderbookAggregator::new()
+    ///     .with_depth(DepthConfig::new(5).with_throttle(Duration::from_millis(100)));
+    /// let mut depth = aggregator.depth_receiver();
+    /// let agg_clone = aggregator.clone();
+    ///
+    /// tokio::spawn(async move {
+    ///     agg_clone.process_updates(handle).await;
+    /// });
+    ///
+    /// let mut view = aggregator.depth("TICKER-1", 5);
+    /// while let Ok(update) = depth.recv().await {
+    ///     let applied = view.as_mut().is_some_and(|v| v.apply(&update));
+    ///     if !applied {
+    ///         // Missed an update (or first view): refresh from the aggregator
+    ///         view = aggregator.depth(&update.ticker, 5);
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn with_depth(mut self, config: DepthConfig) -> Self {
+        self.depth = Some(Arc::new(DepthPublisher::new(
+            config,
+            self.depth_sender.clone(),
+        )));
+        self
+    }
+
+    /// Validate books after every update and publish [`BookHealth`] events on
+    /// [`health_receiver`](Self::health_receiver).
+    ///
+    /// Crossed books, deltas that drive a level negative, and off-grid prices
+    /// are reported as they happen, along with every snapshot that replaces a
+    /// book. Markets without an update for
+    /// [`quiet_after`](BookHealthConfig::quiet_after) are reported quiet by
+    /// [`process_updates`](Self::process_updates), or by
+    /// [`check_health`](Self::check_health) when driving the aggregator with
+    /// [`apply_update`](Self::apply_update). Use [`is_healthy`](Self::is_healthy)
+    /// to gate trading on a market. Call this before cloning the aggregator;
+    /// clones share the monitor.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use kalshi_trade_rs::orderbook::{BookHealth, BookHealthConfig, OrderbookAggregator};
+    /// # async fn example(handle: kalshi_trade_rs::ws::KalshiStreamHandle) {
+    /// let aggregator = OrderbookAggregator::new().with_health(BookHealthConfig::default());
+    /// let mut health = aggregator.health_receiver();
+    /// let agg_clone = aggregator.clone();
+    ///
+    /// tokio::spawn(async move {
+    ///     agg_clone.process_updates(handle).await;
+    /// });
+    ///
+    /// while let Ok(event) = health.recv().await {
+    ///     if let BookHealth::Crossed { ticker, bid, ask } = event {
+    ///         println!("{ticker} crossed: bid {bid} >= ask {ask}");
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn with_health(mut self, config: BookHealthConfig) -> Self {
+        self.health = Some(Arc::new(HealthMonitor::new(
+            config,
+            self.health_sender.clone(),
+        )));
+        self
+    }
+
     /// Process updates from a WebSocket handle.
     ///
     /// This method runs in a loop, processing orderbook updates until
@@ -190,30 +426,33 @@ impl OrderbookAggregator {
     /// # }
     /// ```
     pub async fn process_updates(&self, mut handle: KalshiStreamHandle) {
+        let mut health_checks = self
+            .health
+            .as_ref()
+            .map(|health| tokio::time::interval(health.check_interval()));
+
         loop {
-            match handle.update_receiver.recv().await {
-                Ok(update) => {
-                    // Check for sequence gaps
-                    if let Some(seq) = update.seq {
-                        self.check_sequence_gap(seq);
-                    }
+            let received = tokio::select! {
+                received = handle.update_receiver.recv() => received,
+                () = tick(&mut health_checks) => {
+                    self.check_health();
+                    continue;
+                }
+            };
 
-                    match &update.msg {
-                        StreamMessage::OrderbookSnapshot(snapshot) => {
-                            self.handle_snapshot(snapshot);
-                        }
-                        StreamMessage::OrderbookDelta(delta) => {
-                            self.handle_delta(delta, update.seq);
-                        }
-                        StreamMessage::Closed { .. } | StreamMessage::ConnectionLost { .. } => {
-                            // Connection ended, exit the loop
-                            break;
-                        }
-                        _ => {
-                            // Ignore other message types
+            match received {
+                Ok(update) => match &update.msg {
+                    StreamMessage::Closed { .. } | StreamMessage::ConnectionLost { .. } => {
+                        // Connection ended, exit the loop
+                        break;
+                    }
+                    _ => {
+                        let stale = self.apply(&update);
+                        if !stale.is_empty() {
+                            self.spawn_resync(&handle, stale);
                         }
                     }
-                }
+                },
                 Err(broadcast::error::RecvError::Lagged(n)) => {
                     // We missed messages - notify via gap channel
                     let _ = self.gap_sender.send(SequenceGap {
@@ -221,6 +460,14 @@ impl OrderbookAggregator {
                         expected: 0,
                         received: n as i64,
                     });
+
+                    // Any book may have missed a delta
+                    if self.resync {
+                        let tickers = self.mark_all_stale();
+                        if !tickers.is_empty() {
+                            self.spawn_resync(&handle, tickers);
+                        }
+                    }
                 }
                 Err(broadcast::error::RecvError::Closed) => {
                     break;
@@ -229,172 +476,767 @@ impl OrderbookAggregator {
         }
     }
 
+    /// Apply a single stream update.
+    ///
+    /// [`process_updates`](Self::process_updates) calls this for every message.
+    /// Use it directly to drive the aggregator from your own receive loop or
+    /// to replay recorded updates. `user_orders` messages update the
+    /// [own-order overlay](Self#own-orders); other messages besides orderbook
+    /// snapshots and deltas are ignored.
+    ///
+    /// Returns `true` when, with [`with_resync`](Self::with_resync) enabled, a
+    /// sequence gap just made the markets on the update's subscription stale;
+    /// [`stale_markets`](Self::stale_markets) lists them. The caller is then
+    /// responsible for obtaining fresh snapshots, which `process_updates` does
+    /// by re-subscribing.
+    pub fn apply_update(&self, update: &StreamUpdate) -> bool {
+        !self.apply(update).is_empty()
+    }
+
+    /// Apply a stream update, returning the markets a sequence gap made stale.
+    fn apply(&self, update: &StreamUpdate) -> Vec<String> {
+        match &update.msg {
+            StreamMessage::OrderbookSnapshot(snapshot) => {
+                self.start_sequence(update.sid, update.seq, &snapshot.market_ticker);
+                self.handle_snapshot(snapshot);
+                Vec::new()
+            }
+            StreamMessage::OrderbookDelta(delta) => {
+                let stale = self.check_sequence(update.sid, update.seq, &delta.market_ticker);
+                self.handle_delta(delta);
+                stale
+            }
+            StreamMessage::UserOrder(order) => {
+                self.own.apply(order);
+                Vec::new()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Record the subscription a snapshot arrived on and seed its sequence.
+    fn start_sequence(&self, sid: i64, seq: Option<i64>, ticker: &str) {
+        let mut sequences = self.sequences.lock().expect("sequence lock poisoned");
+        sequences.markets.insert(ticker.to_string(), sid);
+        if let Some(seq) = seq {
+            sequences.last.insert(sid, seq);
+        }
+    }
+
+    /// Check a delta's sequence number against its subscription.
+    ///
+    /// On a gap, reports a [`SequenceGap`] and, with resync enabled, marks
+    /// every live book on the subscription stale, since the missed message
+    /// could have been for any of them. Returns the markets marked stale.
+    fn check_sequence(&self, sid: i64, seq: Option<i64>, ticker: &str) -> Vec<String> {
+        let Some(seq) = seq else {
+            return Vec::new();
+        };
+        let mut tickers = {
+            let mut sequences = self.sequences.lock().expect("sequence lock poisoned");
+            match sequences.last.insert(sid, seq) {
+                Some(last) if seq != last + 1 => {
+                    let _ = self.gap_sender.send(SequenceGap {
+                        ticker: Some(ticker.to_string()),
+                        expected: last + 1,
+                        received: seq,
+                    });
+                }
+                _ => return Vec::new(),
+            }
+            if !self.resync {
+                return Vec::new();
+            }
+            sequences
+                .markets
+                .iter()
+                .filter(|&(_, &market_sid)| market_sid == sid)
+                .map(|(market, _)| market.clone())
+                .collect::<Vec<_>>()
+        };
+        if !tickers.iter().any(|market| market == ticker) {
+            tickers.push(ticker.to_string());
+        }
+        tickers.retain(|market| self.mark_stale(market));
+        tickers.sort();
+        tickers
+    }
+
     /// Handle an orderbook snapshot.
-    fn handle_snapshot(&self, snapshot: &crate::ws::OrderbookSnapshotData) {
-        let ticker = snapshot.market_ticker.clone();
-        let new_state = OrderbookState::from_snapshot(snapshot);
+    fn handle_snapshot(&self, snapshot: &OrderbookSnapshotData) {
+        if let Some(health) = &self.health {
+            for (side, levels) in [
+                (Side::Yes, &snapshot.yes_dollars_fp),
+                (Side::No, &snapshot.no_dollars_fp),
+            ] {
+                for (price, _) in levels.iter().flatten() {
+                    if !health::on_grid(price) {
+                        health.report(BookHealth::OffGrid {
+                            ticker: snapshot.market_ticker.clone(),
+                            side,
+                            price_dollars: price.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        self.install(
+            &snapshot.market_ticker,
+            OrderbookState::from_snapshot(snapshot),
+            true,
+        );
+    }
+
+    /// Replace a market's book with `new_state` and publish it.
+    ///
+    /// With `replace_live` unset, a live book is left in place. Returns
+    /// whether the book was replaced.
+    fn install(&self, ticker: &str, mut new_state: OrderbookState, replace_live: bool) -> bool {
+        let slot = self.store.get_or_insert(ticker);
 
         // Update state
-        {
-            let mut state = self.state.write().expect("state lock poisoned");
-            state.insert(ticker.clone(), new_state);
+        let (resynced, top) = {
+            let mut orderbook = slot.write();
+            if !replace_live && orderbook.is_initialized() && !orderbook.is_stale() {
+                return false;
+            }
+            let resynced = orderbook.is_stale();
+            new_state.continue_from(&orderbook);
+            *orderbook = new_state;
+            (resynced, slot.publish(&orderbook))
+        };
+
+        if resynced {
+            debug!("Orderbook for {} resynced from snapshot", ticker);
+        }
+        if let Some(health) = &self.health {
+            health.reinitialized(ticker, top, resynced);
         }
 
+        if let Some(depth) = &self.depth {
+            depth.reset(ticker);
+        }
+        self.publish_depth(ticker);
+
         // Emit update
-        if let Some(summary) = self.summary(&ticker) {
+        if let Some(top) = top {
             let _ = self.update_sender.send(OrderbookUpdate {
-                ticker,
-                summary,
+                ticker: ticker.to_string(),
+                summary: top.summary(ticker),
                 delta: None,
+                resynced,
             });
         }
+        true
     }
 
     /// Handle an orderbook delta.
-    fn handle_delta(&self, delta: &crate::ws::OrderbookDeltaData, seq: Option<i64>) {
-        let ticker = delta.market_ticker.clone();
+    ///
+    /// This is the hot path: the market lookup is lock-free and the book's
+    /// write lock is taken exactly once.
+    fn handle_delta(&self, delta: &OrderbookDeltaData) {
+        let ticker = &delta.market_ticker;
+        let Some(slot) = self.store.get(ticker) else {
+            return;
+        };
+        let price = (delta.price_dollars.parse::<f64>().unwrap_or(0.0) * 100.0).round() as i64;
+        let quantity_change = delta.delta_fp.parse::<f64>().unwrap_or(0.0).round() as i64;
 
-        let new_qty = {
-            let mut state = self.state.write().expect("state lock poisoned");
-            let Some(orderbook) = state.get_mut(&ticker) else {
-                return;
-            };
-            if !orderbook.is_initialized() {
+        let (previous_qty, new_qty, top) = {
+            let mut orderbook = slot.write();
+            // Stale books are discarded when the resync snapshot arrives
+            if !orderbook.is_initialized() || orderbook.is_stale() {
                 return;
             }
 
-            // Check sequence gap for this specific market
-            if let (Some(last), Some(new)) = (orderbook.last_seq(), seq)
-                && new != last + 1
-            {
-                // Gap detected
-                let _ = self.gap_sender.send(SequenceGap {
-                    ticker: Some(ticker.clone()),
-                    expected: last + 1,
-                    received: new,
+            let previous_qty = orderbook.depth_at_price(delta.side, price);
+            let new_qty = orderbook.apply_delta(delta);
+            (previous_qty, new_qty, slot.publish(&orderbook))
+        };
+
+        self.publish_depth(ticker);
+
+        if let Some(health) = &self.health {
+            if !health::on_grid(&delta.price_dollars) {
+                health.report(BookHealth::OffGrid {
+                    ticker: ticker.clone(),
+                    side: delta.side,
+                    price_dollars: delta.price_dollars.clone(),
                 });
             }
+            if previous_qty + quantity_change < 0 {
+                health.report(BookHealth::NegativeQuantity {
+                    ticker: ticker.clone(),
+                    side: delta.side,
+                    price,
+                    quantity: previous_qty + quantity_change,
+                });
+            }
+            health.updated(ticker, top);
+        }
 
-            orderbook.update_seq(seq);
-            orderbook.apply_delta(delta)
-        };
-
-        if let Some(summary) = self.summary(&ticker) {
-            let price = (delta.price_dollars.parse::<f64>().unwrap_or(0.0) * 100.0).round() as i64;
-            let quantity_change = delta.delta_fp.parse::<f64>().unwrap_or(0.0).round() as i64;
+        if let Some(top) = top {
             let _ = self.update_sender.send(OrderbookUpdate {
-                ticker,
-                summary,
+                ticker: ticker.clone(),
+                summary: top.summary(ticker),
                 delta: Some(OrderbookDelta {
                     side: delta.side,
                     price,
                     quantity_change,
                     new_quantity: new_qty,
                 }),
+                resynced: false,
             });
         }
     }
 
-    /// Check for global sequence gaps.
-    fn check_sequence_gap(&self, _seq: i64) {
-        // Global sequence tracking would require additional state.
-        // For now, we rely on per-market sequence tracking in handle_delta.
+    /// Publish the depth view for a changed market, honoring the throttle.
+    fn publish_depth(&self, ticker: &str) {
+        let Some(depth) = &self.depth else {
+            return;
+        };
+
+        match depth.admit(ticker, Instant::now()) {
+            Admission::Now => self.flush_depth(depth, ticker),
+            Admission::Schedule(deadline) => match tokio::runtime::Handle::try_current() {
+                Ok(runtime) => {
+                    let aggregator = self.clone();
+                    let depth = depth.clone();
+                    let ticker = ticker.to_string();
+                    runtime.spawn(async move {
+                        sleep_until(deadline).await;
+                        aggregator.flush_depth(&depth, &ticker);
+                    });
+                }
+                // No runtime to defer on, so publish unthrottled
+                Err(_) => self.flush_depth(depth, ticker),
+            },
+            Admission::Pending => {}
+        }
+    }
+
+    fn flush_depth(&self, depth: &DepthPublisher, ticker: &str) {
+        let current = self.with_live(ticker, |ob| {
+            DepthSnapshot::from_state(ticker, ob, depth.levels())
+        });
+        depth.publish(ticker, current, Instant::now());
+    }
+
+    /// Mark every initialized market stale, returning their tickers.
+    fn mark_all_stale(&self) -> Vec<String> {
+        let mut tickers = Vec::new();
+        for (ticker, _) in self.store.markets().iter() {
+            if self.mark_stale(ticker) {
+                tickers.push(ticker.clone());
+            }
+        }
+        tickers
+    }
+
+    /// Mark a live book stale. Returns whether it was live.
+    fn mark_stale(&self, ticker: &str) -> bool {
+        let Some(slot) = self.store.get(ticker) else {
+            return false;
+        };
+        let mut orderbook = slot.write();
+        if !orderbook.is_initialized() || orderbook.is_stale() {
+            return false;
+        }
+        orderbook.mark_stale();
+        slot.publish(&orderbook);
+        true
+    }
+
+    /// Request fresh snapshots for stale markets.
+    ///
+    /// Kalshi sends an `orderbook_snapshot` when a market is added to an
+    /// `orderbook_delta` subscription, so removing and re-adding the markets
+    /// replaces the stale books in stream order. A REST snapshot is not used
+    /// because it carries no sequence number to line buffered deltas up against.
+    fn spawn_resync(&self, handle: &KalshiStreamHandle, tickers: Vec<String>) {
+        let mut handle = handle.clone();
+        tokio::spawn(async move {
+            let markets: Vec<&str> = tickers.iter().map(String::as_str).collect();
+            warn!("Resyncing orderbooks after sequence gap: {:?}", markets);
+
+            if let Err(e) = handle.unsubscribe(Channel::OrderbookDelta, &markets).await {
+                warn!("Orderbook resync unsubscribe failed: {}", e);
+            }
+            if let Err(e) = handle.subscribe(Channel::OrderbookDelta, &markets).await {
+                // Markets stay stale until a snapshot arrives
+                warn!("Orderbook resync subscribe failed: {}", e);
+            }
+        });
     }
 
     /// Clear all orderbook state.
     ///
     /// Call this on reconnection to reset state before receiving new snapshots.
     pub fn clear(&self) {
-        let mut state = self.state.write().expect("state lock poisoned");
-        state.clear();
+        self.store.clear();
+        *self.sequences.lock().expect("sequence lock poisoned") = Sequences::default();
+        if let Some(depth) = &self.depth {
+            depth.reset_all();
+        }
+        if let Some(health) = &self.health {
+            health.clear();
+        }
     }
 
     /// Clear state for a specific market.
     pub fn clear_market(&self, ticker: &str) {
-        let mut state = self.state.write().expect("state lock poisoned");
-        state.remove(ticker);
+        self.store.remove(ticker);
+        self.sequences
+            .lock()
+            .expect("sequence lock poisoned")
+            .markets
+            .remove(ticker);
+        if let Some(depth) = &self.depth {
+            depth.reset(ticker);
+        }
+        if let Some(health) = &self.health {
+            health.remove(ticker);
+        }
     }
 
     /// Get a summary of the orderbook for a market.
     ///
-    /// Returns `None` if the market is not being tracked.
+    /// Returns `None` if the market is not being tracked or is stale.
     pub fn summary(&self, ticker: &str) -> Option<OrderbookSummary> {
-        let state = self.state.read().expect("state lock poisoned");
-        let orderbook = state.get(ticker)?;
-
-        Some(OrderbookSummary {
-            ticker: ticker.to_string(),
-            best_bid: orderbook.best_yes_bid(),
-            best_ask: orderbook.best_yes_ask(),
-            spread: orderbook.spread(),
-            midpoint: orderbook.midpoint(),
-            total_yes_liquidity: orderbook.total_yes_liquidity(),
-            total_no_liquidity: orderbook.total_no_liquidity(),
-        })
+        Some(self.store.get(ticker)?.top()?.summary(ticker))
     }
 
     /// Get the best YES bid for a market.
     ///
     /// Returns (price, quantity) or None.
     pub fn best_bid(&self, ticker: &str) -> Option<(i64, i64)> {
-        let state = self.state.read().expect("state lock poisoned");
-        state.get(ticker)?.best_yes_bid()
+        self.store.get(ticker)?.top()?.best_bid
     }
 
     /// Get the best YES ask for a market.
     ///
     /// Returns (price, quantity) or None.
     pub fn best_ask(&self, ticker: &str) -> Option<(i64, i64)> {
-        let state = self.state.read().expect("state lock poisoned");
-        state.get(ticker)?.best_yes_ask()
+        self.store.get(ticker)?.top()?.best_ask
     }
 
     /// Get the spread for a market in cents.
     pub fn spread(&self, ticker: &str) -> Option<i64> {
-        let state = self.state.read().expect("state lock poisoned");
-        state.get(ticker)?.spread()
+        self.store.get(ticker)?.top()?.spread
     }
 
     /// Get the midpoint price for a market.
     pub fn midpoint(&self, ticker: &str) -> Option<f64> {
-        let state = self.state.read().expect("state lock poisoned");
-        state.get(ticker)?.midpoint()
+        self.store.get(ticker)?.top()?.midpoint
     }
 
     /// Get the quantity at a specific price level.
     pub fn depth_at_price(&self, ticker: &str, side: Side, price: i64) -> i64 {
-        let state = self.state.read().expect("state lock poisoned");
-        state
-            .get(ticker)
-            .map(|ob| ob.depth_at_price(side, price))
+        self.with_live(ticker, |ob| ob.depth_at_price(side, price))
             .unwrap_or(0)
     }
 
     /// Get the full orderbook ladder for a market.
     ///
     /// Returns all YES and NO price levels with their quantities.
-    /// Returns `None` if the market is not being tracked.
+    /// Returns `None` if the market is not being tracked or is stale.
     pub fn full_book(&self, ticker: &str) -> Option<OrderbookLadder> {
-        let state = self.state.read().expect("state lock poisoned");
-        let orderbook = state.get(ticker)?;
-
-        Some(OrderbookLadder {
+        self.with_live(ticker, |orderbook| OrderbookLadder {
             ticker: ticker.to_string(),
             yes_levels: orderbook.yes_levels().clone(),
             no_levels: orderbook.no_levels().clone(),
         })
     }
 
+    /// Walk the asks to estimate buying `contracts` of `side`.
+    ///
+    /// Buying YES consumes NO bids (YES ask = 100 - NO bid) and buying NO
+    /// consumes YES bids. If the book is too thin the returned [`Sweep`] is
+    /// partial, with the shortfall in [`Sweep::unfilled`].
+    ///
+    /// Returns `None` if the market is not being tracked or is stale.
+    pub fn cost_to_buy(&self, ticker: &str, side: Side, contracts: i64) -> Option<Sweep> {
+        self.with_live(ticker, |orderbook| {
+            impact::sweep_contracts(ticker, side, orderbook.asks(side), contracts)
+        })
+    }
+
+    /// Walk the bids to estimate selling `contracts` of `side`.
+    ///
+    /// [`Sweep::cost`] is then the proceeds in cents. If the book is too thin
+    /// the returned [`Sweep`] is partial, with the shortfall in
+    /// [`Sweep::unfilled`].
+    ///
+    /// Returns `None` if the market is not being tracked or is stale.
+    pub fn proceeds_to_sell(&self, ticker: &str, side: Side, contracts: i64) -> Option<Sweep> {
+        self.with_live(ticker, |orderbook| {
+            impact::sweep_contracts(ticker, side, orderbook.bids(side), contracts)
+        })
+    }
+
+    /// Get the volume-weighted average price in cents to buy `contracts` of `side`.
+    ///
+    /// Returns `None` if the market is not being tracked, is stale, or the
+    /// book cannot fill the full size.
+    pub fn vwap_for_size(&self, ticker: &str, side: Side, contracts: i64) -> Option<f64> {
+        self.cost_to_buy(ticker, side, contracts)
+            .filter(Sweep::is_complete)?
+            .vwap()
+    }
+
+    /// Find how many contracts of `side` a budget of `budget_cents` buys.
+    ///
+    /// Levels are taken best price first until the next contract no longer
+    /// fits the budget. Fees are not included.
+    ///
+    /// Returns `None` if the market is not being tracked or is stale.
+    pub fn max_contracts_for_budget(
+        &self,
+        ticker: &str,
+        side: Side,
+        budget_cents: i64,
+    ) -> Option<Sweep> {
+        self.with_live(ticker, |orderbook| {
+            impact::sweep_budget(ticker, side, orderbook.asks(side), budget_cents)
+        })
+    }
+
+    /// Get the best ask for `side` left after buying `contracts`.
+    ///
+    /// Returns `None` if the market is not being tracked, is stale, or the
+    /// sweep would leave no asks.
+    pub fn price_after_sweep(&self, ticker: &str, side: Side, contracts: i64) -> Option<i64> {
+        self.with_live(ticker, |orderbook| {
+            impact::price_after(orderbook.asks(side), contracts)
+        })?
+    }
+
+    /// Get all asks for `side` priced within `cents` of the best ask.
+    ///
+    /// [`Sweep::contracts`] is the cumulative depth available in that band.
+    ///
+    /// Returns `None` if the market is not being tracked or is stale.
+    pub fn cumulative_depth_within(&self, ticker: &str, side: Side, cents: i64) -> Option<Sweep> {
+        self.with_live(ticker, |orderbook| {
+            impact::sweep_within(ticker, side, orderbook.asks(side), cents)
+        })
+    }
+
+    /// Get the top `levels` per side of a market's book in YES prices.
+    ///
+    /// Only the requested levels are copied, unlike [`full_book`](Self::full_book).
+    /// Returns `None` if the market is not being tracked or is stale.
+    pub fn depth(&self, ticker: &str, levels: usize) -> Option<DepthSnapshot> {
+        self.with_live(ticker, |orderbook| {
+            DepthSnapshot::from_state(ticker, orderbook, levels)
+        })
+    }
+
+    /// Get a market's book version.
+    ///
+    /// The version increases with every snapshot and delta applied, including
+    /// when a snapshot replaces an existing book. Returns `None` if the market
+    /// is not being tracked.
+    pub fn version(&self, ticker: &str) -> Option<u64> {
+        Some(self.store.get(ticker)?.read().version())
+    }
+
     /// Get the list of tracked markets.
     pub fn tracked_markets(&self) -> Vec<String> {
-        let state = self.state.read().expect("state lock poisoned");
-        state.keys().cloned().collect()
+        self.store.markets().keys().cloned().collect()
     }
 
     /// Check if a market has been initialized with a snapshot.
     pub fn is_initialized(&self, ticker: &str) -> bool {
-        let state = self.state.read().expect("state lock poisoned");
-        state.get(ticker).is_some_and(|ob| ob.is_initialized())
+        self.store
+            .get(ticker)
+            .is_some_and(|slot| slot.read().is_initialized())
+    }
+
+    /// Check if a market is stale and awaiting a resync snapshot.
+    pub fn is_stale(&self, ticker: &str) -> bool {
+        self.store
+            .get(ticker)
+            .is_some_and(|slot| slot.read().is_stale())
+    }
+
+    /// Get the list of markets that are stale and awaiting a resync snapshot.
+    pub fn stale_markets(&self) -> Vec<String> {
+        self.store
+            .markets()
+            .iter()
+            .filter(|(_, slot)| slot.read().is_stale())
+            .map(|(ticker, _)| ticker.clone())
+            .collect()
+    }
+
+    /// Initialize books from REST for markets without a live book.
+    ///
+    /// Each market is fetched with `get_orderbook_with_params` and installed
+    /// as a live book, replacing any stale or restored one. Markets that
+    /// already have a live book, or get one from the stream while the request
+    /// is in flight, are left alone. A market that cannot be fetched is
+    /// recorded in [`SeedReport::failed`] and the rest are still seeded.
+    ///
+    /// REST books carry no sequence number, so seed before subscribing to
+    /// `orderbook_delta`: deltas are only applied on top of the snapshot the
+    /// subscription sends, which then replaces the seeded book. A seeded book
+    /// without a subscription is never updated.
+    pub async fn seed_from_rest(&self, client: &KalshiClient, tickers: &[&str]) -> SeedReport {
+        let mut report = SeedReport::default();
+        for &ticker in tickers {
+            if self.with_live(ticker, |_| ()).is_some() {
+                report.skipped.push(ticker.to_string());
+                continue;
+            }
+            let response = match client
+                .get_orderbook_with_params(ticker, GetOrderbookParams::new())
+                .await
+            {
+                Ok(response) => response,
+                Err(e) => {
+                    report.failed.push((ticker.to_string(), e.to_string()));
+                    continue;
+                }
+            };
+            if self.install(ticker, OrderbookState::from_rest(&response), false) {
+                report.seeded.push(ticker.to_string());
+            } else {
+                report.skipped.push(ticker.to_string());
+            }
+        }
+        report
+    }
+
+    /// Capture every live book for [`restore`](Self::restore).
+    ///
+    /// Stale books and markets still waiting for a snapshot are skipped.
+    pub fn checkpoint(&self) -> OrderbookCheckpoint {
+        let mut books: Vec<BookCheckpoint> = self
+            .store
+            .markets()
+            .iter()
+            .filter_map(|(ticker, slot)| {
+                let orderbook = slot.read();
+                (orderbook.is_initialized() && !orderbook.is_stale())
+                    .then(|| BookCheckpoint::from_state(ticker, &orderbook))
+            })
+            .collect();
+        books.sort_unstable_by(|a, b| a.ticker.cmp(&b.ticker));
+
+        OrderbookCheckpoint {
+            saved_at: Utc::now(),
+            books,
+        }
+    }
+
+    /// Load books from a checkpoint, marked stale.
+    ///
+    /// A checkpoint is out of date as soon as it is taken, so restored books
+    /// are hidden from queries and listed by [`stale_markets`](Self::stale_markets)
+    /// until a live snapshot, or [`seed_from_rest`](Self::seed_from_rest),
+    /// replaces them. That snapshot is published with
+    /// [`OrderbookUpdate::resynced`] set. Markets that already have a book are
+    /// not touched. Returns the number of books restored.
+    pub fn restore(&self, checkpoint: &OrderbookCheckpoint) -> usize {
+        let mut restored = 0;
+        for book in &checkpoint.books {
+            let slot = self.store.get_or_insert(&book.ticker);
+            let mut orderbook = slot.write();
+            if orderbook.is_initialized() {
+                continue;
+            }
+            let mut state = book.to_state();
+            state.continue_from(&orderbook);
+            state.mark_stale();
+            *orderbook = state;
+            slot.publish(&orderbook);
+            restored += 1;
+        }
+        restored
+    }
+
+    /// Compare a market's live book against a fresh REST orderbook.
+    ///
+    /// Deltas applied while the request is in flight show up as differences,
+    /// so treat drift as real only when it persists across checks. Returns
+    /// `Ok(None)` if the market is not being tracked or is stale.
+    pub async fn check_drift(
+        &self,
+        client: &KalshiClient,
+        ticker: &str,
+    ) -> Result<Option<BookDrift>> {
+        if self.with_live(ticker, |_| ()).is_none() {
+            return Ok(None);
+        }
+        let response = client
+            .get_orderbook_with_params(ticker, GetOrderbookParams::new())
+            .await?;
+        let remote = OrderbookState::from_rest(&response);
+        Ok(self.with_live(ticker, |local| BookDrift::between(ticker, local, &remote)))
+    }
+
+    /// Get our resting orders in a market, best price first on each ladder.
+    ///
+    /// Queue positions are estimated against the live book; see
+    /// [`OwnOrder::queue_position`].
+    pub fn own_orders(&self, ticker: &str) -> Vec<OwnOrder> {
+        let mut orders = self.own.orders(ticker);
+        let own = self.own.levels(ticker);
+        self.with_live(ticker, |orderbook| {
+            for order in &mut orders {
+                let external = (orderbook.depth_at_price(order.side, order.price)
+                    - own.get(order.side, order.price))
+                .max(0);
+                order.queue_position =
+                    Some(order.queue_position.map_or(external, |q| q.min(external)));
+            }
+        });
+        orders
+    }
+
+    /// Get our resting quantity at a bid level.
+    pub fn own_depth_at_price(&self, ticker: &str, side: Side, price: i64) -> i64 {
+        self.own.levels(ticker).get(side, price)
+    }
+
+    /// Get the quantity at a bid level that is not ours.
+    pub fn external_depth_at_price(&self, ticker: &str, side: Side, price: i64) -> i64 {
+        (self.depth_at_price(ticker, side, price) - self.own_depth_at_price(ticker, side, price))
+            .max(0)
+    }
+
+    /// Get the best YES bid from other participants, skipping levels that
+    /// only hold our orders.
+    ///
+    /// Returns (price, external quantity) or None.
+    pub fn best_external_bid(&self, ticker: &str) -> Option<(i64, i64)> {
+        self.best_external(ticker, Side::Yes)
+    }
+
+    /// Get the best YES ask from other participants, skipping levels that
+    /// only hold our orders.
+    ///
+    /// Returns (price, external quantity) or None.
+    pub fn best_external_ask(&self, ticker: &str) -> Option<(i64, i64)> {
+        self.best_external(ticker, Side::No)
+            .map(|(no_price, qty)| (100 - no_price, qty))
+    }
+
+    /// Best bid on `side`'s ladder with our orders removed.
+    fn best_external(&self, ticker: &str, side: Side) -> Option<(i64, i64)> {
+        let own = self.own.levels(ticker);
+        self.with_live(ticker, |orderbook| {
+            let levels = match side {
+                Side::Yes => orderbook.yes_levels(),
+                Side::No => orderbook.no_levels(),
+            };
+            levels.iter().rev().find_map(|(&price, &qty)| {
+                let external = qty - own.get(side, price);
+                (external > 0).then_some((price, external))
+            })
+        })?
+    }
+
+    /// Get `side`'s bid ladder with each level split into our resting size
+    /// and everyone else's, best price first.
+    ///
+    /// Returns `None` if the market is not being tracked or is stale.
+    pub fn own_overlay(&self, ticker: &str, side: Side) -> Option<Vec<LevelOverlay>> {
+        let orders = self.own_orders(ticker);
+        let own = self.own.levels(ticker);
+        self.with_live(ticker, |orderbook| {
+            let levels = match side {
+                Side::Yes => orderbook.yes_levels(),
+                Side::No => orderbook.no_levels(),
+            };
+            levels
+                .iter()
+                .rev()
+                .map(|(&price, &quantity)| {
+                    let own = own.get(side, price);
+                    LevelOverlay {
+                        price,
+                        quantity,
+                        own,
+                        external: (quantity - own).max(0),
+                        queue_position: orders
+                            .iter()
+                            .filter(|o| o.side == side && o.price == price)
+                            .filter_map(|o| o.queue_position)
+                            .min(),
+                    }
+                })
+                .collect()
+        })
+    }
+
+    /// Record queue positions from `get_queue_positions` for our orders.
+    pub fn apply_queue_positions(&self, positions: &[QueuePosition]) {
+        self.own.apply_queue_positions(positions);
+    }
+
+    /// Fetch queue positions for all our resting orders from REST.
+    ///
+    /// Between refreshes, positions are only tightened as levels shrink.
+    pub async fn refresh_queue_positions(&self, client: &KalshiClient) -> Result<()> {
+        let response = client.get_queue_positions().await?;
+        self.apply_queue_positions(&response.queue_positions);
+        Ok(())
+    }
+
+    /// Forget all tracked own orders.
+    ///
+    /// `user_orders` does not replay resting orders, so after a reconnect
+    /// call this and rebuild from REST if orders may have changed meanwhile.
+    pub fn clear_own_orders(&self) {
+        self.own.clear();
+    }
+
+    /// Check whether a market's book can be trusted right now.
+    ///
+    /// A healthy book is live and not crossed. With monitoring enabled by
+    /// [`with_health`](Self::with_health) it must also have been updated
+    /// within [`quiet_after`](BookHealthConfig::quiet_after).
+    pub fn is_healthy(&self, ticker: &str) -> bool {
+        let Some(top) = self.store.get(ticker).and_then(|slot| slot.top()) else {
+            return false;
+        };
+        let crossed =
+            matches!((top.best_bid, top.best_ask), (Some((bid, _)), Some((ask, _))) if bid >= ask);
+        !crossed
+            && self
+                .health
+                .as_ref()
+                .is_none_or(|health| health.is_healthy(ticker, Instant::now()))
+    }
+
+    /// Get the time since a market's book last changed.
+    ///
+    /// Returns `None` unless monitoring is enabled with
+    /// [`with_health`](Self::with_health) and the market has been updated.
+    pub fn time_since_update(&self, ticker: &str) -> Option<std::time::Duration> {
+        self.health.as_ref()?.since_update(ticker, Instant::now())
+    }
+
+    /// Report markets that have gone quiet.
+    ///
+    /// [`process_updates`](Self::process_updates) calls this every
+    /// [`check_interval`](BookHealthConfig::check_interval); call it yourself
+    /// when driving the aggregator with [`apply_update`](Self::apply_update).
+    /// Does nothing unless monitoring is enabled.
+    pub fn check_health(&self) {
+        if let Some(health) = &self.health {
+            health.check_quiet(Instant::now());
+        }
+    }
+
+    /// Run `f` on a market's book under its read lock, hiding books that
+    /// are stale or still waiting for their first snapshot.
+    fn with_live<R>(&self, ticker: &str, f: impl FnOnce(&OrderbookState) -> R) -> Option<R> {
+        let slot = self.store.get(ticker)?;
+        let orderbook = slot.read();
+        (orderbook.is_initialized() && !orderbook.is_stale()).then(|| f(&orderbook))
     }
 
     /// Subscribe to orderbook updates.
@@ -404,6 +1246,22 @@ impl OrderbookAggregator {
         self.update_sender.subscribe()
     }
 
+    /// Subscribe to top-N depth updates.
+    ///
+    /// Nothing is published unless the feed was enabled with
+    /// [`with_depth`](Self::with_depth).
+    pub fn depth_receiver(&self) -> broadcast::Receiver<DepthUpdate> {
+        self.depth_sender.subscribe()
+    }
+
+    /// Subscribe to book health events.
+    ///
+    /// Nothing is published unless monitoring was enabled with
+    /// [`with_health`](Self::with_health).
+    pub fn health_receiver(&self) -> broadcast::Receiver<BookHealth> {
+        self.health_sender.subscribe()
+    }
+
     /// Subscribe to sequence gap notifications.
     ///
     /// Returns a receiver that will receive notifications when sequence
@@ -413,11 +1271,20 @@ impl OrderbookAggregator {
     }
 }
 
+/// Wait for the next tick, or forever without an interval.
+async fn tick(interval: &mut Option<Interval>) {
+    match interval {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
 impl std::fmt::Debug for OrderbookAggregator {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let state = self.state.read().expect("state lock poisoned");
         f.debug_struct("OrderbookAggregator")
-            .field("tracked_markets", &state.keys().collect::<Vec<_>>())
+            .field("tracked_markets", &self.tracked_markets())
             .finish()
     }
 }
@@ -478,7 +1345,7 @@ mod tests {
             subaccount: None,
             ts: None,
         };
-        agg.handle_delta(&delta, Some(1));
+        agg.handle_delta(&delta);
 
         // Best bid should now be 46
         assert_eq!(agg.best_bid("TEST"), Some((46, 50)));
@@ -611,32 +1478,26 @@ mod tests {
         agg.handle_snapshot(&snapshot);
 
         // Apply deltas: add a new level, remove an existing one
-        agg.handle_delta(
-            &OrderbookDeltaData {
-                market_ticker: "TEST".to_string(),
-                market_id: String::new(),
-                side: Side::Yes,
-                price_dollars: "0.46".to_string(),
-                delta_fp: "75".to_string(),
-                client_order_id: None,
-                subaccount: None,
-                ts: None,
-            },
-            Some(1),
-        );
-        agg.handle_delta(
-            &OrderbookDeltaData {
-                market_ticker: "TEST".to_string(),
-                market_id: String::new(),
-                side: Side::Yes,
-                price_dollars: "0.44".to_string(),
-                delta_fp: "-200".to_string(),
-                client_order_id: None,
-                subaccount: None,
-                ts: None,
-            },
-            Some(2),
-        );
+        agg.handle_delta(&OrderbookDeltaData {
+            market_ticker: "TEST".to_string(),
+            market_id: String::new(),
+            side: Side::Yes,
+            price_dollars: "0.46".to_string(),
+            delta_fp: "75".to_string(),
+            client_order_id: None,
+            subaccount: None,
+            ts: None,
+        });
+        agg.handle_delta(&OrderbookDeltaData {
+            market_ticker: "TEST".to_string(),
+            market_id: String::new(),
+            side: Side::Yes,
+            price_dollars: "0.44".to_string(),
+            delta_fp: "-200".to_string(),
+            client_order_id: None,
+            subaccount: None,
+            ts: None,
+        });
 
         let ladder = agg.full_book("TEST").unwrap();
         // Level 46 was added, level 44 was removed
@@ -653,19 +1514,16 @@ mod tests {
         let agg = OrderbookAggregator::new();
 
         // Deltas before snapshot: no entry exists, delta is dropped
-        agg.handle_delta(
-            &OrderbookDeltaData {
-                market_ticker: "TEST".to_string(),
-                market_id: String::new(),
-                side: Side::Yes,
-                price_dollars: "0.45".to_string(),
-                delta_fp: "100".to_string(),
-                client_order_id: None,
-                subaccount: None,
-                ts: None,
-            },
-            Some(1),
-        );
+        agg.handle_delta(&OrderbookDeltaData {
+            market_ticker: "TEST".to_string(),
+            market_id: String::new(),
+            side: Side::Yes,
+            price_dollars: "0.45".to_string(),
+            delta_fp: "100".to_string(),
+            client_order_id: None,
+            subaccount: None,
+            ts: None,
+        });
 
         // No entry was created — full_book returns None
         assert!(agg.full_book("TEST").is_none());
@@ -689,4 +1547,564 @@ mod tests {
         assert!(agg2.is_initialized("TEST"));
         assert_eq!(agg2.best_bid("TEST"), Some((45, 100)));
     }
+
+    fn yes_delta(ticker: &str, price_dollars: &str, delta_fp: &str) -> OrderbookDeltaData {
+        OrderbookDeltaData {
+            market_ticker: ticker.to_string(),
+            market_id: String::new(),
+            side: Side::Yes,
+            price_dollars: price_dollars.to_string(),
+            delta_fp: delta_fp.to_string(),
+            client_order_id: None,
+            subaccount: None,
+            ts: None,
+        }
+    }
+
+    fn on_sid(sid: i64, seq: i64, msg: StreamMessage) -> StreamUpdate {
+        StreamUpdate {
+            channel: "orderbook_delta".to_string(),
+            sid,
+            seq: Some(seq),
+            msg,
+        }
+    }
+
+    fn snapshot_of(ticker: &str) -> StreamMessage {
+        StreamMessage::OrderbookSnapshot(OrderbookSnapshotData {
+            market_ticker: ticker.to_string(),
+            market_id: String::new(),
+            yes_dollars_fp: Some(vec![("0.45".to_string(), "100.00".to_string())]),
+            no_dollars_fp: None,
+        })
+    }
+
+    #[test]
+    fn test_gap_without_resync_keeps_applying_deltas() {
+        let agg = OrderbookAggregator::new();
+        let mut gaps = agg.gap_receiver();
+
+        agg.apply_update(&on_sid(1, 1, snapshot_of("TEST")));
+        let delta = |price| StreamMessage::OrderbookDelta(yes_delta("TEST", price, "10"));
+        assert!(!agg.apply_update(&on_sid(1, 2, delta("0.46"))));
+        assert!(!agg.apply_update(&on_sid(1, 4, delta("0.47"))));
+
+        let gap = gaps.try_recv().unwrap();
+        assert_eq!(gap.ticker.as_deref(), Some("TEST"));
+        assert_eq!((gap.expected, gap.received), (3, 4));
+        assert!(!agg.is_stale("TEST"));
+        assert_eq!(agg.best_bid("TEST"), Some((47, 10)));
+    }
+
+    #[test]
+    fn test_gap_with_resync_marks_market_stale() {
+        let agg = OrderbookAggregator::new().with_resync();
+        let mut updates = agg.update_receiver();
+
+        agg.apply_update(&on_sid(1, 1, snapshot_of("TEST")));
+        assert!(!updates.try_recv().unwrap().resynced);
+
+        let delta = |price| StreamMessage::OrderbookDelta(yes_delta("TEST", price, "10"));
+        assert!(!agg.apply_update(&on_sid(1, 2, delta("0.46"))));
+        assert!(agg.apply_update(&on_sid(1, 4, delta("0.47"))));
+
+        // Stale books are hidden from queries
+        assert!(agg.is_stale("TEST"));
+        assert_eq!(agg.stale_markets(), vec!["TEST".to_string()]);
+        assert!(agg.is_initialized("TEST"));
+        assert!(agg.summary("TEST").is_none());
+        assert!(agg.best_bid("TEST").is_none());
+        assert!(agg.full_book("TEST").is_none());
+        assert_eq!(agg.depth_at_price("TEST", Side::Yes, 45), 0);
+
+        // Further deltas are dropped without triggering another resync
+        assert!(!agg.apply_update(&on_sid(1, 5, delta("0.48"))));
+        assert!(!agg.apply_update(&on_sid(1, 7, delta("0.48"))));
+        updates.try_recv().unwrap(); // delta at seq 2
+        assert!(updates.try_recv().is_err());
+
+        agg.apply_update(&on_sid(2, 1, snapshot_of("TEST")));
+
+        let update = updates.try_recv().unwrap();
+        assert!(update.resynced);
+        assert!(!agg.is_stale("TEST"));
+        assert_eq!(agg.best_bid("TEST"), Some((45, 100)));
+    }
+
+    #[tokio::test]
+    async fn test_resync_resubscribes_against_mock_server() {
+        use crate::ws::mock_server::MockKalshiServer;
+
+        let mut server = MockKalshiServer::start().await;
+        let client = server.connect_client().await;
+        let mut conn = server.accept().await;
+
+        let mut handle = client.handle();
+        let subscribe =
+            tokio::spawn(async move { handle.subscribe(Channel::OrderbookDelta, &["TEST"]).await });
+        let (_, sids) = conn.expect_subscribe().await;
+        subscribe.await.unwrap().unwrap();
+
+        let agg = OrderbookAggregator::new().with_resync();
+        let mut updates = agg.update_receiver();
+        let processor = agg.clone();
+        let handle = client.handle();
+        tokio::spawn(async move { processor.process_updates(handle).await });
+
+        let sid = sids[0];
+        conn.push_orderbook_snapshot(sid, 1, "TEST", &[("0.45", "100.00")], &[])
+            .await;
+        conn.push_orderbook_delta(sid, 2, "TEST", "yes", "0.46", "10.00")
+            .await;
+        conn.push_orderbook_delta(sid, 4, "TEST", "yes", "0.47", "10.00")
+            .await;
+
+        // The only market on the subscription, so resync unsubscribes the channel
+        let unsubscribe = conn.expect_command("unsubscribe").await;
+        assert_eq!(unsubscribe["params"]["sids"][0], sid);
+        conn.ack_unsubscribe(&unsubscribe).await;
+        assert!(agg.is_stale("TEST"));
+
+        let (resubscribe, new_sids) = conn.expect_subscribe().await;
+        assert_eq!(resubscribe["params"]["market_ticker"], "TEST");
+        conn.push_orderbook_snapshot(new_sids[0], 1, "TEST", &[("0.50", "20.00")], &[])
+            .await;
+
+        let resynced = loop {
+            let update = tokio::time::timeout(std::time::Duration::from_secs(5), updates.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            if update.resynced {
+                break update;
+            }
+        };
+        assert_eq!(resynced.summary.best_bid, Some((50, 20)));
+        assert!(!agg.is_stale("TEST"));
+    }
+
+    fn impact_book() -> OrderbookAggregator {
+        let agg = OrderbookAggregator::new();
+        // YES asks: 100-60=40 x10, 100-58=42 x20. NO asks: 100-45=55 x30.
+        agg.handle_snapshot(&OrderbookSnapshotData {
+            market_ticker: "TEST".to_string(),
+            market_id: String::new(),
+            yes_dollars_fp: Some(vec![("0.45".to_string(), "30.00".to_string())]),
+            no_dollars_fp: Some(vec![
+                ("0.60".to_string(), "10.00".to_string()),
+                ("0.58".to_string(), "20.00".to_string()),
+            ]),
+        });
+        agg
+    }
+
+    #[test]
+    fn test_cost_to_buy_walks_complement_side() {
+        let agg = impact_book();
+
+        let yes = agg.cost_to_buy("TEST", Side::Yes, 15).unwrap();
+        assert!(yes.is_complete());
+        assert_eq!(yes.cost, 40 * 10 + 42 * 5);
+        assert_eq!(yes.best_price(), agg.best_ask("TEST").map(|(p, _)| p));
+        assert_eq!(yes.worst_price(), Some(42));
+
+        let no = agg.cost_to_buy("TEST", Side::No, 40).unwrap();
+        assert_eq!(no.contracts, 30);
+        assert_eq!(no.unfilled, 10);
+        assert_eq!(no.levels.len(), 1);
+        assert_eq!(no.levels[0].price, 55);
+    }
+
+    #[test]
+    fn test_impact_queries() {
+        let agg = impact_book();
+
+        assert_eq!(
+            agg.vwap_for_size("TEST", Side::Yes, 20),
+            Some((400.0 + 420.0) / 20.0)
+        );
+        assert_eq!(agg.vwap_for_size("TEST", Side::Yes, 31), None);
+
+        let budget = agg
+            .max_contracts_for_budget("TEST", Side::Yes, 1000)
+            .unwrap();
+        assert_eq!(budget.contracts, 24);
+        assert_eq!(budget.cost, 400 + 42 * 14);
+
+        assert_eq!(agg.price_after_sweep("TEST", Side::Yes, 10), Some(42));
+        assert_eq!(agg.price_after_sweep("TEST", Side::Yes, 30), None);
+
+        let within = agg.cumulative_depth_within("TEST", Side::Yes, 1).unwrap();
+        assert_eq!(within.contracts, 10);
+        let within = agg.cumulative_depth_within("TEST", Side::Yes, 2).unwrap();
+        assert_eq!(within.contracts, 30);
+    }
+
+    #[test]
+    fn test_sequence_tracked_per_subscription() {
+        let agg = OrderbookAggregator::new().with_resync();
+        let mut gaps = agg.gap_receiver();
+        let delta = |ticker| StreamMessage::OrderbookDelta(yes_delta(ticker, "0.46", "1"));
+
+        // Two markets share sid 1 and interleave its sequence numbers
+        agg.apply_update(&on_sid(1, 1, snapshot_of("A")));
+        agg.apply_update(&on_sid(1, 2, snapshot_of("B")));
+        agg.apply_update(&on_sid(2, 1, snapshot_of("C")));
+        for (seq, ticker) in [(3, "A"), (4, "B"), (5, "B"), (6, "A")] {
+            assert!(!agg.apply_update(&on_sid(1, seq, delta(ticker))));
+        }
+        assert!(!agg.apply_update(&on_sid(2, 2, delta("C"))));
+        assert!(gaps.try_recv().is_err());
+        assert_eq!(agg.best_bid("A"), Some((46, 2)));
+
+        // A gap on sid 1 may have hidden a delta for either of its markets
+        assert!(agg.apply_update(&on_sid(1, 8, delta("B"))));
+        let gap = gaps.try_recv().unwrap();
+        assert_eq!(gap.ticker.as_deref(), Some("B"));
+        assert_eq!((gap.expected, gap.received), (7, 8));
+        assert_eq!(agg.stale_markets().len(), 2);
+        assert!(agg.is_stale("A") && agg.is_stale("B"));
+        assert!(!agg.is_stale("C"));
+    }
+
+    #[test]
+    fn test_impact_queries_hide_unknown_and_stale_markets() {
+        let agg = impact_book().with_resync();
+        assert!(agg.cost_to_buy("OTHER", Side::Yes, 1).is_none());
+
+        // A gap marks the book stale.
+        let delta = || StreamMessage::OrderbookDelta(yes_delta("TEST", "0.44", "5"));
+        agg.apply_update(&on_sid(1, 1, delta()));
+        agg.apply_update(&on_sid(1, 3, delta()));
+        assert!(agg.is_stale("TEST"));
+        assert!(agg.cost_to_buy("TEST", Side::Yes, 1).is_none());
+        assert!(
+            agg.max_contracts_for_budget("TEST", Side::Yes, 100)
+                .is_none()
+        );
+        assert!(agg.cumulative_depth_within("TEST", Side::Yes, 5).is_none());
+    }
+
+    fn depth_snapshot() -> OrderbookSnapshotData {
+        OrderbookSnapshotData {
+            market_ticker: "TEST".to_string(),
+            market_id: String::new(),
+            yes_dollars_fp: Some(vec![
+                ("0.45".to_string(), "10.00".to_string()),
+                ("0.44".to_string(), "20.00".to_string()),
+                ("0.43".to_string(), "30.00".to_string()),
+            ]),
+            no_dollars_fp: Some(vec![("0.53".to_string(), "15.00".to_string())]),
+        }
+    }
+
+    #[test]
+    fn test_depth_view_and_version() {
+        let agg = OrderbookAggregator::new();
+        agg.handle_snapshot(&depth_snapshot());
+
+        let view = agg.depth("TEST", 2).unwrap();
+        assert_eq!(view.bids, vec![(45, 10), (44, 20)]);
+        assert_eq!(view.asks, vec![(47, 15)]);
+        assert_eq!(view.version, 1);
+
+        agg.handle_delta(&yes_delta("TEST", "0.40", "5"));
+        assert_eq!(agg.version("TEST"), Some(2));
+
+        // A replacing snapshot keeps the version increasing
+        agg.handle_snapshot(&depth_snapshot());
+        assert_eq!(agg.version("TEST"), Some(3));
+    }
+
+    #[test]
+    fn test_depth_feed_publishes_diffs() {
+        let agg = OrderbookAggregator::new().with_depth(DepthConfig::new(2));
+        let mut depth = agg.depth_receiver();
+
+        agg.handle_snapshot(&depth_snapshot());
+        let full = depth.try_recv().unwrap();
+        assert!(full.is_full());
+        let mut view = agg.depth("TEST", 2).unwrap();
+
+        // Below the top two levels: no update
+        agg.handle_delta(&yes_delta("TEST", "0.43", "5"));
+        assert!(depth.try_recv().is_err());
+
+        // New best bid pushes 44 out of the view
+        agg.handle_delta(&yes_delta("TEST", "0.46", "7"));
+        let update = depth.try_recv().unwrap();
+        assert_eq!(update.base_version, Some(1));
+        assert_eq!(update.version, 3);
+        assert!(view.apply(&update));
+        assert_eq!(view, agg.depth("TEST", 2).unwrap());
+        assert_eq!(view.bids, vec![(46, 7), (45, 10)]);
+
+        // A missed update is detected by the version check
+        agg.handle_delta(&yes_delta("TEST", "0.46", "1"));
+        agg.handle_delta(&yes_delta("TEST", "0.46", "1"));
+        let _missed = depth.try_recv().unwrap();
+        let next = depth.try_recv().unwrap();
+        assert!(!view.apply(&next));
+    }
+
+    #[tokio::test]
+    async fn test_depth_feed_throttle_conflates() {
+        let agg = OrderbookAggregator::new()
+            .with_depth(DepthConfig::new(5).with_throttle(std::time::Duration::from_millis(50)));
+        let mut depth = agg.depth_receiver();
+
+        agg.handle_snapshot(&depth_snapshot());
+        assert!(depth.recv().await.unwrap().is_full());
+
+        for _ in 1..=3 {
+            agg.handle_delta(&yes_delta("TEST", "0.46", "1"));
+        }
+        assert!(depth.try_recv().is_err());
+
+        let update = tokio::time::timeout(std::time::Duration::from_secs(1), depth.recv())
+            .await
+            .expect("conflated update")
+            .unwrap();
+        assert_eq!(update.base_version, Some(1));
+        assert_eq!(update.version, 4);
+        assert_eq!(
+            update.changes,
+            vec![crate::orderbook::DepthChange {
+                side: crate::orderbook::DepthSide::Bid,
+                price: 46,
+                quantity: 3,
+            }]
+        );
+        assert!(depth.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_restored_books_stale_until_snapshot() {
+        let source = OrderbookAggregator::new();
+        source.handle_snapshot(&depth_snapshot());
+        let checkpoint = source.checkpoint();
+        assert_eq!(checkpoint.books.len(), 1);
+
+        let agg = OrderbookAggregator::new();
+        let mut updates = agg.update_receiver();
+        assert_eq!(agg.restore(&checkpoint), 1);
+
+        assert!(agg.is_stale("TEST"));
+        assert_eq!(agg.stale_markets(), vec!["TEST".to_string()]);
+        assert!(agg.summary("TEST").is_none());
+        assert!(updates.try_recv().is_err());
+        // Stale books are not checkpointed again
+        assert!(agg.checkpoint().books.is_empty());
+
+        // Deltas do not apply to a restored book
+        agg.handle_delta(&yes_delta("TEST", "0.46", "5"));
+        assert!(agg.summary("TEST").is_none());
+
+        agg.handle_snapshot(&depth_snapshot());
+        let update = updates.try_recv().unwrap();
+        assert!(update.resynced);
+        assert_eq!(agg.best_bid("TEST"), Some((45, 10)));
+        assert_eq!(agg.checkpoint().books, checkpoint.books);
+
+        // Restoring again leaves the live book alone
+        assert_eq!(agg.restore(&checkpoint), 0);
+        assert!(!agg.is_stale("TEST"));
+    }
+
+    #[test]
+    fn test_rest_seed_keeps_live_books() {
+        let response: crate::models::OrderbookResponse =
+            serde_json::from_value(serde_json::json!({
+                "orderbook_fp": {
+                    "yes_dollars": [["0.40", "7.00"]],
+                    "no_dollars": [["0.55", "9.00"]],
+                }
+            }))
+            .unwrap();
+
+        let agg = OrderbookAggregator::new();
+        assert!(agg.install("TEST", OrderbookState::from_rest(&response), false));
+        assert_eq!(agg.best_bid("TEST"), Some((40, 7)));
+        assert_eq!(agg.best_ask("TEST"), Some((45, 9)));
+
+        // A stream snapshot replaces the seeded book, which then wins
+        agg.handle_snapshot(&depth_snapshot());
+        assert!(!agg.install("TEST", OrderbookState::from_rest(&response), false));
+        assert_eq!(agg.best_bid("TEST"), Some((45, 10)));
+    }
+
+    #[tokio::test]
+    async fn test_seed_from_rest_reports_each_market() {
+        use crate::client::mock_http::MockHttpServer;
+
+        let server = MockHttpServer::start().await;
+        let book = serde_json::json!({
+            "orderbook_fp": {
+                "yes_dollars": [["0.40", "7.00"]],
+                "no_dollars": [["0.55", "9.00"]],
+            }
+        });
+        server.respond("GET", "/markets/A/orderbook", 200, book.clone());
+        server.respond("GET", "/markets/B/orderbook", 500, serde_json::json!({}));
+        server.respond("GET", "/markets/C/orderbook", 200, book);
+
+        let agg = OrderbookAggregator::new();
+        agg.handle_snapshot(&depth_snapshot());
+        let report = agg
+            .seed_from_rest(&server.client(), &["A", "B", "TEST", "C"])
+            .await;
+
+        assert_eq!(report.seeded, vec!["A".to_string(), "C".to_string()]);
+        assert_eq!(report.skipped, vec!["TEST".to_string()]);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].0, "B");
+        assert!(!report.is_complete());
+        assert_eq!(agg.best_bid("C"), Some((40, 7)));
+        assert!(
+            server
+                .requests_to("GET", "/markets/TEST/orderbook")
+                .is_empty()
+        );
+    }
+
+    fn resting_yes_bid(order_id: &str, price_dollars: &str, remaining_fp: &str) -> StreamUpdate {
+        let order = serde_json::from_value(serde_json::json!({
+            "order_id": order_id,
+            "user_id": "user",
+            "ticker": "TEST",
+            "status": "resting",
+            "side": "yes",
+            "is_yes": true,
+            "yes_price_dollars": price_dollars,
+            "fill_count_fp": "0.00",
+            "remaining_count_fp": remaining_fp,
+            "initial_count_fp": remaining_fp,
+            "taker_fill_cost_dollars": "0.00",
+            "maker_fill_cost_dollars": "0.00",
+            "taker_fees_dollars": "0.00",
+            "maker_fees_dollars": "0.00",
+            "client_order_id": "",
+            "created_time": "2026-01-01T00:00:00Z",
+            "action": "buy",
+        }))
+        .unwrap();
+        StreamUpdate {
+            channel: "user_orders".to_string(),
+            sid: 2,
+            seq: None,
+            msg: StreamMessage::UserOrder(Box::new(order)),
+        }
+    }
+
+    #[test]
+    fn test_own_orders_excluded_from_external_depth() {
+        let agg = OrderbookAggregator::new();
+        agg.handle_snapshot(&depth_snapshot());
+
+        // Our 10 is the whole best bid at 45, and 5 of the 20 at 44
+        agg.apply_update(&resting_yes_bid("a", "0.45", "10.00"));
+        agg.apply_update(&resting_yes_bid("b", "0.44", "5.00"));
+
+        assert_eq!(agg.own_depth_at_price("TEST", Side::Yes, 45), 10);
+        assert_eq!(agg.external_depth_at_price("TEST", Side::Yes, 45), 0);
+        assert_eq!(agg.external_depth_at_price("TEST", Side::Yes, 44), 15);
+        assert_eq!(agg.best_bid("TEST"), Some((45, 10)));
+        assert_eq!(agg.best_external_bid("TEST"), Some((44, 15)));
+        assert_eq!(agg.best_external_ask("TEST"), Some((47, 15)));
+
+        let overlay = agg.own_overlay("TEST", Side::Yes).unwrap();
+        assert_eq!(
+            overlay[1],
+            LevelOverlay {
+                price: 44,
+                quantity: 20,
+                own: 5,
+                external: 15,
+                queue_position: Some(15),
+            }
+        );
+        assert_eq!(overlay[2].queue_position, None);
+
+        // A reported position is kept until the level shrinks below it
+        agg.apply_queue_positions(&[QueuePosition {
+            order_id: "b".to_string(),
+            market_ticker: "TEST".to_string(),
+            queue_position_fp: "6.00".to_string(),
+        }]);
+        assert_eq!(agg.own_orders("TEST")[1].queue_position, Some(6));
+        agg.handle_delta(&yes_delta("TEST", "0.44", "-12"));
+        assert_eq!(agg.own_orders("TEST")[1].queue_position, Some(3));
+
+        agg.clear_own_orders();
+        assert_eq!(agg.best_external_bid("TEST"), Some((45, 10)));
+    }
+
+    #[test]
+    fn test_health_reports_invariant_violations() {
+        let agg = OrderbookAggregator::new().with_health(BookHealthConfig::default());
+        let mut health = agg.health_receiver();
+
+        // Best bid 45, best ask 47
+        agg.handle_snapshot(&depth_snapshot());
+        assert_eq!(
+            health.try_recv().unwrap(),
+            BookHealth::Reinitialized {
+                ticker: "TEST".to_string(),
+                resynced: false,
+            }
+        );
+        assert!(agg.is_healthy("TEST"));
+        assert!(agg.time_since_update("TEST").is_some());
+
+        // A bid at the ask crosses the book
+        agg.handle_delta(&yes_delta("TEST", "0.47", "5"));
+        assert_eq!(
+            health.try_recv().unwrap(),
+            BookHealth::Crossed {
+                ticker: "TEST".to_string(),
+                bid: 47,
+                ask: 47,
+            }
+        );
+        assert!(!agg.is_healthy("TEST"));
+        assert_eq!(agg.spread("TEST"), Some(0));
+
+        agg.handle_delta(&yes_delta("TEST", "0.47", "-5"));
+        assert!(matches!(
+            health.try_recv().unwrap(),
+            BookHealth::Recovered { .. }
+        ));
+        assert!(agg.is_healthy("TEST"));
+
+        agg.handle_delta(&yes_delta("TEST", "0.44", "-25"));
+        assert_eq!(
+            health.try_recv().unwrap(),
+            BookHealth::NegativeQuantity {
+                ticker: "TEST".to_string(),
+                side: Side::Yes,
+                price: 44,
+                quantity: -5,
+            }
+        );
+
+        agg.handle_delta(&yes_delta("TEST", "0.425", "1"));
+        assert!(matches!(
+            health.try_recv().unwrap(),
+            BookHealth::OffGrid { .. }
+        ));
+        assert!(health.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_is_healthy_without_monitoring() {
+        let agg = OrderbookAggregator::new();
+        assert!(!agg.is_healthy("TEST"));
+
+        agg.handle_snapshot(&depth_snapshot());
+        assert!(agg.is_healthy("TEST"));
+        assert_eq!(agg.time_since_update("TEST"), None);
+
+        agg.handle_delta(&yes_delta("TEST", "0.48", "5"));
+        assert!(!agg.is_healthy("TEST"));
+    }
 }
@@ -0,0 +1,251 @@
+//! Saving books across restarts and comparing them against REST.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::models::Side;
+
+use super::state::OrderbookState;
+
+/// Every live book in an [`OrderbookAggregator`](super::OrderbookAggregator)
+/// at one point in time.
+///
+/// Taken with [`OrderbookAggregator::checkpoint`](super::OrderbookAggregator::checkpoint)
+/// and loaded back with [`OrderbookAggregator::restore`](super::OrderbookAggregator::restore).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderbookCheckpoint {
+    /// When the checkpoint was taken.
+    pub saved_at: DateTime<Utc>,
+    /// One entry per book, sorted by ticker.
+    pub books: Vec<BookCheckpoint>,
+}
+
+impl OrderbookCheckpoint {
+    /// Write the checkpoint to `path` as JSON.
+    ///
+    /// The file is written next to `path` and renamed into place, so a crash
+    /// mid-write leaves the previous checkpoint intact.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let json = serde_json::to_vec(self)?;
+        let tmp = temp_path(path);
+        fs::write(&tmp, json)
+            .and_then(|()| fs::rename(&tmp, path))
+            .map_err(|e| Error::CheckpointFileError(path.display().to_string(), e.to_string()))
+    }
+
+    /// Read a checkpoint written by [`save`](Self::save).
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let json = fs::read(path)
+            .map_err(|e| Error::CheckpointFileError(path.display().to_string(), e.to_string()))?;
+        Ok(serde_json::from_slice(&json)?)
+    }
+}
+
+/// Sibling of `path` to write before renaming into place.
+///
+/// The suffix is appended to the whole file name, so a target that already
+/// ends in `.tmp` does not share its temporary file with itself.
+pub(crate) fn temp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+/// Outcome of [`OrderbookAggregator::seed_from_rest`](super::OrderbookAggregator::seed_from_rest).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SeedReport {
+    /// Markets installed from REST.
+    pub seeded: Vec<String>,
+    /// Markets left alone because they already had a live book.
+    pub skipped: Vec<String>,
+    /// Markets whose REST book could not be fetched, as (ticker, error).
+    pub failed: Vec<(String, String)>,
+}
+
+impl SeedReport {
+    /// Whether every market was seeded or already live.
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// One market's levels in an [`OrderbookCheckpoint`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BookCheckpoint {
+    /// Market ticker.
+    pub ticker: String,
+    /// YES bids as (price, quantity), ascending by price.
+    pub yes: Vec<(i64, i64)>,
+    /// NO bids as (price, quantity), ascending by price.
+    pub no: Vec<(i64, i64)>,
+}
+
+impl BookCheckpoint {
+    pub(crate) fn from_state(ticker: &str, orderbook: &OrderbookState) -> Self {
+        let levels = |map: &BTreeMap<i64, i64>| map.iter().map(|(&p, &q)| (p, q)).collect();
+        Self {
+            ticker: ticker.to_string(),
+            yes: levels(orderbook.yes_levels()),
+            no: levels(orderbook.no_levels()),
+        }
+    }
+
+    pub(crate) fn to_state(&self) -> OrderbookState {
+        let levels = |pairs: &[(i64, i64)]| pairs.iter().copied().filter(|&(_, q)| q > 0).collect();
+        OrderbookState::from_levels(levels(&self.yes), levels(&self.no))
+    }
+}
+
+/// A price level where the local book and REST disagree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LevelDrift {
+    /// Bid side of the level.
+    pub side: Side,
+    /// Bid price in cents.
+    pub price: i64,
+    /// Quantity in the local book.
+    pub local: i64,
+    /// Quantity reported by REST.
+    pub remote: i64,
+}
+
+/// Differences between a live book and a REST orderbook fetched for it.
+///
+/// Returned by [`OrderbookAggregator::check_drift`](super::OrderbookAggregator::check_drift).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BookDrift {
+    /// Market ticker.
+    pub ticker: String,
+    /// Version of the local book that was compared.
+    pub version: u64,
+    /// Levels that differ, YES bids first, each side ascending by price.
+    pub levels: Vec<LevelDrift>,
+}
+
+impl BookDrift {
+    pub(crate) fn between(ticker: &str, local: &OrderbookState, remote: &OrderbookState) -> Self {
+        let mut levels = Vec::new();
+        for (side, local, remote) in [
+            (Side::Yes, local.yes_levels(), remote.yes_levels()),
+            (Side::No, local.no_levels(), remote.no_levels()),
+        ] {
+            let mut prices: Vec<i64> = local.keys().chain(remote.keys()).copied().collect();
+            prices.sort_unstable();
+            prices.dedup();
+            for price in prices {
+                let local = local.get(&price).copied().unwrap_or(0);
+                let remote = remote.get(&price).copied().unwrap_or(0);
+                if local != remote {
+                    levels.push(LevelDrift {
+                        side,
+                        price,
+                        local,
+                        remote,
+                    });
+                }
+            }
+        }
+
+        Self {
+            ticker: ticker.to_string(),
+            version: local.version(),
+            levels,
+        }
+    }
+
+    /// Whether the books matched.
+    pub fn is_empty(&self) -> bool {
+        self.levels.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(yes: &[(i64, i64)], no: &[(i64, i64)]) -> OrderbookState {
+        OrderbookState::from_levels(yes.iter().copied().collect(), no.iter().copied().collect())
+    }
+
+    #[test]
+    fn test_checkpoint_file_round_trip() {
+        let checkpoint = OrderbookCheckpoint {
+            saved_at: Utc::now(),
+            books: vec![BookCheckpoint::from_state(
+                "TEST",
+                &state(&[(44, 5), (45, 10)], &[(53, 20)]),
+            )],
+        };
+
+        let path = std::env::temp_dir().join(format!(
+            "kalshi-orderbook-checkpoint-{}.json",
+            std::process::id()
+        ));
+        checkpoint.save(&path).unwrap();
+        let loaded = OrderbookCheckpoint::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded, checkpoint);
+        let restored = loaded.books[0].to_state();
+        assert_eq!(restored.best_yes_bid(), Some((45, 10)));
+        assert_eq!(restored.total_yes_liquidity(), 15);
+    }
+
+    #[test]
+    fn test_temp_path_appends_suffix() {
+        assert_eq!(
+            temp_path(Path::new("/data/books.json")),
+            Path::new("/data/books.json.tmp")
+        );
+        assert_eq!(
+            temp_path(Path::new("/data/books.tmp")),
+            Path::new("/data/books.tmp.tmp")
+        );
+    }
+
+    #[test]
+    fn test_load_missing_file() {
+        let err = OrderbookCheckpoint::load("/nonexistent/checkpoint.json").unwrap_err();
+        assert!(matches!(err, Error::CheckpointFileError(..)));
+    }
+
+    #[test]
+    fn test_drift_lists_differing_levels() {
+        let local = state(&[(44, 5), (45, 10)], &[(53, 20)]);
+        let remote = state(&[(45, 12)], &[(53, 20), (54, 3)]);
+
+        let drift = BookDrift::between("TEST", &local, &remote);
+        assert_eq!(
+            drift.levels,
+            vec![
+                LevelDrift {
+                    side: Side::Yes,
+                    price: 44,
+                    local: 5,
+                    remote: 0
+                },
+                LevelDrift {
+                    side: Side::Yes,
+                    price: 45,
+                    local: 10,
+                    remote: 12
+                },
+                LevelDrift {
+                    side: Side::No,
+                    price: 54,
+                    local: 0,
+                    remote: 3
+                },
+            ]
+        );
+
+        assert!(BookDrift::between("TEST", &local, &local).is_empty());
+    }
+}
@@ -0,0 +1,443 @@
+//! Fixed-depth ladder views and the diffs published between them.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tokio::sync::broadcast;
+use tokio::time::Instant;
+
+use crate::models::Side;
+
+use super::state::OrderbookState;
+
+/// Configuration for the top-N depth feed.
+///
+/// Enable the feed with [`OrderbookAggregator::with_depth`](super::OrderbookAggregator::with_depth).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DepthConfig {
+    /// Number of price levels kept per side.
+    pub levels: usize,
+    /// Minimum time between updates for the same market.
+    ///
+    /// Changes arriving inside the window are conflated into one update sent
+    /// when it closes. `None` publishes every change.
+    pub throttle: Option<Duration>,
+}
+
+impl DepthConfig {
+    /// Create a config publishing the top `levels` per side on every change.
+    pub fn new(levels: usize) -> Self {
+        Self {
+            levels,
+            throttle: None,
+        }
+    }
+
+    /// Publish at most one update per market per `interval`.
+    #[must_use]
+    pub fn with_throttle(mut self, interval: Duration) -> Self {
+        self.throttle = Some(interval);
+        self
+    }
+}
+
+impl Default for DepthConfig {
+    fn default() -> Self {
+        Self::new(10)
+    }
+}
+
+/// Side of a YES-denominated ladder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DepthSide {
+    /// YES bids, best (highest) first.
+    Bid,
+    /// YES asks derived from NO bids (`100 - no_price`), best (lowest) first.
+    Ask,
+}
+
+/// The top levels of a market's book, expressed in YES prices.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DepthSnapshot {
+    /// Market ticker.
+    pub ticker: String,
+    /// Book version this view was taken at.
+    pub version: u64,
+    /// YES bids as (price, quantity), best first.
+    pub bids: Vec<(i64, i64)>,
+    /// YES asks as (price, quantity), best first.
+    pub asks: Vec<(i64, i64)>,
+}
+
+impl DepthSnapshot {
+    pub(crate) fn from_state(ticker: &str, orderbook: &OrderbookState, levels: usize) -> Self {
+        Self {
+            ticker: ticker.to_string(),
+            version: orderbook.version(),
+            bids: orderbook
+                .yes_levels()
+                .iter()
+                .rev()
+                .take(levels)
+                .map(|(&price, &qty)| (price, qty))
+                .collect(),
+            asks: orderbook.asks(Side::Yes).take(levels).collect(),
+        }
+    }
+
+    /// Apply a [`DepthUpdate`] to bring this view up to date.
+    ///
+    /// Full updates replace the view. Incremental updates only apply when
+    /// their [`base_version`](DepthUpdate::base_version) matches this view's
+    /// version. Returns `false` if the update was not applied, meaning an
+    /// update was missed and the view should be refreshed with
+    /// [`OrderbookAggregator::depth`](super::OrderbookAggregator::depth).
+    pub fn apply(&mut self, update: &DepthUpdate) -> bool {
+        if update.ticker != self.ticker {
+            return false;
+        }
+        match update.base_version {
+            None => {
+                self.bids.clear();
+                self.asks.clear();
+            }
+            Some(base) if base == self.version => {}
+            Some(_) => return false,
+        }
+
+        for change in &update.changes {
+            let levels = match change.side {
+                DepthSide::Bid => &mut self.bids,
+                DepthSide::Ask => &mut self.asks,
+            };
+            levels.retain(|(price, _)| *price != change.price);
+            if change.quantity > 0 {
+                levels.push((change.price, change.quantity));
+            }
+        }
+        self.bids
+            .sort_unstable_by_key(|&(price, _)| std::cmp::Reverse(price));
+        self.asks.sort_unstable_by_key(|&(price, _)| price);
+        self.version = update.version;
+        true
+    }
+
+    /// Levels that differ between `self` and `next`.
+    fn diff(&self, next: &DepthSnapshot) -> Vec<DepthChange> {
+        let mut changes = Vec::new();
+        for (side, before, after) in [
+            (DepthSide::Bid, &self.bids, &next.bids),
+            (DepthSide::Ask, &self.asks, &next.asks),
+        ] {
+            let before_map: HashMap<i64, i64> = before.iter().copied().collect();
+            let after_map: HashMap<i64, i64> = after.iter().copied().collect();
+
+            for &(price, _) in before {
+                if !after_map.contains_key(&price) {
+                    changes.push(DepthChange {
+                        side,
+                        price,
+                        quantity: 0,
+                    });
+                }
+            }
+            for &(price, quantity) in after {
+                if before_map.get(&price) != Some(&quantity) {
+                    changes.push(DepthChange {
+                        side,
+                        price,
+                        quantity,
+                    });
+                }
+            }
+        }
+        changes
+    }
+
+    /// Every level as a change, for full updates.
+    fn changes(&self) -> Vec<DepthChange> {
+        let bids = self.bids.iter().map(|&(price, quantity)| DepthChange {
+            side: DepthSide::Bid,
+            price,
+            quantity,
+        });
+        let asks = self.asks.iter().map(|&(price, quantity)| DepthChange {
+            side: DepthSide::Ask,
+            price,
+            quantity,
+        });
+        bids.chain(asks).collect()
+    }
+}
+
+/// A single level change in a [`DepthUpdate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DepthChange {
+    /// Ladder side.
+    pub side: DepthSide,
+    /// YES price in cents.
+    pub price: i64,
+    /// New quantity at this level. Zero means the level left the top N.
+    pub quantity: i64,
+}
+
+/// A change to a market's top-N depth view.
+///
+/// Published on [`OrderbookAggregator::depth_receiver`](super::OrderbookAggregator::depth_receiver).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DepthUpdate {
+    /// Market ticker.
+    pub ticker: String,
+    /// Book version after this update.
+    pub version: u64,
+    /// Version of the previous update for this market, which this one builds
+    /// on. `None` marks a full update listing every level, sent for the first
+    /// view of a market and after a book snapshot replaces it.
+    pub base_version: Option<u64>,
+    /// Changed levels. For full updates, every level in the view.
+    pub changes: Vec<DepthChange>,
+}
+
+impl DepthUpdate {
+    /// Whether this update replaces the whole view.
+    pub fn is_full(&self) -> bool {
+        self.base_version.is_none()
+    }
+}
+
+/// Whether a changed market can publish now.
+pub(crate) enum Admission {
+    /// Publish immediately.
+    Now,
+    /// Throttled; schedule a flush at the deadline.
+    Schedule(Instant),
+    /// Throttled and a flush is already scheduled.
+    Pending,
+}
+
+#[derive(Debug, Default)]
+struct MarketDepth {
+    /// The last view sent, or `None` if the next update must be full.
+    published: Option<DepthSnapshot>,
+    last_sent: Option<Instant>,
+    flush_scheduled: bool,
+}
+
+/// Tracks what each market last published and applies throttling.
+#[derive(Debug)]
+pub(crate) struct DepthPublisher {
+    config: DepthConfig,
+    sender: broadcast::Sender<DepthUpdate>,
+    markets: Mutex<HashMap<String, MarketDepth>>,
+}
+
+impl DepthPublisher {
+    pub(crate) fn new(config: DepthConfig, sender: broadcast::Sender<DepthUpdate>) -> Self {
+        Self {
+            config,
+            sender,
+            markets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn levels(&self) -> usize {
+        self.config.levels
+    }
+
+    pub(crate) fn admit(&self, ticker: &str, now: Instant) -> Admission {
+        let Some(throttle) = self.config.throttle else {
+            return Admission::Now;
+        };
+        let mut markets = self.markets.lock().expect("depth lock poisoned");
+        let market = markets.entry(ticker.to_string()).or_default();
+        if market.flush_scheduled {
+            return Admission::Pending;
+        }
+        match market.last_sent {
+            Some(last) if now < last + throttle => {
+                market.flush_scheduled = true;
+                Admission::Schedule(last + throttle)
+            }
+            _ => Admission::Now,
+        }
+    }
+
+    /// Publish the difference between the last view sent and `current`.
+    ///
+    /// `None` means the book is unavailable (stale or removed); nothing is
+    /// sent and the next view will be a full update.
+    pub(crate) fn publish(&self, ticker: &str, current: Option<DepthSnapshot>, now: Instant) {
+        let mut markets = self.markets.lock().expect("depth lock poisoned");
+        let market = markets.entry(ticker.to_string()).or_default();
+        market.flush_scheduled = false;
+
+        let Some(current) = current else {
+            market.published = None;
+            return;
+        };
+
+        let update = match &market.published {
+            // A concurrent publish already sent this version or a later one
+            Some(previous) if previous.version >= current.version => return,
+            Some(previous) => {
+                let changes = previous.diff(&current);
+                if changes.is_empty() {
+                    return;
+                }
+                DepthUpdate {
+                    ticker: ticker.to_string(),
+                    version: current.version,
+                    base_version: Some(previous.version),
+                    changes,
+                }
+            }
+            None => DepthUpdate {
+                ticker: ticker.to_string(),
+                version: current.version,
+                base_version: None,
+                changes: current.changes(),
+            },
+        };
+
+        market.published = Some(current);
+        market.last_sent = Some(now);
+        let _ = self.sender.send(update);
+    }
+
+    /// Force the next update for `ticker` to be full.
+    pub(crate) fn reset(&self, ticker: &str) {
+        let mut markets = self.markets.lock().expect("depth lock poisoned");
+        if let Some(market) = markets.get_mut(ticker) {
+            market.published = None;
+        }
+    }
+
+    /// Forget every market.
+    pub(crate) fn reset_all(&self) {
+        let mut markets = self.markets.lock().expect("depth lock poisoned");
+        markets.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(version: u64, bids: &[(i64, i64)], asks: &[(i64, i64)]) -> DepthSnapshot {
+        DepthSnapshot {
+            ticker: "TEST".to_string(),
+            version,
+            bids: bids.to_vec(),
+            asks: asks.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_diff_reports_changed_added_and_removed_levels() {
+        let before = snapshot(1, &[(45, 10), (44, 5)], &[(47, 3)]);
+        let after = snapshot(2, &[(46, 1), (45, 12)], &[(47, 3)]);
+
+        let mut changes = before.diff(&after);
+        changes.sort_by_key(|c| c.price);
+        assert_eq!(
+            changes,
+            vec![
+                DepthChange {
+                    side: DepthSide::Bid,
+                    price: 44,
+                    quantity: 0
+                },
+                DepthChange {
+                    side: DepthSide::Bid,
+                    price: 45,
+                    quantity: 12
+                },
+                DepthChange {
+                    side: DepthSide::Bid,
+                    price: 46,
+                    quantity: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_apply_reconstructs_view() {
+        let before = snapshot(1, &[(45, 10), (44, 5)], &[(47, 3), (48, 9)]);
+        let after = snapshot(4, &[(46, 1), (45, 12)], &[(48, 9)]);
+
+        let update = DepthUpdate {
+            ticker: "TEST".to_string(),
+            version: 4,
+            base_version: Some(1),
+            changes: before.diff(&after),
+        };
+        let mut view = before.clone();
+        assert!(view.apply(&update));
+        assert_eq!(view, after);
+    }
+
+    #[test]
+    fn test_apply_rejects_version_mismatch() {
+        let mut view = snapshot(3, &[(45, 10)], &[]);
+        let update = DepthUpdate {
+            ticker: "TEST".to_string(),
+            version: 6,
+            base_version: Some(5),
+            changes: vec![],
+        };
+        assert!(!view.apply(&update));
+        assert_eq!(view.version, 3);
+    }
+
+    #[test]
+    fn test_publisher_sends_full_then_diffs() {
+        let (sender, mut receiver) = broadcast::channel(16);
+        let publisher = DepthPublisher::new(DepthConfig::new(5), sender);
+        let now = Instant::now();
+
+        publisher.publish("TEST", Some(snapshot(1, &[(45, 10)], &[])), now);
+        let full = receiver.try_recv().unwrap();
+        assert!(full.is_full());
+        assert_eq!(full.changes.len(), 1);
+
+        // Unchanged view: nothing sent
+        publisher.publish("TEST", Some(snapshot(2, &[(45, 10)], &[])), now);
+        assert!(receiver.try_recv().is_err());
+
+        publisher.publish("TEST", Some(snapshot(3, &[(45, 8)], &[])), now);
+        let diff = receiver.try_recv().unwrap();
+        assert_eq!(diff.base_version, Some(1));
+        assert_eq!(diff.version, 3);
+
+        publisher.reset("TEST");
+        publisher.publish("TEST", Some(snapshot(4, &[(45, 8)], &[])), now);
+        assert!(receiver.try_recv().unwrap().is_full());
+    }
+
+    #[test]
+    fn test_publisher_throttle_admission() {
+        let (sender, _receiver) = broadcast::channel(16);
+        let config = DepthConfig::new(5).with_throttle(Duration::from_millis(100));
+        let publisher = DepthPublisher::new(config, sender);
+        let now = Instant::now();
+
+        assert!(matches!(publisher.admit("TEST", now), Admission::Now));
+        publisher.publish("TEST", Some(snapshot(1, &[(45, 10)], &[])), now);
+
+        let later = now + Duration::from_millis(10);
+        assert!(matches!(
+            publisher.admit("TEST", later),
+            Admission::Schedule(deadline) if deadline == now + Duration::from_millis(100)
+        ));
+        assert!(matches!(publisher.admit("TEST", later), Admission::Pending));
+
+        publisher.publish("TEST", Some(snapshot(2, &[(45, 9)], &[])), later);
+        let after_window = later + Duration::from_millis(100);
+        assert!(matches!(
+            publisher.admit("TEST", after_window),
+            Admission::Now
+        ));
+    }
+}
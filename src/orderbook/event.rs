@@ -0,0 +1,596 @@
+//! Combined view over the markets of one event.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tokio::sync::broadcast;
+use tracing::debug;
+
+use crate::client::KalshiClient;
+use crate::error::Result;
+use crate::fees::quadratic_fee_cents;
+use crate::models::{Action, Event, EventResponse, GetEventParams, Market, Side};
+
+use super::aggregator::OrderbookAggregator;
+use super::impact::Sweep;
+
+/// Default channel capacity for basket alerts.
+const DEFAULT_ALERT_CAPACITY: usize = 64;
+
+/// Configuration for [`EventBook`] basket pricing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventBookConfig {
+    /// Contracts traded in each market when pricing a basket.
+    ///
+    /// Default: 1.
+    pub contracts: i64,
+
+    /// Taker fee multiplier for the quadratic fee
+    /// `ceil(multiplier * contracts * P * (1 - P))`, with `P` in dollars.
+    ///
+    /// Default: 0.07, the standard Kalshi taker rate. Scale it by the
+    /// series' `fee_multiplier` for series with a different schedule, or see
+    /// [`fees`](crate::fees) for the full fee rules.
+    pub fee_multiplier: f64,
+
+    /// Minimum edge in cents, after fees, before an alert is raised.
+    ///
+    /// Default: 1.
+    pub min_edge_cents: i64,
+}
+
+impl Default for EventBookConfig {
+    fn default() -> Self {
+        Self {
+            contracts: 1,
+            fee_multiplier: 0.07,
+            min_edge_cents: 1,
+        }
+    }
+}
+
+/// Top of book for one market in an event.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventLeg {
+    /// Market ticker.
+    pub ticker: String,
+    /// Best YES bid (price, quantity).
+    pub best_bid: Option<(i64, i64)>,
+    /// Best YES ask (price, quantity).
+    pub best_ask: Option<(i64, i64)>,
+    /// YES midpoint in cents.
+    pub midpoint: Option<f64>,
+    /// Midpoint normalized so the event's probabilities sum to 1.
+    ///
+    /// `None` unless every market in the event has a midpoint.
+    pub probability: Option<f64>,
+}
+
+/// Price of buying or selling one side in every market of an event.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BasketQuote {
+    /// Side traded in every market.
+    pub side: Side,
+    /// Whether the basket buys or sells `side`.
+    pub action: Action,
+    /// Contracts traded per market.
+    pub contracts: i64,
+    /// Total price of the legs in cents, before fees: paid when buying,
+    /// received when selling.
+    pub cost_cents: i64,
+    /// Estimated taker fees in cents.
+    pub fees_cents: i64,
+    /// Settlement value in cents if exactly one market resolves YES: received
+    /// when buying, owed when selling.
+    ///
+    /// A YES basket settles at `100 * contracts`; a NO basket at
+    /// `100 * contracts * (markets - 1)`.
+    pub payout_cents: i64,
+    /// Per-market fills, in the event's market order.
+    pub legs: Vec<Sweep>,
+}
+
+impl BasketQuote {
+    /// Profit in cents after fees if exactly one market resolves YES.
+    /// Positive means the basket is mispriced in the trader's favor.
+    pub fn edge_cents(&self) -> i64 {
+        let gross = match self.action {
+            Action::Buy => self.payout_cents - self.cost_cents,
+            Action::Sell => self.cost_cents - self.payout_cents,
+        };
+        gross - self.fees_cents
+    }
+}
+
+/// Point-in-time view of an event's combined book.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventSnapshot {
+    /// Event ticker.
+    pub event_ticker: String,
+    /// One entry per market, in the event's market order.
+    pub legs: Vec<EventLeg>,
+    /// Sum of best YES bids in cents, if every market has a bid.
+    pub bid_sum: Option<i64>,
+    /// Sum of best YES asks in cents, if every market has an ask.
+    pub ask_sum: Option<i64>,
+    /// Sum of best YES asks over 100 cents. Positive values are the
+    /// bookmaker's margin on buying every outcome.
+    pub overround: Option<i64>,
+    /// Buying YES in every market, if every book can fill it.
+    pub buy_all_yes: Option<BasketQuote>,
+    /// Buying NO in every market, if every book can fill it.
+    pub buy_all_no: Option<BasketQuote>,
+    /// Selling YES in every market, if every book can fill it.
+    pub sell_all_yes: Option<BasketQuote>,
+}
+
+/// A basket whose price after fees beats its settlement value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BasketAlert {
+    /// Event ticker.
+    pub event_ticker: String,
+    /// The mispriced basket.
+    pub quote: BasketQuote,
+}
+
+/// Combined orderbook for the mutually exclusive markets of an event.
+///
+/// Reads live books from an [`OrderbookAggregator`], so the event's markets
+/// must be subscribed on `orderbook_delta` and fed to that aggregator. The
+/// book reports an implied probability distribution from midpoints, the
+/// overround, and what it costs to buy YES or NO, or sell YES, in every
+/// market.
+///
+/// When exactly one market in the event resolves YES, buying YES across the
+/// whole set pays $1 per contract and buying NO pays $1 per contract for each
+/// market but one. [`check`](Self::check) raises a [`BasketAlert`] when either
+/// basket costs less than that payout after fees, or when selling YES across
+/// the set (the bid sum) brings in more than the $1 owed. This assumes the
+/// markets are exhaustive as well as mutually exclusive; events with an
+/// implicit "none of the above" outcome can leave every market NO.
+///
+/// # Example
+///
+/// ```no_run
+/// use kalshi_trade_rs::orderbook::{EventBook, OrderbookAggregator};
+/// # use kalshi_trade_rs::KalshiClient;
+///
+/// # async fn example(client: &KalshiClient, aggregator: OrderbookAggregator) -> kalshi_trade_rs::Result<()> {
+/// let book = EventBook::load(client, aggregator, "KXFEDDECISION-25DEC").await?;
+///
+/// let snapshot = book.snapshot();
+/// for leg in &snapshot.legs {
+///     println!("{}: {:?}", leg.ticker, leg.probability);
+/// }
+/// println!("overround: {:?}", snapshot.overround);
+///
+/// let mut alerts = book.alert_receiver();
+/// tokio::spawn(async move { book.watch().await });
+/// while let Ok(alert) = alerts.recv().await {
+///     println!("{} basket edge {}¢", alert.quote.side, alert.quote.edge_cents());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct EventBook {
+    event_ticker: String,
+    markets: Arc<Vec<String>>,
+    mutually_exclusive: bool,
+    aggregator: OrderbookAggregator,
+    config: EventBookConfig,
+    alert_sender: broadcast::Sender<BasketAlert>,
+    /// Whether each basket's last check was above the alert threshold, so
+    /// alerts fire once per crossing rather than on every update. Indexed
+    /// like [`BASKETS`].
+    alerted: Arc<[AtomicBool; 3]>,
+}
+
+/// Baskets checked for alerts.
+const BASKETS: [(Side, Action); 3] = [
+    (Side::Yes, Action::Buy),
+    (Side::No, Action::Buy),
+    (Side::Yes, Action::Sell),
+];
+
+impl EventBook {
+    /// Create an event book from an event and its markets.
+    pub fn new(aggregator: OrderbookAggregator, event: &Event, markets: &[Market]) -> Self {
+        let (alert_sender, _) = broadcast::channel(DEFAULT_ALERT_CAPACITY);
+        Self {
+            event_ticker: event.event_ticker.clone(),
+            markets: Arc::new(markets.iter().map(|m| m.ticker.clone()).collect()),
+            mutually_exclusive: event.mutually_exclusive,
+            aggregator,
+            config: EventBookConfig::default(),
+            alert_sender,
+            alerted: Arc::new(Default::default()),
+        }
+    }
+
+    /// Create an event book from a `get_event` response.
+    ///
+    /// Uses the nested markets when present, otherwise the top-level list.
+    pub fn from_response(aggregator: OrderbookAggregator, response: &EventResponse) -> Self {
+        let markets = response
+            .event
+            .markets
+            .as_deref()
+            .unwrap_or(&response.markets);
+        Self::new(aggregator, &response.event, markets)
+    }
+
+    /// Fetch an event with its nested markets and build its book.
+    pub async fn load(
+        client: &KalshiClient,
+        aggregator: OrderbookAggregator,
+        event_ticker: &str,
+    ) -> Result<Self> {
+        let params = GetEventParams::new().with_nested_markets(true);
+        let response = client.get_event_with_params(event_ticker, params).await?;
+        Ok(Self::from_response(aggregator, &response))
+    }
+
+    /// Set basket pricing options.
+    #[must_use]
+    pub fn with_config(mut self, config: EventBookConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Event ticker.
+    pub fn event_ticker(&self) -> &str {
+        &self.event_ticker
+    }
+
+    /// Market tickers in the event.
+    pub fn markets(&self) -> &[String] {
+        &self.markets
+    }
+
+    /// Whether Kalshi flags the event's markets as mutually exclusive.
+    ///
+    /// Basket payouts, and so alerts, only hold for mutually exclusive events.
+    pub fn is_mutually_exclusive(&self) -> bool {
+        self.mutually_exclusive
+    }
+
+    /// Take a snapshot of the combined book.
+    pub fn snapshot(&self) -> EventSnapshot {
+        let mut legs: Vec<EventLeg> = self
+            .markets
+            .iter()
+            .map(|ticker| {
+                let summary = self.aggregator.summary(ticker);
+                EventLeg {
+                    ticker: ticker.clone(),
+                    best_bid: summary.as_ref().and_then(|s| s.best_bid),
+                    best_ask: summary.as_ref().and_then(|s| s.best_ask),
+                    midpoint: summary.as_ref().and_then(|s| s.midpoint),
+                    probability: None,
+                }
+            })
+            .collect();
+
+        let mid_sum: Option<f64> = legs.iter().map(|leg| leg.midpoint).sum();
+        if let Some(total) = mid_sum.filter(|total| *total > 0.0) {
+            for leg in &mut legs {
+                leg.probability = leg.midpoint.map(|mid| mid / total);
+            }
+        }
+
+        let bid_sum: Option<i64> = legs.iter().map(|leg| Some(leg.best_bid?.0)).sum();
+        let ask_sum: Option<i64> = legs.iter().map(|leg| Some(leg.best_ask?.0)).sum();
+
+        EventSnapshot {
+            event_ticker: self.event_ticker.clone(),
+            legs,
+            bid_sum,
+            ask_sum,
+            overround: ask_sum.map(|sum| sum - 100),
+            buy_all_yes: self.basket(Side::Yes),
+            buy_all_no: self.basket(Side::No),
+            sell_all_yes: self.sell_basket(Side::Yes),
+        }
+    }
+
+    /// Price buying `side` in every market by walking each book.
+    ///
+    /// Returns `None` if any market has no live book or cannot fill
+    /// [`EventBookConfig::contracts`].
+    pub fn basket(&self, side: Side) -> Option<BasketQuote> {
+        self.quote(side, Action::Buy)
+    }
+
+    /// Price selling `side` in every market by walking each book's bids.
+    ///
+    /// Returns `None` if any market has no live book or cannot fill
+    /// [`EventBookConfig::contracts`].
+    pub fn sell_basket(&self, side: Side) -> Option<BasketQuote> {
+        self.quote(side, Action::Sell)
+    }
+
+    fn quote(&self, side: Side, action: Action) -> Option<BasketQuote> {
+        if self.markets.is_empty() {
+            return None;
+        }
+        let contracts = self.config.contracts;
+
+        let mut legs = Vec::with_capacity(self.markets.len());
+        for ticker in self.markets.iter() {
+            let sweep = match action {
+                Action::Buy => self.aggregator.cost_to_buy(ticker, side, contracts)?,
+                Action::Sell => self.aggregator.proceeds_to_sell(ticker, side, contracts)?,
+            };
+            if !sweep.is_complete() {
+                return None;
+            }
+            legs.push(sweep);
+        }
+
+        let cost_cents = legs.iter().map(|leg| leg.cost).sum();
+        let fees_cents = legs
+            .iter()
+            .flat_map(|leg| &leg.levels)
+            .map(|level| {
+                quadratic_fee_cents(self.config.fee_multiplier, level.price, level.quantity)
+            })
+            .sum();
+        let winners = match side {
+            Side::Yes => 1,
+            Side::No => self.markets.len() as i64 - 1,
+        };
+
+        Some(BasketQuote {
+            side,
+            action,
+            contracts,
+            cost_cents,
+            fees_cents,
+            payout_cents: 100 * contracts * winners,
+            legs,
+        })
+    }
+
+    /// Check the buy-all-YES, buy-all-NO and sell-all-YES baskets and return
+    /// alerts for newly mispriced ones.
+    ///
+    /// Selling YES fills against the same YES bids as buying NO, so those two
+    /// baskets cross the threshold together with the same edge.
+    ///
+    /// An alert is returned when a basket's edge first reaches
+    /// [`EventBookConfig::min_edge_cents`], and again only after it has
+    /// dropped below. Always empty for events that are not mutually exclusive.
+    pub fn check(&self) -> Vec<BasketAlert> {
+        if !self.mutually_exclusive {
+            return Vec::new();
+        }
+
+        let mut alerts = Vec::new();
+        for ((side, action), alerted) in BASKETS.into_iter().zip(self.alerted.iter()) {
+            let quote = self
+                .quote(side, action)
+                .filter(|quote| quote.edge_cents() >= self.config.min_edge_cents);
+            let was_alerted = alerted.swap(quote.is_some(), Ordering::Relaxed);
+            if let Some(quote) = quote
+                && !was_alerted
+            {
+                alerts.push(BasketAlert {
+                    event_ticker: self.event_ticker.clone(),
+                    quote,
+                });
+            }
+        }
+        alerts
+    }
+
+    /// Re-check the baskets on every book change for the event's markets.
+    ///
+    /// Alerts are published on [`alert_receiver`](Self::alert_receiver).
+    /// Never returns on its own: the book holds an aggregator handle, so the
+    /// update channel stays open. Spawn it in a task and abort the task to
+    /// stop watching.
+    pub async fn watch(&self) {
+        let mut updates = self.aggregator.update_receiver();
+        loop {
+            match updates.recv().await {
+                Ok(update) => {
+                    if !self.markets.contains(&update.ticker) {
+                        continue;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    debug!("Event book {} lagged by {} updates", self.event_ticker, n);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+
+            for alert in self.check() {
+                let _ = self.alert_sender.send(alert);
+            }
+        }
+    }
+
+    /// Subscribe to basket alerts raised by [`watch`](Self::watch).
+    pub fn alert_receiver(&self) -> broadcast::Receiver<BasketAlert> {
+        self.alert_sender.subscribe()
+    }
+}
+
+impl std::fmt::Debug for EventBook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventBook")
+            .field("event_ticker", &self.event_ticker)
+            .field("markets", &self.markets)
+            .field("mutually_exclusive", &self.mutually_exclusive)
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ws::{OrderbookSnapshotData, StreamMessage, StreamUpdate};
+
+    fn event(mutually_exclusive: bool) -> Event {
+        serde_json::from_value(serde_json::json!({
+            "event_ticker": "EVT",
+            "series_ticker": "SER",
+            "title": "Who wins?",
+            "sub_title": "",
+            "category": "",
+            "collateral_return_type": "",
+            "mutually_exclusive": mutually_exclusive,
+            "available_on_brokers": false,
+        }))
+        .unwrap()
+    }
+
+    /// A book with a single YES bid and YES ask (via the NO side).
+    fn seed(aggregator: &OrderbookAggregator, ticker: &str, yes_bid: i64, yes_ask: i64) {
+        let level =
+            |cents: i64| vec![(format!("{:.2}", cents as f64 / 100.0), "10.00".to_string())];
+        aggregator.apply_update(&StreamUpdate {
+            channel: "orderbook_snapshot".to_string(),
+            sid: 1,
+            seq: None,
+            msg: StreamMessage::OrderbookSnapshot(OrderbookSnapshotData {
+                market_ticker: ticker.to_string(),
+                market_id: String::new(),
+                yes_dollars_fp: Some(level(yes_bid)),
+                no_dollars_fp: Some(level(100 - yes_ask)),
+            }),
+        });
+    }
+
+    fn book(aggregator: &OrderbookAggregator, mutually_exclusive: bool) -> EventBook {
+        let mut book = EventBook::new(aggregator.clone(), &event(mutually_exclusive), &[]);
+        book.markets = Arc::new(vec!["A".to_string(), "B".to_string(), "C".to_string()]);
+        book
+    }
+
+    #[test]
+    fn test_snapshot_distribution_and_overround() {
+        let aggregator = OrderbookAggregator::new();
+        seed(&aggregator, "A", 48, 52);
+        seed(&aggregator, "B", 28, 32);
+        seed(&aggregator, "C", 18, 22);
+
+        let snapshot = book(&aggregator, true).snapshot();
+
+        assert_eq!(snapshot.bid_sum, Some(94));
+        assert_eq!(snapshot.ask_sum, Some(106));
+        assert_eq!(snapshot.overround, Some(6));
+        let probabilities: Vec<f64> = snapshot
+            .legs
+            .iter()
+            .map(|leg| leg.probability.unwrap())
+            .collect();
+        assert_eq!(probabilities, vec![0.5, 0.3, 0.2]);
+
+        let yes = snapshot.buy_all_yes.unwrap();
+        assert_eq!(yes.cost_cents, 106);
+        assert_eq!(yes.payout_cents, 100);
+        assert!(yes.edge_cents() < 0);
+
+        // NO asks are 100 - YES bid: 52 + 72 + 82
+        let no = snapshot.buy_all_no.unwrap();
+        assert_eq!(no.cost_cents, 206);
+        assert_eq!(no.payout_cents, 200);
+
+        let sell = snapshot.sell_all_yes.unwrap();
+        assert_eq!(sell.action, Action::Sell);
+        assert_eq!(sell.cost_cents, 94);
+        assert_eq!(sell.payout_cents, 100);
+        assert!(sell.edge_cents() < 0);
+    }
+
+    #[test]
+    fn test_missing_market_leaves_sums_empty() {
+        let aggregator = OrderbookAggregator::new();
+        seed(&aggregator, "A", 48, 52);
+        seed(&aggregator, "B", 28, 32);
+
+        let snapshot = book(&aggregator, true).snapshot();
+        assert_eq!(snapshot.ask_sum, None);
+        assert!(snapshot.legs.iter().all(|leg| leg.probability.is_none()));
+        assert!(snapshot.buy_all_yes.is_none());
+    }
+
+    #[test]
+    fn test_check_alerts_once_per_crossing() {
+        let aggregator = OrderbookAggregator::new();
+        // YES asks sum to 90; fees are 2 + 2 + 2 = 6, leaving a 4 cent edge
+        seed(&aggregator, "A", 38, 40);
+        seed(&aggregator, "B", 28, 30);
+        seed(&aggregator, "C", 18, 20);
+        let book = book(&aggregator, true);
+
+        let alerts = book.check();
+        assert_eq!(alerts.len(), 1);
+        let quote = &alerts[0].quote;
+        assert_eq!(quote.side, Side::Yes);
+        assert_eq!(quote.cost_cents, 90);
+        assert_eq!(quote.fees_cents, 6);
+        assert_eq!(quote.edge_cents(), 4);
+
+        // Still mispriced: no repeat
+        assert!(book.check().is_empty());
+
+        // Back to fair, then mispriced again: alerts again
+        seed(&aggregator, "A", 58, 60);
+        assert!(book.check().is_empty());
+        seed(&aggregator, "A", 38, 40);
+        assert_eq!(book.check().len(), 1);
+    }
+
+    #[test]
+    fn test_check_alerts_on_rich_bids() {
+        let aggregator = OrderbookAggregator::new();
+        // YES bids sum to 110; fees are 2 + 2 + 2 = 6, leaving a 4 cent edge
+        seed(&aggregator, "A", 50, 52);
+        seed(&aggregator, "B", 35, 37);
+        seed(&aggregator, "C", 25, 27);
+        let book = book(&aggregator, true);
+
+        let alerts = book.check();
+        assert_eq!(alerts.len(), 2);
+        let sell = &alerts[1].quote;
+        assert_eq!((sell.side, sell.action), (Side::Yes, Action::Sell));
+        assert_eq!(sell.cost_cents, 110);
+        assert_eq!(sell.fees_cents, 6);
+        assert_eq!(sell.edge_cents(), 4);
+
+        // Buying NO hits the same bids for the same edge
+        let no = &alerts[0].quote;
+        assert_eq!((no.side, no.action), (Side::No, Action::Buy));
+        assert_eq!(no.edge_cents(), 4);
+        assert!(book.check().is_empty());
+    }
+
+    #[test]
+    fn test_check_ignores_non_exclusive_events() {
+        let aggregator = OrderbookAggregator::new();
+        seed(&aggregator, "A", 38, 40);
+        seed(&aggregator, "B", 28, 30);
+        seed(&aggregator, "C", 18, 20);
+
+        let book = book(&aggregator, false);
+        assert!(book.basket(Side::Yes).unwrap().edge_cents() > 0);
+        assert!(book.check().is_empty());
+    }
+
+    #[test]
+    fn test_from_response_without_markets() {
+        let aggregator = OrderbookAggregator::new();
+        let response = EventResponse {
+            event: event(true),
+            markets: Vec::new(),
+        };
+        let book = EventBook::from_response(aggregator, &response);
+        assert_eq!(book.event_ticker(), "EVT");
+        assert!(book.markets().is_empty());
+        assert!(book.basket(Side::Yes).is_none());
+    }
+}
@@ -0,0 +1,462 @@
+//! Rolling trade-flow and order-flow statistics per market.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::broadcast;
+use tokio::time::Instant;
+use tracing::debug;
+
+use crate::models::Side;
+use crate::ws::{KalshiStreamHandle, StreamMessage, TradeData};
+
+use super::aggregator::{OrderbookAggregator, OrderbookUpdate};
+
+/// Default rolling window.
+const DEFAULT_WINDOW: Duration = Duration::from_secs(60);
+
+/// Default channel capacity for stats broadcasts.
+const DEFAULT_STATS_CAPACITY: usize = 1024;
+
+/// Flow statistics for one market over the rolling window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowStats {
+    /// Market ticker.
+    pub ticker: String,
+    /// Length of the rolling window.
+    pub window: Duration,
+    /// Trades where the taker bought YES.
+    pub yes_trades: u64,
+    /// Trades where the taker bought NO.
+    pub no_trades: u64,
+    /// Contracts traded with a YES taker.
+    pub yes_volume: i64,
+    /// Contracts traded with a NO taker.
+    pub no_volume: i64,
+    /// Order-flow imbalance: the net change in top-of-book queues, in
+    /// contracts. Positive values mean bid-side pressure.
+    pub ofi: i64,
+    /// YES midpoint in cents.
+    pub midpoint: Option<f64>,
+    /// Size-weighted YES mid in cents, leaning toward the side with less
+    /// size at the touch.
+    pub microprice: Option<f64>,
+    /// Square root of the summed squared midpoint changes, in cents.
+    pub realized_volatility: f64,
+    /// Time since the last trade, if one was seen.
+    pub time_since_last_trade: Option<Duration>,
+}
+
+impl FlowStats {
+    /// Aggressor imbalance in `[-1, 1]`: `(yes_volume - no_volume)` over the
+    /// total. `None` without trades in the window.
+    pub fn trade_imbalance(&self) -> Option<f64> {
+        let total = self.yes_volume + self.no_volume;
+        (total > 0).then(|| (self.yes_volume - self.no_volume) as f64 / total as f64)
+    }
+}
+
+/// Best bid and ask with sizes, in YES cents.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Touch {
+    bid: Option<(i64, i64)>,
+    ask: Option<(i64, i64)>,
+}
+
+impl Touch {
+    fn midpoint(&self) -> Option<f64> {
+        Some((self.bid?.0 + self.ask?.0) as f64 / 2.0)
+    }
+
+    fn microprice(&self) -> Option<f64> {
+        let (bid, bid_qty) = self.bid?;
+        let (ask, ask_qty) = self.ask?;
+        let total = bid_qty + ask_qty;
+        (total > 0).then(|| (bid * ask_qty + ask * bid_qty) as f64 / total as f64)
+    }
+
+    /// Order-flow imbalance contribution of moving from `prev` to `self`.
+    ///
+    /// Follows Cont, Kukanov and Stoikov: bid size added at or above the
+    /// previous best bid counts positive, ask size added at or below the
+    /// previous best ask counts negative, and size removed counts the other
+    /// way. A side that is missing contributes nothing.
+    fn ofi_since(&self, prev: &Touch) -> i64 {
+        let mut e = 0;
+        if let (Some((bid, qty)), Some((prev_bid, prev_qty))) = (self.bid, prev.bid) {
+            if bid >= prev_bid {
+                e += qty;
+            }
+            if bid <= prev_bid {
+                e -= prev_qty;
+            }
+        }
+        if let (Some((ask, qty)), Some((prev_ask, prev_qty))) = (self.ask, prev.ask) {
+            if ask <= prev_ask {
+                e -= qty;
+            }
+            if ask >= prev_ask {
+                e += prev_qty;
+            }
+        }
+        e
+    }
+}
+
+#[derive(Debug, Default)]
+struct MarketFlow {
+    /// (time, taker side, contracts)
+    trades: VecDeque<(Instant, Side, i64)>,
+    /// (time, OFI contribution)
+    ofi: VecDeque<(Instant, i64)>,
+    /// (time, squared midpoint change)
+    mid_moves: VecDeque<(Instant, f64)>,
+    touch: Option<Touch>,
+    last_trade: Option<Instant>,
+}
+
+impl MarketFlow {
+    fn prune(&mut self, cutoff: Instant) {
+        while self.trades.front().is_some_and(|(at, ..)| *at < cutoff) {
+            self.trades.pop_front();
+        }
+        while self.ofi.front().is_some_and(|(at, _)| *at < cutoff) {
+            self.ofi.pop_front();
+        }
+        while self.mid_moves.front().is_some_and(|(at, _)| *at < cutoff) {
+            self.mid_moves.pop_front();
+        }
+    }
+
+    fn stats(&self, ticker: &str, window: Duration, now: Instant) -> FlowStats {
+        let mut stats = FlowStats {
+            ticker: ticker.to_string(),
+            window,
+            yes_trades: 0,
+            no_trades: 0,
+            yes_volume: 0,
+            no_volume: 0,
+            ofi: self.ofi.iter().map(|(_, e)| e).sum(),
+            midpoint: self.touch.and_then(|t| t.midpoint()),
+            microprice: self.touch.and_then(|t| t.microprice()),
+            realized_volatility: self.mid_moves.iter().map(|(_, sq)| sq).sum::<f64>().sqrt(),
+            time_since_last_trade: self.last_trade.map(|at| now.saturating_duration_since(at)),
+        };
+        for &(_, side, count) in &self.trades {
+            match side {
+                Side::Yes => {
+                    stats.yes_trades += 1;
+                    stats.yes_volume += count;
+                }
+                Side::No => {
+                    stats.no_trades += 1;
+                    stats.no_volume += count;
+                }
+            }
+        }
+        stats
+    }
+}
+
+/// Rolling trade-flow and order-flow statistics per market.
+///
+/// Trades come from the `trade` channel; order-flow imbalance, microprice and
+/// midpoint volatility come from the top of book published by an
+/// [`OrderbookAggregator`]. Statistics cover a rolling window (60 seconds by
+/// default) and are published on [`stats_receiver`](Self::stats_receiver)
+/// after every trade or top-of-book change.
+///
+/// # Example
+///
+/// ```no_run
+/// use kalshi_trade_rs::orderbook::{FlowAnalytics, OrderbookAggregator};
+/// use std::time::Duration;
+///
+/// # async fn example(handle: kalshi_trade_rs::ws::KalshiStreamHandle) {
+/// // The handle is subscribed to `OrderbookDelta` and `Trade`
+/// let aggregator = OrderbookAggregator::new();
+/// let flow = FlowAnalytics::new(aggregator.clone()).with_window(Duration::from_secs(30));
+/// let mut stats = flow.stats_receiver();
+///
+/// let agg_clone = aggregator.clone();
+/// let book_handle = handle.clone();
+/// tokio::spawn(async move { agg_clone.process_updates(book_handle).await });
+/// let flow_clone = flow.clone();
+/// tokio::spawn(async move { flow_clone.process_updates(handle).await });
+///
+/// while let Ok(s) = stats.recv().await {
+///     println!(
+///         "{}: ofi={} imbalance={:?} micro={:?}",
+///         s.ticker, s.ofi, s.trade_imbalance(), s.microprice
+///     );
+/// }
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct FlowAnalytics {
+    aggregator: OrderbookAggregator,
+    window: Duration,
+    markets: Arc<Mutex<HashMap<String, MarketFlow>>>,
+    stats_sender: broadcast::Sender<FlowStats>,
+}
+
+impl FlowAnalytics {
+    /// Create flow analytics reading books from `aggregator`.
+    pub fn new(aggregator: OrderbookAggregator) -> Self {
+        let (stats_sender, _) = broadcast::channel(DEFAULT_STATS_CAPACITY);
+        Self {
+            aggregator,
+            window: DEFAULT_WINDOW,
+            markets: Arc::new(Mutex::new(HashMap::new())),
+            stats_sender,
+        }
+    }
+
+    /// Set the rolling window.
+    #[must_use]
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Process trades from a WebSocket handle and book changes from the
+    /// aggregator.
+    ///
+    /// The aggregator must be fed separately, usually by running its own
+    /// [`process_updates`](OrderbookAggregator::process_updates) on a clone of
+    /// the same handle. Runs until the connection is closed or lost; spawn it
+    /// in a task.
+    pub async fn process_updates(&self, mut handle: KalshiStreamHandle) {
+        let mut books = self.aggregator.update_receiver();
+        loop {
+            tokio::select! {
+                result = handle.update_receiver.recv() => match result {
+                    Ok(update) => match &update.msg {
+                        StreamMessage::Closed { .. } | StreamMessage::ConnectionLost { .. } => break,
+                        StreamMessage::Trade(trade) => self.apply_trade(trade),
+                        _ => {}
+                    },
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        debug!("Flow analytics lagged by {} stream messages", n);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                result = books.recv() => match result {
+                    Ok(update) => self.apply_book_update(&update),
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        // A missed change would show up as one large OFI jump
+                        debug!("Flow analytics lagged by {} book updates", n);
+                        self.reset_touches();
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+            }
+        }
+    }
+
+    /// Record a public trade.
+    pub fn apply_trade(&self, trade: &TradeData) {
+        let count = trade.count_fp.parse::<f64>().unwrap_or(0.0).round() as i64;
+        let now = Instant::now();
+        self.update(&trade.market_ticker, now, |flow| {
+            flow.trades.push_back((now, trade.taker_side, count));
+            flow.last_trade = Some(now);
+        });
+    }
+
+    /// Record a top-of-book change published by the aggregator.
+    ///
+    /// Snapshots (updates without a delta) set a new baseline rather than
+    /// counting as flow.
+    pub fn apply_book_update(&self, update: &OrderbookUpdate) {
+        let touch = Touch {
+            bid: update.summary.best_bid,
+            ask: update.summary.best_ask,
+        };
+        let now = Instant::now();
+        let is_delta = update.delta.is_some();
+        self.update(&update.ticker, now, |flow| {
+            if is_delta && let Some(prev) = flow.touch {
+                let e = touch.ofi_since(&prev);
+                if e != 0 {
+                    flow.ofi.push_back((now, e));
+                }
+                if let (Some(mid), Some(prev_mid)) = (touch.midpoint(), prev.midpoint())
+                    && mid != prev_mid
+                {
+                    flow.mid_moves.push_back((now, (mid - prev_mid).powi(2)));
+                }
+            }
+            flow.touch = Some(touch);
+        });
+    }
+
+    /// Get the current statistics for a market.
+    ///
+    /// Returns `None` if nothing has been seen for the market.
+    pub fn stats(&self, ticker: &str) -> Option<FlowStats> {
+        let now = Instant::now();
+        let mut markets = self.markets.lock().expect("flow lock poisoned");
+        let flow = markets.get_mut(ticker)?;
+        flow.prune(now.checked_sub(self.window).unwrap_or(now));
+        Some(flow.stats(ticker, self.window, now))
+    }
+
+    /// Subscribe to statistics published after every trade or book change.
+    pub fn stats_receiver(&self) -> broadcast::Receiver<FlowStats> {
+        self.stats_sender.subscribe()
+    }
+
+    fn update(&self, ticker: &str, now: Instant, apply: impl FnOnce(&mut MarketFlow)) {
+        let stats = {
+            let mut markets = self.markets.lock().expect("flow lock poisoned");
+            let flow = markets.entry(ticker.to_string()).or_default();
+            apply(flow);
+            flow.prune(now.checked_sub(self.window).unwrap_or(now));
+            flow.stats(ticker, self.window, now)
+        };
+        let _ = self.stats_sender.send(stats);
+    }
+
+    /// Drop every market's baseline so the next book change starts fresh.
+    fn reset_touches(&self) {
+        let mut markets = self.markets.lock().expect("flow lock poisoned");
+        for flow in markets.values_mut() {
+            flow.touch = None;
+        }
+    }
+}
+
+impl std::fmt::Debug for FlowAnalytics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FlowAnalytics")
+            .field("window", &self.window)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::{OrderbookDelta, OrderbookSummary};
+
+    fn trade(taker_side: Side, count: &str) -> TradeData {
+        TradeData {
+            trade_id: String::new(),
+            market_ticker: "TEST".to_string(),
+            yes_price_dollars: "0.45".to_string(),
+            no_price_dollars: "0.55".to_string(),
+            count_fp: count.to_string(),
+            taker_side,
+            ts: 0,
+        }
+    }
+
+    fn book(bid: (i64, i64), ask: (i64, i64), is_delta: bool) -> OrderbookUpdate {
+        OrderbookUpdate {
+            ticker: "TEST".to_string(),
+            summary: OrderbookSummary {
+                ticker: "TEST".to_string(),
+                best_bid: Some(bid),
+                best_ask: Some(ask),
+                spread: Some(ask.0 - bid.0),
+                midpoint: Some((bid.0 + ask.0) as f64 / 2.0),
+                total_yes_liquidity: bid.1,
+                total_no_liquidity: ask.1,
+            },
+            delta: is_delta.then_some(OrderbookDelta {
+                side: Side::Yes,
+                price: bid.0,
+                quantity_change: 0,
+                new_quantity: bid.1,
+            }),
+            resynced: false,
+        }
+    }
+
+    #[test]
+    fn test_ofi_terms() {
+        let prev = Touch {
+            bid: Some((45, 10)),
+            ask: Some((47, 20)),
+        };
+        // Bid queue grows at the same price
+        let grow = Touch {
+            bid: Some((45, 15)),
+            ..prev
+        };
+        assert_eq!(grow.ofi_since(&prev), 5);
+        // Bid improves: the whole new queue counts
+        let improve = Touch {
+            bid: Some((46, 3)),
+            ..prev
+        };
+        assert_eq!(improve.ofi_since(&prev), 3);
+        // Ask drops a level: the whole new ask queue counts against
+        let ask_down = Touch {
+            ask: Some((46, 4)),
+            ..prev
+        };
+        assert_eq!(ask_down.ofi_since(&prev), -4);
+        // Ask level eaten: previous queue counts for the bid side
+        let ask_up = Touch {
+            ask: Some((48, 7)),
+            ..prev
+        };
+        assert_eq!(ask_up.ofi_since(&prev), 20);
+    }
+
+    #[test]
+    fn test_microprice_leans_to_thin_side() {
+        let touch = Touch {
+            bid: Some((45, 30)),
+            ask: Some((47, 10)),
+        };
+        // Heavy bid, thin ask: price is closer to the ask
+        assert_eq!(touch.microprice(), Some(46.5));
+    }
+
+    #[tokio::test]
+    async fn test_stats_accumulate_and_publish() {
+        let flow = FlowAnalytics::new(OrderbookAggregator::new());
+        let mut receiver = flow.stats_receiver();
+
+        flow.apply_trade(&trade(Side::Yes, "30.00"));
+        flow.apply_trade(&trade(Side::No, "10.00"));
+        flow.apply_book_update(&book((45, 10), (47, 20), false));
+        flow.apply_book_update(&book((45, 15), (47, 20), true));
+        flow.apply_book_update(&book((46, 5), (48, 20), true));
+
+        let stats = flow.stats("TEST").unwrap();
+        assert_eq!((stats.yes_trades, stats.no_trades), (1, 1));
+        assert_eq!((stats.yes_volume, stats.no_volume), (30, 10));
+        assert_eq!(stats.trade_imbalance(), Some(0.5));
+        // +5 from the bid growing, then +5 for the new bid and +20 for the
+        // ask queue that was lifted
+        assert_eq!(stats.ofi, 30);
+        assert_eq!(stats.midpoint, Some(47.0));
+        assert_eq!(stats.realized_volatility, 1.0);
+        assert!(stats.time_since_last_trade.is_some());
+
+        let mut published = 0;
+        while receiver.try_recv().is_ok() {
+            published += 1;
+        }
+        assert_eq!(published, 5);
+        assert!(flow.stats("OTHER").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_window_expires_samples() {
+        let flow =
+            FlowAnalytics::new(OrderbookAggregator::new()).with_window(Duration::from_millis(20));
+        flow.apply_trade(&trade(Side::Yes, "5.00"));
+        tokio::time::sleep(Duration::from_millis(40)).await;
+
+        let stats = flow.stats("TEST").unwrap();
+        assert_eq!(stats.yes_volume, 0);
+        assert_eq!(stats.trade_imbalance(), None);
+        assert!(stats.time_since_last_trade.unwrap() >= Duration::from_millis(40));
+    }
+}
@@ -0,0 +1,311 @@
+//! Book invariant checks and per-market activity tracking.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tokio::sync::broadcast;
+use tokio::time::Instant;
+
+use crate::models::Side;
+
+use super::store::TopOfBook;
+
+/// Configuration for book health monitoring.
+///
+/// Enable monitoring with [`OrderbookAggregator::with_health`](super::OrderbookAggregator::with_health).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BookHealthConfig {
+    /// How long a market can go without an update before it is reported
+    /// [`Quiet`](BookHealth::Quiet).
+    ///
+    /// Kalshi only sends deltas when a book changes, so a quiet book is not
+    /// necessarily wrong; it means nothing confirms it is still current.
+    ///
+    /// Default: 30 seconds.
+    pub quiet_after: Duration,
+
+    /// How often [`process_updates`](super::OrderbookAggregator::process_updates)
+    /// looks for quiet markets.
+    ///
+    /// Default: 1 second.
+    pub check_interval: Duration,
+}
+
+impl Default for BookHealthConfig {
+    fn default() -> Self {
+        Self {
+            quiet_after: Duration::from_secs(30),
+            check_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+/// A change in a market's book health.
+///
+/// Published on [`OrderbookAggregator::health_receiver`](super::OrderbookAggregator::health_receiver).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BookHealth {
+    /// The best YES bid is at or above the best YES ask, which only happens
+    /// when the book has missed an update.
+    Crossed {
+        /// Market ticker.
+        ticker: String,
+        /// Best YES bid in cents.
+        bid: i64,
+        /// Best YES ask in cents.
+        ask: i64,
+    },
+    /// A delta removed more than the level held, so an earlier increase was
+    /// missed. The level is dropped.
+    NegativeQuantity {
+        /// Market ticker.
+        ticker: String,
+        /// Bid side of the level.
+        side: Side,
+        /// Price in cents.
+        price: i64,
+        /// Quantity the delta would have left.
+        quantity: i64,
+    },
+    /// A price that is not a whole cent between 1 and 99.
+    OffGrid {
+        /// Market ticker.
+        ticker: String,
+        /// Bid side of the level.
+        side: Side,
+        /// Price as received, in dollars.
+        price_dollars: String,
+    },
+    /// No update for longer than [`BookHealthConfig::quiet_after`].
+    Quiet {
+        /// Market ticker.
+        ticker: String,
+        /// Time since the last update.
+        idle: Duration,
+    },
+    /// A crossed or quiet market is healthy again.
+    Recovered {
+        /// Market ticker.
+        ticker: String,
+    },
+    /// A snapshot replaced the market's book.
+    Reinitialized {
+        /// Market ticker.
+        ticker: String,
+        /// Whether the replaced book was stale.
+        resynced: bool,
+    },
+}
+
+impl BookHealth {
+    /// Market the event is about.
+    pub fn ticker(&self) -> &str {
+        match self {
+            Self::Crossed { ticker, .. }
+            | Self::NegativeQuantity { ticker, .. }
+            | Self::OffGrid { ticker, .. }
+            | Self::Quiet { ticker, .. }
+            | Self::Recovered { ticker }
+            | Self::Reinitialized { ticker, .. } => ticker,
+        }
+    }
+}
+
+/// Whether a price in dollars is a whole cent between 1 and 99.
+pub(crate) fn on_grid(price_dollars: &str) -> bool {
+    let Ok(dollars) = price_dollars.parse::<f64>() else {
+        return false;
+    };
+    let cents = dollars * 100.0;
+    (cents - cents.round()).abs() < 1e-6 && (1.0..=99.0).contains(&cents.round())
+}
+
+fn crossed(top: &TopOfBook) -> Option<(i64, i64)> {
+    let (bid, _) = top.best_bid?;
+    let (ask, _) = top.best_ask?;
+    (bid >= ask).then_some((bid, ask))
+}
+
+#[derive(Debug)]
+struct MarketHealth {
+    last_update: Instant,
+    crossed: bool,
+    quiet: bool,
+}
+
+/// Tracks per-market health and publishes transitions.
+#[derive(Debug)]
+pub(crate) struct HealthMonitor {
+    config: BookHealthConfig,
+    sender: broadcast::Sender<BookHealth>,
+    markets: Mutex<HashMap<String, MarketHealth>>,
+}
+
+impl HealthMonitor {
+    pub fn new(config: BookHealthConfig, sender: broadcast::Sender<BookHealth>) -> Self {
+        Self {
+            config,
+            sender,
+            markets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn check_interval(&self) -> Duration {
+        self.config.check_interval
+    }
+
+    /// Record a snapshot replacing the book.
+    pub fn reinitialized(&self, ticker: &str, top: Option<TopOfBook>, resynced: bool) {
+        self.markets
+            .lock()
+            .expect("health lock poisoned")
+            .remove(ticker);
+        self.report(BookHealth::Reinitialized {
+            ticker: ticker.to_string(),
+            resynced,
+        });
+        self.updated(ticker, top);
+    }
+
+    /// Record a change to a live book and check it for crossing.
+    pub fn updated(&self, ticker: &str, top: Option<TopOfBook>) {
+        let crossed = top.as_ref().and_then(crossed);
+        let mut events = Vec::new();
+        {
+            let mut markets = self.markets.lock().expect("health lock poisoned");
+            let health = markets
+                .entry(ticker.to_string())
+                .or_insert_with(|| MarketHealth {
+                    last_update: Instant::now(),
+                    crossed: false,
+                    quiet: false,
+                });
+            let was_unhealthy = health.crossed || health.quiet;
+            health.last_update = Instant::now();
+            health.quiet = false;
+
+            match crossed {
+                Some((bid, ask)) if !health.crossed => {
+                    health.crossed = true;
+                    events.push(BookHealth::Crossed {
+                        ticker: ticker.to_string(),
+                        bid,
+                        ask,
+                    });
+                }
+                Some(_) => {}
+                None => {
+                    health.crossed = false;
+                    if was_unhealthy {
+                        events.push(BookHealth::Recovered {
+                            ticker: ticker.to_string(),
+                        });
+                    }
+                }
+            }
+        }
+        for event in events {
+            self.report(event);
+        }
+    }
+
+    pub fn report(&self, event: BookHealth) {
+        let _ = self.sender.send(event);
+    }
+
+    /// Report markets that have just gone quiet.
+    pub fn check_quiet(&self, now: Instant) {
+        let mut events = Vec::new();
+        {
+            let mut markets = self.markets.lock().expect("health lock poisoned");
+            for (ticker, health) in markets.iter_mut() {
+                let idle = now.saturating_duration_since(health.last_update);
+                if !health.quiet && idle > self.config.quiet_after {
+                    health.quiet = true;
+                    events.push(BookHealth::Quiet {
+                        ticker: ticker.clone(),
+                        idle,
+                    });
+                }
+            }
+        }
+        for event in events {
+            self.report(event);
+        }
+    }
+
+    /// Whether the market is neither crossed nor quiet.
+    pub fn is_healthy(&self, ticker: &str, now: Instant) -> bool {
+        let markets = self.markets.lock().expect("health lock poisoned");
+        markets.get(ticker).is_some_and(|health| {
+            !health.crossed
+                && now.saturating_duration_since(health.last_update) <= self.config.quiet_after
+        })
+    }
+
+    pub fn since_update(&self, ticker: &str, now: Instant) -> Option<Duration> {
+        let markets = self.markets.lock().expect("health lock poisoned");
+        Some(now.saturating_duration_since(markets.get(ticker)?.last_update))
+    }
+
+    pub fn remove(&self, ticker: &str) {
+        self.markets
+            .lock()
+            .expect("health lock poisoned")
+            .remove(ticker);
+    }
+
+    pub fn clear(&self) {
+        self.markets.lock().expect("health lock poisoned").clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_on_grid() {
+        assert!(on_grid("0.45"));
+        assert!(on_grid("0.4500"));
+        assert!(on_grid("0.01"));
+        assert!(on_grid("0.99"));
+        assert!(!on_grid("0.455"));
+        assert!(!on_grid("0.00"));
+        assert!(!on_grid("1.00"));
+        assert!(!on_grid("abc"));
+    }
+
+    #[test]
+    fn test_quiet_reported_once_until_update() {
+        let (sender, mut receiver) = broadcast::channel(16);
+        let monitor = HealthMonitor::new(
+            BookHealthConfig {
+                quiet_after: Duration::from_secs(5),
+                ..Default::default()
+            },
+            sender,
+        );
+        monitor.updated("TEST", None);
+        let start = Instant::now();
+
+        monitor.check_quiet(start + Duration::from_secs(1));
+        assert!(receiver.try_recv().is_err());
+        assert!(monitor.is_healthy("TEST", start + Duration::from_secs(1)));
+
+        monitor.check_quiet(start + Duration::from_secs(10));
+        assert!(matches!(receiver.try_recv(), Ok(BookHealth::Quiet { .. })));
+        monitor.check_quiet(start + Duration::from_secs(20));
+        assert!(receiver.try_recv().is_err());
+        assert!(!monitor.is_healthy("TEST", start + Duration::from_secs(20)));
+
+        monitor.updated("TEST", None);
+        assert_eq!(
+            receiver.try_recv().unwrap(),
+            BookHealth::Recovered {
+                ticker: "TEST".to_string()
+            }
+        );
+    }
+}
@@ -0,0 +1,280 @@
+//! Market-impact calculations that walk one side of a book.
+//!
+//! Buying YES lifts resting NO bids and buying NO lifts resting YES bids, so
+//! the ask ladder for a side is the opposite side's bids converted with
+//! `ask = 100 - bid`. Selling a side hits that side's own bids. Callers pass
+//! the ladder in, best price first.
+
+use crate::models::Side;
+
+/// One price level consumed by a sweep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SweepLevel {
+    /// Price in cents for the side being bought or sold.
+    pub price: i64,
+    /// Contracts taken at this level.
+    pub quantity: i64,
+    /// Cost of this level in cents (`price * quantity`).
+    pub cost: i64,
+}
+
+/// Result of walking one side of a book to buy or sell contracts.
+///
+/// Returned by [`OrderbookAggregator`](super::OrderbookAggregator) impact
+/// queries such as [`cost_to_buy`](super::OrderbookAggregator::cost_to_buy).
+/// For sells, [`cost`](Self::cost) is the proceeds. Costs exclude exchange
+/// fees.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sweep {
+    /// Market ticker.
+    pub ticker: String,
+    /// Side being bought or sold.
+    pub side: Side,
+    /// Levels consumed, best price first.
+    pub levels: Vec<SweepLevel>,
+    /// Total contracts filled across all levels.
+    pub contracts: i64,
+    /// Total cost in cents across all levels.
+    pub cost: i64,
+    /// Contracts requested but not available in the book.
+    ///
+    /// Always zero for budget and price-band queries.
+    pub unfilled: i64,
+}
+
+impl Sweep {
+    /// Whether the book could fill the whole request.
+    pub fn is_complete(&self) -> bool {
+        self.unfilled == 0
+    }
+
+    /// Volume-weighted average fill price in cents.
+    ///
+    /// Returns `None` if nothing was filled.
+    pub fn vwap(&self) -> Option<f64> {
+        (self.contracts > 0).then(|| self.cost as f64 / self.contracts as f64)
+    }
+
+    /// Price of the first level taken, in cents.
+    pub fn best_price(&self) -> Option<i64> {
+        self.levels.first().map(|level| level.price)
+    }
+
+    /// Price of the last level taken, in cents.
+    ///
+    /// This is the limit price needed to fill the sweep as a single order.
+    pub fn worst_price(&self) -> Option<i64> {
+        self.levels.last().map(|level| level.price)
+    }
+
+    /// Cents between the worst and best price levels taken.
+    pub fn slippage(&self) -> Option<i64> {
+        Some(self.worst_price()? - self.best_price()?)
+    }
+}
+
+/// Accumulates levels into a [`Sweep`].
+struct SweepBuilder {
+    sweep: Sweep,
+}
+
+impl SweepBuilder {
+    fn new(ticker: &str, side: Side) -> Self {
+        Self {
+            sweep: Sweep {
+                ticker: ticker.to_string(),
+                side,
+                levels: Vec::new(),
+                contracts: 0,
+                cost: 0,
+                unfilled: 0,
+            },
+        }
+    }
+
+    fn take(&mut self, price: i64, quantity: i64) {
+        let cost = price * quantity;
+        self.sweep.levels.push(SweepLevel {
+            price,
+            quantity,
+            cost,
+        });
+        self.sweep.contracts += quantity;
+        self.sweep.cost += cost;
+    }
+
+    fn finish(self) -> Sweep {
+        self.sweep
+    }
+}
+
+/// Buy up to `contracts`, taking levels in order.
+pub(crate) fn sweep_contracts(
+    ticker: &str,
+    side: Side,
+    asks: impl Iterator<Item = (i64, i64)>,
+    contracts: i64,
+) -> Sweep {
+    let mut builder = SweepBuilder::new(ticker, side);
+    let mut remaining = contracts.max(0);
+
+    for (price, quantity) in asks {
+        if remaining == 0 {
+            break;
+        }
+        let take = quantity.min(remaining);
+        builder.take(price, take);
+        remaining -= take;
+    }
+
+    let mut sweep = builder.finish();
+    sweep.unfilled = remaining;
+    sweep
+}
+
+/// Buy as many contracts as `budget` cents allows, taking levels in order.
+pub(crate) fn sweep_budget(
+    ticker: &str,
+    side: Side,
+    asks: impl Iterator<Item = (i64, i64)>,
+    budget: i64,
+) -> Sweep {
+    let mut builder = SweepBuilder::new(ticker, side);
+    let mut remaining = budget.max(0);
+
+    for (price, quantity) in asks {
+        if price <= 0 {
+            continue;
+        }
+        let take = quantity.min(remaining / price);
+        if take == 0 {
+            break;
+        }
+        builder.take(price, take);
+        remaining -= price * take;
+        if take < quantity {
+            break;
+        }
+    }
+
+    builder.finish()
+}
+
+/// Take every level priced within `cents` of the best ask.
+pub(crate) fn sweep_within(
+    ticker: &str,
+    side: Side,
+    asks: impl Iterator<Item = (i64, i64)>,
+    cents: i64,
+) -> Sweep {
+    let mut builder = SweepBuilder::new(ticker, side);
+    let mut limit = None;
+
+    for (price, quantity) in asks {
+        let limit = *limit.get_or_insert(price + cents.max(0));
+        if price > limit {
+            break;
+        }
+        builder.take(price, quantity);
+    }
+
+    builder.finish()
+}
+
+/// Best ask left after `contracts` have been bought.
+///
+/// Returns `None` if the sweep would exhaust the book.
+pub(crate) fn price_after(asks: impl Iterator<Item = (i64, i64)>, contracts: i64) -> Option<i64> {
+    let mut remaining = contracts.max(0);
+    for (price, quantity) in asks {
+        if quantity > remaining {
+            return Some(price);
+        }
+        remaining -= quantity;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Asks at 40x10, 42x20, 45x5.
+    fn asks() -> impl Iterator<Item = (i64, i64)> {
+        [(40, 10), (42, 20), (45, 5)].into_iter()
+    }
+
+    #[test]
+    fn test_sweep_contracts_partial_level() {
+        let sweep = sweep_contracts("TEST", Side::Yes, asks(), 15);
+
+        assert!(sweep.is_complete());
+        assert_eq!(sweep.contracts, 15);
+        assert_eq!(sweep.cost, 40 * 10 + 42 * 5);
+        assert_eq!(
+            sweep.levels,
+            vec![
+                SweepLevel {
+                    price: 40,
+                    quantity: 10,
+                    cost: 400
+                },
+                SweepLevel {
+                    price: 42,
+                    quantity: 5,
+                    cost: 210
+                },
+            ]
+        );
+        assert_eq!(sweep.vwap(), Some(610.0 / 15.0));
+        assert_eq!(sweep.slippage(), Some(2));
+    }
+
+    #[test]
+    fn test_sweep_contracts_exceeding_book() {
+        let sweep = sweep_contracts("TEST", Side::Yes, asks(), 50);
+
+        assert!(!sweep.is_complete());
+        assert_eq!(sweep.contracts, 35);
+        assert_eq!(sweep.unfilled, 15);
+        assert_eq!(sweep.worst_price(), Some(45));
+    }
+
+    #[test]
+    fn test_sweep_budget() {
+        // 400 buys the first level, 100 more buys 2 at 42 with 16 left over.
+        let sweep = sweep_budget("TEST", Side::No, asks(), 500);
+
+        assert_eq!(sweep.contracts, 12);
+        assert_eq!(sweep.cost, 484);
+        assert_eq!(sweep.worst_price(), Some(42));
+    }
+
+    #[test]
+    fn test_sweep_budget_too_small() {
+        let sweep = sweep_budget("TEST", Side::Yes, asks(), 39);
+
+        assert_eq!(sweep.contracts, 0);
+        assert!(sweep.levels.is_empty());
+        assert_eq!(sweep.vwap(), None);
+    }
+
+    #[test]
+    fn test_sweep_within() {
+        let sweep = sweep_within("TEST", Side::Yes, asks(), 2);
+        assert_eq!(sweep.contracts, 30);
+        assert_eq!(sweep.worst_price(), Some(42));
+
+        let sweep = sweep_within("TEST", Side::Yes, asks(), 0);
+        assert_eq!(sweep.contracts, 10);
+    }
+
+    #[test]
+    fn test_price_after() {
+        assert_eq!(price_after(asks(), 0), Some(40));
+        assert_eq!(price_after(asks(), 9), Some(40));
+        assert_eq!(price_after(asks(), 10), Some(42));
+        assert_eq!(price_after(asks(), 34), Some(45));
+        assert_eq!(price_after(asks(), 35), None);
+    }
+}
@@ -69,11 +69,45 @@
 //!
 //! The aggregator handles this conversion automatically when reporting
 //! best ask prices.
+//!
+//! # Market Impact
+//!
+//! Before sending a taker order, walk the book to see what it would cost:
+//!
+//! ```no_run
+//! use kalshi_trade_rs::models::Side;
+//! use kalshi_trade_rs::orderbook::OrderbookAggregator;
+//!
+//! # fn example(aggregator: &OrderbookAggregator) {
+//! if let Some(sweep) = aggregator.cost_to_buy("TICKER-1", Side::Yes, 500) {
+//!     println!("vwap={:?} worst={:?}", sweep.vwap(), sweep.worst_price());
+//!     for level in &sweep.levels {
+//!         println!("  {} @ {}¢", level.quantity, level.price);
+//!     }
+//! }
+//! # }
+//! ```
 
 mod aggregator;
+mod checkpoint;
+mod depth;
+mod event;
+mod flow;
+mod health;
+mod impact;
+mod own;
 mod state;
+mod store;
 
 pub use aggregator::{
     OrderbookAggregator, OrderbookDelta, OrderbookLadder, OrderbookSummary, OrderbookUpdate,
     SequenceGap,
 };
+pub(crate) use checkpoint::temp_path;
+pub use checkpoint::{BookCheckpoint, BookDrift, LevelDrift, OrderbookCheckpoint, SeedReport};
+pub use depth::{DepthChange, DepthConfig, DepthSide, DepthSnapshot, DepthUpdate};
+pub use event::{BasketAlert, BasketQuote, EventBook, EventBookConfig, EventLeg, EventSnapshot};
+pub use flow::{FlowAnalytics, FlowStats};
+pub use health::{BookHealth, BookHealthConfig};
+pub use impact::{Sweep, SweepLevel};
+pub use own::{LevelOverlay, OwnOrder};
@@ -0,0 +1,289 @@
+//! Our own resting orders, tracked so they can be separated from the book.
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use crate::models::{Action, OrderStatus, QueuePosition, Side};
+use crate::ws::UserOrderData;
+
+/// One of our resting orders, placed on the bid ladder it rests on.
+///
+/// Kalshi books only hold bids: buying YES and selling NO both rest on the
+/// YES bids, and buying NO and selling YES rest on the NO bids.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnOrder {
+    /// Order ID.
+    pub order_id: String,
+    /// Market ticker.
+    pub ticker: String,
+    /// Bid ladder the order rests on.
+    pub side: Side,
+    /// Whether the order buys or sells its own side.
+    pub action: Action,
+    /// Bid price in cents on that ladder.
+    pub price: i64,
+    /// Contracts still resting.
+    pub remaining: i64,
+    /// Estimated contracts ahead of this order at its price.
+    ///
+    /// An upper bound: the other contracts at the level, or the position last
+    /// reported by `get_queue_positions` if that is lower. `None` when the
+    /// market's book is not live and no position has been reported.
+    pub queue_position: Option<i64>,
+}
+
+impl OwnOrder {
+    /// Parse a resting order from a `user_orders` message.
+    ///
+    /// Messages may omit the action, in which case `known` (the action
+    /// recorded from an earlier message) is used. Returns `None` for orders
+    /// that are no longer resting, or whose action has never been reported
+    /// and so cannot be placed on a ladder.
+    fn from_update(data: &UserOrderData, known: Option<Action>) -> Option<Self> {
+        let remaining = data.remaining_count_fp.parse::<f64>().ok()?.round() as i64;
+        if data.status != OrderStatus::Resting || remaining <= 0 {
+            return None;
+        }
+
+        let yes_price = (data.yes_price_dollars.parse::<f64>().ok()? * 100.0).round() as i64;
+        let action = data.action.or(known)?;
+        let side = match (data.side, action) {
+            (Side::Yes, Action::Buy) | (Side::No, Action::Sell) => Side::Yes,
+            (Side::No, Action::Buy) | (Side::Yes, Action::Sell) => Side::No,
+        };
+        let price = match side {
+            Side::Yes => yes_price,
+            Side::No => 100 - yes_price,
+        };
+
+        Some(Self {
+            order_id: data.order_id.clone(),
+            ticker: data.ticker.clone(),
+            side,
+            action,
+            price,
+            remaining,
+            queue_position: None,
+        })
+    }
+}
+
+/// A bid level split into our resting size and everyone else's.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LevelOverlay {
+    /// Bid price in cents.
+    pub price: i64,
+    /// Displayed quantity at the level.
+    pub quantity: i64,
+    /// Our resting quantity at the level.
+    pub own: i64,
+    /// Quantity that is not ours.
+    pub external: i64,
+    /// Estimated contracts ahead of our earliest order here, if we have one.
+    pub queue_position: Option<i64>,
+}
+
+/// Our resting quantity per bid level for one market.
+#[derive(Debug, Default)]
+pub(crate) struct OwnLevels {
+    yes: HashMap<i64, i64>,
+    no: HashMap<i64, i64>,
+}
+
+impl OwnLevels {
+    pub fn get(&self, side: Side, price: i64) -> i64 {
+        let levels = match side {
+            Side::Yes => &self.yes,
+            Side::No => &self.no,
+        };
+        levels.get(&price).copied().unwrap_or(0)
+    }
+}
+
+/// Resting orders by market, then order ID.
+#[derive(Debug, Default)]
+pub(crate) struct OwnOrders {
+    markets: RwLock<HashMap<String, HashMap<String, OwnOrder>>>,
+}
+
+impl OwnOrders {
+    /// Apply a `user_orders` update.
+    pub fn apply(&self, data: &UserOrderData) {
+        let mut markets = self.markets.write().expect("own orders lock poisoned");
+        let known = markets
+            .get(&data.ticker)
+            .and_then(|orders| orders.get(&data.order_id))
+            .map(|order| order.action);
+        match OwnOrder::from_update(data, known) {
+            Some(mut order) => {
+                let orders = markets.entry(order.ticker.clone()).or_default();
+                // An amend that keeps the price keeps its reported position
+                if let Some(previous) = orders.get(&order.order_id)
+                    && previous.side == order.side
+                    && previous.price == order.price
+                {
+                    order.queue_position = previous.queue_position;
+                }
+                orders.insert(order.order_id.clone(), order);
+            }
+            None => {
+                if let Some(orders) = markets.get_mut(&data.ticker) {
+                    orders.remove(&data.order_id);
+                    if orders.is_empty() {
+                        markets.remove(&data.ticker);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Record queue positions reported by REST for known orders.
+    pub fn apply_queue_positions(&self, positions: &[QueuePosition]) {
+        let mut markets = self.markets.write().expect("own orders lock poisoned");
+        for position in positions {
+            let Some(order) = markets
+                .get_mut(&position.market_ticker)
+                .and_then(|orders| orders.get_mut(&position.order_id))
+            else {
+                continue;
+            };
+            if let Ok(ahead) = position.queue_position_fp.parse::<f64>() {
+                order.queue_position = Some(ahead.round() as i64);
+            }
+        }
+    }
+
+    /// Resting orders in a market, best price first on each ladder.
+    pub fn orders(&self, ticker: &str) -> Vec<OwnOrder> {
+        let markets = self.markets.read().expect("own orders lock poisoned");
+        let mut orders: Vec<OwnOrder> = markets
+            .get(ticker)
+            .map(|orders| orders.values().cloned().collect())
+            .unwrap_or_default();
+        orders.sort_by(|a, b| {
+            (a.side == Side::No, std::cmp::Reverse(a.price), &a.order_id).cmp(&(
+                b.side == Side::No,
+                std::cmp::Reverse(b.price),
+                &b.order_id,
+            ))
+        });
+        orders
+    }
+
+    /// Our resting quantity per level in a market.
+    pub fn levels(&self, ticker: &str) -> OwnLevels {
+        let markets = self.markets.read().expect("own orders lock poisoned");
+        let mut levels = OwnLevels::default();
+        for order in markets.get(ticker).into_iter().flat_map(|o| o.values()) {
+            let side = match order.side {
+                Side::Yes => &mut levels.yes,
+                Side::No => &mut levels.no,
+            };
+            *side.entry(order.price).or_default() += order.remaining;
+        }
+        levels
+    }
+
+    pub fn clear(&self) {
+        self.markets
+            .write()
+            .expect("own orders lock poisoned")
+            .clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user_order(
+        order_id: &str,
+        side: &str,
+        action: &str,
+        yes_price: &str,
+        remaining: &str,
+        status: &str,
+    ) -> UserOrderData {
+        serde_json::from_value(serde_json::json!({
+            "order_id": order_id,
+            "user_id": "user",
+            "ticker": "TEST",
+            "status": status,
+            "side": side,
+            "is_yes": side == "yes",
+            "yes_price_dollars": yes_price,
+            "fill_count_fp": "0.00",
+            "remaining_count_fp": remaining,
+            "initial_count_fp": remaining,
+            "taker_fill_cost_dollars": "0.00",
+            "maker_fill_cost_dollars": "0.00",
+            "taker_fees_dollars": "0.00",
+            "maker_fees_dollars": "0.00",
+            "client_order_id": "",
+            "created_time": "2026-01-01T00:00:00Z",
+            "action": (!action.is_empty()).then_some(action),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_orders_rest_on_bid_ladders() {
+        let own = OwnOrders::default();
+        own.apply(&user_order("a", "yes", "buy", "0.45", "10.00", "resting"));
+        own.apply(&user_order("b", "no", "buy", "0.45", "5.00", "resting"));
+        own.apply(&user_order("c", "yes", "sell", "0.47", "3.00", "resting"));
+        own.apply(&user_order("d", "no", "sell", "0.44", "2.00", "resting"));
+
+        let levels = own.levels("TEST");
+        assert_eq!(levels.get(Side::Yes, 45), 10);
+        assert_eq!(levels.get(Side::Yes, 44), 2);
+        // NO buy at YES 45 is a NO bid at 55; a YES sell at 47 is a NO bid at 53
+        assert_eq!(levels.get(Side::No, 55), 5);
+        assert_eq!(levels.get(Side::No, 53), 3);
+
+        let ids: Vec<_> = own.orders("TEST").into_iter().map(|o| o.order_id).collect();
+        assert_eq!(ids, vec!["a", "d", "b", "c"]);
+    }
+
+    #[test]
+    fn test_updates_and_removals() {
+        let own = OwnOrders::default();
+        own.apply(&user_order("a", "yes", "buy", "0.45", "10.00", "resting"));
+        own.apply_queue_positions(&[QueuePosition {
+            order_id: "a".to_string(),
+            market_ticker: "TEST".to_string(),
+            queue_position_fp: "25.00".to_string(),
+        }]);
+
+        // Partial fill keeps the reported position
+        own.apply(&user_order("a", "yes", "buy", "0.45", "4.00", "resting"));
+        let order = &own.orders("TEST")[0];
+        assert_eq!(order.remaining, 4);
+        assert_eq!(order.queue_position, Some(25));
+
+        // A price change loses it
+        own.apply(&user_order("a", "yes", "buy", "0.46", "4.00", "resting"));
+        assert_eq!(own.orders("TEST")[0].queue_position, None);
+
+        own.apply(&user_order("a", "yes", "buy", "0.46", "0.00", "executed"));
+        assert!(own.orders("TEST").is_empty());
+        assert!(own.markets.read().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_missing_action_keeps_known_action() {
+        let own = OwnOrders::default();
+        own.apply(&user_order("a", "yes", "sell", "0.47", "3.00", "resting"));
+
+        // A partial fill without an action stays on the NO ladder
+        own.apply(&user_order("a", "yes", "", "0.47", "2.00", "resting"));
+        let levels = own.levels("TEST");
+        assert_eq!(levels.get(Side::No, 53), 2);
+        assert_eq!(levels.get(Side::Yes, 47), 0);
+        assert_eq!(own.orders("TEST")[0].action, Action::Sell);
+
+        // An order never reported with an action is not placed
+        own.apply(&user_order("b", "yes", "", "0.40", "5.00", "resting"));
+        assert_eq!(own.orders("TEST").len(), 1);
+    }
+}
@@ -2,7 +2,7 @@
 
 use std::collections::BTreeMap;
 
-use crate::models::Side;
+use crate::models::{OrderbookResponse, PriceLevelDollars, PriceLevelDollarsCountFp, Side};
 use crate::ws::{OrderbookDeltaData, OrderbookSnapshotData};
 
 /// Internal orderbook state for a single market.
@@ -14,10 +14,16 @@ pub(crate) struct OrderbookState {
     yes_levels: BTreeMap<i64, i64>,
     /// NO side price levels: price_cents -> quantity
     no_levels: BTreeMap<i64, i64>,
-    /// Last sequence number seen
-    last_seq: Option<i64>,
     /// Whether we've received the initial snapshot
     initialized: bool,
+    /// Whether the book is known to be out of sync and awaits a fresh snapshot
+    stale: bool,
+    /// Incremented on every snapshot and delta applied
+    version: u64,
+    /// Running sum of YES quantities
+    yes_total: i64,
+    /// Running sum of NO quantities
+    no_total: i64,
 }
 
 impl OrderbookState {
@@ -41,53 +47,104 @@ impl OrderbookState {
             .map(|levels| dollars_levels_to_btree(levels))
             .unwrap_or_default();
 
+        Self::from_levels(yes_levels, no_levels)
+    }
+
+    /// Initialize from a REST `GET /markets/{ticker}/orderbook` response.
+    ///
+    /// Prefers the fixed-point `orderbook_fp` levels and falls back to the
+    /// legacy integer counts.
+    pub fn from_rest(response: &OrderbookResponse) -> Self {
+        let (yes, no) = match &response.orderbook_fp {
+            Some(book) => {
+                let pairs = |levels: &Option<Vec<PriceLevelDollarsCountFp>>| {
+                    levels
+                        .iter()
+                        .flatten()
+                        .map(|l| (l.price.clone(), l.quantity.clone()))
+                        .collect::<Vec<_>>()
+                };
+                (pairs(&book.yes_dollars), pairs(&book.no_dollars))
+            }
+            None => {
+                let book = &response.orderbook;
+                let pairs = |levels: &Option<Vec<PriceLevelDollars>>| {
+                    levels
+                        .iter()
+                        .flatten()
+                        .map(|l| (l.price.clone(), l.quantity.to_string()))
+                        .collect::<Vec<_>>()
+                };
+                (pairs(&book.yes_dollars), pairs(&book.no_dollars))
+            }
+        };
+
+        Self::from_levels(dollars_levels_to_btree(&yes), dollars_levels_to_btree(&no))
+    }
+
+    /// Initialize from price levels in cents.
+    pub fn from_levels(yes_levels: BTreeMap<i64, i64>, no_levels: BTreeMap<i64, i64>) -> Self {
         Self {
+            yes_total: yes_levels.values().sum(),
+            no_total: no_levels.values().sum(),
             yes_levels,
             no_levels,
-            last_seq: None,
             initialized: true,
+            stale: false,
+            version: 1,
         }
     }
 
+    /// Continue the version sequence of a book this one replaces.
+    pub fn continue_from(&mut self, previous: &OrderbookState) {
+        self.version = previous.version + 1;
+    }
+
     /// Apply a delta update.
     ///
     /// Returns the new quantity at the price level after applying the delta.
     /// Parses `price_dollars` and `delta_fp` strings from the v2 API.
     pub fn apply_delta(&mut self, delta: &OrderbookDeltaData) -> i64 {
-        let levels = match delta.side {
-            Side::Yes => &mut self.yes_levels,
-            Side::No => &mut self.no_levels,
+        let (levels, total) = match delta.side {
+            Side::Yes => (&mut self.yes_levels, &mut self.yes_total),
+            Side::No => (&mut self.no_levels, &mut self.no_total),
         };
 
         let price = (delta.price_dollars.parse::<f64>().unwrap_or(0.0) * 100.0).round() as i64;
         let delta_qty = delta.delta_fp.parse::<f64>().unwrap_or(0.0).round() as i64;
         let current = levels.get(&price).copied().unwrap_or(0);
         let new_qty = current + delta_qty;
+        self.version += 1;
 
         if new_qty <= 0 {
             levels.remove(&price);
+            *total -= current;
             0
         } else {
             levels.insert(price, new_qty);
+            *total += new_qty - current;
             new_qty
         }
     }
 
-    /// Update the last sequence number.
-    pub fn update_seq(&mut self, seq: Option<i64>) {
-        if let Some(s) = seq {
-            self.last_seq = Some(s);
-        }
+    /// Whether the orderbook has been initialized with a snapshot.
+    pub fn is_initialized(&self) -> bool {
+        self.initialized
     }
 
-    /// Get the last sequence number.
-    pub fn last_seq(&self) -> Option<i64> {
-        self.last_seq
+    /// Get the book version, which increases with every change.
+    pub fn version(&self) -> u64 {
+        self.version
     }
 
-    /// Whether the orderbook has been initialized with a snapshot.
-    pub fn is_initialized(&self) -> bool {
-        self.initialized
+    /// Mark the book as out of sync until the next snapshot replaces it.
+    pub fn mark_stale(&mut self) {
+        self.stale = true;
+    }
+
+    /// Whether the book is out of sync and awaiting a fresh snapshot.
+    pub fn is_stale(&self) -> bool {
+        self.stale
     }
 
     /// Get the best YES bid (highest price someone will pay for YES).
@@ -140,6 +197,29 @@ impl OrderbookState {
             .map(|(&yes_price, &qty)| (100 - yes_price, qty))
     }
 
+    /// Iterate the asks for buying `side`, best (lowest) price first.
+    ///
+    /// Asks are the opposite side's bids converted with `100 - bid`, so the
+    /// YES asks come from NO bids and vice versa. Yields (price, quantity).
+    pub fn asks(&self, side: Side) -> impl Iterator<Item = (i64, i64)> + '_ {
+        let bids = match side {
+            Side::Yes => &self.no_levels,
+            Side::No => &self.yes_levels,
+        };
+        bids.iter().rev().map(|(&price, &qty)| (100 - price, qty))
+    }
+
+    /// Iterate the bids for selling `side`, best (highest) price first.
+    ///
+    /// Yields (price, quantity).
+    pub fn bids(&self, side: Side) -> impl Iterator<Item = (i64, i64)> + '_ {
+        let bids = match side {
+            Side::Yes => &self.yes_levels,
+            Side::No => &self.no_levels,
+        };
+        bids.iter().rev().map(|(&price, &qty)| (price, qty))
+    }
+
     /// Get the YES spread (ask - bid) in cents.
     ///
     /// Returns None if either bid or ask is unavailable.
@@ -169,12 +249,12 @@ impl OrderbookState {
 
     /// Get total YES liquidity (sum of all YES bid quantities).
     pub fn total_yes_liquidity(&self) -> i64 {
-        self.yes_levels.values().sum()
+        self.yes_total
     }
 
     /// Get total NO liquidity (sum of all NO bid quantities).
     pub fn total_no_liquidity(&self) -> i64 {
-        self.no_levels.values().sum()
+        self.no_total
     }
 
     /// Get all YES levels (price -> quantity).
@@ -192,8 +272,11 @@ impl OrderbookState {
     pub fn clear(&mut self) {
         self.yes_levels.clear();
         self.no_levels.clear();
-        self.last_seq = None;
+        self.yes_total = 0;
+        self.no_total = 0;
         self.initialized = false;
+        self.stale = false;
+        self.version += 1;
     }
 }
 
@@ -428,6 +511,29 @@ mod tests {
         assert_eq!(state.best_yes_ask(), Some((44, 250)));
     }
 
+    #[test]
+    fn test_asks_use_complement() {
+        let snapshot = OrderbookSnapshotData {
+            market_ticker: "TEST".to_string(),
+            market_id: String::new(),
+            yes_dollars_fp: Some(vec![
+                ("0.40".to_string(), "10.00".to_string()),
+                ("0.42".to_string(), "20.00".to_string()),
+            ]),
+            no_dollars_fp: Some(vec![
+                ("0.55".to_string(), "150.00".to_string()),
+                ("0.56".to_string(), "250.00".to_string()),
+            ]),
+        };
+
+        let state = OrderbookState::from_snapshot(&snapshot);
+
+        let yes_asks: Vec<_> = state.asks(Side::Yes).collect();
+        assert_eq!(yes_asks, vec![(44, 250), (45, 150)]);
+        let no_asks: Vec<_> = state.asks(Side::No).collect();
+        assert_eq!(no_asks, vec![(58, 20), (60, 10)]);
+    }
+
     #[test]
     fn test_spread() {
         let snapshot = OrderbookSnapshotData {
@@ -477,6 +583,23 @@ mod tests {
 
         assert_eq!(state.total_yes_liquidity(), 300);
         assert_eq!(state.total_no_liquidity(), 400);
+
+        let mut state = state;
+        for delta_fp in ["25", "-150"] {
+            state.apply_delta(&OrderbookDeltaData {
+                market_ticker: "TEST".to_string(),
+                market_id: String::new(),
+                side: Side::Yes,
+                price_dollars: "0.45".to_string(),
+                delta_fp: delta_fp.to_string(),
+                client_order_id: None,
+                subaccount: None,
+                ts: None,
+            });
+        }
+        // Level at 45 removed entirely; an over-sized decrease only removes what was there
+        assert_eq!(state.total_yes_liquidity(), 200);
+        assert_eq!(state.total_no_liquidity(), 400);
     }
 
     #[test]
@@ -489,17 +612,44 @@ mod tests {
         };
 
         let mut state = OrderbookState::from_snapshot(&snapshot);
-        state.update_seq(Some(42));
         assert!(state.is_initialized());
 
         state.clear();
 
         assert!(!state.is_initialized());
-        assert_eq!(state.last_seq(), None);
         assert_eq!(state.total_yes_liquidity(), 0);
         assert_eq!(state.total_no_liquidity(), 0);
     }
 
+    #[test]
+    fn test_version_increments() {
+        let snapshot = OrderbookSnapshotData {
+            market_ticker: "TEST".to_string(),
+            market_id: String::new(),
+            yes_dollars_fp: Some(vec![("0.45".to_string(), "100.00".to_string())]),
+            no_dollars_fp: None,
+        };
+
+        let mut state = OrderbookState::from_snapshot(&snapshot);
+        assert_eq!(state.version(), 1);
+
+        state.apply_delta(&OrderbookDeltaData {
+            market_ticker: "TEST".to_string(),
+            market_id: String::new(),
+            side: Side::Yes,
+            price_dollars: "0.45".to_string(),
+            delta_fp: "-10".to_string(),
+            client_order_id: None,
+            subaccount: None,
+            ts: None,
+        });
+        assert_eq!(state.version(), 2);
+
+        let mut replacement = OrderbookState::from_snapshot(&snapshot);
+        replacement.continue_from(&state);
+        assert_eq!(replacement.version(), 3);
+    }
+
     #[test]
     fn test_empty_orderbook() {
         let state = OrderbookState::new();
@@ -510,4 +660,32 @@ mod tests {
         assert_eq!(state.spread(), None);
         assert_eq!(state.midpoint(), None);
     }
+
+    #[test]
+    fn test_from_rest() {
+        let fixed_point: OrderbookResponse = serde_json::from_value(serde_json::json!({
+            "orderbook": null,
+            "orderbook_fp": {
+                "yes_dollars": [["0.45", "100.00"], ["0.44", "0.00"]],
+                "no_dollars": [["0.53", "150.00"]],
+            }
+        }))
+        .unwrap();
+        let state = OrderbookState::from_rest(&fixed_point);
+        assert!(state.is_initialized());
+        assert_eq!(state.best_yes_bid(), Some((45, 100)));
+        assert_eq!(state.best_yes_ask(), Some((47, 150)));
+        assert_eq!(state.depth_at_price(Side::Yes, 44), 0);
+
+        let legacy: OrderbookResponse = serde_json::from_value(serde_json::json!({
+            "orderbook": {
+                "yes_dollars": [["0.45", 100]],
+                "no_dollars": [["0.53", 150]],
+            }
+        }))
+        .unwrap();
+        let state = OrderbookState::from_rest(&legacy);
+        assert_eq!(state.best_yes_bid(), Some((45, 100)));
+        assert_eq!(state.total_no_liquidity(), 150);
+    }
 }
@@ -0,0 +1,187 @@
+//! Per-market orderbook storage.
+//!
+//! The market index is an immutable map that is swapped atomically when a
+//! market is added or removed, so looking a market up never takes a lock.
+//! Each market then has its own lock around its book, and a top-of-book
+//! summary republished after every change that readers load without locking.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use arc_swap::{ArcSwap, ArcSwapOption};
+
+use super::aggregator::OrderbookSummary;
+use super::state::OrderbookState;
+
+/// Best prices and totals of a live book, published after every change.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct TopOfBook {
+    pub best_bid: Option<(i64, i64)>,
+    pub best_ask: Option<(i64, i64)>,
+    pub spread: Option<i64>,
+    pub midpoint: Option<f64>,
+    pub total_yes_liquidity: i64,
+    pub total_no_liquidity: i64,
+}
+
+impl TopOfBook {
+    fn from_state(orderbook: &OrderbookState) -> Self {
+        Self {
+            best_bid: orderbook.best_yes_bid(),
+            best_ask: orderbook.best_yes_ask(),
+            spread: orderbook.spread(),
+            midpoint: orderbook.midpoint(),
+            total_yes_liquidity: orderbook.total_yes_liquidity(),
+            total_no_liquidity: orderbook.total_no_liquidity(),
+        }
+    }
+
+    pub fn summary(&self, ticker: &str) -> OrderbookSummary {
+        OrderbookSummary {
+            ticker: ticker.to_string(),
+            best_bid: self.best_bid,
+            best_ask: self.best_ask,
+            spread: self.spread,
+            midpoint: self.midpoint,
+            total_yes_liquidity: self.total_yes_liquidity,
+            total_no_liquidity: self.total_no_liquidity,
+        }
+    }
+}
+
+/// One market's book and its published top of book.
+#[derive(Debug, Default)]
+pub(crate) struct MarketSlot {
+    book: RwLock<OrderbookState>,
+    /// `None` while the book is uninitialized or stale.
+    top: ArcSwapOption<TopOfBook>,
+}
+
+impl MarketSlot {
+    pub fn read(&self) -> RwLockReadGuard<'_, OrderbookState> {
+        self.book.read().expect("orderbook lock poisoned")
+    }
+
+    pub fn write(&self) -> RwLockWriteGuard<'_, OrderbookState> {
+        self.book.write().expect("orderbook lock poisoned")
+    }
+
+    /// Republish the top of book from `orderbook`, which must be this slot's
+    /// book, still held under its write lock.
+    pub fn publish(&self, orderbook: &OrderbookState) -> Option<TopOfBook> {
+        if orderbook.is_initialized() && !orderbook.is_stale() {
+            let top = TopOfBook::from_state(orderbook);
+            self.top.store(Some(Arc::new(top)));
+            Some(top)
+        } else {
+            self.top.store(None);
+            None
+        }
+    }
+
+    /// Load the last published top of book without locking.
+    pub fn top(&self) -> Option<TopOfBook> {
+        self.top.load().as_deref().copied()
+    }
+}
+
+type MarketIndex = HashMap<String, Arc<MarketSlot>>;
+
+/// All tracked markets.
+#[derive(Debug, Default)]
+pub(crate) struct BookStore {
+    markets: ArcSwap<MarketIndex>,
+    /// Serializes index rewrites so concurrent inserts are not lost.
+    index_lock: Mutex<()>,
+}
+
+impl BookStore {
+    pub fn get(&self, ticker: &str) -> Option<Arc<MarketSlot>> {
+        self.markets.load().get(ticker).cloned()
+    }
+
+    pub fn get_or_insert(&self, ticker: &str) -> Arc<MarketSlot> {
+        if let Some(slot) = self.get(ticker) {
+            return slot;
+        }
+        self.rewrite(|markets| {
+            markets
+                .entry(ticker.to_string())
+                .or_insert_with(|| Arc::new(MarketSlot::default()))
+                .clone()
+        })
+    }
+
+    pub fn remove(&self, ticker: &str) {
+        if self.get(ticker).is_some() {
+            self.rewrite(|markets| markets.remove(ticker));
+        }
+    }
+
+    pub fn clear(&self) {
+        let _guard = self.index_lock.lock().expect("index lock poisoned");
+        self.markets.store(Arc::default());
+    }
+
+    /// The current index. Later inserts and removals are not reflected.
+    pub fn markets(&self) -> Arc<MarketIndex> {
+        self.markets.load_full()
+    }
+
+    fn rewrite<R>(&self, apply: impl FnOnce(&mut MarketIndex) -> R) -> R {
+        let _guard = self.index_lock.lock().expect("index lock poisoned");
+        let mut markets = MarketIndex::clone(&self.markets.load());
+        let result = apply(&mut markets);
+        self.markets.store(Arc::new(markets));
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ws::OrderbookSnapshotData;
+
+    fn snapshot() -> OrderbookState {
+        OrderbookState::from_snapshot(&OrderbookSnapshotData {
+            market_ticker: "TEST".to_string(),
+            market_id: String::new(),
+            yes_dollars_fp: Some(vec![("0.45".to_string(), "10.00".to_string())]),
+            no_dollars_fp: Some(vec![("0.53".to_string(), "20.00".to_string())]),
+        })
+    }
+
+    #[test]
+    fn test_index_insert_and_remove() {
+        let store = BookStore::default();
+        assert!(store.get("TEST").is_none());
+
+        let slot = store.get_or_insert("TEST");
+        assert!(Arc::ptr_eq(&slot, &store.get_or_insert("TEST")));
+
+        // Earlier index snapshots are unaffected by later changes
+        let before = store.markets();
+        store.remove("TEST");
+        assert!(store.get("TEST").is_none());
+        assert!(before.contains_key("TEST"));
+    }
+
+    #[test]
+    fn test_publish_hides_stale_books() {
+        let slot = MarketSlot::default();
+        assert_eq!(slot.top(), None);
+
+        let mut book = slot.write();
+        *book = snapshot();
+        let top = slot.publish(&book).unwrap();
+        assert_eq!(top.best_bid, Some((45, 10)));
+        assert_eq!(top.best_ask, Some((47, 20)));
+        assert_eq!(top.spread, Some(2));
+        assert_eq!(top.midpoint, Some(46.0));
+        assert_eq!(slot.top(), Some(top));
+
+        book.mark_stale();
+        assert_eq!(slot.publish(&book), None);
+        assert_eq!(slot.top(), None);
+    }
+}
@@ -0,0 +1,400 @@
+//! Cancelling every resting order in a scope.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use tracing::{debug, warn};
+
+use crate::batch::{BatchManager, RateLimitTier};
+use crate::client::KalshiClient;
+use crate::error::Result;
+use crate::models::{BatchCancelOrderItem, GetOrdersParams, Order, OrderStatus};
+
+/// Largest page `get_orders` returns.
+const LIST_PAGE_SIZE: i64 = 200;
+
+/// Cancel rounds before giving up on orders that keep resting.
+const MAX_ROUNDS: usize = 3;
+
+/// Which resting orders [`KalshiClient::cancel_all`] and
+/// [`BatchManager::cancel_all`] cancel.
+///
+/// An empty filter cancels every resting order in every subaccount.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CancelAllFilter {
+    ticker: Option<String>,
+    event_ticker: Option<String>,
+    order_group_id: Option<String>,
+    subaccount: Option<i32>,
+    tier: RateLimitTier,
+    trigger_groups: bool,
+}
+
+impl Default for CancelAllFilter {
+    fn default() -> Self {
+        Self {
+            ticker: None,
+            event_ticker: None,
+            order_group_id: None,
+            subaccount: None,
+            tier: RateLimitTier::default(),
+            trigger_groups: true,
+        }
+    }
+}
+
+impl CancelAllFilter {
+    /// Create a filter matching every resting order.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only orders in this market.
+    #[must_use]
+    pub fn ticker(mut self, ticker: impl Into<String>) -> Self {
+        self.ticker = Some(ticker.into());
+        self
+    }
+
+    /// Only orders in this event's markets.
+    #[must_use]
+    pub fn event_ticker(mut self, event_ticker: impl Into<String>) -> Self {
+        self.event_ticker = Some(event_ticker.into());
+        self
+    }
+
+    /// Only orders in this order group.
+    #[must_use]
+    pub fn order_group_id(mut self, order_group_id: impl Into<String>) -> Self {
+        self.order_group_id = Some(order_group_id.into());
+        self
+    }
+
+    /// Only orders in this subaccount (0 for the primary account).
+    #[must_use]
+    pub fn subaccount(mut self, subaccount: i32) -> Self {
+        self.subaccount = Some(subaccount);
+        self
+    }
+
+    /// Pace cancellations for this rate limit tier.
+    ///
+    /// Only used by [`KalshiClient::cancel_all`]; [`BatchManager::cancel_all`]
+    /// paces with the manager's own rate limiter.
+    ///
+    /// Default: [`RateLimitTier::Basic`].
+    #[must_use]
+    pub fn tier(mut self, tier: RateLimitTier) -> Self {
+        self.tier = tier;
+        self
+    }
+
+    /// Whether to trigger the order groups of orders in scope.
+    ///
+    /// Disable this to cancel orders one by one when their groups were
+    /// already triggered, or must stay usable.
+    ///
+    /// Default: `true`.
+    #[must_use]
+    pub fn trigger_groups(mut self, trigger_groups: bool) -> Self {
+        self.trigger_groups = trigger_groups;
+        self
+    }
+
+    fn matches(&self, order: &Order) -> bool {
+        self.order_group_id
+            .as_ref()
+            .is_none_or(|group| order.order_group_id.as_ref() == Some(group))
+    }
+
+    /// Whether every order of a group is in scope, so triggering the group
+    /// cancels nothing the filter excludes.
+    fn covers_whole_groups(&self) -> bool {
+        self.ticker.is_none() && self.event_ticker.is_none()
+    }
+}
+
+/// An order [`KalshiClient::cancel_all`] could not cancel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CancelFailure {
+    /// Order ID.
+    pub order_id: String,
+    /// Market ticker.
+    pub ticker: String,
+    /// Subaccount number, `None` for the primary account.
+    pub subaccount: Option<i32>,
+    /// The last error reported for the order, if any.
+    pub reason: Option<String>,
+}
+
+/// Outcome of [`KalshiClient::cancel_all`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CancelAllReport {
+    /// Orders that are no longer resting, sorted. Includes orders canceled
+    /// through their group and any that filled while the call ran.
+    pub canceled: Vec<String>,
+    /// Order groups triggered, as (group ID, subaccount). They stay
+    /// triggered, refusing new orders, until reset with
+    /// [`KalshiClient::reset_order_group`].
+    pub triggered_groups: Vec<(String, Option<i32>)>,
+    /// Orders still resting after the last attempt.
+    pub failed: Vec<CancelFailure>,
+    /// Cancel rounds run, including the verification that ended the call.
+    pub rounds: usize,
+}
+
+impl CancelAllReport {
+    /// Whether every order in scope was canceled.
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// List every resting order matching `filter`.
+async fn list_resting(client: &KalshiClient, filter: &CancelAllFilter) -> Result<Vec<Order>> {
+    let mut orders = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let mut params = GetOrdersParams::new()
+            .status(OrderStatus::Resting)
+            .limit(LIST_PAGE_SIZE);
+        if let Some(ticker) = &filter.ticker {
+            params = params.ticker(ticker);
+        }
+        if let Some(event_ticker) = &filter.event_ticker {
+            params = params.event_ticker(event_ticker);
+        }
+        if let Some(subaccount) = filter.subaccount {
+            params = params.subaccount(subaccount);
+        }
+        if let Some(cursor) = cursor.take() {
+            params = params.cursor(cursor);
+        }
+        let response = client.get_orders_with_params(params).await?;
+        let done = response.cursor.is_empty() || response.orders.is_empty();
+        orders.extend(
+            response
+                .orders
+                .into_iter()
+                .filter(|order| order.status == OrderStatus::Resting && filter.matches(order)),
+        );
+        if done {
+            return Ok(orders);
+        }
+        cursor = Some(response.cursor);
+    }
+}
+
+/// Order groups of `orders` to trigger, as (group ID, subaccount).
+fn groups_to_trigger(filter: &CancelAllFilter, orders: &[Order]) -> Vec<(String, Option<i32>)> {
+    if !filter.trigger_groups || !filter.covers_whole_groups() {
+        return Vec::new();
+    }
+    let groups: BTreeSet<(String, Option<i32>)> = orders
+        .iter()
+        .filter_map(|order| {
+            let group = order.order_group_id.clone()?;
+            Some((group, order.subaccount_number))
+        })
+        .collect();
+    groups.into_iter().collect()
+}
+
+/// Cancel every resting order matching `filter`, paced for its tier.
+pub(crate) async fn cancel_all(
+    client: &KalshiClient,
+    filter: &CancelAllFilter,
+) -> Result<CancelAllReport> {
+    cancel_all_with(&BatchManager::new(client, filter.tier), filter).await
+}
+
+/// Cancel every resting order matching `filter`, pacing cancels through
+/// `manager`.
+pub(crate) async fn cancel_all_with(
+    manager: &BatchManager,
+    filter: &CancelAllFilter,
+) -> Result<CancelAllReport> {
+    let client = manager.client();
+    let mut report = CancelAllReport::default();
+    let mut canceled = HashSet::new();
+    let mut last_errors: HashMap<String, String> = HashMap::new();
+    let mut triggered = HashSet::new();
+
+    let mut resting = list_resting(client, filter).await?;
+    while !resting.is_empty() && report.rounds < MAX_ROUNDS {
+        report.rounds += 1;
+
+        // Triggering a group cancels all of its orders in one request. Groups
+        // are triggered once; orders they leave resting are canceled directly
+        // in later rounds.
+        let mut triggered_now = HashSet::new();
+        for (group, subaccount) in groups_to_trigger(filter, &resting) {
+            if !triggered.insert((group.clone(), subaccount)) {
+                continue;
+            }
+            let result = match subaccount {
+                Some(subaccount) if subaccount != 0 => {
+                    client
+                        .trigger_order_group_for_subaccount(&group, subaccount)
+                        .await
+                }
+                _ => client.trigger_order_group(&group).await,
+            };
+            match result {
+                Ok(()) => {
+                    triggered_now.insert((group.clone(), subaccount));
+                    report.triggered_groups.push((group, subaccount));
+                }
+                Err(e) => warn!(order_group_id = %group, error = %e, "order group trigger failed"),
+            }
+        }
+
+        let items: Vec<BatchCancelOrderItem> = resting
+            .iter()
+            .filter(|order| {
+                order.order_group_id.as_ref().is_none_or(|group| {
+                    !triggered_now.contains(&(group.clone(), order.subaccount_number))
+                })
+            })
+            .map(|order| {
+                let item = BatchCancelOrderItem::new(&order.order_id);
+                match order.subaccount_number {
+                    Some(subaccount) => item.subaccount(subaccount),
+                    None => item,
+                }
+            })
+            .collect();
+
+        let result = manager.cancel_orders_with_items(items).await;
+        for cancel in &result.completed.orders {
+            match &cancel.error {
+                None => {
+                    canceled.insert(cancel.order_id.clone());
+                }
+                Some(error) => {
+                    last_errors.insert(cancel.order_id.clone(), error.message.clone());
+                }
+            }
+        }
+        if let Some(e) = result.error {
+            warn!(error = %e, "batch cancel stopped early");
+        }
+
+        // Verify against what is actually still resting
+        let still_resting = list_resting(client, filter).await?;
+        let ids: HashSet<&str> = still_resting.iter().map(|o| o.order_id.as_str()).collect();
+        for order in &resting {
+            if !ids.contains(order.order_id.as_str()) {
+                canceled.insert(order.order_id.clone());
+            }
+        }
+        debug!(
+            round = report.rounds,
+            remaining = still_resting.len(),
+            "cancel all round"
+        );
+        resting = still_resting;
+    }
+
+    for order in &resting {
+        canceled.remove(&order.order_id);
+    }
+    report.failed = resting
+        .into_iter()
+        .map(|order| CancelFailure {
+            reason: last_errors.get(&order.order_id).cloned(),
+            order_id: order.order_id,
+            ticker: order.ticker,
+            subaccount: order.subaccount_number,
+        })
+        .collect();
+    report.canceled = canceled.into_iter().collect();
+    report.canceled.sort();
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    use crate::test_fixtures as fixtures;
+
+    fn order(order_id: &str, group: Option<&str>, subaccount: Option<i32>) -> Order {
+        fixtures::order(json!({
+            "order_id": order_id,
+            "remaining_count_fp": "1.00",
+            "initial_count_fp": "1.00",
+            "order_group_id": group,
+            "subaccount_number": subaccount,
+        }))
+    }
+
+    #[test]
+    fn test_groups_triggered_only_when_fully_in_scope() {
+        let orders = vec![
+            order("a", Some("g1"), None),
+            order("b", Some("g1"), None),
+            order("c", Some("g2"), Some(3)),
+            order("d", None, None),
+        ];
+
+        assert_eq!(
+            groups_to_trigger(&CancelAllFilter::new(), &orders),
+            vec![("g1".to_string(), None), ("g2".to_string(), Some(3))]
+        );
+        assert!(groups_to_trigger(&CancelAllFilter::new().ticker("TEST"), &orders).is_empty());
+        assert!(
+            groups_to_trigger(&CancelAllFilter::new().trigger_groups(false), &orders).is_empty()
+        );
+        assert_eq!(
+            groups_to_trigger(&CancelAllFilter::new().subaccount(3), &orders[2..]),
+            vec![("g2".to_string(), Some(3))]
+        );
+    }
+
+    #[test]
+    fn test_group_filter() {
+        let filter = CancelAllFilter::new().order_group_id("g1");
+        assert!(filter.matches(&order("a", Some("g1"), None)));
+        assert!(!filter.matches(&order("b", Some("g2"), None)));
+        assert!(!filter.matches(&order("c", None, None)));
+        assert!(CancelAllFilter::new().matches(&order("c", None, None)));
+    }
+    #[tokio::test]
+    async fn test_cancel_all_through_shared_manager() {
+        use crate::client::mock_http::MockHttpServer;
+
+        let server = MockHttpServer::start().await;
+        server.respond_once(
+            "GET",
+            "/portfolio/orders",
+            200,
+            serde_json::json!({ "orders": [order("a", None, None)], "cursor": "" }),
+        );
+        server.respond(
+            "GET",
+            "/portfolio/orders",
+            200,
+            serde_json::json!({ "orders": [], "cursor": "" }),
+        );
+        server.respond(
+            "DELETE",
+            "/portfolio/orders/batched",
+            200,
+            serde_json::json!({ "orders": [{ "order_id": "a", "reduced_by_fp": "1.00" }] }),
+        );
+
+        let manager = BatchManager::new(&server.client(), RateLimitTier::Basic);
+        let report = manager.cancel_all(CancelAllFilter::new()).await.unwrap();
+        assert_eq!(report.canceled, vec!["a".to_string()]);
+        assert_eq!(report.rounds, 1);
+        assert!(report.is_complete());
+        assert_eq!(
+            server
+                .requests_to("DELETE", "/portfolio/orders/batched")
+                .len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,551 @@
+//! Client order ID generation and recovery of ambiguous submissions.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use chrono::Utc;
+use rand_core::{OsRng, RngCore};
+
+use crate::batch::{api_status, order_rejection};
+use crate::client::KalshiClient;
+use crate::error::{Error, Result};
+use crate::models::{
+    BatchOrderErrorKind, BatchOrderResult, CreateOrderRequest, GetOrdersParams, Order,
+};
+
+/// Longest prefix a [`ClientOrderIdGenerator`] accepts.
+const MAX_PREFIX_LEN: usize = 16;
+
+/// Seconds subtracted from the submit time when looking orders up, to allow
+/// for clock skew against the exchange.
+pub(crate) const LOOKUP_SLACK_SECS: i64 = 5;
+
+/// Largest page `get_orders` returns.
+const LOOKUP_PAGE_SIZE: i64 = 200;
+
+const BASE36: &[u8; 36] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+/// Width of the millisecond timestamp, good until the year 5188.
+const TIME_WIDTH: usize = 9;
+
+/// Width of the per-millisecond sequence and of the instance tag.
+const COUNTER_WIDTH: usize = 4;
+
+/// Exclusive upper bound of a [`COUNTER_WIDTH`] base-36 number.
+const COUNTER_LIMIT: u64 = 36u64.pow(COUNTER_WIDTH as u32);
+
+fn push_base36(out: &mut String, mut value: u64, width: usize) {
+    let mut digits = vec![b'0'; width];
+    for digit in digits.iter_mut().rev() {
+        *digit = BASE36[(value % 36) as usize];
+        value /= 36;
+    }
+    out.extend(digits.into_iter().map(char::from));
+}
+
+#[derive(Debug, Default)]
+struct Sequence {
+    millis: u64,
+    count: u64,
+}
+
+/// Generates unique, time-sortable client order IDs.
+///
+/// IDs are `{prefix}-{time}{sequence}{instance}`: the creation time in
+/// milliseconds, a counter within the millisecond, and a random tag chosen
+/// when the generator is created, all fixed-width base 36. IDs from one
+/// generator sort in creation order, and two generators with the same prefix
+/// can only collide if they draw the same tag and issue IDs in the same
+/// millisecond.
+///
+/// Use a different prefix per strategy so orders can be attributed from
+/// their client order ID alone. Clones share the counter.
+///
+/// # Example
+///
+/// ```
+/// use kalshi_trade_rs::orders::ClientOrderIdGenerator;
+///
+/// let ids = ClientOrderIdGenerator::new("mm").unwrap();
+/// let first = ids.next_id();
+/// let second = ids.next_id();
+/// assert!(first.starts_with("mm-"));
+/// assert!(first < second);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ClientOrderIdGenerator {
+    prefix: String,
+    instance: u64,
+    sequence: Arc<Mutex<Sequence>>,
+}
+
+impl Default for ClientOrderIdGenerator {
+    /// A generator without a prefix.
+    fn default() -> Self {
+        Self::with_valid_prefix(String::new())
+    }
+}
+
+impl ClientOrderIdGenerator {
+    /// Create a generator whose IDs start with `prefix`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidClientOrderIdPrefix`] if the prefix is longer
+    /// than 16 characters or contains anything but ASCII letters, digits,
+    /// `-` and `_`.
+    pub fn new(prefix: impl Into<String>) -> Result<Self> {
+        let prefix = prefix.into();
+        let valid = prefix.len() <= MAX_PREFIX_LEN
+            && prefix
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(Error::InvalidClientOrderIdPrefix(prefix));
+        }
+        Ok(Self::with_valid_prefix(prefix))
+    }
+
+    fn with_valid_prefix(prefix: String) -> Self {
+        Self {
+            prefix,
+            instance: u64::from(OsRng.next_u32()) % COUNTER_LIMIT,
+            sequence: Arc::new(Mutex::new(Sequence::default())),
+        }
+    }
+
+    /// The prefix IDs start with.
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// The prefix of an ID issued by a generator, or `None` if the ID has
+    /// no prefix or was not issued by one.
+    ///
+    /// ```
+    /// use kalshi_trade_rs::orders::ClientOrderIdGenerator;
+    ///
+    /// let id = ClientOrderIdGenerator::new("twap").unwrap().next_id();
+    /// assert_eq!(ClientOrderIdGenerator::prefix_of(&id), Some("twap"));
+    /// assert_eq!(ClientOrderIdGenerator::prefix_of("manual-order"), None);
+    /// ```
+    pub fn prefix_of(id: &str) -> Option<&str> {
+        let (prefix, suffix) = id.rsplit_once('-')?;
+        let generated = suffix.len() == TIME_WIDTH + 2 * COUNTER_WIDTH
+            && suffix
+                .bytes()
+                .all(|b| b.is_ascii_digit() || b.is_ascii_lowercase());
+        (generated && !prefix.is_empty()).then_some(prefix)
+    }
+
+    /// Generate the next ID.
+    pub fn next_id(&self) -> String {
+        let now = Utc::now().timestamp_millis().max(0) as u64;
+        let (millis, count) = {
+            let mut sequence = self.sequence.lock().expect("client order ID lock poisoned");
+            if now > sequence.millis {
+                sequence.millis = now;
+                sequence.count = 0;
+            } else {
+                sequence.count += 1;
+                // Out of IDs this millisecond, borrow the next one
+                if sequence.count == COUNTER_LIMIT {
+                    sequence.millis += 1;
+                    sequence.count = 0;
+                }
+            }
+            (sequence.millis, sequence.count)
+        };
+
+        let mut id = String::with_capacity(self.prefix.len() + 1 + TIME_WIDTH + 2 * COUNTER_WIDTH);
+        if !self.prefix.is_empty() {
+            id.push_str(&self.prefix);
+            id.push('-');
+        }
+        push_base36(&mut id, millis, TIME_WIDTH);
+        push_base36(&mut id, count, COUNTER_WIDTH);
+        push_base36(&mut id, self.instance, COUNTER_WIDTH);
+        id
+    }
+
+    /// Give every order without a client order ID a fresh one.
+    pub(crate) fn stamp(&self, orders: &mut [CreateOrderRequest]) {
+        for order in orders {
+            if order.client_order_id.is_none() {
+                order.client_order_id = Some(self.next_id());
+            }
+        }
+    }
+}
+
+/// Whether a failed submission may still have reached the exchange.
+///
+/// Transport errors can happen after the request was sent, 5xx responses
+/// can come from a gateway in front of a server that processed it, and a
+/// success response that fails to decode still means the order was created.
+/// Any other error means the request was rejected.
+pub(crate) fn is_ambiguous(error: &Error) -> bool {
+    match error {
+        Error::Http(_) | Error::Json(_) => true,
+        Error::Api(msg) => msg.starts_with('5') || msg.starts_with("JSON decode error"),
+        _ => false,
+    }
+}
+
+/// Whether the exchange rejected an order because its client order ID is
+/// already in use.
+///
+/// After an ambiguous failure this means the first attempt was created after
+/// all, but too late for the lookup to see it.
+pub(crate) fn is_duplicate(error: &Error) -> bool {
+    // Kalshi answers a reused client order ID with 409 Conflict, whatever
+    // code the body carries
+    api_status(error) == Some(409)
+        || order_rejection(error)
+            .is_some_and(|rejection| rejection.kind() == BatchOrderErrorKind::DuplicateOrder)
+}
+
+/// Look up which of `requests` were created, by client order ID.
+///
+/// Searches orders created at or after `since` (Unix seconds) in each
+/// market and subaccount the requests target.
+pub(crate) async fn find_created(
+    client: &KalshiClient,
+    requests: &[CreateOrderRequest],
+    since: i64,
+) -> Result<HashMap<String, Order>> {
+    let mut wanted: HashMap<(&str, Option<i32>), HashSet<&str>> = HashMap::new();
+    for request in requests {
+        if let Some(id) = request.client_order_id.as_deref() {
+            wanted
+                .entry((request.ticker.as_str(), request.subaccount))
+                .or_default()
+                .insert(id);
+        }
+    }
+
+    let mut found = HashMap::new();
+    for ((ticker, subaccount), mut ids) in wanted {
+        let mut cursor: Option<String> = None;
+        while !ids.is_empty() {
+            let mut params = GetOrdersParams::new()
+                .ticker(ticker)
+                .min_ts(since)
+                .limit(LOOKUP_PAGE_SIZE);
+            if let Some(subaccount) = subaccount {
+                params = params.subaccount(subaccount);
+            }
+            if let Some(cursor) = cursor.take() {
+                params = params.cursor(cursor);
+            }
+            let response = client.get_orders_with_params(params).await?;
+            for order in &response.orders {
+                if ids.remove(order.client_order_id.as_str()) {
+                    found.insert(order.client_order_id.clone(), order.clone());
+                }
+            }
+            if response.cursor.is_empty() || response.orders.is_empty() {
+                break;
+            }
+            cursor = Some(response.cursor);
+        }
+    }
+    Ok(found)
+}
+
+/// Replace duplicate client order ID rejections in `results` with the orders
+/// already holding those IDs.
+///
+/// `results` are the results of resubmitting `requests` after an ambiguous
+/// failure. Rejections stay as they are if the lookup fails.
+pub(crate) async fn resolve_duplicates(
+    client: &KalshiClient,
+    requests: &[CreateOrderRequest],
+    since: i64,
+    mut results: Vec<BatchOrderResult>,
+) -> Vec<BatchOrderResult> {
+    let duplicated: Vec<CreateOrderRequest> = requests
+        .iter()
+        .filter(|request| {
+            results.iter().any(|result| {
+                result.client_order_id.is_some()
+                    && result.client_order_id == request.client_order_id
+                    && result
+                        .error
+                        .as_ref()
+                        .is_some_and(|error| error.kind() == BatchOrderErrorKind::DuplicateOrder)
+            })
+        })
+        .cloned()
+        .collect();
+    if duplicated.is_empty() {
+        return results;
+    }
+
+    let mut found = match find_created(client, &duplicated, since).await {
+        Ok(found) => found,
+        Err(e) => {
+            tracing::warn!(error = %e, "Could not look up orders rejected as duplicates");
+            return results;
+        }
+    };
+    for result in &mut results {
+        let existing = result
+            .client_order_id
+            .as_ref()
+            .and_then(|id| found.remove(id));
+        if let Some(order) = existing {
+            result.order = Some(order);
+            result.error = None;
+        }
+    }
+    results
+}
+
+/// Combine orders found by [`find_created`] with the results of resubmitting
+/// the rest, in the order of `requests`.
+///
+/// `sent` holds one result per request not in `found`, in request order.
+pub(crate) fn merge_batch_results(
+    requests: &[CreateOrderRequest],
+    found: &HashMap<String, Order>,
+    sent: Vec<BatchOrderResult>,
+) -> Vec<BatchOrderResult> {
+    let mut sent = sent.into_iter();
+    requests
+        .iter()
+        .filter_map(|request| {
+            let existing = request
+                .client_order_id
+                .as_ref()
+                .and_then(|id| found.get(id));
+            match existing {
+                Some(order) => Some(BatchOrderResult {
+                    client_order_id: request.client_order_id.clone(),
+                    order: Some(order.clone()),
+                    error: None,
+                }),
+                None => sent.next(),
+            }
+        })
+        .collect()
+}
+
+/// Requests in `requests` not in `found`.
+pub(crate) fn missing(
+    requests: &[CreateOrderRequest],
+    found: &HashMap<String, Order>,
+) -> Vec<CreateOrderRequest> {
+    requests
+        .iter()
+        .filter(|request| {
+            request
+                .client_order_id
+                .as_ref()
+                .is_none_or(|id| !found.contains_key(id))
+        })
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::mock_http::MockHttpServer;
+    use crate::models::{Action, Side};
+    use crate::test_fixtures as fixtures;
+
+    fn order(client_order_id: &str) -> Order {
+        fixtures::order(serde_json::json!({
+            "order_id": format!("order-{client_order_id}"),
+            "client_order_id": client_order_id,
+            "remaining_count_fp": "1.00",
+            "initial_count_fp": "1.00",
+        }))
+    }
+
+    fn orders_page(orders: &[Order]) -> serde_json::Value {
+        serde_json::json!({ "orders": orders, "cursor": "" })
+    }
+
+    #[test]
+    fn test_ids_sortable_and_unique() {
+        let ids = ClientOrderIdGenerator::new("mm").unwrap();
+        let generated: Vec<String> = (0..1000).map(|_| ids.next_id()).collect();
+        let mut sorted = generated.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(sorted, generated);
+        assert!(generated.iter().all(|id| id.len() == 3 + 17));
+
+        // Clones continue the same sequence
+        let clone = ids.clone();
+        assert!(clone.next_id() > generated[999]);
+
+        assert!(!ClientOrderIdGenerator::default().next_id().contains('-'));
+    }
+
+    #[test]
+    fn test_invalid_prefix() {
+        assert!(matches!(
+            ClientOrderIdGenerator::new("has space"),
+            Err(Error::InvalidClientOrderIdPrefix(_))
+        ));
+        assert!(ClientOrderIdGenerator::new("a".repeat(17)).is_err());
+        assert!(ClientOrderIdGenerator::new("twap_v2-a").is_ok());
+    }
+
+    #[test]
+    fn test_ambiguous_errors() {
+        assert!(is_ambiguous(&Error::Api(
+            "504 Gateway Timeout: upstream".to_string()
+        )));
+        assert!(!is_ambiguous(&Error::Api(
+            "429 Too Many Requests: slow down".to_string()
+        )));
+        assert!(!is_ambiguous(&Error::InvalidPrice(0)));
+        assert!(is_ambiguous(&Error::Api(
+            "JSON decode error: missing field `order`. Response: {}".to_string()
+        )));
+
+        assert!(is_duplicate(&Error::Api(
+            r#"409 Conflict: {"error":{"code":"order_already_exists","message":"duplicate"}}"#
+                .to_string()
+        )));
+        assert!(is_duplicate(&Error::Api(
+            r#"409 Conflict: {"error":{"code":"conflict","message":"duplicate"}}"#.to_string()
+        )));
+        assert!(!is_duplicate(&Error::Api(
+            r#"400 Bad Request: {"error":{"code":"invalid_parameters","message":""}}"#.to_string()
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_undecodable_create_response_is_looked_up() {
+        let server = MockHttpServer::start().await;
+        server.respond("POST", "/portfolio/orders", 201, serde_json::json!({}));
+        server.respond("GET", "/portfolio/orders", 200, orders_page(&[order("a")]));
+        let client = server
+            .client()
+            .with_client_order_ids(ClientOrderIdGenerator::default());
+
+        let request =
+            CreateOrderRequest::new("TEST", Side::Yes, Action::Buy, 1).client_order_id("a");
+        let response = client.create_order(request).await.unwrap();
+        assert_eq!(response.order.order_id, "order-a");
+        assert_eq!(server.requests_to("POST", "/portfolio/orders").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_resubmit_returns_existing_order() {
+        let server = MockHttpServer::start().await;
+        server.respond_once("POST", "/portfolio/orders", 503, serde_json::json!({}));
+        server.respond(
+            "POST",
+            "/portfolio/orders",
+            409,
+            serde_json::json!({
+                "error": { "code": "order_already_exists", "message": "duplicate client order id" }
+            }),
+        );
+        // The first attempt only shows up on the second lookup
+        server.respond_once("GET", "/portfolio/orders", 200, orders_page(&[]));
+        server.respond("GET", "/portfolio/orders", 200, orders_page(&[order("a")]));
+        let client = server
+            .client()
+            .with_client_order_ids(ClientOrderIdGenerator::default());
+
+        let request =
+            CreateOrderRequest::new("TEST", Side::Yes, Action::Buy, 1).client_order_id("a");
+        let response = client.create_order(request).await.unwrap();
+        assert_eq!(response.order.order_id, "order-a");
+        assert_eq!(server.requests_to("POST", "/portfolio/orders").len(), 2);
+        assert_eq!(server.requests_to("GET", "/portfolio/orders").len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_conflict_resubmit_returns_existing_order() {
+        let server = MockHttpServer::start().await;
+        server.respond_once("POST", "/portfolio/orders", 503, serde_json::json!({}));
+        server.respond("POST", "/portfolio/orders", 409, serde_json::json!({}));
+        server.respond_once("GET", "/portfolio/orders", 200, orders_page(&[]));
+        server.respond("GET", "/portfolio/orders", 200, orders_page(&[order("a")]));
+        let client = server
+            .client()
+            .with_client_order_ids(ClientOrderIdGenerator::default());
+
+        // A bare 409 is still recognized as the first attempt having landed
+        let request =
+            CreateOrderRequest::new("TEST", Side::Yes, Action::Buy, 1).client_order_id("a");
+        let response = client.create_order(request).await.unwrap();
+        assert_eq!(response.order.order_id, "order-a");
+        assert_eq!(server.requests_to("POST", "/portfolio/orders").len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_batch_resubmit_resolves_duplicates() {
+        let server = MockHttpServer::start().await;
+        server.respond("GET", "/portfolio/orders", 200, orders_page(&[order("a")]));
+        let client = server.client();
+        let requests = vec![
+            CreateOrderRequest::new("TEST", Side::Yes, Action::Buy, 1).client_order_id("a"),
+            CreateOrderRequest::new("TEST", Side::Yes, Action::Buy, 1).client_order_id("b"),
+        ];
+        let rejected = |id: &str, code: &str| BatchOrderResult {
+            client_order_id: Some(id.to_string()),
+            order: None,
+            error: Some(crate::models::BatchOrderError {
+                code: code.to_string(),
+                message: String::new(),
+                details: None,
+                service: None,
+            }),
+        };
+        let results = vec![
+            rejected("a", "order_already_exists"),
+            rejected("b", "insufficient_balance"),
+        ];
+
+        let resolved = resolve_duplicates(&client, &requests, 0, results).await;
+        assert_eq!(resolved[0].order.as_ref().unwrap().order_id, "order-a");
+        assert!(resolved[0].error.is_none());
+        assert!(resolved[1].order.is_none());
+        assert!(resolved[1].error.is_some());
+    }
+
+    #[test]
+    fn test_merge_keeps_request_order() {
+        let request = |id: &str| {
+            CreateOrderRequest::new("TEST", Side::Yes, Action::Buy, 1).client_order_id(id)
+        };
+        let requests = vec![request("a"), request("b"), request("c")];
+        let found = HashMap::from([("b".to_string(), order("b"))]);
+
+        let resend: Vec<_> = missing(&requests, &found)
+            .into_iter()
+            .map(|r| r.client_order_id.unwrap())
+            .collect();
+        assert_eq!(resend, vec!["a", "c"]);
+
+        let sent = resend
+            .into_iter()
+            .map(|id| BatchOrderResult {
+                client_order_id: Some(id),
+                order: None,
+                error: None,
+            })
+            .collect();
+        let merged: Vec<_> = merge_batch_results(&requests, &found, sent)
+            .into_iter()
+            .map(|r| (r.client_order_id.unwrap(), r.order.is_some()))
+            .collect();
+        assert_eq!(
+            merged,
+            vec![
+                ("a".to_string(), false),
+                ("b".to_string(), true),
+                ("c".to_string(), false)
+            ]
+        );
+    }
+}
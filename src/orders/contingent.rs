@@ -0,0 +1,1253 @@
+//! Client-side contingent orders: OCO pairs, price triggers and brackets.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use tracing::{debug, warn};
+
+use super::client_id::{
+    ClientOrderIdGenerator, LOOKUP_SLACK_SECS, find_created, is_ambiguous, is_duplicate,
+};
+use crate::batch::order_rejection;
+use crate::client::KalshiClient;
+use crate::error::{Error, Result};
+use crate::models::{Action, CreateOrderRequest, Order, OrderStatus, Side};
+use crate::orderbook::{OrderbookAggregator, OrderbookUpdate, temp_path};
+use crate::units::{cents, contracts};
+use crate::ws::{KalshiStreamHandle, StreamMessage};
+
+/// Default capacity of the event broadcast channel.
+const DEFAULT_EVENT_CAPACITY: usize = 64;
+
+/// Which price a [`PriceTrigger`] watches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceSource {
+    /// Last traded price, from `price_dollars` on the `ticker` channel.
+    LastPrice,
+    /// Midpoint of the best bid and ask, from an
+    /// [`OrderbookAggregator`] attached with
+    /// [`ContingentManager::with_orderbook`].
+    Midpoint,
+}
+
+/// Which way the price must move to fire a [`PriceTrigger`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TriggerDirection {
+    /// Fire when the price is at or above the level.
+    AtOrAbove,
+    /// Fire when the price is at or below the level.
+    AtOrBelow,
+}
+
+/// A price level on one side of a market.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PriceTrigger {
+    /// Market ticker.
+    pub ticker: String,
+    /// Side the level is quoted on; NO levels are compared against
+    /// 100 minus the YES price.
+    pub side: Side,
+    /// Price watched.
+    pub source: PriceSource,
+    /// Direction of the move.
+    pub direction: TriggerDirection,
+    /// Level in cents.
+    pub price: i64,
+}
+
+impl PriceTrigger {
+    /// Fire when the `side` last price reaches `price` or higher.
+    pub fn at_or_above(ticker: impl Into<String>, side: Side, price: i64) -> Self {
+        Self {
+            ticker: ticker.into(),
+            side,
+            source: PriceSource::LastPrice,
+            direction: TriggerDirection::AtOrAbove,
+            price,
+        }
+    }
+
+    /// Fire when the `side` last price reaches `price` or lower.
+    pub fn at_or_below(ticker: impl Into<String>, side: Side, price: i64) -> Self {
+        Self {
+            direction: TriggerDirection::AtOrBelow,
+            ..Self::at_or_above(ticker, side, price)
+        }
+    }
+
+    /// Watch a different price.
+    #[must_use]
+    pub fn source(mut self, source: PriceSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Whether a YES price in cents fires the trigger.
+    pub fn is_hit(&self, yes_price: f64) -> bool {
+        let price = match self.side {
+            Side::Yes => yes_price,
+            Side::No => 100.0 - yes_price,
+        };
+        match self.direction {
+            TriggerDirection::AtOrAbove => price >= self.price as f64,
+            TriggerDirection::AtOrBelow => price <= self.price as f64,
+        }
+    }
+}
+
+/// An order sent when its trigger fires.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriggerLeg {
+    /// When to send the order.
+    pub trigger: PriceTrigger,
+    /// The order to send.
+    pub order: CreateOrderRequest,
+}
+
+impl TriggerLeg {
+    /// Send `order` when `trigger` fires.
+    pub fn new(trigger: PriceTrigger, order: CreateOrderRequest) -> Self {
+        Self { trigger, order }
+    }
+
+    /// Sell `count` contracts of a `side` position at `limit` when `trigger`
+    /// fires, without opening a position on the other side.
+    pub fn exit(trigger: PriceTrigger, count: i64, limit: i64) -> Self {
+        let order = CreateOrderRequest::new(&trigger.ticker, trigger.side, Action::Sell, count)
+            .reduce_only(true);
+        let order = match trigger.side {
+            Side::Yes => order.yes_price(limit),
+            Side::No => order.no_price(limit),
+        };
+        Self { trigger, order }
+    }
+}
+
+/// What a contingent order waits for and does.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Contingency {
+    /// Two resting orders; when either fills or leaves the book, the other
+    /// is canceled.
+    Oco {
+        /// The two order IDs.
+        order_ids: [String; 2],
+        /// Subaccount the orders belong to.
+        subaccount: Option<i32>,
+    },
+    /// Send an order when a price is reached.
+    Trigger(Box<TriggerLeg>),
+    /// Take-profit and stop-loss exits; the first to fire is sent and the
+    /// other is dropped.
+    Bracket {
+        /// Exit when the price rises to the target.
+        take_profit: Box<TriggerLeg>,
+        /// Exit when the price falls to the stop.
+        stop_loss: Box<TriggerLeg>,
+    },
+}
+
+impl Contingency {
+    /// Pair two resting orders so that one filling cancels the other.
+    pub fn oco(first: impl Into<String>, second: impl Into<String>) -> Self {
+        Self::Oco {
+            order_ids: [first.into(), second.into()],
+            subaccount: None,
+        }
+    }
+
+    /// Send `order` when `trigger` fires.
+    pub fn trigger(trigger: PriceTrigger, order: CreateOrderRequest) -> Self {
+        Self::Trigger(Box::new(TriggerLeg::new(trigger, order)))
+    }
+
+    /// Reduce-only exits for `count` contracts of a `side` position, on
+    /// the last price.
+    ///
+    /// Sells at `take_profit` once the price reaches it, or at `stop_limit`
+    /// once the price falls to `stop`.
+    pub fn bracket(
+        ticker: impl Into<String>,
+        side: Side,
+        count: i64,
+        take_profit: i64,
+        stop: i64,
+        stop_limit: i64,
+    ) -> Self {
+        let ticker = ticker.into();
+        Self::Bracket {
+            take_profit: Box::new(TriggerLeg::exit(
+                PriceTrigger::at_or_above(&ticker, side, take_profit),
+                count,
+                take_profit,
+            )),
+            stop_loss: Box::new(TriggerLeg::exit(
+                PriceTrigger::at_or_below(ticker, side, stop),
+                count,
+                stop_limit,
+            )),
+        }
+    }
+
+    fn legs_mut(&mut self) -> Vec<&mut TriggerLeg> {
+        match self {
+            Self::Oco { .. } => Vec::new(),
+            Self::Trigger(leg) => vec![&mut **leg],
+            Self::Bracket {
+                take_profit,
+                stop_loss,
+            } => vec![&mut **take_profit, &mut **stop_loss],
+        }
+    }
+
+    /// The leg fired by a price, if any.
+    fn fired(&self, ticker: &str, source: PriceSource, yes_price: f64) -> Option<&TriggerLeg> {
+        let hit = |leg: &&TriggerLeg| {
+            leg.trigger.ticker == ticker
+                && leg.trigger.source == source
+                && leg.trigger.is_hit(yes_price)
+        };
+        match self {
+            Self::Oco { .. } => None,
+            Self::Trigger(leg) => Some(&**leg).filter(hit),
+            Self::Bracket {
+                take_profit,
+                stop_loss,
+            } => Some(&**take_profit)
+                .filter(hit)
+                .or(Some(&**stop_loss).filter(hit)),
+        }
+    }
+
+    /// The other leg of an OCO pair containing `order_id`.
+    fn oco_partner(&self, order_id: &str) -> Option<(&str, Option<i32>)> {
+        match self {
+            Self::Oco {
+                order_ids: [first, second],
+                subaccount,
+            } if first == order_id => Some((second, *subaccount)),
+            Self::Oco {
+                order_ids: [first, second],
+                subaccount,
+            } if second == order_id => Some((first, *subaccount)),
+            _ => None,
+        }
+    }
+}
+
+/// A pending contingency tracked by a [`ContingentManager`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContingentOrder {
+    /// ID assigned by the manager.
+    pub id: u64,
+    /// When the contingency was added.
+    pub created_at: DateTime<Utc>,
+    /// What it waits for.
+    pub contingency: Contingency,
+    /// The triggered order, once a trigger has fired and until the order is
+    /// known to exist.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub firing: Option<Firing>,
+}
+
+/// A triggered order whose creation is not yet confirmed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Firing {
+    /// The order being sent, with its client order ID.
+    pub order: CreateOrderRequest,
+    /// YES price in cents that fired it.
+    pub price: f64,
+    /// When it was first sent, in Unix seconds.
+    pub sent_at: i64,
+}
+
+/// Outcomes published by a [`ContingentManager`].
+#[derive(Debug, Clone)]
+pub enum ContingentEvent {
+    /// A trigger fired and its order was created.
+    Triggered {
+        /// Contingency ID.
+        id: u64,
+        /// YES price in cents that fired it.
+        price: f64,
+        /// The created order's ID.
+        order_id: String,
+    },
+    /// One leg of an OCO pair filled or closed and the other was canceled.
+    OcoResolved {
+        /// Contingency ID.
+        id: u64,
+        /// The leg that filled or closed.
+        closed: String,
+        /// The leg that was canceled.
+        canceled: String,
+    },
+    /// Sending a triggered order or canceling an OCO leg failed.
+    Failed {
+        /// Contingency ID.
+        id: u64,
+        /// The error reported.
+        error: String,
+        /// Whether the contingency stays pending and will be retried on the
+        /// next matching update. Rejected orders are not retried.
+        pending: bool,
+    },
+}
+
+#[derive(Debug)]
+struct State {
+    pending: BTreeMap<u64, ContingentOrder>,
+    /// Contingencies whose order is being sent or looked up.
+    sending: HashSet<u64>,
+    next_id: u64,
+    store: Option<Arc<Store>>,
+    revision: u64,
+}
+
+impl State {
+    /// Serialize pending contingencies for the store, if there is one.
+    fn snapshot(&mut self) -> Result<Option<Snapshot>> {
+        let Some(store) = &self.store else {
+            return Ok(None);
+        };
+        let orders: Vec<&ContingentOrder> = self.pending.values().collect();
+        let json = serde_json::to_vec(&orders)?;
+        self.revision += 1;
+        Ok(Some(Snapshot {
+            store: Arc::clone(store),
+            revision: self.revision,
+            json,
+        }))
+    }
+}
+
+/// The file pending contingencies are written to.
+#[derive(Debug)]
+struct Store {
+    path: PathBuf,
+    /// Revision of the last snapshot written.
+    written: Mutex<u64>,
+}
+
+impl Store {
+    fn error(&self, e: impl ToString) -> Error {
+        Error::ContingentStoreError(self.path.display().to_string(), e.to_string())
+    }
+}
+
+/// Pending contingencies serialized at one revision.
+///
+/// Taken under the state lock and written after it is released, so
+/// snapshots can reach the file out of order; a stale one is skipped.
+struct Snapshot {
+    store: Arc<Store>,
+    revision: u64,
+    json: Vec<u8>,
+}
+
+impl Snapshot {
+    /// Write the snapshot unless a later one is already on disk.
+    fn write(self) -> Result<()> {
+        let mut written = self
+            .store
+            .written
+            .lock()
+            .expect("contingent store lock poisoned");
+        if *written >= self.revision {
+            return Ok(());
+        }
+        let path = &self.store.path;
+        let tmp = temp_path(path);
+        fs::write(&tmp, &self.json)
+            .and_then(|()| fs::rename(&tmp, path))
+            .map_err(|e| self.store.error(e))?;
+        *written = self.revision;
+        Ok(())
+    }
+}
+
+/// Write a snapshot on the calling thread.
+fn save(snapshot: Result<Option<Snapshot>>) -> Result<()> {
+    snapshot?.map_or(Ok(()), Snapshot::write)
+}
+
+/// Write a snapshot on a blocking thread, logging failures.
+async fn persist(snapshot: Result<Option<Snapshot>>) {
+    let saved = match snapshot {
+        Ok(Some(snapshot)) => {
+            let store = Arc::clone(&snapshot.store);
+            tokio::task::spawn_blocking(move || snapshot.write())
+                .await
+                .unwrap_or_else(|e| Err(store.error(e)))
+        }
+        Ok(None) => return,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = saved {
+        warn!(error = %e, "failed to save contingent orders");
+    }
+}
+
+/// What becomes of a fired contingency after a send attempt.
+enum Outcome {
+    /// The order exists or was rejected; the contingency is done.
+    Done,
+    /// The order may exist; look it up before sending again.
+    Unknown,
+    /// The order was not created; wait for the trigger again.
+    Rearm,
+}
+
+/// Runs contingent orders that Kalshi does not support natively.
+///
+/// Each [`Contingency`] is held in this process until it fires:
+///
+/// - An OCO pair watches two resting orders on the `fill` and
+///   `user_orders` channels. When either fills, even partly, or leaves the
+///   book, the other is canceled.
+/// - A trigger sends an order once a [`PriceTrigger`] is hit, on the last
+///   price from the `ticker` channel or the midpoint of an attached
+///   orderbook.
+/// - A bracket holds a take-profit and a stop-loss trigger. The first to
+///   fire is sent, the other dropped.
+///
+/// Triggered orders are stamped with a client order ID when they are added,
+/// from the client's generator or a default one. A fired contingency is
+/// marked [`firing`](ContingentOrder::firing) before its order is sent and
+/// dropped once the order is created or rejected. If the send fails in a way
+/// that leaves the order's fate unknown, or the process stops mid-send, the
+/// next update for the market looks the order up by client order ID before
+/// sending it again, so it is never created twice. With a store attached,
+/// pending contingencies are written to a JSON file after every change and
+/// loaded back on startup.
+///
+/// Triggers are checked only while [`run`](Self::run) is processing
+/// updates, and a trigger whose price was crossed while the process was down
+/// fires on the first update after it restarts.
+///
+/// Clones share state.
+///
+/// # Example
+///
+/// ```no_run
+/// use kalshi_trade_rs::{
+///     KalshiClient, KalshiConfig, Side,
+///     orders::{Contingency, ContingentManager},
+///     ws::{Channel, KalshiStreamClient},
+/// };
+///
+/// # async fn example() -> kalshi_trade_rs::Result<()> {
+/// let config = KalshiConfig::from_env()?;
+/// let client = KalshiClient::new(config.clone())?;
+/// let contingent = ContingentManager::new(client).with_store("contingent.json")?;
+///
+/// // Exit 100 YES at 70, or stop out at 40 with a limit of 38
+/// contingent.add(Contingency::bracket("KXBTC-25JAN", Side::Yes, 100, 70, 40, 38))?;
+///
+/// let stream = KalshiStreamClient::connect(&config).await?;
+/// let mut handle = stream.handle();
+/// handle.subscribe(Channel::Ticker, &["KXBTC-25JAN"]).await?;
+/// handle.subscribe(Channel::Fill, &[]).await?;
+/// handle.subscribe(Channel::UserOrders, &[]).await?;
+/// contingent.run(handle).await;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct ContingentManager {
+    client: KalshiClient,
+    client_order_ids: ClientOrderIdGenerator,
+    orderbook: Option<OrderbookAggregator>,
+    state: Arc<Mutex<State>>,
+    event_sender: broadcast::Sender<ContingentEvent>,
+}
+
+impl ContingentManager {
+    /// Create a manager with no store.
+    pub fn new(client: KalshiClient) -> Self {
+        let (event_sender, _) = broadcast::channel(DEFAULT_EVENT_CAPACITY);
+        let client_order_ids = client.client_order_ids().cloned().unwrap_or_default();
+        Self {
+            client,
+            client_order_ids,
+            orderbook: None,
+            state: Arc::new(Mutex::new(State {
+                pending: BTreeMap::new(),
+                sending: HashSet::new(),
+                next_id: 1,
+                store: None,
+                revision: 0,
+            })),
+            event_sender,
+        }
+    }
+
+    /// Watch book midpoints for [`PriceSource::Midpoint`] triggers.
+    #[must_use]
+    pub fn with_orderbook(mut self, orderbook: OrderbookAggregator) -> Self {
+        self.orderbook = Some(orderbook);
+        self
+    }
+
+    /// Persist pending contingencies to `path`, loading any already there.
+    ///
+    /// Contingencies added before this call are kept and written out with
+    /// the loaded ones.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ContingentStoreError`] if the file exists but
+    /// cannot be read or written, or [`Error::Json`] if it is not a valid
+    /// store.
+    pub fn with_store(self, path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let loaded: Vec<ContingentOrder> = match fs::read(path) {
+            Ok(json) => serde_json::from_slice(&json)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => {
+                return Err(Error::ContingentStoreError(
+                    path.display().to_string(),
+                    e.to_string(),
+                ));
+            }
+        };
+        let snapshot = {
+            let mut state = self.lock();
+            for mut order in loaded {
+                self.stamp(&mut order.contingency);
+                state.next_id = state.next_id.max(order.id + 1);
+                state.pending.insert(order.id, order);
+            }
+            state.store = Some(Arc::new(Store {
+                path: path.to_path_buf(),
+                written: Mutex::new(0),
+            }));
+            state.snapshot()
+        };
+        save(snapshot)?;
+        Ok(self)
+    }
+
+    /// Get a receiver for contingent order events.
+    pub fn event_receiver(&self) -> broadcast::Receiver<ContingentEvent> {
+        self.event_sender.subscribe()
+    }
+
+    /// Every pending contingency, oldest first, including fired ones whose
+    /// order is not yet confirmed.
+    pub fn pending(&self) -> Vec<ContingentOrder> {
+        self.lock().pending.values().cloned().collect()
+    }
+
+    /// Start tracking a contingency, returning its ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be written; the contingency is
+    /// not added.
+    pub fn add(&self, mut contingency: Contingency) -> Result<u64> {
+        self.stamp(&mut contingency);
+
+        let (id, snapshot) = {
+            let mut state = self.lock();
+            let id = state.next_id;
+            state.next_id += 1;
+            state.pending.insert(
+                id,
+                ContingentOrder {
+                    id,
+                    created_at: Utc::now(),
+                    contingency,
+                    firing: None,
+                },
+            );
+            (id, state.snapshot())
+        };
+        if let Err(e) = save(snapshot) {
+            self.lock().pending.remove(&id);
+            return Err(e);
+        }
+        Ok(id)
+    }
+
+    /// Stop tracking a contingency, returning it if it was pending.
+    ///
+    /// OCO legs are left resting.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be written; the contingency is
+    /// removed from memory regardless.
+    pub fn remove(&self, id: u64) -> Result<Option<ContingentOrder>> {
+        let (removed, snapshot) = {
+            let mut state = self.lock();
+            let removed = state.pending.remove(&id);
+            let snapshot = match removed {
+                Some(_) => state.snapshot(),
+                None => Ok(None),
+            };
+            (removed, snapshot)
+        };
+        save(snapshot)?;
+        Ok(removed)
+    }
+
+    // =========================================================================
+    // Processing
+    // =========================================================================
+
+    /// Process stream and book updates until the stream closes.
+    ///
+    /// Subscribe the handle to [`Channel::Ticker`] for the markets with
+    /// last-price triggers, and to [`Channel::Fill`] and
+    /// [`Channel::UserOrders`] for OCO pairs. OCO legs are checked over
+    /// REST on startup and after the stream lags, so legs that filled while
+    /// nothing was listening are still resolved.
+    ///
+    /// [`Channel::Ticker`]: crate::ws::Channel::Ticker
+    /// [`Channel::Fill`]: crate::ws::Channel::Fill
+    /// [`Channel::UserOrders`]: crate::ws::Channel::UserOrders
+    pub async fn run(&self, mut handle: KalshiStreamHandle) {
+        self.check_oco_legs().await;
+        let mut book = self.orderbook.as_ref().map(|b| b.update_receiver());
+
+        loop {
+            tokio::select! {
+                received = handle.update_receiver.recv() => match received {
+                    Ok(update) => match &update.msg {
+                        StreamMessage::Ticker(data) => {
+                            if let Some(price) = cents(&data.price_dollars) {
+                                self.apply_price(&data.market_ticker, PriceSource::LastPrice, price)
+                                    .await;
+                            }
+                        }
+                        StreamMessage::Fill(fill) => self.close_oco(&fill.order_id).await,
+                        StreamMessage::UserOrder(data)
+                            if data.status != OrderStatus::Resting
+                                || contracts(&data.fill_count_fp) > 0 =>
+                        {
+                            self.close_oco(&data.order_id).await;
+                        }
+                        StreamMessage::Closed { .. } | StreamMessage::ConnectionLost { .. } => {
+                            break;
+                        }
+                        _ => {}
+                    },
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        debug!(missed = n, "contingent order updates lagged, checking legs");
+                        self.check_oco_legs().await;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                Some(update) = next_book_update(&mut book) => {
+                    let midpoint = self
+                        .orderbook
+                        .as_ref()
+                        .and_then(|b| b.midpoint(&update.ticker));
+                    if let Some(price) = midpoint {
+                        self.apply_price(&update.ticker, PriceSource::Midpoint, price).await;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Fire every trigger hit by a YES price in cents.
+    ///
+    /// Fired contingencies whose order's fate is unknown are looked up and,
+    /// if missing, sent again on any price for their market.
+    ///
+    /// [`run`](Self::run) calls this for ticker and book updates; call it
+    /// directly to drive triggers from another price feed.
+    pub async fn apply_price(&self, ticker: &str, source: PriceSource, yes_price: f64) {
+        let (sends, snapshot) = {
+            let mut state = self.lock();
+            let sent_at = Utc::now().timestamp();
+            let mut sends = Vec::new();
+            let mut fired = false;
+            let State {
+                pending, sending, ..
+            } = &mut *state;
+            for order in pending.values_mut() {
+                if sending.contains(&order.id) {
+                    continue;
+                }
+                let resumed = match &order.firing {
+                    Some(firing) if firing.order.ticker == ticker => true,
+                    Some(_) => continue,
+                    None => {
+                        let Some(leg) = order.contingency.fired(ticker, source, yes_price) else {
+                            continue;
+                        };
+                        let request = leg.order.clone();
+                        order.firing = Some(Firing {
+                            order: request,
+                            price: yes_price,
+                            sent_at,
+                        });
+                        fired = true;
+                        false
+                    }
+                };
+                if let Some(firing) = &order.firing {
+                    sending.insert(order.id);
+                    sends.push((order.id, firing.clone(), resumed));
+                }
+            }
+            let snapshot = if fired { state.snapshot() } else { Ok(None) };
+            (sends, snapshot)
+        };
+        // Record the firing before sending, so a restart cannot lose it
+        persist(snapshot).await;
+
+        for (id, firing, resumed) in sends {
+            self.send(id, firing, resumed).await;
+        }
+    }
+
+    /// Create a fired contingency's order, first looking for it if an
+    /// earlier attempt may have created it.
+    async fn send(&self, id: u64, firing: Firing, resumed: bool) {
+        let found = if resumed {
+            self.find(&firing).await
+        } else {
+            Ok(None)
+        };
+        let created = match found {
+            Ok(Some(order)) => Ok(order),
+            Ok(None) => self.create(&firing).await,
+            Err(e) => {
+                warn!(id, error = %e, "triggered order lookup failed");
+                self.settle(id, Outcome::Unknown).await;
+                self.publish(ContingentEvent::Failed {
+                    id,
+                    error: e.to_string(),
+                    pending: true,
+                });
+                return;
+            }
+        };
+
+        match created {
+            Ok(order) => {
+                debug!(id, order_id = %order.order_id, "contingent order triggered");
+                self.settle(id, Outcome::Done).await;
+                self.publish(ContingentEvent::Triggered {
+                    id,
+                    price: firing.price,
+                    order_id: order.order_id,
+                });
+            }
+            Err(e) => {
+                let outcome = if order_rejection(&e).is_some() {
+                    Outcome::Done
+                } else if is_ambiguous(&e) {
+                    Outcome::Unknown
+                } else {
+                    Outcome::Rearm
+                };
+                let pending = !matches!(outcome, Outcome::Done);
+                warn!(id, error = %e, pending, "triggered order failed");
+                self.settle(id, outcome).await;
+                self.publish(ContingentEvent::Failed {
+                    id,
+                    error: e.to_string(),
+                    pending,
+                });
+            }
+        }
+    }
+
+    /// The fired order, if an earlier attempt created it.
+    async fn find(&self, firing: &Firing) -> Result<Option<Order>> {
+        let since = firing.sent_at - LOOKUP_SLACK_SECS;
+        let client_order_id = firing.order.client_order_id.as_deref().unwrap_or_default();
+        let mut found =
+            find_created(&self.client, std::slice::from_ref(&firing.order), since).await?;
+        Ok(found.remove(client_order_id))
+    }
+
+    async fn create(&self, firing: &Firing) -> Result<Order> {
+        match self.client.create_order(firing.order.clone()).await {
+            Ok(response) => Ok(response.order),
+            // An earlier attempt was created after all
+            Err(e) if is_duplicate(&e) => self.find(firing).await.ok().flatten().ok_or(e),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Finish a send attempt for contingency `id`.
+    async fn settle(&self, id: u64, outcome: Outcome) {
+        let snapshot = {
+            let mut state = self.lock();
+            state.sending.remove(&id);
+            match outcome {
+                Outcome::Done => {
+                    state.pending.remove(&id);
+                }
+                Outcome::Unknown => return,
+                Outcome::Rearm => {
+                    if let Some(order) = state.pending.get_mut(&id) {
+                        order.firing = None;
+                    }
+                }
+            }
+            state.snapshot()
+        };
+        persist(snapshot).await;
+    }
+
+    /// Cancel the partner of an OCO leg that filled or closed.
+    async fn close_oco(&self, order_id: &str) {
+        let found = {
+            let mut state = self.lock();
+            let found = state.pending.values().find_map(|o| {
+                let (partner, subaccount) = o.contingency.oco_partner(order_id)?;
+                Some((o.id, partner.to_string(), subaccount))
+            });
+            let Some((id, partner, subaccount)) = found else {
+                return;
+            };
+            let pending = state.pending.remove(&id);
+            let snapshot = state.snapshot();
+            pending.map(|p| (p, partner, subaccount, snapshot))
+        };
+        let Some((pending, partner, subaccount, snapshot)) = found else {
+            return;
+        };
+        persist(snapshot).await;
+
+        let canceled = match subaccount {
+            Some(subaccount) if subaccount != 0 => {
+                self.client
+                    .cancel_order_for_subaccount(&partner, subaccount)
+                    .await
+            }
+            _ => self.client.cancel_order(&partner).await,
+        };
+        let id = pending.id;
+        match canceled {
+            // A rejected cancel means the partner already left the book
+            Ok(_) => {}
+            Err(e) if order_rejection(&e).is_some() => {
+                debug!(id, order_id = %partner, error = %e, "OCO partner already closed");
+            }
+            Err(e) => {
+                warn!(id, order_id = %partner, error = %e, "OCO cancel failed");
+                self.restore(pending).await;
+                self.publish(ContingentEvent::Failed {
+                    id,
+                    error: e.to_string(),
+                    pending: true,
+                });
+                return;
+            }
+        }
+        self.publish(ContingentEvent::OcoResolved {
+            id,
+            closed: order_id.to_string(),
+            canceled: partner,
+        });
+    }
+
+    /// Resolve OCO pairs whose legs filled or closed unseen.
+    async fn check_oco_legs(&self) {
+        let legs: Vec<String> = self
+            .lock()
+            .pending
+            .values()
+            .filter_map(|o| match &o.contingency {
+                Contingency::Oco { order_ids, .. } => Some(order_ids.clone()),
+                _ => None,
+            })
+            .flatten()
+            .collect();
+
+        for order_id in legs {
+            match self.client.get_order(&order_id).await {
+                Ok(response) => {
+                    let order = response.order;
+                    if order.status != OrderStatus::Resting || contracts(&order.fill_count_fp) > 0 {
+                        self.close_oco(&order_id).await;
+                    }
+                }
+                Err(e) => warn!(order_id = %order_id, error = %e, "OCO leg lookup failed"),
+            }
+        }
+    }
+
+    /// Stamp trigger legs that have no client order ID.
+    fn stamp(&self, contingency: &mut Contingency) {
+        for leg in contingency.legs_mut() {
+            self.client_order_ids
+                .stamp(std::slice::from_mut(&mut leg.order));
+        }
+    }
+
+    async fn restore(&self, order: ContingentOrder) {
+        let snapshot = {
+            let mut state = self.lock();
+            state.pending.insert(order.id, order);
+            state.snapshot()
+        };
+        persist(snapshot).await;
+    }
+
+    fn publish(&self, event: ContingentEvent) {
+        // Ignore send errors - no receivers is fine
+        let _ = self.event_sender.send(event);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().expect("contingent order lock poisoned")
+    }
+}
+
+impl std::fmt::Debug for ContingentManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.lock();
+        f.debug_struct("ContingentManager")
+            .field("pending", &state.pending.len())
+            .field("store", &state.store.as_ref().map(|s| &s.path))
+            .finish_non_exhaustive()
+    }
+}
+
+/// Next book update, or `None` once the book is gone or was never set.
+async fn next_book_update(
+    receiver: &mut Option<broadcast::Receiver<OrderbookUpdate>>,
+) -> Option<OrderbookUpdate> {
+    let rx = receiver.as_mut()?;
+    loop {
+        match rx.recv().await {
+            Ok(update) => return Some(update),
+            Err(broadcast::error::RecvError::Lagged(_)) => {}
+            Err(broadcast::error::RecvError::Closed) => {
+                *receiver = None;
+                return None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    use crate::client::mock_http::MockHttpServer;
+    use crate::test_fixtures as fixtures;
+    use crate::ws::mock_server::test_config;
+
+    fn manager() -> ContingentManager {
+        ContingentManager::new(KalshiClient::new(test_config()).unwrap())
+    }
+
+    fn store_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "kalshi-contingent-{name}-{}.json",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    fn buy_above(price: i64) -> Contingency {
+        Contingency::trigger(
+            PriceTrigger::at_or_above("TEST", Side::Yes, price),
+            CreateOrderRequest::new("TEST", Side::Yes, Action::Buy, 10).yes_price(price + 1),
+        )
+    }
+
+    #[test]
+    fn test_trigger_compares_on_its_side() {
+        let above = PriceTrigger::at_or_above("TEST", Side::Yes, 60);
+        assert!(above.is_hit(60.0));
+        assert!(!above.is_hit(59.5));
+
+        // YES at 35 is NO at 65
+        let no_stop = PriceTrigger::at_or_below("TEST", Side::No, 65);
+        assert!(no_stop.is_hit(35.0));
+        assert!(!no_stop.is_hit(34.0));
+    }
+
+    #[test]
+    fn test_bracket_fires_first_leg_hit() {
+        let bracket = Contingency::bracket("TEST", Side::Yes, 10, 70, 40, 38);
+
+        let leg = bracket.fired("TEST", PriceSource::LastPrice, 72.0).unwrap();
+        assert_eq!(leg.order.yes_price, Some(70));
+        assert_eq!(leg.order.action, Action::Sell);
+        assert_eq!(leg.order.reduce_only, Some(true));
+
+        let leg = bracket.fired("TEST", PriceSource::LastPrice, 39.0).unwrap();
+        assert_eq!(leg.order.yes_price, Some(38));
+
+        assert!(
+            bracket
+                .fired("TEST", PriceSource::LastPrice, 50.0)
+                .is_none()
+        );
+        assert!(
+            bracket
+                .fired("OTHER", PriceSource::LastPrice, 72.0)
+                .is_none()
+        );
+        assert!(bracket.fired("TEST", PriceSource::Midpoint, 72.0).is_none());
+    }
+
+    #[test]
+    fn test_oco_partner() {
+        let oco = Contingency::oco("a", "b");
+        assert_eq!(oco.oco_partner("a"), Some(("b", None)));
+        assert_eq!(oco.oco_partner("b"), Some(("a", None)));
+        assert_eq!(oco.oco_partner("c"), None);
+    }
+
+    #[tokio::test]
+    async fn test_trigger_legs_stamped_on_add() {
+        let client = KalshiClient::new(test_config())
+            .unwrap()
+            .with_client_order_ids(crate::orders::ClientOrderIdGenerator::new("exit").unwrap());
+        let contingent = ContingentManager::new(client);
+        let id = contingent
+            .add(Contingency::bracket("TEST", Side::Yes, 10, 70, 40, 38))
+            .unwrap();
+
+        // Prices between the legs fire nothing
+        contingent
+            .apply_price("TEST", PriceSource::LastPrice, 55.0)
+            .await;
+
+        let pending = contingent.pending();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].id, id);
+        let Contingency::Bracket {
+            take_profit,
+            stop_loss,
+        } = &pending[0].contingency
+        else {
+            panic!("expected a bracket");
+        };
+        let tp_id = take_profit.order.client_order_id.as_deref().unwrap();
+        let sl_id = stop_loss.order.client_order_id.as_deref().unwrap();
+        assert!(tp_id.starts_with("exit-"));
+        assert_ne!(tp_id, sl_id);
+    }
+
+    #[test]
+    fn test_store_round_trip() {
+        let path = std::env::temp_dir().join(format!(
+            "kalshi-contingent-store-{}.json",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+
+        let contingent = manager().with_store(&path).unwrap();
+        let first = contingent.add(Contingency::oco("a", "b")).unwrap();
+        let second = contingent
+            .add(Contingency::bracket("TEST", Side::No, 5, 80, 30, 25))
+            .unwrap();
+        contingent.remove(first).unwrap();
+
+        let reloaded = manager().with_store(&path).unwrap();
+        let pending = reloaded.pending();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].id, second);
+        assert!(matches!(
+            pending[0].contingency,
+            Contingency::Bracket { .. }
+        ));
+        assert_eq!(
+            reloaded.add(Contingency::oco("c", "d")).unwrap(),
+            second + 1
+        );
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_trigger_fires_once() {
+        let server = MockHttpServer::start().await;
+        server.respond(
+            "POST",
+            "/portfolio/orders",
+            201,
+            json!({ "order": fixtures::order(json!({ "order_id": "exit" })) }),
+        );
+        let contingent = ContingentManager::new(server.client());
+        let mut events = contingent.event_receiver();
+        let id = contingent
+            .add(Contingency::bracket("TEST", Side::Yes, 10, 70, 40, 38))
+            .unwrap();
+
+        contingent
+            .apply_price("TEST", PriceSource::LastPrice, 72.0)
+            .await;
+        assert!(matches!(
+            events.try_recv(),
+            Ok(ContingentEvent::Triggered { id: fired, order_id, .. })
+                if fired == id && order_id == "exit"
+        ));
+        assert!(contingent.pending().is_empty());
+
+        // Stamped without a generator on the client
+        let sent = server.requests_to("POST", "/portfolio/orders");
+        assert_eq!(sent.len(), 1);
+        assert!(sent[0].body["client_order_id"].is_string());
+
+        contingent
+            .apply_price("TEST", PriceSource::LastPrice, 30.0)
+            .await;
+        assert_eq!(server.requests_to("POST", "/portfolio/orders").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_ambiguous_failure_looked_up_after_restart() {
+        let server = MockHttpServer::start().await;
+        server.respond_once("POST", "/portfolio/orders", 500, json!({}));
+        let path = store_path("firing");
+
+        let contingent = ContingentManager::new(server.client())
+            .with_store(&path)
+            .unwrap();
+        let mut events = contingent.event_receiver();
+        let id = contingent.add(buy_above(60)).unwrap();
+        contingent
+            .apply_price("TEST", PriceSource::LastPrice, 60.0)
+            .await;
+        assert!(matches!(
+            events.try_recv(),
+            Ok(ContingentEvent::Failed { pending: true, .. })
+        ));
+
+        // The firing survives a restart
+        let reloaded = ContingentManager::new(server.client())
+            .with_store(&path)
+            .unwrap();
+        let pending = reloaded.pending();
+        assert_eq!(pending[0].id, id);
+        let firing = pending[0].firing.clone().unwrap();
+        assert_eq!(firing.price, 60.0);
+
+        // The first attempt was created after all
+        server.respond(
+            "GET",
+            "/portfolio/orders",
+            200,
+            json!({
+                "orders": [fixtures::order(json!({
+                    "order_id": "created",
+                    "client_order_id": firing.order.client_order_id,
+                }))],
+                "cursor": "",
+            }),
+        );
+        let mut events = reloaded.event_receiver();
+        // Any price for the market resumes it
+        reloaded
+            .apply_price("TEST", PriceSource::LastPrice, 50.0)
+            .await;
+        assert!(matches!(
+            events.try_recv(),
+            Ok(ContingentEvent::Triggered { order_id, price, .. })
+                if order_id == "created" && price == 60.0
+        ));
+        assert!(reloaded.pending().is_empty());
+        assert_eq!(server.requests_to("POST", "/portfolio/orders").len(), 1);
+
+        let stored: Vec<ContingentOrder> =
+            serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+        assert!(stored.is_empty());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_failed_trigger_rearms() {
+        let server = MockHttpServer::start().await;
+        server.respond_once("POST", "/portfolio/orders", 429, json!({}));
+        server.respond(
+            "POST",
+            "/portfolio/orders",
+            201,
+            json!({ "order": fixtures::order(json!({})) }),
+        );
+        let contingent = ContingentManager::new(server.client());
+        let mut events = contingent.event_receiver();
+        contingent.add(buy_above(60)).unwrap();
+
+        contingent
+            .apply_price("TEST", PriceSource::LastPrice, 61.0)
+            .await;
+        assert!(matches!(
+            events.try_recv(),
+            Ok(ContingentEvent::Failed { pending: true, .. })
+        ));
+        assert!(contingent.pending()[0].firing.is_none());
+
+        // Not resent until the trigger is hit again
+        contingent
+            .apply_price("TEST", PriceSource::LastPrice, 55.0)
+            .await;
+        assert_eq!(server.requests_to("POST", "/portfolio/orders").len(), 1);
+        contingent
+            .apply_price("TEST", PriceSource::LastPrice, 62.0)
+            .await;
+        assert!(matches!(
+            events.try_recv(),
+            Ok(ContingentEvent::Triggered { price, .. }) if price == 62.0
+        ));
+        assert!(contingent.pending().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_oco_cancels_sibling() {
+        let server = MockHttpServer::start().await;
+        server.respond(
+            "DELETE",
+            "/portfolio/orders/b",
+            200,
+            json!({
+                "order": fixtures::order(json!({ "order_id": "b", "status": "canceled" })),
+                "reduced_by_fp": "10.00",
+            }),
+        );
+        server.respond_once("DELETE", "/portfolio/orders/d", 500, json!({}));
+        let contingent = ContingentManager::new(server.client());
+        let mut events = contingent.event_receiver();
+        let first = contingent.add(Contingency::oco("a", "b")).unwrap();
+        let second = contingent.add(Contingency::oco("c", "d")).unwrap();
+
+        contingent.close_oco("a").await;
+        assert!(matches!(
+            events.try_recv(),
+            Ok(ContingentEvent::OcoResolved { id, closed, canceled })
+                if id == first && closed == "a" && canceled == "b"
+        ));
+
+        // A failed cancel keeps the pair for another try
+        contingent.close_oco("c").await;
+        assert!(matches!(
+            events.try_recv(),
+            Ok(ContingentEvent::Failed { id, pending: true, .. }) if id == second
+        ));
+        let pending = contingent.pending();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].id, second);
+
+        // The sibling is already gone: the rejected cancel resolves the pair
+        contingent.close_oco("d").await;
+        assert!(matches!(
+            events.try_recv(),
+            Ok(ContingentEvent::OcoResolved { canceled, .. }) if canceled == "c"
+        ));
+        assert!(contingent.pending().is_empty());
+    }
+}
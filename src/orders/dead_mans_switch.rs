@@ -0,0 +1,836 @@
+//! Dead-man's switch built on an order group.
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::broadcast;
+use tokio::time::{Instant, MissedTickBehavior};
+use tracing::{debug, warn};
+
+use super::cancel_all::{CancelAllFilter, CancelAllReport, cancel_all_with};
+use crate::batch::{BatchManager, RateLimitTier};
+use crate::client::KalshiClient;
+use crate::error::{DisconnectReason, Error, Result};
+use crate::models::{
+    BatchCreateOrdersRequest, BatchCreateOrdersResponse, CreateOrderGroupRequest,
+    CreateOrderRequest, OrderResponse,
+};
+use crate::ws::{KalshiStreamHandle, OrderGroupEventType, StreamMessage};
+
+/// Default capacity of the event broadcast channel.
+const DEFAULT_EVENT_CAPACITY: usize = 64;
+
+/// Configuration for [`DeadMansSwitch`].
+#[derive(Debug, Clone)]
+pub struct DeadMansSwitchConfig {
+    /// How long the switch waits for a [`heartbeat`](DeadMansSwitch::heartbeat)
+    /// before tripping.
+    ///
+    /// Default: 10 seconds.
+    pub timeout: Duration,
+
+    /// How often the watchdog checks the last heartbeat.
+    ///
+    /// Default: 1 second.
+    pub check_interval: Duration,
+
+    /// Trip when the WebSocket stream closes or the connection is lost.
+    ///
+    /// Default: `true`.
+    pub trigger_on_disconnect: bool,
+
+    /// After triggering the group, cancel any of its orders still resting.
+    ///
+    /// Default: `true`.
+    pub cancel_on_trigger: bool,
+
+    /// Contracts limit of a group created by [`DeadMansSwitch::create`].
+    ///
+    /// The exchange also triggers the group when this many contracts match
+    /// within its rolling window, so keep it above normal trading volume.
+    ///
+    /// Default: 1,000,000.
+    pub contracts_limit: i64,
+
+    /// Name of a group created by [`DeadMansSwitch::create`].
+    ///
+    /// Default: `None`.
+    pub name: Option<String>,
+
+    /// Subaccount the group and its orders belong to.
+    ///
+    /// Default: `None` (the primary account).
+    pub subaccount: Option<i32>,
+}
+
+impl Default for DeadMansSwitchConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(10),
+            check_interval: Duration::from_secs(1),
+            trigger_on_disconnect: true,
+            cancel_on_trigger: true,
+            contracts_limit: 1_000_000,
+            name: None,
+            subaccount: None,
+        }
+    }
+}
+
+/// Why a [`DeadMansSwitch`] tripped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TripReason {
+    /// No heartbeat arrived within the timeout.
+    HeartbeatTimeout {
+        /// Time since the last heartbeat.
+        elapsed: Duration,
+    },
+    /// The WebSocket stream closed or lost its connection.
+    Disconnected(DisconnectReason),
+    /// The exchange reported the group triggered, for example because its
+    /// contracts limit was hit.
+    GroupTriggered,
+    /// [`DeadMansSwitch::trip`] was called.
+    Manual,
+}
+
+impl fmt::Display for TripReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::HeartbeatTimeout { elapsed } => {
+                write!(f, "no heartbeat for {}ms", elapsed.as_millis())
+            }
+            Self::Disconnected(reason) => write!(f, "stream disconnected: {reason}"),
+            Self::GroupTriggered => write!(f, "order group triggered by the exchange"),
+            Self::Manual => write!(f, "tripped manually"),
+        }
+    }
+}
+
+/// State changes published by a [`DeadMansSwitch`].
+#[derive(Debug, Clone)]
+pub enum SwitchEvent {
+    /// The exchange reported a change to the switch's order group.
+    GroupUpdated {
+        /// What happened to the group.
+        event_type: OrderGroupEventType,
+        /// New contracts limit, present on limit updates.
+        contracts_limit_fp: Option<String>,
+    },
+    /// The switch tripped. Orders through it are refused until
+    /// [rearmed](DeadMansSwitch::rearm).
+    Tripped(TripReason),
+    /// Orders left resting after the trigger were canceled.
+    Canceled(CancelAllReport),
+    /// Triggering the group or canceling its orders failed.
+    TripFailed {
+        /// The error reported.
+        error: String,
+    },
+    /// The group was reset and the switch accepts orders again.
+    Rearmed,
+}
+
+#[derive(Debug)]
+struct State {
+    last_beat: Instant,
+    tripped: Option<TripReason>,
+}
+
+/// Time since `last_beat` if it is `timeout` or more.
+fn expired(last_beat: Instant, now: Instant, timeout: Duration) -> Option<Duration> {
+    let elapsed = now.saturating_duration_since(last_beat);
+    (elapsed >= timeout).then_some(elapsed)
+}
+
+/// Cancels a set of orders when the process stops proving it is alive.
+///
+/// The switch owns a Kalshi order group. Orders placed through it carry the
+/// group's ID, so triggering the group cancels all of them in one request.
+/// Call [`heartbeat`](Self::heartbeat) from the trading loop and run
+/// [`run`](Self::run) alongside it; if the heartbeat goes stale or the
+/// stream disconnects, the watchdog triggers the group and cancels anything
+/// left resting.
+///
+/// The watchdog runs in this process, so it cannot act once the process has
+/// exited. It covers a stalled trading loop and a lost market data
+/// connection; for shutdowns, call [`trip`](Self::trip) from the shutdown
+/// path. A loop that blocks the runtime's threads also blocks the watchdog,
+/// so run it on a separate runtime if that is a concern.
+///
+/// Clones share state.
+///
+/// # Example
+///
+/// ```no_run
+/// use kalshi_trade_rs::{
+///     Action, CreateOrderRequest, KalshiClient, KalshiConfig, Side,
+///     orders::{DeadMansSwitch, DeadMansSwitchConfig},
+///     ws::{Channel, KalshiStreamClient},
+/// };
+///
+/// # async fn example() -> kalshi_trade_rs::Result<()> {
+/// let config = KalshiConfig::from_env()?;
+/// let client = KalshiClient::new(config.clone())?;
+/// let switch = DeadMansSwitch::create(client, DeadMansSwitchConfig::default()).await?;
+///
+/// let stream = KalshiStreamClient::connect(&config).await?;
+/// let mut handle = stream.handle();
+/// handle.subscribe(Channel::OrderGroupUpdates, &[]).await?;
+///
+/// let watchdog = switch.clone();
+/// tokio::spawn(async move { watchdog.run(handle).await });
+///
+/// loop {
+///     switch.heartbeat();
+///     let order = CreateOrderRequest::new("KXBTC-25JAN", Side::Yes, Action::Buy, 10)
+///         .yes_price(45);
+///     switch.create_order(order).await?;
+///     # break;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct DeadMansSwitch {
+    client: KalshiClient,
+    batch: BatchManager,
+    order_group_id: String,
+    config: DeadMansSwitchConfig,
+    state: Arc<Mutex<State>>,
+    event_sender: broadcast::Sender<SwitchEvent>,
+}
+
+impl DeadMansSwitch {
+    /// Create a new order group and a switch that owns it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the contracts limit is below 1 or the group
+    /// cannot be created.
+    pub async fn create(client: KalshiClient, config: DeadMansSwitchConfig) -> Result<Self> {
+        let mut request = CreateOrderGroupRequest::try_new(config.contracts_limit)?;
+        if let Some(name) = &config.name {
+            request = request.name(name);
+        }
+        if let Some(subaccount) = config.subaccount {
+            request = request.subaccount(subaccount);
+        }
+        let response = client.create_order_group(request).await?;
+        Ok(Self::with_group(client, response.order_group_id, config))
+    }
+
+    /// Take over an existing order group, resetting it so it accepts orders.
+    ///
+    /// `contracts_limit` and `name` in `config` are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the group cannot be reset.
+    pub async fn reuse(
+        client: KalshiClient,
+        order_group_id: impl Into<String>,
+        config: DeadMansSwitchConfig,
+    ) -> Result<Self> {
+        let switch = Self::with_group(client, order_group_id.into(), config);
+        switch.reset_group().await?;
+        Ok(switch)
+    }
+
+    fn with_group(
+        client: KalshiClient,
+        order_group_id: String,
+        config: DeadMansSwitchConfig,
+    ) -> Self {
+        let (event_sender, _) = broadcast::channel(DEFAULT_EVENT_CAPACITY);
+        Self {
+            batch: BatchManager::new(&client, RateLimitTier::default()),
+            client,
+            order_group_id,
+            config,
+            state: Arc::new(Mutex::new(State {
+                last_beat: Instant::now(),
+                tripped: None,
+            })),
+            event_sender,
+        }
+    }
+
+    /// Cancel orders left resting after a trip through `manager`.
+    ///
+    /// Share the manager used for other batch requests so cancels count
+    /// against the same rate limit. Default: a manager of its own for the
+    /// [`Basic`](RateLimitTier::Basic) tier.
+    #[must_use]
+    pub fn with_batch_manager(mut self, manager: BatchManager) -> Self {
+        self.batch = manager;
+        self
+    }
+
+    /// The order group this switch triggers.
+    pub fn order_group_id(&self) -> &str {
+        &self.order_group_id
+    }
+
+    /// Get a receiver for switch events.
+    pub fn event_receiver(&self) -> broadcast::Receiver<SwitchEvent> {
+        self.event_sender.subscribe()
+    }
+
+    /// Why the switch tripped, or `None` while it is armed.
+    pub fn tripped(&self) -> Option<TripReason> {
+        self.lock().tripped.clone()
+    }
+
+    /// Whether the switch has tripped and not been rearmed.
+    pub fn is_tripped(&self) -> bool {
+        self.lock().tripped.is_some()
+    }
+
+    /// Record that the trading loop is alive.
+    pub fn heartbeat(&self) {
+        self.lock().last_beat = Instant::now();
+    }
+
+    // =========================================================================
+    // Orders
+    // =========================================================================
+
+    /// Put an order in this switch's group and subaccount.
+    #[must_use]
+    pub fn attach(&self, mut request: CreateOrderRequest) -> CreateOrderRequest {
+        request.order_group_id = Some(self.order_group_id.clone());
+        if let Some(subaccount) = self.config.subaccount {
+            request.subaccount = Some(subaccount);
+        }
+        request
+    }
+
+    /// Create an order in this switch's group.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::SwitchTripped`] while the switch is tripped, or any
+    /// error from the client.
+    pub async fn create_order(&self, request: CreateOrderRequest) -> Result<OrderResponse> {
+        self.ensure_armed()?;
+        self.client.create_order(self.attach(request)).await
+    }
+
+    /// Create a batch of orders in this switch's group.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::SwitchTripped`] while the switch is tripped, or any
+    /// error from the client.
+    pub async fn batch_create_orders(
+        &self,
+        mut request: BatchCreateOrdersRequest,
+    ) -> Result<BatchCreateOrdersResponse> {
+        self.ensure_armed()?;
+        request.orders = request
+            .orders
+            .into_iter()
+            .map(|order| self.attach(order))
+            .collect();
+        self.client.batch_create_orders(request).await
+    }
+
+    fn ensure_armed(&self) -> Result<()> {
+        match &self.lock().tripped {
+            Some(reason) => Err(Error::SwitchTripped(reason.clone())),
+            None => Ok(()),
+        }
+    }
+
+    // =========================================================================
+    // Tripping
+    // =========================================================================
+
+    /// Trip the switch now: trigger the group and, if configured, cancel its
+    /// remaining orders.
+    ///
+    /// Does nothing if the switch has already tripped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if neither the trigger nor the cancel succeeded.
+    pub async fn trip(&self) -> Result<()> {
+        self.fire(TripReason::Manual).await
+    }
+
+    /// Reset the group and arm the switch again with a fresh heartbeat.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the group cannot be reset; the switch stays
+    /// tripped.
+    pub async fn rearm(&self) -> Result<()> {
+        self.reset_group().await?;
+        {
+            let mut state = self.lock();
+            state.tripped = None;
+            state.last_beat = Instant::now();
+        }
+        self.publish(SwitchEvent::Rearmed);
+        Ok(())
+    }
+
+    async fn fire(&self, reason: TripReason) -> Result<()> {
+        {
+            let mut state = self.lock();
+            if state.tripped.is_some() {
+                return Ok(());
+            }
+            state.tripped = Some(reason.clone());
+        }
+        warn!(order_group_id = %self.order_group_id, %reason, "dead man's switch tripped");
+        self.publish(SwitchEvent::Tripped(reason));
+
+        let triggered = self.trigger_group().await;
+        if let Err(e) = &triggered {
+            warn!(order_group_id = %self.order_group_id, error = %e, "order group trigger failed");
+            self.publish(SwitchEvent::TripFailed {
+                error: e.to_string(),
+            });
+        }
+        if !self.config.cancel_on_trigger {
+            if triggered.is_err() {
+                // Nothing was canceled, let the watchdog try again
+                self.lock().tripped = None;
+            }
+            return triggered;
+        }
+
+        // Triggering the group again would cancel nothing new
+        let mut filter = CancelAllFilter::new()
+            .order_group_id(&self.order_group_id)
+            .trigger_groups(triggered.is_err());
+        if let Some(subaccount) = self.config.subaccount {
+            filter = filter.subaccount(subaccount);
+        }
+        match cancel_all_with(&self.batch, &filter).await {
+            Ok(report) => {
+                self.publish(SwitchEvent::Canceled(report));
+                Ok(())
+            }
+            Err(e) => {
+                warn!(order_group_id = %self.order_group_id, error = %e, "cancel after trigger failed");
+                self.publish(SwitchEvent::TripFailed {
+                    error: e.to_string(),
+                });
+                match triggered {
+                    Ok(()) => Ok(()),
+                    Err(_) => {
+                        self.lock().tripped = None;
+                        Err(e)
+                    }
+                }
+            }
+        }
+    }
+
+    async fn trigger_group(&self) -> Result<()> {
+        match self.config.subaccount {
+            Some(subaccount) if subaccount != 0 => {
+                self.client
+                    .trigger_order_group_for_subaccount(&self.order_group_id, subaccount)
+                    .await
+            }
+            _ => self.client.trigger_order_group(&self.order_group_id).await,
+        }
+    }
+
+    async fn reset_group(&self) -> Result<()> {
+        match self.config.subaccount {
+            Some(subaccount) if subaccount != 0 => {
+                self.client
+                    .reset_order_group_for_subaccount(&self.order_group_id, subaccount)
+                    .await
+            }
+            _ => self.client.reset_order_group(&self.order_group_id).await,
+        }
+    }
+
+    // =========================================================================
+    // Watchdog
+    // =========================================================================
+
+    /// Watch the heartbeat and the stream.
+    ///
+    /// Checks the heartbeat every
+    /// [`check_interval`](DeadMansSwitchConfig::check_interval) and trips
+    /// when it is older than the timeout. When the stream closes or loses
+    /// its connection, trips if
+    /// [`trigger_on_disconnect`](DeadMansSwitchConfig::trigger_on_disconnect)
+    /// is set. Returns once the stream is gone and the switch has tripped;
+    /// until then the heartbeat is still checked.
+    ///
+    /// Subscribe the handle to [`Channel::OrderGroupUpdates`] to receive
+    /// [`SwitchEvent::GroupUpdated`] events and to stop placing orders when
+    /// the exchange triggers the group itself.
+    ///
+    /// [`Channel::OrderGroupUpdates`]: crate::ws::Channel::OrderGroupUpdates
+    pub async fn run(&self, mut handle: KalshiStreamHandle) {
+        let mut check = tokio::time::interval(self.config.check_interval);
+        check.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        let mut connected = true;
+        loop {
+            let received = tokio::select! {
+                received = handle.update_receiver.recv(), if connected => received,
+                _ = check.tick() => {
+                    self.check_heartbeat().await;
+                    if !connected && self.is_tripped() {
+                        break;
+                    }
+                    continue;
+                }
+            };
+
+            let disconnect = match received {
+                Ok(update) => match update.msg {
+                    StreamMessage::OrderGroupUpdate(data)
+                        if data.order_group_id == self.order_group_id =>
+                    {
+                        self.apply_group_update(data.event_type, data.contracts_limit_fp);
+                        continue;
+                    }
+                    StreamMessage::Closed { reason }
+                    | StreamMessage::ConnectionLost { reason, .. } => reason,
+                    _ => continue,
+                },
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    debug!(missed = n, "dead man's switch updates lagged");
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => DisconnectReason::SessionDied,
+            };
+
+            connected = false;
+            if self.config.trigger_on_disconnect
+                && let Err(e) = self.fire(TripReason::Disconnected(disconnect)).await
+            {
+                warn!(error = %e, "dead man's switch could not cancel on disconnect");
+            }
+            if self.is_tripped() {
+                break;
+            }
+        }
+    }
+
+    async fn check_heartbeat(&self) {
+        let elapsed = {
+            let state = self.lock();
+            if state.tripped.is_some() {
+                return;
+            }
+            expired(state.last_beat, Instant::now(), self.config.timeout)
+        };
+        if let Some(elapsed) = elapsed
+            && let Err(e) = self.fire(TripReason::HeartbeatTimeout { elapsed }).await
+        {
+            warn!(error = %e, "dead man's switch could not cancel on heartbeat timeout");
+        }
+    }
+
+    fn apply_group_update(
+        &self,
+        event_type: OrderGroupEventType,
+        contracts_limit_fp: Option<String>,
+    ) {
+        let newly_tripped = event_type == OrderGroupEventType::Triggered && {
+            let mut state = self.lock();
+            let armed = state.tripped.is_none();
+            if armed {
+                state.tripped = Some(TripReason::GroupTriggered);
+            }
+            armed
+        };
+        self.publish(SwitchEvent::GroupUpdated {
+            event_type,
+            contracts_limit_fp,
+        });
+        if newly_tripped {
+            warn!(order_group_id = %self.order_group_id, "order group triggered by the exchange");
+            self.publish(SwitchEvent::Tripped(TripReason::GroupTriggered));
+        }
+    }
+
+    fn publish(&self, event: SwitchEvent) {
+        // No receivers is fine
+        let _ = self.event_sender.send(event);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().expect("dead man's switch lock poisoned")
+    }
+}
+
+impl fmt::Debug for DeadMansSwitch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeadMansSwitch")
+            .field("order_group_id", &self.order_group_id)
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::mock_http::MockHttpServer;
+    use crate::models::{Action, Side};
+    use crate::test_fixtures as fixtures;
+    use crate::ws::mock_server::{MockConnection, MockKalshiServer};
+
+    #[test]
+    fn test_expiry() {
+        let beat = Instant::now();
+        let timeout = Duration::from_secs(10);
+
+        assert_eq!(expired(beat, beat + Duration::from_secs(9), timeout), None);
+        assert_eq!(
+            expired(beat, beat + Duration::from_secs(10), timeout),
+            Some(Duration::from_secs(10))
+        );
+        assert_eq!(
+            expired(beat, beat + Duration::from_secs(12), timeout),
+            Some(Duration::from_secs(12))
+        );
+        // A heartbeat racing the check is never treated as expired
+        assert_eq!(expired(beat + Duration::from_secs(1), beat, timeout), None);
+    }
+
+    #[test]
+    fn test_trip_reason_display() {
+        let reason = TripReason::HeartbeatTimeout {
+            elapsed: Duration::from_millis(10_500),
+        };
+        assert_eq!(reason.to_string(), "no heartbeat for 10500ms");
+        assert_eq!(
+            TripReason::Disconnected(DisconnectReason::PingTimeout).to_string(),
+            "stream disconnected: ping timeout"
+        );
+    }
+    // =========================================================================
+    // Sessions against mock servers
+    // =========================================================================
+
+    const GROUP: &str = "g1";
+    const TRIGGER: &str = "/portfolio/order_groups/g1/trigger";
+    const RESET: &str = "/portfolio/order_groups/g1/reset";
+
+    struct Session {
+        http: MockHttpServer,
+        _ws: MockKalshiServer,
+        conn: MockConnection,
+        switch: DeadMansSwitch,
+        events: broadcast::Receiver<SwitchEvent>,
+        run: tokio::task::JoinHandle<()>,
+    }
+
+    /// A switch reusing group `g1`, watching a mock stream with a short
+    /// heartbeat timeout.
+    async fn session() -> Session {
+        session_with(config()).await
+    }
+
+    fn config() -> DeadMansSwitchConfig {
+        DeadMansSwitchConfig {
+            timeout: Duration::from_millis(200),
+            check_interval: Duration::from_millis(20),
+            ..Default::default()
+        }
+    }
+
+    async fn session_with(config: DeadMansSwitchConfig) -> Session {
+        let http = MockHttpServer::start().await;
+        http.respond("PUT", TRIGGER, 200, serde_json::json!({}));
+        http.respond("PUT", RESET, 200, serde_json::json!({}));
+        http.respond(
+            "GET",
+            "/portfolio/orders",
+            200,
+            serde_json::json!({ "orders": [], "cursor": "" }),
+        );
+
+        let switch = DeadMansSwitch::reuse(http.client(), GROUP, config)
+            .await
+            .unwrap();
+        let events = switch.event_receiver();
+
+        let mut ws = MockKalshiServer::start().await;
+        let stream = ws.connect_client().await;
+        let conn = ws.accept().await;
+        let watchdog = switch.clone();
+        let handle = stream.handle();
+        let run = tokio::spawn(async move {
+            let _stream = stream;
+            watchdog.run(handle).await;
+        });
+
+        Session {
+            http,
+            _ws: ws,
+            conn,
+            switch,
+            events,
+            run,
+        }
+    }
+
+    async fn next_event(events: &mut broadcast::Receiver<SwitchEvent>) -> SwitchEvent {
+        tokio::time::timeout(Duration::from_secs(5), events.recv())
+            .await
+            .expect("timed out waiting for switch event")
+            .unwrap()
+    }
+
+    fn resting_in_group(order_id: &str) -> serde_json::Value {
+        serde_json::to_value(fixtures::order(serde_json::json!({
+            "order_id": order_id,
+            "remaining_count_fp": "1.00",
+            "initial_count_fp": "1.00",
+            "order_group_id": GROUP,
+        })))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_timeout_fires_and_rearms() {
+        let mut session = session().await;
+        // One order outlives the trigger and is canceled directly
+        session.http.respond_once(
+            "GET",
+            "/portfolio/orders",
+            200,
+            serde_json::json!({ "orders": [resting_in_group("a")], "cursor": "" }),
+        );
+        session.http.respond(
+            "DELETE",
+            "/portfolio/orders/batched",
+            200,
+            serde_json::json!({ "orders": [{ "order_id": "a", "reduced_by_fp": "1.00" }] }),
+        );
+
+        assert!(matches!(
+            next_event(&mut session.events).await,
+            SwitchEvent::Tripped(TripReason::HeartbeatTimeout { .. })
+        ));
+        let SwitchEvent::Canceled(report) = next_event(&mut session.events).await else {
+            panic!("expected the remaining orders to be canceled");
+        };
+        assert_eq!(report.canceled, vec!["a".to_string()]);
+        // The switch's own trigger is not repeated by the cancel
+        assert!(report.triggered_groups.is_empty());
+        assert_eq!(session.http.requests_to("PUT", TRIGGER).len(), 1);
+
+        let order = CreateOrderRequest::new("TEST", Side::Yes, Action::Buy, 1).yes_price(45);
+        assert!(matches!(
+            session.switch.create_order(order).await,
+            Err(Error::SwitchTripped(TripReason::HeartbeatTimeout { .. }))
+        ));
+
+        session.switch.rearm().await.unwrap();
+        assert!(matches!(
+            next_event(&mut session.events).await,
+            SwitchEvent::Rearmed
+        ));
+        assert!(!session.switch.is_tripped());
+        // Once when the switch took over the group, once to rearm
+        assert_eq!(session.http.requests_to("PUT", RESET).len(), 2);
+        session.run.abort();
+    }
+
+    #[tokio::test]
+    async fn test_heartbeats_keep_switch_armed() {
+        let session = session().await;
+        for _ in 0..10 {
+            session.switch.heartbeat();
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert!(!session.switch.is_tripped());
+        assert!(session.http.requests_to("PUT", TRIGGER).is_empty());
+        session.run.abort();
+    }
+
+    #[tokio::test]
+    async fn test_trips_on_disconnect() {
+        let mut session = session().await;
+        session.switch.heartbeat();
+        session.conn.drop_connection();
+
+        tokio::time::timeout(Duration::from_secs(5), session.run)
+            .await
+            .expect("run should return once the stream is gone")
+            .unwrap();
+        assert!(matches!(
+            next_event(&mut session.events).await,
+            SwitchEvent::Tripped(TripReason::Disconnected(_))
+        ));
+        assert!(matches!(
+            session.switch.tripped(),
+            Some(TripReason::Disconnected(_))
+        ));
+        assert_eq!(session.http.requests_to("PUT", TRIGGER).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_checked_after_disconnect() {
+        let mut session = session_with(DeadMansSwitchConfig {
+            trigger_on_disconnect: false,
+            ..config()
+        })
+        .await;
+        session.switch.heartbeat();
+        session.conn.drop_connection();
+
+        // Still armed and watching the heartbeat without the stream
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!session.switch.is_tripped());
+        assert!(!session.run.is_finished());
+
+        tokio::time::timeout(Duration::from_secs(5), session.run)
+            .await
+            .expect("run should return once the switch trips")
+            .unwrap();
+        assert!(matches!(
+            next_event(&mut session.events).await,
+            SwitchEvent::Tripped(TripReason::HeartbeatTimeout { .. })
+        ));
+        assert_eq!(session.http.requests_to("PUT", TRIGGER).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_group_triggered_by_exchange() {
+        let mut session = session().await;
+        session.switch.heartbeat();
+        session
+            .conn
+            .push(
+                "order_group_updates",
+                1,
+                Some(1),
+                serde_json::json!({ "order_group_id": GROUP, "event_type": "triggered" }),
+            )
+            .await;
+
+        assert!(matches!(
+            next_event(&mut session.events).await,
+            SwitchEvent::GroupUpdated {
+                event_type: OrderGroupEventType::Triggered,
+                ..
+            }
+        ));
+        assert!(matches!(
+            next_event(&mut session.events).await,
+            SwitchEvent::Tripped(TripReason::GroupTriggered)
+        ));
+        assert_eq!(session.switch.tripped(), Some(TripReason::GroupTriggered));
+        // The exchange already triggered the group
+        assert!(session.http.requests_to("PUT", TRIGGER).is_empty());
+        session.run.abort();
+    }
+}
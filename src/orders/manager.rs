@@ -0,0 +1,999 @@
+//! Live order state from submissions, the stream and REST reconciliation.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tokio::sync::broadcast;
+use tokio::time::MissedTickBehavior;
+use tracing::{debug, warn};
+
+use crate::client::KalshiClient;
+use crate::error::Result;
+use crate::models::{
+    Action, AmendOrderRequest, CreateOrderRequest, DecreaseOrderRequest, GetOrdersParams, Order,
+    OrderStatus, Side,
+};
+use crate::units::{contracts, whole_cents};
+use crate::ws::{FillData, KalshiStreamHandle, StreamMessage, StreamUpdate, UserOrderData};
+
+/// Default capacity for the transition broadcast channel.
+const DEFAULT_TRANSITION_CAPACITY: usize = 1024;
+
+/// Default time between REST reconciliations in
+/// [`OrderManager::process_updates`].
+const DEFAULT_RECONCILE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Largest page `get_orders` returns.
+const RECONCILE_PAGE_SIZE: i64 = 200;
+
+/// Most unknown orders to hold fills for before dropping the oldest.
+const MAX_ORPHAN_ORDERS: usize = 1024;
+
+/// An order's state as last seen by an [`OrderManager`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackedOrder {
+    /// Exchange order ID.
+    pub order_id: String,
+    /// Client order ID, empty if none was set.
+    pub client_order_id: String,
+    /// Market ticker.
+    pub ticker: String,
+    /// Side of the order.
+    pub side: Side,
+    /// Buy or sell.
+    pub action: Action,
+    /// Current status.
+    pub status: OrderStatus,
+    /// Limit price in cents on the YES side.
+    pub yes_price: i64,
+    /// Contracts originally requested, after any amendment.
+    pub initial: i64,
+    /// Contracts filled so far.
+    pub filled: i64,
+    /// Contracts still resting.
+    pub remaining: i64,
+    /// Order group the order belongs to.
+    pub order_group_id: Option<String>,
+    /// Subaccount number, `None` for the primary account.
+    pub subaccount: Option<i32>,
+}
+
+impl TrackedOrder {
+    /// Whether the order is still resting on the book.
+    pub fn is_open(&self) -> bool {
+        self.status == OrderStatus::Resting
+    }
+
+    fn from_order(order: &Order) -> Self {
+        Self {
+            order_id: order.order_id.clone(),
+            client_order_id: order.client_order_id.clone(),
+            ticker: order.ticker.clone(),
+            side: order.side,
+            action: order.action,
+            status: order.status,
+            yes_price: whole_cents(&order.yes_price_dollars).unwrap_or(0),
+            initial: contracts(&order.initial_count_fp),
+            filled: contracts(&order.fill_count_fp),
+            remaining: contracts(&order.remaining_count_fp),
+            order_group_id: order.order_group_id.clone(),
+            subaccount: order.subaccount_number,
+        }
+    }
+
+    /// `user_orders` messages may omit the action, in which case `known`
+    /// (the action already tracked) or buy is assumed.
+    fn from_user_order(data: &UserOrderData, known: Option<Action>) -> Self {
+        Self {
+            order_id: data.order_id.clone(),
+            client_order_id: data.client_order_id.clone(),
+            ticker: data.ticker.clone(),
+            side: data.side,
+            action: data.action.or(known).unwrap_or(Action::Buy),
+            status: data.status,
+            yes_price: whole_cents(&data.yes_price_dollars).unwrap_or(0),
+            initial: contracts(&data.initial_count_fp),
+            filled: contracts(&data.fill_count_fp),
+            remaining: contracts(&data.remaining_count_fp),
+            order_group_id: data.order_group_id.clone(),
+            subaccount: data.subaccount_number,
+        }
+    }
+}
+
+/// Where an order state change came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransitionSource {
+    /// A response to a request made through the [`OrderManager`].
+    Response,
+    /// A `user_orders` or `fill` stream message.
+    Stream,
+    /// A REST reconciliation.
+    Reconcile,
+}
+
+/// A change in a tracked order's state.
+///
+/// Published on [`OrderManager::transition_receiver`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderTransition {
+    /// Status before the change, `None` for an order seen for the first time.
+    pub previous: Option<OrderStatus>,
+    /// The order after the change.
+    pub order: TrackedOrder,
+    /// Contracts filled by this change.
+    pub newly_filled: i64,
+    /// Where the change came from.
+    pub source: TransitionSource,
+}
+
+/// Criteria for [`OrderManager::open_orders`].
+///
+/// An empty filter matches every open order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OrderFilter {
+    ticker: Option<String>,
+    side: Option<Side>,
+    order_group_id: Option<String>,
+    subaccount: Option<i32>,
+}
+
+impl OrderFilter {
+    /// Create a filter that matches every order.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only orders in this market.
+    #[must_use]
+    pub fn ticker(mut self, ticker: impl Into<String>) -> Self {
+        self.ticker = Some(ticker.into());
+        self
+    }
+
+    /// Only orders on this side.
+    #[must_use]
+    pub fn side(mut self, side: Side) -> Self {
+        self.side = Some(side);
+        self
+    }
+
+    /// Only orders in this order group.
+    #[must_use]
+    pub fn order_group_id(mut self, order_group_id: impl Into<String>) -> Self {
+        self.order_group_id = Some(order_group_id.into());
+        self
+    }
+
+    /// Only orders in this subaccount (0 for the primary account).
+    #[must_use]
+    pub fn subaccount(mut self, subaccount: i32) -> Self {
+        self.subaccount = Some(subaccount);
+        self
+    }
+
+    /// Whether an order meets every criterion.
+    pub fn matches(&self, order: &TrackedOrder) -> bool {
+        self.ticker.as_ref().is_none_or(|t| *t == order.ticker)
+            && self.side.is_none_or(|s| s == order.side)
+            && self
+                .order_group_id
+                .as_ref()
+                .is_none_or(|g| order.order_group_id.as_ref() == Some(g))
+            && self
+                .subaccount
+                .is_none_or(|s| order.subaccount.unwrap_or(0) == s)
+    }
+}
+
+/// One order and what has been reported about it.
+///
+/// Snapshots (REST responses and `user_orders` messages) report cumulative
+/// fills, while `fill` messages report increments. They arrive on different
+/// channels in no particular order, so both are kept and the larger fill
+/// count wins; neither can overstate what has filled.
+#[derive(Debug)]
+struct Entry {
+    order: TrackedOrder,
+    /// Fill count from the latest snapshot.
+    reported_filled: i64,
+    /// Remaining count from the latest snapshot.
+    reported_remaining: i64,
+    /// Total of the `fill` messages seen.
+    stream_filled: i64,
+    /// Trade IDs already counted in `stream_filled`.
+    trades: HashSet<String>,
+}
+
+impl Entry {
+    fn new(order: TrackedOrder) -> Self {
+        Self {
+            reported_filled: order.filled,
+            reported_remaining: order.remaining,
+            stream_filled: 0,
+            trades: HashSet::new(),
+            order,
+        }
+    }
+
+    fn is_closed(&self) -> bool {
+        self.order.status != OrderStatus::Resting
+    }
+
+    /// Merge a snapshot. Once an order is closed, later snapshots can only
+    /// raise its fill count: a resting snapshot after a cancel is stale.
+    fn apply_snapshot(&mut self, snapshot: TrackedOrder) {
+        let behind = (self.reported_filled - snapshot.filled).max(0);
+        self.reported_filled = self.reported_filled.max(snapshot.filled);
+        if self.is_closed() {
+            self.settle();
+            return;
+        }
+        // An older snapshot still counts fills reported since as resting
+        self.reported_remaining = (snapshot.remaining - behind).max(0);
+        let client_order_id = if snapshot.client_order_id.is_empty() {
+            std::mem::take(&mut self.order.client_order_id)
+        } else {
+            snapshot.client_order_id.clone()
+        };
+        self.order = TrackedOrder {
+            client_order_id,
+            ..snapshot
+        };
+        self.settle();
+    }
+
+    /// Count a `fill` message. Returns false for a trade already counted.
+    fn apply_fill(&mut self, trade_id: &str, count: i64) -> bool {
+        if !self.trades.insert(trade_id.to_string()) {
+            return false;
+        }
+        self.stream_filled += count;
+        self.settle();
+        true
+    }
+
+    /// Derive the fill count, remaining count and status from what has been
+    /// reported.
+    fn settle(&mut self) {
+        let filled = self.reported_filled.max(self.stream_filled);
+        self.order.filled = filled;
+        if self.is_closed() {
+            self.order.remaining = 0;
+            return;
+        }
+        let unreported = filled - self.reported_filled;
+        self.order.remaining = (self.reported_remaining - unreported).max(0);
+        if self.order.remaining == 0 {
+            self.order.status = OrderStatus::Executed;
+        }
+    }
+}
+
+/// Order state shared between clones of an [`OrderManager`].
+#[derive(Debug, Default)]
+struct Orders {
+    entries: HashMap<String, Entry>,
+    /// Client order ID to order ID.
+    client_ids: HashMap<String, String>,
+    /// Fills for orders not yet seen, by order ID.
+    ///
+    /// A fill can arrive before the create response or the `user_orders`
+    /// message that introduces its order.
+    orphan_fills: HashMap<String, OrphanFills>,
+}
+
+/// Fills held for an order not yet seen.
+#[derive(Debug)]
+struct OrphanFills {
+    /// When the first fill arrived.
+    received: Instant,
+    /// Trade ID and contract count of each fill.
+    fills: Vec<(String, i64)>,
+}
+
+impl Orders {
+    fn get(&self, order_id: &str) -> Option<&TrackedOrder> {
+        self.entries.get(order_id).map(|entry| &entry.order)
+    }
+
+    /// Merge a snapshot, returning the transition if anything changed.
+    fn apply_snapshot(
+        &mut self,
+        snapshot: TrackedOrder,
+        source: TransitionSource,
+    ) -> Option<OrderTransition> {
+        let order_id = snapshot.order_id.clone();
+        let (previous, before) = match self.entries.get_mut(&order_id) {
+            Some(entry) => {
+                let before = entry.order.clone();
+                entry.apply_snapshot(snapshot);
+                (Some(before.status), Some(before))
+            }
+            None => {
+                let mut entry = Entry::new(snapshot);
+                if let Some(orphans) = self.orphan_fills.remove(&order_id) {
+                    for (trade_id, count) in orphans.fills {
+                        entry.apply_fill(&trade_id, count);
+                    }
+                }
+                entry.settle();
+                self.entries.insert(order_id.clone(), entry);
+                (None, None)
+            }
+        };
+
+        let order = &self.entries[&order_id].order;
+        if !order.client_order_id.is_empty() {
+            self.client_ids
+                .insert(order.client_order_id.clone(), order_id.clone());
+        }
+        transition(previous, before.as_ref(), order, source)
+    }
+
+    fn apply_user_order(&mut self, data: &UserOrderData) -> Option<OrderTransition> {
+        let known = self.get(&data.order_id).map(|order| order.action);
+        self.apply_snapshot(
+            TrackedOrder::from_user_order(data, known),
+            TransitionSource::Stream,
+        )
+    }
+
+    fn apply_fill(&mut self, fill: &FillData) -> Option<OrderTransition> {
+        let count = contracts(&fill.count_fp);
+        let Some(entry) = self.entries.get_mut(&fill.order_id) else {
+            self.hold_orphan(fill, count);
+            return None;
+        };
+        let before = entry.order.clone();
+        if !entry.apply_fill(&fill.trade_id, count) {
+            return None;
+        }
+        transition(
+            Some(before.status),
+            Some(&before),
+            &entry.order,
+            TransitionSource::Stream,
+        )
+    }
+
+    /// Hold a fill for an unknown order, dropping the oldest held order at
+    /// capacity.
+    fn hold_orphan(&mut self, fill: &FillData, count: i64) {
+        if !self.orphan_fills.contains_key(&fill.order_id)
+            && self.orphan_fills.len() >= MAX_ORPHAN_ORDERS
+        {
+            let oldest = self
+                .orphan_fills
+                .iter()
+                .min_by_key(|(_, orphans)| orphans.received)
+                .map(|(order_id, _)| order_id.clone());
+            if let Some(order_id) = oldest {
+                warn!(
+                    order_id,
+                    "too many unknown orders with fills, dropping oldest"
+                );
+                self.orphan_fills.remove(&order_id);
+            }
+        }
+        self.orphan_fills
+            .entry(fill.order_id.clone())
+            .or_insert_with(|| OrphanFills {
+                received: Instant::now(),
+                fills: Vec::new(),
+            })
+            .fills
+            .push((fill.trade_id.clone(), count));
+    }
+
+    /// Drop fills held for unknown orders since before `cutoff`.
+    ///
+    /// Fills held since then may belong to a submit still in flight.
+    fn prune_orphans(&mut self, cutoff: Instant) {
+        self.orphan_fills
+            .retain(|_, orphans| orphans.received >= cutoff);
+    }
+
+    fn open_orders(&self, filter: &OrderFilter) -> Vec<TrackedOrder> {
+        let mut orders: Vec<TrackedOrder> = self
+            .entries
+            .values()
+            .map(|entry| &entry.order)
+            .filter(|order| order.is_open() && filter.matches(order))
+            .cloned()
+            .collect();
+        orders.sort_by(|a, b| (&a.ticker, &a.order_id).cmp(&(&b.ticker, &b.order_id)));
+        orders
+    }
+
+    fn open_order_ids(&self) -> Vec<String> {
+        self.entries
+            .values()
+            .filter(|entry| !entry.is_closed())
+            .map(|entry| entry.order.order_id.clone())
+            .collect()
+    }
+
+    fn remove_closed(&mut self) -> usize {
+        let closed: Vec<String> = self
+            .entries
+            .values()
+            .filter(|entry| entry.is_closed())
+            .map(|entry| entry.order.order_id.clone())
+            .collect();
+        for order_id in &closed {
+            if let Some(entry) = self.entries.remove(order_id) {
+                self.client_ids.remove(&entry.order.client_order_id);
+            }
+        }
+        closed.len()
+    }
+}
+
+fn transition(
+    previous: Option<OrderStatus>,
+    before: Option<&TrackedOrder>,
+    after: &TrackedOrder,
+    source: TransitionSource,
+) -> Option<OrderTransition> {
+    if before == Some(after) {
+        return None;
+    }
+    Some(OrderTransition {
+        previous,
+        order: after.clone(),
+        newly_filled: after.filled - before.map_or(0, |b| b.filled),
+        source,
+    })
+}
+
+/// Submits orders and tracks their state.
+///
+/// The manager indexes every order it sees by order ID and client order ID.
+/// State comes from three places:
+///
+/// - responses to [`submit`](Self::submit), [`cancel`](Self::cancel),
+///   [`amend`](Self::amend) and [`decrease`](Self::decrease);
+/// - `user_orders` and `fill` messages, applied by
+///   [`process_updates`](Self::process_updates) or
+///   [`apply_update`](Self::apply_update);
+/// - [`reconcile`](Self::reconcile), which compares resting orders against
+///   REST and looks up any order the stream left open.
+///
+/// These can arrive in any order. A fill that arrives before its create
+/// response is held until the order is known, fills are never counted twice,
+/// and a stale resting report cannot reopen a closed order. Every change is
+/// published as an [`OrderTransition`].
+///
+/// Cloning is cheap; clones share the same state.
+///
+/// # Example
+///
+/// ```no_run
+/// use kalshi_trade_rs::orders::{OrderFilter, OrderManager};
+/// use kalshi_trade_rs::{Action, Channel, CreateOrderRequest, KalshiClient, Side};
+///
+/// # async fn example(
+/// #     client: KalshiClient,
+/// #     mut handle: kalshi_trade_rs::ws::KalshiStreamHandle,
+/// # ) -> kalshi_trade_rs::Result<()> {
+/// handle.subscribe(Channel::UserOrders, &[]).await?;
+/// handle.subscribe(Channel::Fill, &[]).await?;
+///
+/// let manager = OrderManager::new(client);
+/// let mut transitions = manager.transition_receiver();
+/// let processor = manager.clone();
+/// tokio::spawn(async move {
+///     processor.process_updates(handle).await;
+/// });
+///
+/// let request = CreateOrderRequest::new("TICKER", Side::Yes, Action::Buy, 10).yes_price(45);
+/// let order = manager.submit(request).await?;
+/// println!("{} is {:?}", order.order_id, order.status);
+///
+/// let open = manager.open_orders(&OrderFilter::new().ticker("TICKER"));
+/// println!("{} open", open.len());
+///
+/// while let Ok(transition) = transitions.recv().await {
+///     println!("{:?} -> {:?}", transition.previous, transition.order.status);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct OrderManager {
+    client: KalshiClient,
+    orders: Arc<Mutex<Orders>>,
+    transition_sender: broadcast::Sender<OrderTransition>,
+    reconcile_interval: Duration,
+    reconciling: Arc<AtomicBool>,
+}
+
+impl OrderManager {
+    /// Create a manager that submits through `client`.
+    pub fn new(client: KalshiClient) -> Self {
+        let (transition_sender, _) = broadcast::channel(DEFAULT_TRANSITION_CAPACITY);
+        Self {
+            client,
+            orders: Arc::new(Mutex::new(Orders::default())),
+            transition_sender,
+            reconcile_interval: DEFAULT_RECONCILE_INTERVAL,
+            reconciling: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Set how often [`process_updates`](Self::process_updates) reconciles
+    /// against REST.
+    ///
+    /// Default: 30 seconds.
+    #[must_use]
+    pub fn with_reconcile_interval(mut self, interval: Duration) -> Self {
+        self.reconcile_interval = interval;
+        self
+    }
+
+    /// Get a receiver for order state changes.
+    pub fn transition_receiver(&self) -> broadcast::Receiver<OrderTransition> {
+        self.transition_sender.subscribe()
+    }
+
+    // =========================================================================
+    // Submission
+    // =========================================================================
+
+    /// Create an order and start tracking it.
+    pub async fn submit(&self, request: CreateOrderRequest) -> Result<TrackedOrder> {
+        let response = self.client.create_order(request).await?;
+        Ok(self.apply_order(&response.order, TransitionSource::Response))
+    }
+
+    /// Cancel an order.
+    ///
+    /// Orders tracked in a subaccount are canceled in that subaccount.
+    pub async fn cancel(&self, order_id: &str) -> Result<TrackedOrder> {
+        let subaccount = self.order(order_id).and_then(|order| order.subaccount);
+        let response = match subaccount {
+            Some(subaccount) if subaccount != 0 => {
+                self.client
+                    .cancel_order_for_subaccount(order_id, subaccount)
+                    .await?
+            }
+            _ => self.client.cancel_order(order_id).await?,
+        };
+        Ok(self.apply_order(&response.order, TransitionSource::Response))
+    }
+
+    /// Amend an order's price or quantity.
+    pub async fn amend(&self, order_id: &str, request: AmendOrderRequest) -> Result<TrackedOrder> {
+        let response = self.client.amend_order(order_id, request).await?;
+        self.apply_order(&response.old_order, TransitionSource::Response);
+        Ok(self.apply_order(&response.order, TransitionSource::Response))
+    }
+
+    /// Reduce an order's quantity.
+    pub async fn decrease(
+        &self,
+        order_id: &str,
+        request: DecreaseOrderRequest,
+    ) -> Result<TrackedOrder> {
+        let response = self.client.decrease_order(order_id, request).await?;
+        Ok(self.apply_order(&response.order, TransitionSource::Response))
+    }
+
+    // =========================================================================
+    // Queries
+    // =========================================================================
+
+    /// Get a tracked order by order ID.
+    pub fn order(&self, order_id: &str) -> Option<TrackedOrder> {
+        self.lock().get(order_id).cloned()
+    }
+
+    /// Get a tracked order by client order ID.
+    pub fn order_by_client_id(&self, client_order_id: &str) -> Option<TrackedOrder> {
+        let orders = self.lock();
+        let order_id = orders.client_ids.get(client_order_id)?;
+        orders.get(order_id).cloned()
+    }
+
+    /// Resting orders matching `filter`, sorted by ticker then order ID.
+    pub fn open_orders(&self, filter: &OrderFilter) -> Vec<TrackedOrder> {
+        self.lock().open_orders(filter)
+    }
+
+    /// Stop tracking canceled and executed orders.
+    ///
+    /// Returns the number of orders removed.
+    pub fn remove_closed(&self) -> usize {
+        self.lock().remove_closed()
+    }
+
+    // =========================================================================
+    // Stream and REST updates
+    // =========================================================================
+
+    /// Process updates from a WebSocket stream until it closes.
+    ///
+    /// The handle should be subscribed to [`Channel::UserOrders`] and
+    /// [`Channel::Fill`]. Reconciles against REST on start, every
+    /// [reconcile interval](Self::with_reconcile_interval), and whenever the
+    /// receiver lags. Reconciliation runs in the background so the stream
+    /// keeps being applied; failures are logged and retried on the next
+    /// interval.
+    ///
+    /// [`Channel::UserOrders`]: crate::ws::Channel::UserOrders
+    /// [`Channel::Fill`]: crate::ws::Channel::Fill
+    pub async fn process_updates(&self, mut handle: KalshiStreamHandle) {
+        let mut reconcile = tokio::time::interval(self.reconcile_interval);
+        reconcile.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            let received = tokio::select! {
+                received = handle.update_receiver.recv() => received,
+                _ = reconcile.tick() => {
+                    self.spawn_reconcile();
+                    continue;
+                }
+            };
+
+            match received {
+                Ok(update) => match &update.msg {
+                    StreamMessage::Closed { .. } | StreamMessage::ConnectionLost { .. } => {
+                        break;
+                    }
+                    _ => self.apply_update(&update),
+                },
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    debug!(missed = n, "order updates lagged, reconciling");
+                    self.spawn_reconcile();
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    }
+
+    /// Apply a single stream update.
+    ///
+    /// [`process_updates`](Self::process_updates) calls this for every
+    /// message. Messages other than `user_orders` and `fill` are ignored.
+    pub fn apply_update(&self, update: &StreamUpdate) {
+        let transition = match &update.msg {
+            StreamMessage::UserOrder(data) => self.lock().apply_user_order(data),
+            StreamMessage::Fill(fill) => self.lock().apply_fill(fill),
+            _ => None,
+        };
+        self.publish(transition);
+    }
+
+    /// Compare tracked orders against REST.
+    ///
+    /// Pages through every resting order, then looks up each tracked order
+    /// that REST no longer lists as resting to learn how it closed. Fills
+    /// held for an unknown order since before the reconcile started are
+    /// dropped: had the order been resting, REST would have listed it, so it
+    /// finished without being seen and its snapshot carries the fill count
+    /// anyway. Fills that arrived during the reconcile are kept for submits
+    /// still in flight.
+    ///
+    /// Returns the number of orders whose state changed.
+    pub async fn reconcile(&self) -> Result<usize> {
+        let started = Instant::now();
+        let mut changed = 0;
+        let mut resting = HashSet::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let mut params = GetOrdersParams::new()
+                .status(OrderStatus::Resting)
+                .limit(RECONCILE_PAGE_SIZE);
+            if let Some(cursor) = cursor.take() {
+                params = params.cursor(cursor);
+            }
+            let response = self.client.get_orders_with_params(params).await?;
+            for order in &response.orders {
+                resting.insert(order.order_id.clone());
+                changed += usize::from(self.update(order, TransitionSource::Reconcile));
+            }
+            if response.cursor.is_empty() || response.orders.is_empty() {
+                break;
+            }
+            cursor = Some(response.cursor);
+        }
+
+        let missing: Vec<String> = self
+            .lock()
+            .open_order_ids()
+            .into_iter()
+            .filter(|order_id| !resting.contains(order_id))
+            .collect();
+        for order_id in missing {
+            let response = self.client.get_order(&order_id).await?;
+            changed += usize::from(self.update(&response.order, TransitionSource::Reconcile));
+        }
+
+        self.lock().prune_orphans(started);
+        Ok(changed)
+    }
+
+    fn spawn_reconcile(&self) {
+        if self.reconciling.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let manager = self.clone();
+        tokio::spawn(async move {
+            match manager.reconcile().await {
+                Ok(changed) => debug!(changed, "reconciled orders"),
+                Err(e) => warn!("order reconciliation failed: {}", e),
+            }
+            manager.reconciling.store(false, Ordering::Release);
+        });
+    }
+
+    /// Merge a REST order, returning the tracked state.
+    fn apply_order(&self, order: &Order, source: TransitionSource) -> TrackedOrder {
+        let (transition, tracked) = {
+            let mut orders = self.lock();
+            let transition = orders.apply_snapshot(TrackedOrder::from_order(order), source);
+            let tracked = orders
+                .get(&order.order_id)
+                .cloned()
+                .expect("order was just tracked");
+            (transition, tracked)
+        };
+        self.publish(transition);
+        tracked
+    }
+
+    /// Merge a REST order, returning whether it changed anything.
+    fn update(&self, order: &Order, source: TransitionSource) -> bool {
+        let transition = self
+            .lock()
+            .apply_snapshot(TrackedOrder::from_order(order), source);
+        let changed = transition.is_some();
+        self.publish(transition);
+        changed
+    }
+
+    fn publish(&self, transition: Option<OrderTransition>) {
+        if let Some(transition) = transition {
+            let _ = self.transition_sender.send(transition);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Orders> {
+        self.orders.lock().expect("order manager lock poisoned")
+    }
+}
+
+impl std::fmt::Debug for OrderManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let orders = self.lock();
+        f.debug_struct("OrderManager")
+            .field("tracked_orders", &orders.entries.len())
+            .field("reconcile_interval", &self.reconcile_interval)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    use crate::test_fixtures as fixtures;
+
+    fn order(order_id: &str, status: &str, filled: &str, remaining: &str) -> Order {
+        fixtures::order(json!({
+            "order_id": order_id,
+            "client_order_id": format!("client-{order_id}"),
+            "status": status,
+            "fill_count_fp": filled,
+            "remaining_count_fp": remaining,
+        }))
+    }
+
+    fn user_order(order_id: &str, status: &str, filled: &str, remaining: &str) -> UserOrderData {
+        fixtures::user_order(json!({
+            "order_id": order_id,
+            "client_order_id": format!("client-{order_id}"),
+            "status": status,
+            "fill_count_fp": filled,
+            "remaining_count_fp": remaining,
+        }))
+    }
+
+    fn fill(order_id: &str, trade_id: &str, count: &str) -> FillData {
+        fixtures::fill(json!({
+            "trade_id": trade_id,
+            "order_id": order_id,
+            "count_fp": count,
+            "post_position_fp": count,
+        }))
+    }
+
+    fn snapshot(order: &Order) -> TrackedOrder {
+        TrackedOrder::from_order(order)
+    }
+
+    #[test]
+    fn test_fill_before_create_response() {
+        let mut orders = Orders::default();
+        assert!(orders.apply_fill(&fill("a", "t1", "4.00")).is_none());
+
+        // The create response was generated before the fill
+        let transition = orders
+            .apply_snapshot(
+                snapshot(&order("a", "resting", "0.00", "10.00")),
+                TransitionSource::Response,
+            )
+            .unwrap();
+        assert_eq!(transition.previous, None);
+        assert_eq!(transition.newly_filled, 4);
+        assert_eq!(transition.order.filled, 4);
+        assert_eq!(transition.order.remaining, 6);
+        assert!(orders.orphan_fills.is_empty());
+    }
+
+    #[test]
+    fn test_fills_not_double_counted() {
+        let mut orders = Orders::default();
+        orders.apply_snapshot(
+            snapshot(&order("a", "resting", "0.00", "10.00")),
+            TransitionSource::Response,
+        );
+
+        // The user_orders message already includes the fill
+        orders.apply_user_order(&user_order("a", "resting", "4.00", "6.00"));
+        assert!(orders.apply_fill(&fill("a", "t1", "4.00")).is_none());
+        // So does an older snapshot arriving late
+        assert!(
+            orders
+                .apply_snapshot(
+                    snapshot(&order("a", "resting", "0.00", "10.00")),
+                    TransitionSource::Reconcile
+                )
+                .is_none()
+        );
+        assert_eq!(orders.get("a").unwrap().filled, 4);
+
+        // A repeated fill is ignored
+        orders.apply_fill(&fill("a", "t2", "6.00"));
+        assert!(orders.apply_fill(&fill("a", "t2", "6.00")).is_none());
+        let order = orders.get("a").unwrap();
+        assert_eq!(order.filled, 10);
+        assert_eq!(order.remaining, 0);
+        assert_eq!(order.status, OrderStatus::Executed);
+    }
+
+    #[test]
+    fn test_stale_resting_does_not_reopen() {
+        let mut orders = Orders::default();
+        orders.apply_user_order(&user_order("a", "resting", "2.00", "8.00"));
+        let transition = orders
+            .apply_user_order(&user_order("a", "canceled", "2.00", "0.00"))
+            .unwrap();
+        assert_eq!(transition.previous, Some(OrderStatus::Resting));
+        assert_eq!(transition.order.status, OrderStatus::Canceled);
+
+        // A reconcile page fetched before the cancel
+        assert!(
+            orders
+                .apply_snapshot(
+                    snapshot(&order("a", "resting", "2.00", "8.00")),
+                    TransitionSource::Reconcile
+                )
+                .is_none()
+        );
+        assert_eq!(orders.get("a").unwrap().status, OrderStatus::Canceled);
+        assert!(orders.open_order_ids().is_empty());
+    }
+
+    #[test]
+    fn test_queries() {
+        let mut orders = Orders::default();
+        orders.apply_user_order(&user_order("a", "resting", "0.00", "10.00"));
+        let mut b = order("b", "resting", "0.00", "10.00");
+        b.side = Side::No;
+        b.order_group_id = Some("group".to_string());
+        b.subaccount_number = Some(2);
+        orders.apply_snapshot(snapshot(&b), TransitionSource::Response);
+        orders.apply_snapshot(
+            snapshot(&order("c", "canceled", "0.00", "0.00")),
+            TransitionSource::Response,
+        );
+
+        let ids = |filter: OrderFilter| -> Vec<String> {
+            orders
+                .open_orders(&filter)
+                .into_iter()
+                .map(|o| o.order_id)
+                .collect()
+        };
+        assert_eq!(ids(OrderFilter::new()), vec!["a", "b"]);
+        assert_eq!(ids(OrderFilter::new().side(Side::Yes)), vec!["a"]);
+        assert_eq!(ids(OrderFilter::new().order_group_id("group")), vec!["b"]);
+        assert_eq!(ids(OrderFilter::new().subaccount(0)), vec!["a"]);
+        assert_eq!(
+            ids(OrderFilter::new().ticker("OTHER")),
+            Vec::<String>::new()
+        );
+
+        assert_eq!(orders.client_ids["client-b"], "b");
+        assert_eq!(orders.remove_closed(), 1);
+        assert!(!orders.client_ids.contains_key("client-c"));
+    }
+    #[test]
+    fn test_prune_keeps_recent_orphans() {
+        let mut orders = Orders::default();
+        orders.apply_fill(&fill("old", "t1", "2.00"));
+        let cutoff = Instant::now();
+        orders.apply_fill(&fill("new", "t2", "3.00"));
+
+        orders.prune_orphans(cutoff);
+        assert!(!orders.orphan_fills.contains_key("old"));
+
+        // The create response for the in-flight order still counts its fill
+        let transition = orders
+            .apply_snapshot(
+                snapshot(&order("new", "resting", "0.00", "10.00")),
+                TransitionSource::Response,
+            )
+            .unwrap();
+        assert_eq!(transition.newly_filled, 3);
+    }
+
+    #[test]
+    fn test_orphan_fills_capped() {
+        let mut orders = Orders::default();
+        for i in 0..=MAX_ORPHAN_ORDERS {
+            orders.apply_fill(&fill(&format!("o{i}"), &format!("t{i}"), "1.00"));
+        }
+        assert_eq!(orders.orphan_fills.len(), MAX_ORPHAN_ORDERS);
+        assert!(!orders.orphan_fills.contains_key("o0"));
+        assert!(
+            orders
+                .orphan_fills
+                .contains_key(&format!("o{MAX_ORPHAN_ORDERS}"))
+        );
+
+        // More fills for a held order do not evict another
+        orders.apply_fill(&fill("o1", "t-extra", "1.00"));
+        assert_eq!(orders.orphan_fills.len(), MAX_ORPHAN_ORDERS);
+        assert_eq!(orders.orphan_fills["o1"].fills.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_applies_and_prunes_orphans() {
+        use crate::client::mock_http::MockHttpServer;
+
+        let server = MockHttpServer::start().await;
+        server.respond(
+            "GET",
+            "/portfolio/orders",
+            200,
+            serde_json::json!({
+                "orders": [order("resting", "resting", "0.00", "10.00")],
+                "cursor": "",
+            }),
+        );
+        let manager = OrderManager::new(server.client());
+        let stream_fill = |order_id: &str, trade_id: &str| StreamUpdate {
+            channel: "fill".to_string(),
+            sid: 1,
+            seq: None,
+            msg: StreamMessage::Fill(fill(order_id, trade_id, "4.00")),
+        };
+        manager.apply_update(&stream_fill("resting", "t1"));
+        manager.apply_update(&stream_fill("gone", "t2"));
+
+        assert_eq!(manager.reconcile().await.unwrap(), 1);
+        let order = manager.order("resting").unwrap();
+        assert_eq!(order.filled, 4);
+        assert_eq!(order.remaining, 6);
+        // Filled before the reconcile started but not resting: finished unseen
+        assert!(manager.lock().orphan_fills.is_empty());
+        assert!(manager.order("gone").is_none());
+    }
+}
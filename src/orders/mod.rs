@@ -0,0 +1,44 @@
+//! Order lifecycle management.
+//!
+//! [`OrderManager`] submits orders through a [`KalshiClient`](crate::KalshiClient)
+//! and keeps their state current from the `user_orders` and `fill` WebSocket
+//! channels, reconciling against REST to catch anything the stream missed.
+//!
+//! [`ClientOrderIdGenerator`] stamps orders with unique client order IDs so
+//! that a submission whose outcome is unknown can be looked up instead of
+//! blindly resent.
+//!
+//! [`RiskGuard`] checks orders against position, size, price and rate limits
+//! before they are sent.
+//!
+//! [`KalshiClient::cancel_all`](crate::KalshiClient::cancel_all) cancels every
+//! resting order in a scope and reports what could not be canceled.
+//!
+//! [`DeadMansSwitch`] places orders in an order group and triggers the group
+//! when the trading loop stops sending heartbeats or the stream disconnects.
+//!
+//! [`ContingentManager`] runs OCO pairs, price-triggered orders and
+//! take-profit/stop-loss brackets client-side, persisting pending ones
+//! across restarts.
+
+mod cancel_all;
+mod client_id;
+mod contingent;
+mod dead_mans_switch;
+mod manager;
+mod risk;
+
+pub use cancel_all::{CancelAllFilter, CancelAllReport, CancelFailure};
+pub(crate) use cancel_all::{cancel_all, cancel_all_with};
+pub use client_id::ClientOrderIdGenerator;
+pub(crate) use client_id::{
+    LOOKUP_SLACK_SECS, find_created, is_ambiguous, is_duplicate, merge_batch_results, missing,
+    resolve_duplicates,
+};
+pub use contingent::{
+    Contingency, ContingentEvent, ContingentManager, ContingentOrder, Firing, PriceSource,
+    PriceTrigger, TriggerDirection, TriggerLeg,
+};
+pub use dead_mans_switch::{DeadMansSwitch, DeadMansSwitchConfig, SwitchEvent, TripReason};
+pub use manager::{OrderFilter, OrderManager, OrderTransition, TrackedOrder, TransitionSource};
+pub use risk::{RiskGuard, RiskLimits, RiskRule, RiskViolation};
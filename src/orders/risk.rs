@@ -0,0 +1,1120 @@
+//! Pre-trade risk checks in front of order submission.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tracing::{debug, warn};
+
+use crate::batch::{AggregatedCreateResponse, BatchManager, BatchOperationResult};
+use crate::client::KalshiClient;
+use crate::error::{Error, Result};
+use crate::models::{
+    Action, AmendOrderRequest, AmendOrderResponse, BatchCreateOrdersRequest,
+    BatchCreateOrdersResponse, CreateOrderRequest, GetOrdersParams, GetPositionsParams, Order,
+    OrderResponse, OrderStatus, Side,
+};
+use crate::orderbook::OrderbookAggregator;
+use crate::units::{contracts, direction, subaccount_key, whole_cents};
+use crate::ws::{KalshiStreamHandle, StreamMessage, StreamUpdate, UserOrderData};
+
+/// Window for [`RiskLimits::max_orders_per_second`].
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// Price assumed for the notional of an order without a limit price.
+const WORST_CASE_PRICE: i64 = 99;
+
+/// Largest page `get_orders` returns.
+const ORDERS_PAGE_SIZE: i64 = 200;
+
+/// Largest page `get_positions` returns.
+const POSITIONS_PAGE_SIZE: i64 = 1000;
+
+/// Contracts in an order, the larger of `count` and `count_fp`.
+///
+/// Kalshi accepts either field, so checking only one could be bypassed by
+/// setting the other.
+fn order_size(order: &CreateOrderRequest) -> i64 {
+    order
+        .count_fp
+        .as_deref()
+        .map_or(order.count, |fp| contracts(fp).max(order.count))
+}
+
+/// Limit price of an order in YES cents.
+fn yes_price(order: &CreateOrderRequest) -> Option<i64> {
+    order
+        .yes_price
+        .or_else(|| order.no_price.map(|p| 100 - p))
+        .or_else(|| order.yes_price_dollars.as_deref().and_then(whole_cents))
+        .or_else(|| {
+            order
+                .no_price_dollars
+                .as_deref()
+                .and_then(whole_cents)
+                .map(|p| 100 - p)
+        })
+}
+
+/// Limits checked before an order is sent.
+///
+/// Every limit is optional; `None` disables the check. Used as the global
+/// limits of a [`RiskGuard`] and, through
+/// [`RiskGuard::with_market_limits`], for individual markets.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RiskLimits {
+    /// Most contracts in a single order.
+    ///
+    /// Default: no limit.
+    pub max_order_size: Option<i64>,
+
+    /// Most a single order can cost, in cents: contracts times the price of
+    /// the side traded. Orders without a limit price are valued at 99¢.
+    ///
+    /// Default: no limit.
+    pub max_order_notional: Option<i64>,
+
+    /// Largest absolute position in one market, in contracts, once the order
+    /// and every resting order adding to the same side have filled.
+    ///
+    /// Default: no limit.
+    pub max_position: Option<i64>,
+
+    /// Largest sum of absolute positions across the markets of one event,
+    /// with the order filled.
+    ///
+    /// Default: no limit.
+    pub max_event_position: Option<i64>,
+
+    /// Furthest an order's YES price may be from the book midpoint, in
+    /// cents. Needs an orderbook from [`RiskGuard::with_orderbook`]; orders
+    /// are rejected when no midpoint or no limit price is available.
+    ///
+    /// Default: no limit.
+    pub price_collar: Option<i64>,
+
+    /// Most resting orders at once.
+    ///
+    /// Default: no limit.
+    pub max_open_orders: Option<usize>,
+
+    /// Most orders sent in any one-second window.
+    ///
+    /// Default: no limit.
+    pub max_orders_per_second: Option<usize>,
+}
+
+/// A risk check that rejected an order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RiskRule {
+    /// [`RiskLimits::max_order_size`].
+    MaxOrderSize,
+    /// [`RiskLimits::max_order_notional`].
+    MaxOrderNotional,
+    /// [`RiskLimits::max_position`].
+    MaxPosition,
+    /// [`RiskLimits::max_event_position`].
+    MaxEventPosition,
+    /// [`RiskLimits::price_collar`].
+    PriceCollar,
+    /// [`RiskLimits::max_open_orders`].
+    MaxOpenOrders,
+    /// [`RiskLimits::max_orders_per_second`].
+    MaxOrdersPerSecond,
+}
+
+impl RiskRule {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::MaxOrderSize => "max order size",
+            Self::MaxOrderNotional => "max order notional",
+            Self::MaxPosition => "max position",
+            Self::MaxEventPosition => "max event position",
+            Self::PriceCollar => "price collar",
+            Self::MaxOpenOrders => "max open orders",
+            Self::MaxOrdersPerSecond => "max orders per second",
+        }
+    }
+}
+
+impl fmt::Display for RiskRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Why [`RiskGuard`] rejected an order.
+///
+/// Returned inside [`Error::RiskRejected`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RiskViolation {
+    /// The rule that failed.
+    pub rule: RiskRule,
+    /// Market of the rejected order.
+    pub ticker: String,
+    /// The configured limit.
+    pub limit: i64,
+    /// The value that broke it, or `None` if it could not be measured (a
+    /// price collar without a midpoint or limit price).
+    pub actual: Option<i64>,
+}
+
+impl fmt::Display for RiskViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.actual {
+            Some(actual) => write!(
+                f,
+                "{} on {}: {} exceeds limit {}",
+                self.rule, self.ticker, actual, self.limit
+            ),
+            None => write!(
+                f,
+                "{} on {}: no reference price to check against limit {}",
+                self.rule, self.ticker, self.limit
+            ),
+        }
+    }
+}
+
+fn exceeds(
+    rule: RiskRule,
+    ticker: &str,
+    limit: Option<i64>,
+    actual: i64,
+) -> std::result::Result<(), RiskViolation> {
+    match limit {
+        Some(limit) if actual > limit => Err(RiskViolation {
+            rule,
+            ticker: ticker.to_string(),
+            limit,
+            actual: Some(actual),
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// A resting order counted against position and open-order limits.
+#[derive(Debug, Clone)]
+struct OpenOrder {
+    ticker: String,
+    action: Action,
+    /// Signed contracts the order adds to the position if it fills.
+    exposure: i64,
+}
+
+impl OpenOrder {
+    fn from_order(order: &Order) -> Option<Self> {
+        let remaining = contracts(&order.remaining_count_fp);
+        (order.status == OrderStatus::Resting && remaining > 0).then(|| Self {
+            ticker: order.ticker.clone(),
+            action: order.action,
+            exposure: direction(order.side, order.action) * remaining,
+        })
+    }
+
+    fn from_update(data: &UserOrderData, action: Action) -> Option<Self> {
+        let remaining = contracts(&data.remaining_count_fp);
+        (data.status == OrderStatus::Resting && remaining > 0).then(|| Self {
+            ticker: data.ticker.clone(),
+            action,
+            exposure: direction(data.side, action) * remaining,
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+struct RiskState {
+    /// Signed position per market and subaccount, `None` for the primary
+    /// account.
+    positions: HashMap<(String, Option<i32>), i64>,
+    /// Resting orders by order ID.
+    open: HashMap<String, OpenOrder>,
+    /// Send times and markets of recent orders, oldest first.
+    sent: VecDeque<(Instant, String)>,
+    /// Event ticker per market.
+    events: HashMap<String, String>,
+}
+
+impl RiskState {
+    /// Signed position in a market across all subaccounts.
+    fn position(&self, ticker: &str) -> i64 {
+        self.positions
+            .iter()
+            .filter(|((market, _), _)| market == ticker)
+            .map(|(_, position)| position)
+            .sum()
+    }
+
+    fn record_order(&mut self, order: &Order) {
+        match OpenOrder::from_order(order) {
+            Some(open) => {
+                self.open.insert(order.order_id.clone(), open);
+            }
+            None => {
+                self.open.remove(&order.order_id);
+            }
+        }
+    }
+
+    /// Signed exposure of resting orders in a market, split into the part
+    /// adding YES and the part adding NO.
+    fn resting_exposure(&self, ticker: &str, excluding: Option<&str>) -> (i64, i64) {
+        self.open
+            .iter()
+            .filter(|(id, open)| open.ticker == ticker && Some(id.as_str()) != excluding)
+            .fold((0, 0), |(yes, no), (_, open)| {
+                if open.exposure > 0 {
+                    (yes + open.exposure, no)
+                } else {
+                    (yes, no + open.exposure)
+                }
+            })
+    }
+}
+
+/// Pre-trade checks applied by [`RiskGuard`] to one set of orders.
+struct Evaluation<'a> {
+    global: &'a RiskLimits,
+    markets: &'a HashMap<String, RiskLimits>,
+    orderbook: Option<&'a OrderbookAggregator>,
+    state: &'a RiskState,
+    now: Instant,
+    /// The order an amendment replaces.
+    replacing: Option<&'a str>,
+}
+
+impl Evaluation<'_> {
+    fn limits(&self, ticker: &str) -> impl Iterator<Item = &RiskLimits> {
+        std::iter::once(self.global).chain(self.markets.get(ticker))
+    }
+
+    /// Check `orders` as if they were all sent now.
+    fn check(&self, orders: &[CreateOrderRequest]) -> std::result::Result<(), RiskViolation> {
+        // Effects of earlier orders in the same batch
+        let mut added: HashMap<&str, i64> = HashMap::new();
+        let mut added_count: HashMap<&str, usize> = HashMap::new();
+
+        let window_start = self.now.checked_sub(RATE_WINDOW);
+        let recent: Vec<&str> = self
+            .state
+            .sent
+            .iter()
+            .filter(|(at, _)| window_start.is_none_or(|start| *at > start))
+            .map(|(_, ticker)| ticker.as_str())
+            .collect();
+
+        for (index, order) in orders.iter().enumerate() {
+            let ticker = order.ticker.as_str();
+            let size = order_size(order);
+            let effect = direction(order.side, order.action) * size;
+            let yes = yes_price(order);
+            let paid = match (order.side, yes) {
+                (_, None) => WORST_CASE_PRICE,
+                (Side::Yes, Some(yes)) => yes,
+                (Side::No, Some(yes)) => 100 - yes,
+            };
+
+            let position = self.state.position(ticker);
+            let (resting_yes, resting_no) = self.state.resting_exposure(ticker, self.replacing);
+            let batch = added.get(ticker).copied().unwrap_or(0);
+            let projected = if effect > 0 {
+                position + resting_yes + batch.max(0) + effect
+            } else {
+                position + resting_no + batch.min(0) + effect
+            };
+
+            let market_sent = recent.iter().filter(|t| **t == ticker).count()
+                + added_count.get(ticker).copied().unwrap_or(0)
+                + 1;
+            let total_sent = recent.len() + index + 1;
+            let market_open = self
+                .state
+                .open
+                .values()
+                .filter(|open| open.ticker == ticker)
+                .count()
+                + added_count.get(ticker).copied().unwrap_or(0)
+                + 1;
+            let total_open = self.state.open.len() + index + 1;
+
+            for (scope, limits) in self.limits(ticker).enumerate() {
+                let is_global = scope == 0;
+                exceeds(RiskRule::MaxOrderSize, ticker, limits.max_order_size, size)?;
+                exceeds(
+                    RiskRule::MaxOrderNotional,
+                    ticker,
+                    limits.max_order_notional,
+                    size * paid,
+                )?;
+                exceeds(
+                    RiskRule::MaxPosition,
+                    ticker,
+                    limits.max_position,
+                    projected.abs(),
+                )?;
+                if let Some(limit) = limits.max_event_position {
+                    exceeds(
+                        RiskRule::MaxEventPosition,
+                        ticker,
+                        Some(limit),
+                        self.event_position(ticker, projected, &added),
+                    )?;
+                }
+                if let Some(limit) = limits.price_collar {
+                    let mid = self.orderbook.and_then(|book| book.midpoint(ticker));
+                    match (yes, mid) {
+                        (Some(yes), Some(mid)) => exceeds(
+                            RiskRule::PriceCollar,
+                            ticker,
+                            Some(limit),
+                            (yes as f64 - mid).abs().ceil() as i64,
+                        )?,
+                        _ => {
+                            return Err(RiskViolation {
+                                rule: RiskRule::PriceCollar,
+                                ticker: ticker.to_string(),
+                                limit,
+                                actual: None,
+                            });
+                        }
+                    }
+                }
+                if self.replacing.is_none() {
+                    let open = if is_global { total_open } else { market_open };
+                    exceeds(
+                        RiskRule::MaxOpenOrders,
+                        ticker,
+                        limits.max_open_orders.map(|l| l as i64),
+                        open as i64,
+                    )?;
+                }
+                let sent = if is_global { total_sent } else { market_sent };
+                exceeds(
+                    RiskRule::MaxOrdersPerSecond,
+                    ticker,
+                    limits.max_orders_per_second.map(|l| l as i64),
+                    sent as i64,
+                )?;
+            }
+
+            *added.entry(ticker).or_default() += effect;
+            *added_count.entry(ticker).or_default() += 1;
+        }
+        Ok(())
+    }
+
+    /// Sum of absolute positions across the event of `ticker`, with that
+    /// market at `projected` and other markets including earlier orders in
+    /// the batch.
+    fn event_position(&self, ticker: &str, projected: i64, added: &HashMap<&str, i64>) -> i64 {
+        let Some(event) = self.state.events.get(ticker) else {
+            return projected.abs();
+        };
+        let mut markets: HashSet<&str> = self
+            .state
+            .positions
+            .keys()
+            .map(|(market, _)| market.as_str())
+            .chain(added.keys().copied())
+            .filter(|market| self.state.events.get(*market) == Some(event))
+            .collect();
+        markets.remove(ticker);
+        markets
+            .into_iter()
+            .map(|market| {
+                let position = self.state.position(market);
+                (position + added.get(market).copied().unwrap_or(0)).abs()
+            })
+            .sum::<i64>()
+            + projected.abs()
+    }
+}
+
+/// Checks orders against configurable limits before they are sent.
+///
+/// The guard wraps [`KalshiClient::create_order`],
+/// [`KalshiClient::amend_order`], [`KalshiClient::batch_create_orders`] and
+/// [`BatchManager::create_orders`]. Orders that break a limit are rejected
+/// locally with [`Error::RiskRejected`], naming the [`RiskRule`] that failed;
+/// a batch is rejected as a whole.
+///
+/// [`global`](Self::new) limits apply to every market, and
+/// [`market limits`](Self::with_market_limits) additionally to one market.
+///
+/// # State
+///
+/// Position and open-order limits need to know what is already held and
+/// resting. [`sync`](Self::sync) loads positions and resting orders from
+/// REST; after that, [`process_updates`](Self::process_updates) (or
+/// [`apply_update`](Self::apply_update)) keeps them current from the `fill`
+/// and `user_orders` channels, and orders sent through the guard are counted
+/// as soon as they are accepted. Orders sent concurrently are checked against
+/// the same state, so open-order and position limits can be overshot by the
+/// orders in flight; the per-second limit counts them as they are checked.
+///
+/// # Example
+///
+/// ```ignore
+/// use kalshi_trade_rs::orders::{RiskGuard, RiskLimits};
+///
+/// let guard = RiskGuard::new(
+///     client.clone(),
+///     RiskLimits {
+///         max_order_size: Some(100),
+///         max_position: Some(500),
+///         max_orders_per_second: Some(5),
+///         ..Default::default()
+///     },
+/// );
+/// guard.sync().await?;
+///
+/// match guard.create_order(request).await {
+///     Err(Error::RiskRejected(violation)) => eprintln!("blocked: {violation}"),
+///     result => println!("{:?}", result?.order.order_id),
+/// }
+/// ```
+#[derive(Clone)]
+pub struct RiskGuard {
+    client: KalshiClient,
+    global: RiskLimits,
+    markets: HashMap<String, RiskLimits>,
+    orderbook: Option<OrderbookAggregator>,
+    state: Arc<Mutex<RiskState>>,
+}
+
+impl RiskGuard {
+    /// Create a guard applying `limits` to every market.
+    pub fn new(client: KalshiClient, limits: RiskLimits) -> Self {
+        Self {
+            client,
+            global: limits,
+            markets: HashMap::new(),
+            orderbook: None,
+            state: Arc::new(Mutex::new(RiskState::default())),
+        }
+    }
+
+    /// Apply additional limits to one market.
+    #[must_use]
+    pub fn with_market_limits(mut self, ticker: impl Into<String>, limits: RiskLimits) -> Self {
+        self.markets.insert(ticker.into(), limits);
+        self
+    }
+
+    /// Use `orderbook` midpoints for [price collars](RiskLimits::price_collar).
+    #[must_use]
+    pub fn with_orderbook(mut self, orderbook: OrderbookAggregator) -> Self {
+        self.orderbook = Some(orderbook);
+        self
+    }
+
+    /// Current signed position in a market across all subaccounts: positive
+    /// holds YES, negative NO.
+    pub fn position(&self, ticker: &str) -> i64 {
+        self.lock().position(ticker)
+    }
+
+    /// Number of resting orders counted against open-order limits.
+    pub fn open_order_count(&self) -> usize {
+        self.lock().open.len()
+    }
+
+    // =========================================================================
+    // Guarded submission
+    // =========================================================================
+
+    /// Check and create an order.
+    pub async fn create_order(&self, request: CreateOrderRequest) -> Result<OrderResponse> {
+        self.admit(std::slice::from_ref(&request), None).await?;
+        let response = self.client.create_order(request).await?;
+        self.lock().record_order(&response.order);
+        Ok(response)
+    }
+
+    /// Check and amend an order.
+    ///
+    /// The amended order is checked as a replacement for the original: its
+    /// resting size no longer counts toward position limits, and it does not
+    /// add to the open-order count.
+    pub async fn amend_order(
+        &self,
+        order_id: &str,
+        request: AmendOrderRequest,
+    ) -> Result<AmendOrderResponse> {
+        let count = request
+            .count
+            .max(request.count_fp.as_deref().map(contracts))
+            .or_else(|| {
+                self.lock()
+                    .open
+                    .get(order_id)
+                    .map(|open| open.exposure.abs())
+            });
+        let mut proposed = CreateOrderRequest::new(
+            request.ticker.clone(),
+            request.side,
+            request.action,
+            count.unwrap_or(0),
+        );
+        proposed.yes_price = request.yes_price;
+        proposed.no_price = request.no_price;
+        proposed.yes_price_dollars = request.yes_price_dollars.clone();
+        proposed.no_price_dollars = request.no_price_dollars.clone();
+        self.admit(std::slice::from_ref(&proposed), Some(order_id))
+            .await?;
+
+        let response = self.client.amend_order(order_id, request).await?;
+        let mut state = self.lock();
+        state.record_order(&response.old_order);
+        state.record_order(&response.order);
+        Ok(response)
+    }
+
+    /// Check every order in a batch, then create them.
+    pub async fn batch_create_orders(
+        &self,
+        request: BatchCreateOrdersRequest,
+    ) -> Result<BatchCreateOrdersResponse> {
+        self.admit(&request.orders, None).await?;
+        let response = self.client.batch_create_orders(request).await?;
+        let mut state = self.lock();
+        for order in response.orders.iter().filter_map(|r| r.order.as_ref()) {
+            state.record_order(order);
+        }
+        Ok(response)
+    }
+
+    /// Check every order, then create them through `manager`.
+    ///
+    /// If any order fails a check, nothing is sent and the violation is
+    /// returned as the result's error.
+    pub async fn create_orders(
+        &self,
+        manager: &BatchManager,
+        orders: Vec<CreateOrderRequest>,
+    ) -> BatchOperationResult<AggregatedCreateResponse> {
+        if let Err(e) = self.admit(&orders, None).await {
+            return BatchOperationResult {
+                completed: AggregatedCreateResponse { orders: vec![] },
+                error: Some(e),
+            };
+        }
+        let result = manager.create_orders(orders).await;
+        let mut state = self.lock();
+        for order in result.completed.successful_orders() {
+            state.record_order(order);
+        }
+        result
+    }
+
+    /// Check orders without sending them.
+    ///
+    /// Passing orders are not counted toward the per-second limit.
+    pub async fn check_orders(&self, orders: &[CreateOrderRequest]) -> Result<()> {
+        self.resolve_events(orders).await;
+        let state = self.lock();
+        self.evaluation(&state, Instant::now(), None)
+            .check(orders)
+            .map_err(Error::RiskRejected)
+    }
+
+    /// Check orders and, if they pass, count them as sent.
+    async fn admit(&self, orders: &[CreateOrderRequest], replacing: Option<&str>) -> Result<()> {
+        self.resolve_events(orders).await;
+        let now = Instant::now();
+        let mut state = self.lock();
+        self.evaluation(&state, now, replacing)
+            .check(orders)
+            .map_err(Error::RiskRejected)?;
+
+        while state
+            .sent
+            .front()
+            .is_some_and(|(at, _)| now.duration_since(*at) >= RATE_WINDOW)
+        {
+            state.sent.pop_front();
+        }
+        state
+            .sent
+            .extend(orders.iter().map(|order| (now, order.ticker.clone())));
+        Ok(())
+    }
+
+    fn evaluation<'a>(
+        &'a self,
+        state: &'a RiskState,
+        now: Instant,
+        replacing: Option<&'a str>,
+    ) -> Evaluation<'a> {
+        Evaluation {
+            global: &self.global,
+            markets: &self.markets,
+            orderbook: self.orderbook.as_ref(),
+            state,
+            now,
+            replacing,
+        }
+    }
+
+    /// Look up the events of markets involved in an event position check.
+    ///
+    /// Markets whose lookup fails are treated as the only market in their
+    /// event until a later lookup succeeds.
+    async fn resolve_events(&self, orders: &[CreateOrderRequest]) {
+        let checks_events = self.global.max_event_position.is_some()
+            || self
+                .markets
+                .values()
+                .any(|limits| limits.max_event_position.is_some());
+        if !checks_events {
+            return;
+        }
+
+        let unresolved: Vec<String> = {
+            let state = self.lock();
+            let tickers: HashSet<&str> = orders
+                .iter()
+                .map(|order| order.ticker.as_str())
+                .chain(state.positions.keys().map(|(ticker, _)| ticker.as_str()))
+                .collect();
+            tickers
+                .into_iter()
+                .filter(|ticker| !state.events.contains_key(*ticker))
+                .map(str::to_string)
+                .collect()
+        };
+        for ticker in unresolved {
+            match self.client.get_market(&ticker).await {
+                Ok(response) => {
+                    self.lock()
+                        .events
+                        .insert(ticker, response.market.event_ticker);
+                }
+                Err(e) => warn!(ticker = %ticker, error = %e, "could not resolve market event"),
+            }
+        }
+    }
+
+    // =========================================================================
+    // State
+    // =========================================================================
+
+    /// Load positions and resting orders from REST, replacing what is tracked.
+    ///
+    /// Positions are loaded for the primary account; subaccount positions
+    /// learned from fills are kept.
+    pub async fn sync(&self) -> Result<()> {
+        let mut positions = HashMap::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let mut params = GetPositionsParams::new().limit(POSITIONS_PAGE_SIZE);
+            if let Some(cursor) = cursor.take() {
+                params = params.cursor(cursor);
+            }
+            let response = self.client.get_positions_with_params(params).await?;
+            for position in &response.market_positions {
+                let held = contracts(&position.position_fp);
+                if held != 0 {
+                    positions.insert((position.ticker.clone(), None), held);
+                }
+            }
+            match response.cursor {
+                Some(next) if !next.is_empty() && !response.market_positions.is_empty() => {
+                    cursor = Some(next);
+                }
+                _ => break,
+            }
+        }
+
+        let mut open = HashMap::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let mut params = GetOrdersParams::new()
+                .status(OrderStatus::Resting)
+                .limit(ORDERS_PAGE_SIZE);
+            if let Some(cursor) = cursor.take() {
+                params = params.cursor(cursor);
+            }
+            let response = self.client.get_orders_with_params(params).await?;
+            for order in &response.orders {
+                if let Some(resting) = OpenOrder::from_order(order) {
+                    open.insert(order.order_id.clone(), resting);
+                }
+            }
+            if response.cursor.is_empty() || response.orders.is_empty() {
+                break;
+            }
+            cursor = Some(response.cursor);
+        }
+
+        let mut state = self.lock();
+        state
+            .positions
+            .retain(|(_, subaccount), _| subaccount.is_some());
+        state.positions.extend(positions);
+        state.open = open;
+        Ok(())
+    }
+
+    /// Apply a single stream update.
+    ///
+    /// `fill` messages set the position of the market and subaccount they
+    /// fill in; `user_orders` messages update resting orders. An update for
+    /// a resting order whose action is neither in the message nor already
+    /// tracked is skipped. Other messages are ignored.
+    pub fn apply_update(&self, update: &StreamUpdate) {
+        match &update.msg {
+            StreamMessage::Fill(fill) => {
+                let subaccount = subaccount_key(fill.subaccount);
+                self.lock().positions.insert(
+                    (fill.market_ticker.clone(), subaccount),
+                    contracts(&fill.post_position_fp),
+                );
+            }
+            StreamMessage::UserOrder(data) => {
+                let mut state = self.lock();
+                let known = state.open.get(&data.order_id).map(|open| open.action);
+                // Without an action the exposure is unknown; an order not
+                // tracked as resting has nothing to remove either
+                let Some(action) = data.action.or(known) else {
+                    debug!(order_id = %data.order_id, "skipping order update without an action");
+                    return;
+                };
+                match OpenOrder::from_update(data, action) {
+                    Some(open) => {
+                        state.open.insert(data.order_id.clone(), open);
+                    }
+                    None => {
+                        state.open.remove(&data.order_id);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Process updates from a WebSocket stream until it closes.
+    ///
+    /// The handle should be subscribed to [`Channel::Fill`] and
+    /// [`Channel::UserOrders`]. If the receiver lags, state is reloaded with
+    /// [`sync`](Self::sync).
+    ///
+    /// [`Channel::Fill`]: crate::ws::Channel::Fill
+    /// [`Channel::UserOrders`]: crate::ws::Channel::UserOrders
+    pub async fn process_updates(&self, mut handle: KalshiStreamHandle) {
+        loop {
+            match handle.update_receiver.recv().await {
+                Ok(update) => match &update.msg {
+                    StreamMessage::Closed { .. } | StreamMessage::ConnectionLost { .. } => break,
+                    _ => self.apply_update(&update),
+                },
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => {
+                    if let Err(e) = self.sync().await {
+                        warn!("risk state sync failed: {}", e);
+                    }
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, RiskState> {
+        self.state.lock().expect("risk state lock poisoned")
+    }
+}
+
+impl fmt::Debug for RiskGuard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RiskGuard")
+            .field("global", &self.global)
+            .field("markets", &self.markets)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    use crate::client::mock_http::MockHttpServer;
+    use crate::test_fixtures as fixtures;
+
+    fn buy_yes(ticker: &str, count: i64, price: i64) -> CreateOrderRequest {
+        CreateOrderRequest::new(ticker, Side::Yes, Action::Buy, count).yes_price(price)
+    }
+
+    fn check(
+        global: RiskLimits,
+        state: &RiskState,
+        orders: &[CreateOrderRequest],
+    ) -> std::result::Result<(), RiskViolation> {
+        Evaluation {
+            global: &global,
+            markets: &HashMap::new(),
+            orderbook: None,
+            state,
+            now: Instant::now(),
+            replacing: None,
+        }
+        .check(orders)
+    }
+
+    #[test]
+    fn test_order_size_and_notional() {
+        let limits = RiskLimits {
+            max_order_size: Some(100),
+            max_order_notional: Some(2_000),
+            ..Default::default()
+        };
+        let state = RiskState::default();
+
+        assert!(check(limits.clone(), &state, &[buy_yes("A", 40, 45)]).is_ok());
+        let err = check(limits.clone(), &state, &[buy_yes("A", 101, 1)]).unwrap_err();
+        assert_eq!(err.rule, RiskRule::MaxOrderSize);
+        assert_eq!(err.actual, Some(101));
+
+        // 50 NO contracts at YES 45 cost 55¢ each
+        let no = CreateOrderRequest::new("A", Side::No, Action::Buy, 50).yes_price(45);
+        let err = check(limits, &state, &[no]).unwrap_err();
+        assert_eq!(err.rule, RiskRule::MaxOrderNotional);
+        assert_eq!(err.actual, Some(2_750));
+        assert_eq!(
+            err.to_string(),
+            "max order notional on A: 2750 exceeds limit 2000"
+        );
+    }
+
+    #[test]
+    fn test_count_fp_checked() {
+        let limits = RiskLimits {
+            max_order_size: Some(100),
+            max_order_notional: Some(2_000),
+            ..Default::default()
+        };
+        let state = RiskState::default();
+
+        let order = buy_yes("A", 1, 1).count_fp("10000.00");
+        let err = check(limits.clone(), &state, &[order]).unwrap_err();
+        assert_eq!(err.rule, RiskRule::MaxOrderSize);
+        assert_eq!(err.actual, Some(10_000));
+
+        let order = buy_yes("A", 1, 45).count_fp("50.00");
+        let err = check(limits, &state, &[order]).unwrap_err();
+        assert_eq!(err.rule, RiskRule::MaxOrderNotional);
+        assert_eq!(err.actual, Some(2_250));
+    }
+
+    #[tokio::test]
+    async fn test_amend_count_fp_checked() {
+        let server = MockHttpServer::start().await;
+        let limits = RiskLimits {
+            max_order_size: Some(100),
+            ..Default::default()
+        };
+        let guard = RiskGuard::new(server.client(), limits);
+
+        let request = AmendOrderRequest::new("A", Side::Yes, Action::Buy)
+            .yes_price(45)
+            .count(1)
+            .count_fp("10000.00");
+        let err = guard.amend_order("order-1", request).await.unwrap_err();
+        assert!(matches!(err, Error::RiskRejected(v) if v.rule == RiskRule::MaxOrderSize));
+        assert!(
+            server
+                .requests_to("POST", "/portfolio/orders/order-1/amend")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_position_counts_resting_and_batch() {
+        let limits = RiskLimits {
+            max_position: Some(100),
+            ..Default::default()
+        };
+        let mut state = RiskState::default();
+        state.positions.insert(("A".to_string(), None), 50);
+        state.open.insert(
+            "resting".to_string(),
+            OpenOrder {
+                ticker: "A".to_string(),
+                action: Action::Buy,
+                exposure: 30,
+            },
+        );
+
+        assert!(check(limits.clone(), &state, &[buy_yes("A", 20, 45)]).is_ok());
+        let err = check(limits.clone(), &state, &[buy_yes("A", 21, 45)]).unwrap_err();
+        assert_eq!(err.rule, RiskRule::MaxPosition);
+        assert_eq!(err.actual, Some(101));
+
+        // Orders earlier in a batch count too
+        let batch = [buy_yes("A", 10, 45), buy_yes("A", 11, 45)];
+        assert!(check(limits.clone(), &state, &batch).is_err());
+
+        // Selling reduces the position
+        let sell = CreateOrderRequest::new("A", Side::Yes, Action::Sell, 120).yes_price(45);
+        assert!(check(limits, &state, &[sell]).is_ok());
+    }
+
+    #[test]
+    fn test_event_position() {
+        let limits = RiskLimits {
+            max_event_position: Some(100),
+            ..Default::default()
+        };
+        let mut state = RiskState::default();
+        state.positions.insert(("EV-A".to_string(), None), 60);
+        state.positions.insert(("OTHER".to_string(), None), 500);
+        for market in ["EV-A", "EV-B"] {
+            state.events.insert(market.to_string(), "EV".to_string());
+        }
+        state
+            .events
+            .insert("OTHER".to_string(), "OTHER".to_string());
+
+        let no =
+            |count| CreateOrderRequest::new("EV-B", Side::No, Action::Buy, count).yes_price(45);
+        assert!(check(limits.clone(), &state, &[no(40)]).is_ok());
+        let err = check(limits, &state, &[no(41)]).unwrap_err();
+        assert_eq!(err.rule, RiskRule::MaxEventPosition);
+        assert_eq!(err.actual, Some(101));
+    }
+
+    #[test]
+    fn test_open_orders_and_rate() {
+        let mut state = RiskState::default();
+        state.open.insert(
+            "resting".to_string(),
+            OpenOrder {
+                ticker: "A".to_string(),
+                action: Action::Buy,
+                exposure: 1,
+            },
+        );
+        let open_limit = RiskLimits {
+            max_open_orders: Some(2),
+            ..Default::default()
+        };
+        assert!(check(open_limit.clone(), &state, &[buy_yes("B", 1, 45)]).is_ok());
+        let err = check(
+            open_limit,
+            &state,
+            &[buy_yes("B", 1, 45), buy_yes("B", 1, 45)],
+        )
+        .unwrap_err();
+        assert_eq!(err.rule, RiskRule::MaxOpenOrders);
+
+        let now = Instant::now();
+        state.sent.push_back((now, "A".to_string()));
+        state.sent.push_back((now, "A".to_string()));
+        let rate_limit = RiskLimits {
+            max_orders_per_second: Some(3),
+            ..Default::default()
+        };
+        assert!(check(rate_limit.clone(), &state, &[buy_yes("A", 1, 45)]).is_ok());
+        let err = check(
+            rate_limit,
+            &state,
+            &[buy_yes("A", 1, 45), buy_yes("A", 1, 45)],
+        )
+        .unwrap_err();
+        assert_eq!(err.rule, RiskRule::MaxOrdersPerSecond);
+        assert_eq!(err.actual, Some(4));
+    }
+
+    #[test]
+    fn test_market_limits_and_collar_without_book() {
+        let markets = HashMap::from([(
+            "A".to_string(),
+            RiskLimits {
+                max_order_size: Some(5),
+                ..Default::default()
+            },
+        )]);
+        let global = RiskLimits {
+            price_collar: Some(10),
+            ..Default::default()
+        };
+        let state = RiskState::default();
+        let evaluation = Evaluation {
+            global: &RiskLimits::default(),
+            markets: &markets,
+            orderbook: None,
+            state: &state,
+            now: Instant::now(),
+            replacing: None,
+        };
+        assert!(evaluation.check(&[buy_yes("B", 10, 45)]).is_ok());
+        assert_eq!(
+            evaluation.check(&[buy_yes("A", 10, 45)]).unwrap_err().rule,
+            RiskRule::MaxOrderSize
+        );
+
+        let err = check(global, &state, &[buy_yes("A", 1, 45)]).unwrap_err();
+        assert_eq!(err.rule, RiskRule::PriceCollar);
+        assert_eq!(err.actual, None);
+    }
+    fn stream(msg: StreamMessage) -> StreamUpdate {
+        StreamUpdate {
+            channel: String::new(),
+            sid: 1,
+            seq: None,
+            msg,
+        }
+    }
+
+    fn user_order(order_id: &str, status: &str, action: Option<&str>) -> StreamMessage {
+        StreamMessage::UserOrder(Box::new(fixtures::user_order(json!({
+            "order_id": order_id,
+            "ticker": "A",
+            "status": status,
+            "action": action,
+        }))))
+    }
+
+    fn fill(post_position: &str, subaccount: Option<i32>) -> StreamMessage {
+        StreamMessage::Fill(fixtures::fill(json!({
+            "trade_id": "trade",
+            "order_id": "order",
+            "market_ticker": "A",
+            "post_position_fp": post_position,
+            "subaccount": subaccount,
+        })))
+    }
+
+    fn guard() -> RiskGuard {
+        let client = KalshiClient::new(crate::ws::mock_server::test_config()).unwrap();
+        RiskGuard::new(client, RiskLimits::default())
+    }
+
+    #[test]
+    fn test_positions_by_subaccount() {
+        let guard = guard();
+        guard.apply_update(&stream(fill("30.00", None)));
+        guard.apply_update(&stream(fill("-10.00", Some(2))));
+        // Subaccount 0 is the primary account
+        guard.apply_update(&stream(fill("40.00", Some(0))));
+        assert_eq!(guard.position("A"), 30);
+        assert_eq!(guard.lock().positions.len(), 2);
+    }
+
+    #[test]
+    fn test_update_without_action() {
+        let guard = guard();
+        // Unknown action: exposure cannot be signed, so nothing is tracked
+        guard.apply_update(&stream(user_order("a", "resting", None)));
+        assert_eq!(guard.open_order_count(), 0);
+
+        guard.apply_update(&stream(user_order("a", "resting", Some("sell"))));
+        guard.apply_update(&stream(user_order("a", "resting", None)));
+        let open = guard.lock().open["a"].clone();
+        assert_eq!(open.action, Action::Sell);
+        assert_eq!(open.exposure, -10);
+
+        guard.apply_update(&stream(user_order("a", "canceled", None)));
+        assert_eq!(guard.open_order_count(), 0);
+    }
+}
@@ -0,0 +1,1169 @@
+//! Real-time positions and profit and loss.
+//!
+//! [`PortfolioTracker`] keeps positions current from the `fill` and
+//! `market_positions` WebSocket channels, marks them to market from the
+//! `ticker` channel or an [`OrderbookAggregator`], and splits profit and
+//! loss into realized, unrealized and fees. Totals are available per
+//! market, event, subaccount and strategy.
+//!
+//! # Accounting
+//!
+//! Positions are signed in YES contracts: positive holds YES, negative
+//! holds NO. A NO contract bought at `n` cents is treated as a YES contract
+//! sold at `100 - n`, so average prices and marks are YES prices in cents
+//! and profit and loss comes out the same from either side.
+//!
+//! Reducing a position realizes `(exit - average) * contracts` against the
+//! average price; adding to it moves the average. Fees are tracked
+//! separately from the `fee_cost` of each fill.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use kalshi_trade_rs::{
+//!     KalshiClient, KalshiConfig,
+//!     portfolio::{PortfolioTracker, PortfolioTrackerConfig},
+//!     ws::{Channel, KalshiStreamClient},
+//! };
+//!
+//! # async fn example() -> kalshi_trade_rs::Result<()> {
+//! let config = KalshiConfig::from_env()?;
+//! let client = KalshiClient::new(config.clone())?;
+//! let tracker = PortfolioTracker::new(client, PortfolioTrackerConfig::default());
+//! tracker.sync().await?;
+//!
+//! let stream = KalshiStreamClient::connect(&config).await?;
+//! let mut handle = stream.handle();
+//! handle.subscribe(Channel::Fill, &[]).await?;
+//! handle.subscribe(Channel::MarketPositions, &[]).await?;
+//! handle.subscribe(Channel::Ticker, &["KXBTC-25JAN"]).await?;
+//!
+//! let background = tracker.clone();
+//! tokio::spawn(async move { background.run(handle).await });
+//!
+//! let total = tracker.total();
+//! println!("net PnL: {:.0}c", total.net());
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::DateTime;
+use tokio::sync::broadcast;
+use tokio::time::{Instant, MissedTickBehavior};
+use tracing::{debug, warn};
+
+use crate::client::KalshiClient;
+use crate::error::Result;
+use crate::models::{Action, GetPositionsParams, MarketPosition, Side};
+use crate::orderbook::{OrderbookAggregator, OrderbookUpdate};
+use crate::orders::ClientOrderIdGenerator;
+use crate::units::{cents, contracts, direction, subaccount_key};
+use crate::ws::{FillData, KalshiStreamHandle, MarketPositionData, StreamMessage, TickerData};
+
+/// Default capacity of the event broadcast channel.
+const DEFAULT_EVENT_CAPACITY: usize = 1024;
+
+/// Largest page `get_positions` returns.
+const POSITIONS_PAGE_SIZE: i64 = 1000;
+
+/// A fill's YES price and fee in cents.
+fn fill_prices(fill: &FillData) -> Option<(f64, f64)> {
+    let yes_price = cents(&fill.yes_price_dollars)?;
+    Some((yes_price, cents(&fill.fee_cost).unwrap_or(0.0)))
+}
+
+/// Cash a fill adds to the balance, in cents.
+fn cash_flow(fill: &FillData, yes_price: f64, fee: f64) -> i64 {
+    let side_price = match fill.side {
+        Side::Yes => yes_price,
+        Side::No => 100.0 - yes_price,
+    };
+    let notional = side_price * contracts(&fill.count_fp) as f64;
+    let flow = match fill.action {
+        Action::Buy => -notional - fee,
+        Action::Sell => notional - fee,
+    };
+    flow.round() as i64
+}
+
+/// Configuration for [`PortfolioTracker`].
+#[derive(Debug, Clone)]
+pub struct PortfolioTrackerConfig {
+    /// Subaccounts whose positions are loaded and reconciled.
+    ///
+    /// Default: empty, meaning the primary account only.
+    pub subaccounts: Vec<i32>,
+
+    /// Time between reconciliations in [`PortfolioTracker::run`].
+    ///
+    /// Default: 60 seconds.
+    pub reconcile_interval: Duration,
+}
+
+impl Default for PortfolioTrackerConfig {
+    fn default() -> Self {
+        Self {
+            subaccounts: Vec::new(),
+            reconcile_interval: Duration::from_secs(60),
+        }
+    }
+}
+
+/// A signed position with its average price and profit and loss.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Lot {
+    position: i64,
+    average: f64,
+    realized: f64,
+    fees: f64,
+    /// Unix time the server last updated the lot, if it said.
+    updated: Option<i64>,
+}
+
+impl Lot {
+    /// A lot as reported by the server. `cost_cents` is the cost of the
+    /// contracts held, on the side held.
+    fn from_server(position: i64, cost_cents: f64, realized: f64, fees: f64) -> Self {
+        let average = match position {
+            0 => 0.0,
+            p if p > 0 => cost_cents / p as f64,
+            p => 100.0 - cost_cents / -p as f64,
+        };
+        Self {
+            position,
+            average,
+            realized,
+            fees,
+            updated: None,
+        }
+    }
+
+    fn from_market_position(position: &MarketPosition) -> Self {
+        Self {
+            updated: position
+                .last_updated_ts
+                .as_deref()
+                .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+                .map(|ts| ts.timestamp()),
+            ..Self::from_server(
+                contracts(&position.position_fp),
+                cents(&position.market_exposure_dollars).unwrap_or(0.0),
+                cents(&position.realized_pnl_dollars).unwrap_or(0.0),
+                cents(&position.fees_paid_dollars).unwrap_or(0.0),
+            )
+        }
+    }
+
+    fn from_position_data(data: &MarketPositionData) -> Self {
+        Self::from_server(
+            contracts(&data.position_fp),
+            cents(&data.position_cost_dollars).unwrap_or(0.0),
+            cents(&data.realized_pnl_dollars).unwrap_or(0.0),
+            cents(&data.fees_paid_dollars).unwrap_or(0.0),
+        )
+    }
+
+    /// Whether the server's figures already include `fill`.
+    fn includes(&self, fill: &FillData) -> bool {
+        self.updated.is_some_and(|updated| fill.ts <= updated)
+    }
+
+    /// Apply `delta` YES contracts traded at `yes_price` cents.
+    fn apply(&mut self, delta: i64, yes_price: f64, fee: f64) {
+        self.fees += fee;
+        if delta == 0 {
+            return;
+        }
+        let old = self.position;
+        if old == 0 || old.signum() == delta.signum() {
+            let held = old.abs() as f64;
+            let added = delta.abs() as f64;
+            self.average = (self.average * held + yes_price * added) / (held + added);
+        } else {
+            let closed = delta.abs().min(old.abs());
+            self.realized += (yes_price - self.average) * closed as f64 * old.signum() as f64;
+            if delta.abs() > old.abs() {
+                self.average = yes_price;
+            }
+        }
+        self.position = old + delta;
+        if self.position == 0 {
+            self.average = 0.0;
+        }
+    }
+
+    fn unrealized(&self, mark: Option<f64>) -> f64 {
+        match mark {
+            Some(mark) if self.position != 0 => (mark - self.average) * self.position as f64,
+            _ => 0.0,
+        }
+    }
+}
+
+/// One position as seen by a [`PortfolioTracker`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PositionSnapshot {
+    /// Market ticker.
+    pub ticker: String,
+    /// Event the market belongs to, once resolved.
+    pub event_ticker: Option<String>,
+    /// Subaccount number, `None` for the primary account.
+    pub subaccount: Option<i32>,
+    /// Strategy the position is attributed to, for per-strategy snapshots.
+    pub strategy: Option<String>,
+    /// Contracts held: positive for YES, negative for NO.
+    pub position: i64,
+    /// Average entry price of the open position, in YES cents.
+    pub average_price: f64,
+    /// Latest mark in YES cents, if the market has been priced.
+    pub mark: Option<f64>,
+    /// Realized profit and loss in cents, before fees.
+    pub realized_pnl: f64,
+    /// Unrealized profit and loss at the mark, in cents.
+    pub unrealized_pnl: f64,
+    /// Fees paid in cents.
+    pub fees: f64,
+}
+
+impl PositionSnapshot {
+    /// Realized plus unrealized, less fees, in cents.
+    pub fn net_pnl(&self) -> f64 {
+        self.realized_pnl + self.unrealized_pnl - self.fees
+    }
+}
+
+/// Profit and loss summed over a group of positions, in cents.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PnlSummary {
+    /// Realized profit and loss, before fees.
+    pub realized: f64,
+    /// Unrealized profit and loss at the latest marks.
+    pub unrealized: f64,
+    /// Fees paid.
+    pub fees: f64,
+    /// Positions with contracts held.
+    pub open_positions: usize,
+}
+
+impl PnlSummary {
+    /// Realized plus unrealized, less fees.
+    pub fn net(&self) -> f64 {
+        self.realized + self.unrealized - self.fees
+    }
+
+    fn add(&mut self, snapshot: &PositionSnapshot) {
+        self.realized += snapshot.realized_pnl;
+        self.unrealized += snapshot.unrealized_pnl;
+        self.fees += snapshot.fees;
+        self.open_positions += usize::from(snapshot.position != 0);
+    }
+}
+
+/// A position the server reports differently from the tracker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PositionDiscrepancy {
+    /// Market ticker.
+    pub ticker: String,
+    /// Subaccount number, `None` for the primary account.
+    pub subaccount: Option<i32>,
+    /// Contracts the tracker held.
+    pub local_position: i64,
+    /// Contracts the server reports.
+    pub server_position: i64,
+}
+
+/// Changes published by a [`PortfolioTracker`].
+#[derive(Debug, Clone)]
+pub enum PortfolioEvent {
+    /// A fill or server update changed a position.
+    Position(PositionSnapshot),
+    /// Reconciliation found a position that disagreed with the server. The
+    /// server's position has been adopted.
+    Discrepancy(PositionDiscrepancy),
+    /// A reconciliation finished.
+    Reconciled {
+        /// Positions that disagreed.
+        discrepancies: usize,
+    },
+}
+
+type PositionKey = (String, Option<i32>);
+type StrategyKey = (String, String, Option<i32>);
+
+/// Positions per subaccount and the balance, as fetched over REST.
+#[derive(Debug)]
+struct Fetched {
+    positions: Vec<(Option<i32>, HashMap<String, Lot>)>,
+    balance: i64,
+    /// Fill sequence number when the balance was requested.
+    balance_since: u64,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    positions: HashMap<PositionKey, Lot>,
+    strategies: HashMap<StrategyKey, Lot>,
+    marks: HashMap<String, f64>,
+    events: HashMap<String, String>,
+    /// Trade IDs applied, with the sequence number of each.
+    seen_trades: HashMap<String, u64>,
+    /// Sequence number of the last fill applied.
+    fill_seq: u64,
+    /// Loads fetching from REST.
+    loading: usize,
+    /// Fills applied while a load was fetching, replayed on its snapshot.
+    journal: Vec<(u64, FillData)>,
+    cash: Option<i64>,
+}
+
+impl State {
+    fn snapshot(
+        &self,
+        ticker: &str,
+        subaccount: Option<i32>,
+        strategy: Option<&str>,
+        lot: &Lot,
+    ) -> PositionSnapshot {
+        let mark = self.marks.get(ticker).copied();
+        PositionSnapshot {
+            ticker: ticker.to_string(),
+            event_ticker: self.events.get(ticker).cloned(),
+            subaccount,
+            strategy: strategy.map(str::to_string),
+            position: lot.position,
+            average_price: lot.average,
+            mark,
+            realized_pnl: lot.realized,
+            unrealized_pnl: lot.unrealized(mark),
+            fees: lot.fees,
+        }
+    }
+
+    fn positions(&self) -> Vec<PositionSnapshot> {
+        self.positions
+            .iter()
+            .map(|((ticker, subaccount), lot)| self.snapshot(ticker, *subaccount, None, lot))
+            .collect()
+    }
+
+    /// Apply a fill, returning the updated position.
+    fn apply_fill(&mut self, fill: &FillData) -> Option<PositionSnapshot> {
+        if self.seen_trades.contains_key(&fill.trade_id) {
+            return None;
+        }
+        self.fill_seq += 1;
+        self.seen_trades
+            .insert(fill.trade_id.clone(), self.fill_seq);
+        if self.loading > 0 {
+            self.journal.push((self.fill_seq, fill.clone()));
+        }
+
+        let (yes_price, fee) = fill_prices(fill)?;
+        let delta = direction(fill.side, fill.action) * contracts(&fill.count_fp);
+        let subaccount = subaccount_key(fill.subaccount);
+
+        if let Some(cash) = &mut self.cash {
+            *cash += cash_flow(fill, yes_price, fee);
+        }
+
+        if let Some(strategy) = fill
+            .client_order_id
+            .as_deref()
+            .and_then(ClientOrderIdGenerator::prefix_of)
+        {
+            self.strategies
+                .entry((strategy.to_string(), fill.market_ticker.clone(), subaccount))
+                .or_default()
+                .apply(delta, yes_price, fee);
+        }
+
+        let key = (fill.market_ticker.clone(), subaccount);
+        let lot = self.positions.entry(key).or_default();
+        lot.apply(delta, yes_price, fee);
+        let lot = lot.clone();
+        Some(self.snapshot(&fill.market_ticker, subaccount, None, &lot))
+    }
+
+    fn apply_position(&mut self, data: &MarketPositionData) -> PositionSnapshot {
+        let subaccount = subaccount_key(data.subaccount);
+        let lot = Lot::from_position_data(data);
+        self.positions
+            .insert((data.market_ticker.clone(), subaccount), lot.clone());
+        self.snapshot(&data.market_ticker, subaccount, None, &lot)
+    }
+
+    fn mark_ticker(&mut self, data: &TickerData) {
+        let bid = cents(&data.yes_bid_dollars).filter(|p| *p > 0.0);
+        let ask = cents(&data.yes_ask_dollars).filter(|p| *p > 0.0);
+        let mark = match (bid, ask) {
+            (Some(bid), Some(ask)) => Some((bid + ask) / 2.0),
+            _ => cents(&data.price_dollars).filter(|p| *p > 0.0),
+        };
+        if let Some(mark) = mark {
+            self.marks.insert(data.market_ticker.clone(), mark);
+        }
+    }
+
+    /// Adopt positions and cash fetched by a load that started at fill
+    /// `since`, returning the positions that differed.
+    ///
+    /// Fills applied while the load was fetching are replayed on top of the
+    /// server's figures unless a position was updated at or after the fill,
+    /// and trades the server's positions already cover are
+    /// forgotten.
+    fn adopt(&mut self, since: u64, fetched: Fetched) -> Vec<PositionDiscrepancy> {
+        let replay: Vec<FillData> = self
+            .journal
+            .iter()
+            .filter(|(seq, _)| *seq > since)
+            .map(|(_, fill)| fill.clone())
+            .collect();
+        let unbalanced: i64 = self
+            .journal
+            .iter()
+            .filter(|(seq, _)| *seq > fetched.balance_since)
+            .filter_map(|(_, fill)| {
+                let (yes_price, fee) = fill_prices(fill)?;
+                Some(cash_flow(fill, yes_price, fee))
+            })
+            .sum();
+        self.cash = Some(fetched.balance + unbalanced);
+
+        let discrepancies = fetched
+            .positions
+            .into_iter()
+            .flat_map(|(subaccount, server)| self.replace_positions(subaccount, server, &replay))
+            .collect();
+        self.seen_trades.retain(|_, seq| *seq > since);
+        discrepancies
+    }
+
+    /// Replace one subaccount's positions with the server's plus `replay`,
+    /// returning those that differed.
+    fn replace_positions(
+        &mut self,
+        subaccount: Option<i32>,
+        mut server: HashMap<String, Lot>,
+        replay: &[FillData],
+    ) -> Vec<PositionDiscrepancy> {
+        for fill in replay
+            .iter()
+            .filter(|fill| subaccount_key(fill.subaccount) == subaccount)
+        {
+            let Some((yes_price, fee)) = fill_prices(fill) else {
+                continue;
+            };
+            let lot = server.entry(fill.market_ticker.clone()).or_default();
+            // The snapshot may have been taken after the fill
+            if !lot.includes(fill) {
+                let delta = direction(fill.side, fill.action) * contracts(&fill.count_fp);
+                lot.apply(delta, yes_price, fee);
+            }
+        }
+
+        let mut discrepancies = Vec::new();
+        for ((ticker, sub), lot) in &mut self.positions {
+            if *sub != subaccount || server.contains_key(ticker) {
+                continue;
+            }
+            if lot.position != 0 {
+                discrepancies.push(PositionDiscrepancy {
+                    ticker: ticker.clone(),
+                    subaccount,
+                    local_position: lot.position,
+                    server_position: 0,
+                });
+                lot.position = 0;
+                lot.average = 0.0;
+            }
+        }
+        for (ticker, lot) in server {
+            let local = self
+                .positions
+                .get(&(ticker.clone(), subaccount))
+                .map_or(0, |local| local.position);
+            if local != lot.position {
+                discrepancies.push(PositionDiscrepancy {
+                    ticker: ticker.clone(),
+                    subaccount,
+                    local_position: local,
+                    server_position: lot.position,
+                });
+            }
+            self.positions.insert((ticker, subaccount), lot);
+        }
+        discrepancies
+    }
+}
+
+/// Tracks positions and mark-to-market profit and loss in real time.
+///
+/// Call [`sync`](Self::sync) to load positions and cash from REST, then
+/// feed it stream updates with [`run`](Self::run) or
+/// [`apply_update`](Self::apply_update). Fills update positions and cash
+/// immediately; `market_positions` updates replace a position with the
+/// server's figures. [`reconcile`](Self::reconcile) compares every position
+/// against REST, adopts the server's view and reports what differed.
+///
+/// Fills whose client order ID carries a
+/// [`ClientOrderIdGenerator`] prefix are also booked to that strategy. Each
+/// strategy keeps its own average price from its own fills, so strategy
+/// figures cover fills seen since the tracker started and are not
+/// reconciled.
+///
+/// Markets are marked to the YES bid/ask midpoint from the `ticker`
+/// channel, falling back to the last price, or to the book midpoint of an
+/// attached orderbook, whichever updated last.
+///
+/// Clones share state.
+#[derive(Clone)]
+pub struct PortfolioTracker {
+    client: KalshiClient,
+    config: PortfolioTrackerConfig,
+    orderbook: Option<OrderbookAggregator>,
+    state: Arc<Mutex<State>>,
+    event_sender: broadcast::Sender<PortfolioEvent>,
+}
+
+impl PortfolioTracker {
+    /// Create an empty tracker.
+    pub fn new(client: KalshiClient, config: PortfolioTrackerConfig) -> Self {
+        let (event_sender, _) = broadcast::channel(DEFAULT_EVENT_CAPACITY);
+        Self {
+            client,
+            config,
+            orderbook: None,
+            state: Arc::new(Mutex::new(State::default())),
+            event_sender,
+        }
+    }
+
+    /// Mark to book midpoints from `orderbook`.
+    #[must_use]
+    pub fn with_orderbook(mut self, orderbook: OrderbookAggregator) -> Self {
+        self.orderbook = Some(orderbook);
+        self
+    }
+
+    /// Get a receiver for portfolio events.
+    pub fn event_receiver(&self) -> broadcast::Receiver<PortfolioEvent> {
+        self.event_sender.subscribe()
+    }
+
+    // =========================================================================
+    // Queries
+    // =========================================================================
+
+    /// Every tracked position, including flat ones with realized profit and
+    /// loss.
+    pub fn positions(&self) -> Vec<PositionSnapshot> {
+        self.lock().positions()
+    }
+
+    /// A position by market and subaccount.
+    pub fn position(&self, ticker: &str, subaccount: Option<i32>) -> Option<PositionSnapshot> {
+        let subaccount = subaccount_key(subaccount);
+        let state = self.lock();
+        let lot = state.positions.get(&(ticker.to_string(), subaccount))?;
+        Some(state.snapshot(ticker, subaccount, None, lot))
+    }
+
+    /// Positions booked to each strategy, keyed by client order ID prefix.
+    pub fn strategy_positions(&self) -> Vec<PositionSnapshot> {
+        let state = self.lock();
+        state
+            .strategies
+            .iter()
+            .map(|((strategy, ticker, subaccount), lot)| {
+                state.snapshot(ticker, *subaccount, Some(strategy), lot)
+            })
+            .collect()
+    }
+
+    /// Totals across every position.
+    pub fn total(&self) -> PnlSummary {
+        let mut total = PnlSummary::default();
+        for snapshot in self.positions() {
+            total.add(&snapshot);
+        }
+        total
+    }
+
+    /// Totals per market ticker, across subaccounts.
+    pub fn by_market(&self) -> HashMap<String, PnlSummary> {
+        group(self.positions(), |s| Some(s.ticker.clone()))
+    }
+
+    /// Totals per event ticker.
+    ///
+    /// Markets whose event has not been resolved yet are left out.
+    pub fn by_event(&self) -> HashMap<String, PnlSummary> {
+        group(self.positions(), |s| s.event_ticker.clone())
+    }
+
+    /// Totals per subaccount, `None` for the primary account.
+    pub fn by_subaccount(&self) -> HashMap<Option<i32>, PnlSummary> {
+        group(self.positions(), |s| Some(s.subaccount))
+    }
+
+    /// Totals per strategy.
+    pub fn by_strategy(&self) -> HashMap<String, PnlSummary> {
+        group(self.strategy_positions(), |s| s.strategy.clone())
+    }
+
+    /// Cash balance in cents, once loaded by [`sync`](Self::sync).
+    pub fn cash(&self) -> Option<i64> {
+        self.lock().cash
+    }
+
+    /// Latest mark for a market, in YES cents.
+    pub fn mark(&self, ticker: &str) -> Option<f64> {
+        self.lock().marks.get(ticker).copied()
+    }
+
+    // =========================================================================
+    // Updates
+    // =========================================================================
+
+    /// Set a market's mark in YES cents, for prices from another source.
+    pub fn set_mark(&self, ticker: impl Into<String>, yes_price: f64) {
+        self.lock().marks.insert(ticker.into(), yes_price);
+    }
+
+    /// Apply a single stream update.
+    ///
+    /// [`run`](Self::run) calls this for every message. Messages other than
+    /// `fill`, `market_positions` and `ticker` are ignored.
+    pub fn apply_update(&self, msg: &StreamMessage) {
+        let snapshot = match msg {
+            StreamMessage::Fill(fill) => self.lock().apply_fill(fill),
+            StreamMessage::MarketPosition(data) => Some(self.lock().apply_position(data)),
+            StreamMessage::Ticker(data) => {
+                self.lock().mark_ticker(data);
+                None
+            }
+            _ => None,
+        };
+        if let Some(snapshot) = snapshot {
+            self.publish(PortfolioEvent::Position(snapshot));
+        }
+    }
+
+    /// Load positions and cash from REST, replacing what is tracked.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if positions or the balance cannot be fetched;
+    /// nothing is replaced in that case.
+    pub async fn sync(&self) -> Result<()> {
+        self.load().await?;
+        Ok(())
+    }
+
+    /// Compare positions against REST, adopt the server's figures and
+    /// report positions that differed.
+    ///
+    /// Each difference is also published as
+    /// [`PortfolioEvent::Discrepancy`]. Cash is replaced by the server's
+    /// balance. Fills that arrive while positions and the balance are
+    /// fetched are applied on top of the server's figures, so they are
+    /// neither lost nor reported as differences.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if positions or the balance cannot be fetched.
+    pub async fn reconcile(&self) -> Result<Vec<PositionDiscrepancy>> {
+        let discrepancies = self.load().await?;
+        for discrepancy in &discrepancies {
+            warn!(
+                ticker = %discrepancy.ticker,
+                local = discrepancy.local_position,
+                server = discrepancy.server_position,
+                "position discrepancy"
+            );
+            self.publish(PortfolioEvent::Discrepancy(discrepancy.clone()));
+        }
+        self.publish(PortfolioEvent::Reconciled {
+            discrepancies: discrepancies.len(),
+        });
+        Ok(discrepancies)
+    }
+
+    async fn load(&self) -> Result<Vec<PositionDiscrepancy>> {
+        let since = {
+            let mut state = self.lock();
+            state.loading += 1;
+            state.fill_seq
+        };
+        let fetched = self.fetch().await;
+
+        let discrepancies = {
+            let mut state = self.lock();
+            let discrepancies = fetched.map(|fetched| state.adopt(since, fetched));
+            state.loading -= 1;
+            if state.loading == 0 {
+                state.journal.clear();
+            }
+            discrepancies?
+        };
+        self.resolve_events().await;
+        Ok(discrepancies)
+    }
+
+    async fn fetch(&self) -> Result<Fetched> {
+        let subaccounts: Vec<Option<i32>> = if self.config.subaccounts.is_empty() {
+            vec![None]
+        } else {
+            self.config.subaccounts.iter().map(|&n| Some(n)).collect()
+        };
+
+        let mut positions = Vec::with_capacity(subaccounts.len());
+        for subaccount in subaccounts {
+            positions.push((
+                subaccount_key(subaccount),
+                self.fetch_positions(subaccount).await?,
+            ));
+        }
+        let balance_since = self.lock().fill_seq;
+        let balance = self.client.get_balance().await?.balance;
+        Ok(Fetched {
+            positions,
+            balance,
+            balance_since,
+        })
+    }
+
+    async fn fetch_positions(&self, subaccount: Option<i32>) -> Result<HashMap<String, Lot>> {
+        let mut positions = HashMap::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let mut params = GetPositionsParams::new().limit(POSITIONS_PAGE_SIZE);
+            if let Some(subaccount) = subaccount {
+                params = params.subaccount(subaccount);
+            }
+            if let Some(cursor) = cursor.take() {
+                params = params.cursor(cursor);
+            }
+            let response = self.client.get_positions_with_params(params).await?;
+            for position in &response.market_positions {
+                positions.insert(position.ticker.clone(), Lot::from_market_position(position));
+            }
+            match response.cursor {
+                Some(next) if !next.is_empty() && !response.market_positions.is_empty() => {
+                    cursor = Some(next);
+                }
+                _ => break,
+            }
+        }
+        Ok(positions)
+    }
+
+    /// Look up the events of markets not yet resolved.
+    ///
+    /// Markets whose lookup fails are retried on the next call.
+    async fn resolve_events(&self) {
+        let unresolved: Vec<String> = {
+            let state = self.lock();
+            let tickers: HashSet<&str> = state
+                .positions
+                .keys()
+                .map(|(ticker, _)| ticker.as_str())
+                .collect();
+            tickers
+                .into_iter()
+                .filter(|ticker| !state.events.contains_key(*ticker))
+                .map(str::to_string)
+                .collect()
+        };
+        for ticker in unresolved {
+            match self.client.get_market(&ticker).await {
+                Ok(response) => {
+                    self.lock()
+                        .events
+                        .insert(ticker, response.market.event_ticker);
+                }
+                Err(e) => warn!(ticker = %ticker, error = %e, "could not resolve market event"),
+            }
+        }
+    }
+
+    /// Process stream and book updates until the stream closes, reconciling
+    /// every [`reconcile_interval`](PortfolioTrackerConfig::reconcile_interval).
+    ///
+    /// Subscribe the handle to [`Channel::Fill`] and
+    /// [`Channel::MarketPositions`], and to [`Channel::Ticker`] for the
+    /// markets to mark from it. A lagged receiver triggers a
+    /// reconciliation.
+    ///
+    /// [`Channel::Fill`]: crate::ws::Channel::Fill
+    /// [`Channel::MarketPositions`]: crate::ws::Channel::MarketPositions
+    /// [`Channel::Ticker`]: crate::ws::Channel::Ticker
+    pub async fn run(&self, mut handle: KalshiStreamHandle) {
+        let period = self.config.reconcile_interval;
+        let mut reconcile = tokio::time::interval_at(Instant::now() + period, period);
+        reconcile.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut book = self.orderbook.as_ref().map(|b| b.update_receiver());
+
+        loop {
+            tokio::select! {
+                received = handle.update_receiver.recv() => match received {
+                    Ok(update) => match &update.msg {
+                        StreamMessage::Closed { .. } | StreamMessage::ConnectionLost { .. } => {
+                            break;
+                        }
+                        StreamMessage::Fill(fill) => {
+                            self.apply_update(&update.msg);
+                            let known = self.lock().events.contains_key(&fill.market_ticker);
+                            if !known {
+                                self.resolve_events().await;
+                            }
+                        }
+                        msg => self.apply_update(msg),
+                    },
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        debug!(missed = n, "portfolio updates lagged, reconciling");
+                        self.reconcile_or_warn().await;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                Some(update) = next_book_update(&mut book) => {
+                    let midpoint = self
+                        .orderbook
+                        .as_ref()
+                        .and_then(|b| b.midpoint(&update.ticker));
+                    if let Some(midpoint) = midpoint {
+                        self.set_mark(update.ticker, midpoint);
+                    }
+                }
+                _ = reconcile.tick() => self.reconcile_or_warn().await,
+            }
+        }
+    }
+
+    async fn reconcile_or_warn(&self) {
+        match self.reconcile().await {
+            Ok(discrepancies) => {
+                debug!(discrepancies = discrepancies.len(), "reconciled portfolio")
+            }
+            Err(e) => warn!("portfolio reconciliation failed: {}", e),
+        }
+    }
+
+    fn publish(&self, event: PortfolioEvent) {
+        // Ignore send errors - no receivers is fine
+        let _ = self.event_sender.send(event);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().expect("portfolio lock poisoned")
+    }
+}
+
+impl std::fmt::Debug for PortfolioTracker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.lock();
+        f.debug_struct("PortfolioTracker")
+            .field("positions", &state.positions.len())
+            .field("cash", &state.cash)
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+fn group<K: std::hash::Hash + Eq>(
+    snapshots: Vec<PositionSnapshot>,
+    key: impl Fn(&PositionSnapshot) -> Option<K>,
+) -> HashMap<K, PnlSummary> {
+    let mut groups: HashMap<K, PnlSummary> = HashMap::new();
+    for snapshot in &snapshots {
+        if let Some(k) = key(snapshot) {
+            groups.entry(k).or_default().add(snapshot);
+        }
+    }
+    groups
+}
+
+/// Next book update, or `None` once the book is gone or was never set.
+async fn next_book_update(
+    receiver: &mut Option<broadcast::Receiver<OrderbookUpdate>>,
+) -> Option<OrderbookUpdate> {
+    let rx = receiver.as_mut()?;
+    loop {
+        match rx.recv().await {
+            Ok(update) => return Some(update),
+            Err(broadcast::error::RecvError::Lagged(_)) => {}
+            Err(broadcast::error::RecvError::Closed) => {
+                *receiver = None;
+                return None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    use crate::test_fixtures as fixtures;
+
+    fn fill(
+        trade_id: &str,
+        side: &str,
+        action: &str,
+        yes_price: &str,
+        count: &str,
+        post_position: &str,
+    ) -> FillData {
+        fixtures::fill(json!({
+            "trade_id": trade_id,
+            "order_id": "order",
+            "is_taker": true,
+            "side": side,
+            "yes_price_dollars": yes_price,
+            "count_fp": count,
+            "fee_cost": "0.02",
+            "action": action,
+            "post_position_fp": post_position,
+            "purchased_side": side,
+            "client_order_id": ClientOrderIdGenerator::new("mm").unwrap().next_id(),
+        }))
+    }
+
+    #[test]
+    fn test_lot_average_and_realized() {
+        let mut lot = Lot::default();
+        lot.apply(10, 40.0, 0.0);
+        lot.apply(10, 50.0, 0.0);
+        assert_eq!(lot.position, 20);
+        assert!((lot.average - 45.0).abs() < 1e-9);
+
+        // Sell 5 at 55: realize 5 * 10
+        lot.apply(-5, 55.0, 0.0);
+        assert!((lot.realized - 50.0).abs() < 1e-9);
+        assert!((lot.average - 45.0).abs() < 1e-9);
+        assert!((lot.unrealized(Some(50.0)) - 75.0).abs() < 1e-9);
+
+        // Flip short: close 15 at 35, open 5 short at 35
+        lot.apply(-20, 35.0, 0.0);
+        assert!((lot.realized - (50.0 - 150.0)).abs() < 1e-9);
+        assert_eq!(lot.position, -5);
+        assert!((lot.average - 35.0).abs() < 1e-9);
+        assert!((lot.unrealized(Some(30.0)) - 25.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_no_position_from_server() {
+        // 10 NO held at a cost of $6.00 is 10 YES short at 40
+        let lot = Lot::from_server(-10, 600.0, 0.0, 0.0);
+        assert!((lot.average - 40.0).abs() < 1e-9);
+        // NO marked at 70 (YES at 30) is 10 cents up per contract
+        assert!((lot.unrealized(Some(30.0)) - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_fills_update_position_cash_and_strategy() {
+        let mut state = State {
+            cash: Some(10_000),
+            ..State::default()
+        };
+
+        // Buy 10 NO at 60 (YES at 40)
+        let snapshot = state
+            .apply_fill(&fill("t1", "no", "buy", "0.40", "10.00", "-10.00"))
+            .unwrap();
+        assert_eq!(snapshot.position, -10);
+        assert!((snapshot.average_price - 40.0).abs() < 1e-9);
+        assert!((snapshot.fees - 2.0).abs() < 1e-9);
+        assert_eq!(state.cash, Some(10_000 - 600 - 2));
+
+        // Duplicate fills are ignored
+        assert!(
+            state
+                .apply_fill(&fill("t1", "no", "buy", "0.40", "10.00", "-10.00"))
+                .is_none()
+        );
+
+        // Sell 4 NO at 70 (YES at 30): 4 * 10 realized
+        let snapshot = state
+            .apply_fill(&fill("t2", "no", "sell", "0.30", "4.00", "-6.00"))
+            .unwrap();
+        assert_eq!(snapshot.position, -6);
+        assert!((snapshot.realized_pnl - 40.0).abs() < 1e-9);
+        assert_eq!(state.cash, Some(10_000 - 600 - 2 + 280 - 2));
+
+        let strategy = &state.strategies[&("mm".to_string(), "TEST".to_string(), None)];
+        assert_eq!(strategy.position, -6);
+    }
+
+    #[test]
+    fn test_replace_positions_reports_differences() {
+        let mut state = State::default();
+        state.positions.insert(
+            ("A".to_string(), None),
+            Lot::from_server(5, 250.0, 0.0, 0.0),
+        );
+        state.positions.insert(
+            ("B".to_string(), None),
+            Lot::from_server(3, 150.0, 0.0, 0.0),
+        );
+        state.positions.insert(
+            ("A".to_string(), Some(2)),
+            Lot::from_server(7, 350.0, 0.0, 0.0),
+        );
+
+        let server = HashMap::from([
+            ("A".to_string(), Lot::from_server(5, 250.0, 0.0, 0.0)),
+            ("C".to_string(), Lot::from_server(-2, 120.0, 0.0, 0.0)),
+        ]);
+        let mut discrepancies = state.replace_positions(None, server, &[]);
+        discrepancies.sort_by(|a, b| a.ticker.cmp(&b.ticker));
+
+        assert_eq!(discrepancies.len(), 2);
+        assert_eq!(
+            (
+                discrepancies[0].ticker.as_str(),
+                discrepancies[0].server_position
+            ),
+            ("B", 0)
+        );
+        assert_eq!(
+            (
+                discrepancies[1].ticker.as_str(),
+                discrepancies[1].local_position
+            ),
+            ("C", 0)
+        );
+        // Other subaccounts are untouched
+        assert_eq!(state.positions[&("A".to_string(), Some(2))].position, 7);
+    }
+
+    #[test]
+    fn test_grouping() {
+        let mut state = State::default();
+        state.positions.insert(
+            ("A-1".to_string(), None),
+            Lot::from_server(10, 400.0, 100.0, 5.0),
+        );
+        state.positions.insert(
+            ("A-2".to_string(), Some(1)),
+            Lot::from_server(0, 0.0, -20.0, 1.0),
+        );
+        state.events.insert("A-1".to_string(), "A".to_string());
+        state.events.insert("A-2".to_string(), "A".to_string());
+        state.marks.insert("A-1".to_string(), 45.0);
+
+        let positions = state.positions();
+        let total = group(positions.clone(), |_| Some(()))[&()];
+        assert!((total.net() - (80.0 + 50.0 - 6.0)).abs() < 1e-9);
+        assert_eq!(total.open_positions, 1);
+
+        let by_event = group(positions.clone(), |s| s.event_ticker.clone());
+        assert_eq!(by_event.len(), 1);
+        let by_subaccount = group(positions, |s| Some(s.subaccount));
+        assert!((by_subaccount[&Some(1)].net() + 21.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_fills_during_load_replayed_on_snapshot() {
+        let mut state = State {
+            cash: Some(10_000),
+            ..State::default()
+        };
+        state.apply_fill(&fill("t1", "yes", "buy", "0.40", "10.00", "10.00"));
+
+        state.loading = 1;
+        let since = state.fill_seq;
+        // Arrives while positions are fetched, after the server's snapshot
+        state.apply_fill(&fill("t2", "yes", "buy", "0.50", "5.00", "15.00"));
+        let balance_since = state.fill_seq;
+        // Arrives after the balance was fetched too
+        state.apply_fill(&fill("t3", "yes", "sell", "0.60", "2.00", "13.00"));
+
+        let fetched = Fetched {
+            positions: vec![(
+                None,
+                HashMap::from([("TEST".to_string(), Lot::from_server(10, 400.0, 0.0, 2.0))]),
+            )],
+            balance: 9_000,
+            balance_since,
+        };
+        let discrepancies = state.adopt(since, fetched);
+
+        assert!(discrepancies.is_empty());
+        let lot = &state.positions[&("TEST".to_string(), None)];
+        assert_eq!(lot.position, 13);
+        assert_eq!(state.cash, Some(9_000 + 120 - 2));
+        // Trades the snapshot covers are forgotten
+        assert!(!state.seen_trades.contains_key("t1"));
+        assert!(state.seen_trades.contains_key("t2"));
+        assert!(state.seen_trades.contains_key("t3"));
+    }
+
+    #[test]
+    fn test_fill_applied_when_position_already_matches() {
+        let mut state = State::default();
+        // Drifted: already at the position the fill leads to
+        state.positions.insert(
+            ("TEST".to_string(), None),
+            Lot::from_server(5, 200.0, 0.0, 0.0),
+        );
+        state.apply_fill(&fill("t1", "yes", "buy", "0.50", "1.00", "5.00"));
+
+        let lot = &state.positions[&("TEST".to_string(), None)];
+        assert_eq!(lot.position, 6);
+        assert!((lot.fees - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_replay_skips_fills_the_snapshot_includes() {
+        let mut state = State {
+            loading: 1,
+            ..State::default()
+        };
+        let since = state.fill_seq;
+        let mut included = fill("t1", "yes", "buy", "0.50", "5.00", "15.00");
+        included.ts = 100;
+        state.apply_fill(&included);
+        let mut later = fill("t2", "yes", "buy", "0.50", "2.00", "17.00");
+        later.ts = 101;
+        state.apply_fill(&later);
+
+        let server = Lot {
+            updated: Some(100),
+            ..Lot::from_server(15, 650.0, 0.0, 2.0)
+        };
+        let fetched = Fetched {
+            positions: vec![(None, HashMap::from([("TEST".to_string(), server)]))],
+            balance: 0,
+            balance_since: state.fill_seq,
+        };
+        state.adopt(since, fetched);
+
+        let lot = &state.positions[&("TEST".to_string(), None)];
+        assert_eq!(lot.position, 17);
+        assert!((lot.fees - 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_server_update_time_parsed() {
+        let position: MarketPosition = serde_json::from_value(json!({
+            "ticker": "TEST",
+            "total_traded_dollars": "0",
+            "position_fp": "1.00",
+            "market_exposure_dollars": "0.50",
+            "realized_pnl_dollars": "0",
+            "fees_paid_dollars": "0",
+            "last_updated_ts": "2025-01-01T00:00:00Z",
+        }))
+        .unwrap();
+        assert_eq!(
+            Lot::from_market_position(&position).updated,
+            Some(1_735_689_600)
+        );
+    }
+}
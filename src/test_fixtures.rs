@@ -0,0 +1,88 @@
+//! Builders for API payloads used across unit tests.
+//!
+//! Each builder starts from a resting 10-contract YES buy on `TEST` at 45¢
+//! and overwrites the fields given in `fields`, a JSON object.
+
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+
+use crate::models::Order;
+use crate::ws::{FillData, UserOrderData};
+
+fn build<T: DeserializeOwned>(mut base: Value, fields: Value) -> T {
+    if let (Some(base), Value::Object(fields)) = (base.as_object_mut(), fields) {
+        base.extend(fields);
+    }
+    serde_json::from_value(base).expect("fixture does not deserialize")
+}
+
+/// An order as returned by the REST API.
+pub(crate) fn order(fields: Value) -> Order {
+    build(
+        json!({
+            "order_id": "o1",
+            "user_id": "user",
+            "client_order_id": "",
+            "ticker": "TEST",
+            "side": "yes",
+            "action": "buy",
+            "type": "limit",
+            "status": "resting",
+            "yes_price_dollars": "0.45",
+            "no_price_dollars": "0.55",
+            "fill_count_fp": "0.00",
+            "remaining_count_fp": "10.00",
+            "initial_count_fp": "10.00",
+            "taker_fill_cost_dollars": "0.00",
+            "maker_fill_cost_dollars": "0.00",
+        }),
+        fields,
+    )
+}
+
+/// An update from the `user_orders` channel.
+pub(crate) fn user_order(fields: Value) -> UserOrderData {
+    build(
+        json!({
+            "order_id": "o1",
+            "user_id": "user",
+            "ticker": "TEST",
+            "status": "resting",
+            "side": "yes",
+            "action": "buy",
+            "is_yes": true,
+            "yes_price_dollars": "0.45",
+            "fill_count_fp": "0.00",
+            "remaining_count_fp": "10.00",
+            "initial_count_fp": "10.00",
+            "taker_fill_cost_dollars": "0.00",
+            "maker_fill_cost_dollars": "0.00",
+            "taker_fees_dollars": "0.00",
+            "maker_fees_dollars": "0.00",
+            "client_order_id": "",
+            "created_time": "2026-01-01T00:00:00Z",
+        }),
+        fields,
+    )
+}
+
+/// A message from the `fill` channel.
+pub(crate) fn fill(fields: Value) -> FillData {
+    build(
+        json!({
+            "trade_id": "t1",
+            "order_id": "o1",
+            "market_ticker": "TEST",
+            "is_taker": false,
+            "side": "yes",
+            "yes_price_dollars": "0.45",
+            "count_fp": "1.00",
+            "fee_cost": "0.00",
+            "action": "buy",
+            "ts": 0,
+            "post_position_fp": "1.00",
+            "purchased_side": "yes",
+        }),
+        fields,
+    )
+}
@@ -0,0 +1,51 @@
+//! Conversions from the API's fixed-point strings, shared by the trading
+//! modules.
+
+use crate::models::{Action, Side};
+
+/// Parse a fixed-point contract count, or 0 if it does not parse.
+pub(crate) fn contracts(fp: &str) -> i64 {
+    fp.parse::<f64>().map(|c| c.round() as i64).unwrap_or(0)
+}
+
+/// Parse a fixed-point dollar amount into cents.
+pub(crate) fn cents(dollars: &str) -> Option<f64> {
+    dollars.parse::<f64>().ok().map(|d| d * 100.0)
+}
+
+/// Parse a fixed-point dollar price into whole cents.
+pub(crate) fn whole_cents(dollars: &str) -> Option<i64> {
+    cents(dollars).map(|c| c.round() as i64)
+}
+
+/// +1 if trading `action` on `side` adds YES exposure, -1 if it adds NO
+/// exposure.
+///
+/// Positions are signed the same way: positive holds YES, negative holds NO.
+pub(crate) fn direction(side: Side, action: Action) -> i64 {
+    match (side, action) {
+        (Side::Yes, Action::Buy) | (Side::No, Action::Sell) => 1,
+        (Side::No, Action::Buy) | (Side::Yes, Action::Sell) => -1,
+    }
+}
+
+/// Subaccount 0 is the primary account, which is keyed as `None`.
+pub(crate) fn subaccount_key(subaccount: Option<i32>) -> Option<i32> {
+    subaccount.filter(|&n| n != 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_point_parsing() {
+        assert_eq!(contracts("10.00"), 10);
+        assert_eq!(contracts("2.50"), 3);
+        assert_eq!(contracts("bad"), 0);
+        assert_eq!(whole_cents("0.45"), Some(45));
+        assert_eq!(whole_cents(""), None);
+        assert_eq!(subaccount_key(Some(0)), None);
+        assert_eq!(subaccount_key(Some(2)), Some(2));
+    }
+}
@@ -160,13 +160,53 @@
 //! # Ok(())
 //! # }
 //! ```
+//!
+//! For long-running consumers whose market set changes over time,
+//! [`SubscriptionManager`] accepts the desired subscriptions declaratively,
+//! sends only the difference, and repairs drift against the server.
+//!
+//! # Stream Statistics
+//!
+//! [`KalshiStreamHandle::stats`] returns a [`StreamStats`] snapshot with message
+//! and byte counts per channel, parse failures, exchange-to-receive latency
+//! histograms, and the backlog of updates not yet read by the slowest handle.
+//!
+//! ```no_run
+//! # use kalshi_trade_rs::ws::KalshiStreamHandle;
+//! # fn example(handle: &KalshiStreamHandle) {
+//! let stats = handle.stats();
+//! for (channel, channel_stats) in &stats.channels {
+//!     println!(
+//!         "{}: {:.1} msg/s, p99 latency {:?}",
+//!         channel,
+//!         channel_stats.messages_per_second,
+//!         channel_stats.latency.quantile(0.99),
+//!     );
+//! }
+//! # }
+//! ```
+//!
+//! Enable the `metrics` cargo feature to also publish these through the
+//! [`metrics`](https://docs.rs/metrics) crate.
+//!
+//! # Unparsed Messages and Raw Frames
+//!
+//! Data messages that fail to deserialize, including message types this crate
+//! does not know yet, are delivered as [`StreamMessage::Unknown`] with the raw
+//! JSON payload rather than dropped. For full visibility,
+//! [`KalshiStreamHandle::raw_frames`] taps the original text of every frame
+//! alongside the update it produced.
 
 mod channel;
 mod client;
 mod command;
+mod manager;
 mod message;
+#[cfg(test)]
+pub(crate) mod mock_server;
 mod protocol;
 mod session;
+mod stats;
 
 use std::time::Duration;
 
@@ -176,13 +216,15 @@ pub use command::{
     CommunicationsSharding, ServerSubscription, SubscribeOptions, SubscribeResult,
     UnsubscribeResult, UpdateAction,
 };
+pub use manager::{SubscriptionManager, SubscriptionManagerConfig, SyncReport};
 pub use message::{
     Action, CollateralReturnType, CommunicationData, EventLifecycleData, FillData,
     MarketLifecycleData, MarketLifecycleEventType, MarketPositionData, MultivariateLookupData,
     MveLeg, OrderGroupEventType, OrderGroupUpdateData, OrderbookDeltaData, OrderbookSnapshotData,
-    QuoteAcceptedData, QuoteData, QuoteExecutedData, RfqData, RfqDeletedData, Side, StreamMessage,
-    StreamUpdate, TickerData, TradeData, UserOrderData, UserOrderEventType,
+    QuoteAcceptedData, QuoteData, QuoteExecutedData, RawFrame, RfqData, RfqDeletedData, Side,
+    StreamMessage, StreamUpdate, TickerData, TradeData, UserOrderData, UserOrderEventType,
 };
+pub use stats::{ChannelStats, LatencyHistogram, StreamStats};
 
 /// Connection strategy for the WebSocket client.
 ///
@@ -11,11 +11,12 @@ use tokio::{
 };
 
 use super::{
-    ConnectStrategy,
+    ConnectStrategy, HealthConfig,
     channel::Channel,
     command::{StreamCommand, SubscribeResult, UnsubscribeResult, UpdateAction},
-    message::StreamUpdate,
+    message::{RawFrame, StreamUpdate},
     session::{KalshiStreamSession, SharedSubscriptions, SubscriptionState},
+    stats::{SharedStats, StatsRecorder, StreamStats},
 };
 
 use crate::{
@@ -72,7 +73,9 @@ pub struct KalshiStreamClient {
     session_handle: JoinHandle<()>,
     cmd_sender: mpsc::Sender<StreamCommand>,
     update_sender: broadcast::Sender<StreamUpdate>,
+    raw_sender: broadcast::Sender<RawFrame>,
     subscriptions: SharedSubscriptions,
+    stats: SharedStats,
 }
 
 impl KalshiStreamClient {
@@ -144,18 +147,45 @@ impl KalshiStreamClient {
         config: &KalshiConfig,
         strategy: ConnectStrategy,
         buffer_size: usize,
+    ) -> Result<Self> {
+        Self::connect_to_url(
+            config,
+            config.environment.ws_url(),
+            strategy,
+            HealthConfig::default(),
+            buffer_size,
+        )
+        .await
+    }
+
+    /// Connect to an explicit WebSocket URL.
+    ///
+    /// Used by tests to point the session at a local mock server instead of
+    /// the environment's Kalshi endpoint.
+    pub(crate) async fn connect_to_url(
+        config: &KalshiConfig,
+        ws_url: &str,
+        strategy: ConnectStrategy,
+        health_config: HealthConfig,
+        buffer_size: usize,
     ) -> Result<Self> {
         let (cmd_sender, cmd_receiver) = mpsc::channel(32);
         let (update_sender, _) = broadcast::channel(buffer_size);
+        let (raw_sender, _) = broadcast::channel(buffer_size);
         let (ready_tx, ready_rx) = oneshot::channel();
         let subscriptions: SharedSubscriptions = Arc::new(RwLock::new(HashMap::new()));
+        let stats: SharedStats = Arc::new(StatsRecorder::new());
 
         let session = KalshiStreamSession::connect(
             config,
+            ws_url,
             strategy,
+            health_config,
             cmd_receiver,
             update_sender.clone(),
+            raw_sender.clone(),
             subscriptions.clone(),
+            stats.clone(),
             ready_tx,
         )
         .await?;
@@ -181,7 +211,9 @@ impl KalshiStreamClient {
             session_handle,
             cmd_sender,
             update_sender,
+            raw_sender,
             subscriptions,
+            stats,
         })
     }
 
@@ -195,7 +227,9 @@ impl KalshiStreamClient {
             cmd_sender: self.cmd_sender.clone(),
             update_sender: self.update_sender.clone(),
             update_receiver: self.update_sender.subscribe(),
+            raw_sender: self.raw_sender.clone(),
             subscriptions: self.subscriptions.clone(),
+            stats: self.stats.clone(),
         }
     }
 
@@ -261,8 +295,12 @@ pub struct KalshiStreamHandle {
     /// if the receiver falls too far behind, it will start missing
     /// messages (lagged error).
     pub update_receiver: broadcast::Receiver<StreamUpdate>,
+    /// Sender for the raw frame tap, used to hand out receivers.
+    raw_sender: broadcast::Sender<RawFrame>,
     /// Shared subscription state.
     subscriptions: SharedSubscriptions,
+    /// Shared stream statistics.
+    stats: SharedStats,
 }
 
 impl Clone for KalshiStreamHandle {
@@ -271,7 +309,9 @@ impl Clone for KalshiStreamHandle {
             cmd_sender: self.cmd_sender.clone(),
             update_sender: self.update_sender.clone(),
             update_receiver: self.update_sender.subscribe(),
+            raw_sender: self.raw_sender.clone(),
             subscriptions: self.subscriptions.clone(),
+            stats: self.stats.clone(),
         }
     }
 }
@@ -292,6 +332,41 @@ impl KalshiStreamHandle {
         !self.cmd_sender.is_closed()
     }
 
+    /// Tap the raw text of every frame received from the server.
+    ///
+    /// Each [`RawFrame`] carries the original JSON alongside the
+    /// [`StreamUpdate`] it produced, which is useful for inspecting fields the
+    /// typed messages do not model yet. The tap is opt-in: the session only
+    /// copies frames while at least one receiver returned by this method is
+    /// alive. Receivers that fall behind miss frames, as with
+    /// [`update_receiver`](Self::update_receiver).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use kalshi_trade_rs::ws::KalshiStreamHandle;
+    /// # async fn example(handle: &KalshiStreamHandle) {
+    /// let mut frames = handle.raw_frames();
+    /// while let Ok(frame) = frames.recv().await {
+    ///     if frame.update.is_none() {
+    ///         println!("non-update frame: {}", frame.text);
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn raw_frames(&self) -> broadcast::Receiver<RawFrame> {
+        self.raw_sender.subscribe()
+    }
+
+    /// Get a snapshot of stream statistics.
+    ///
+    /// Covers the current connection: message and byte counts, parse failures,
+    /// per-channel rates and latency histograms, and the broadcast backlog.
+    /// All handles of a client share the same statistics.
+    pub fn stats(&self) -> StreamStats {
+        self.stats.snapshot()
+    }
+
     /// Subscribe to a channel for specific markets.
     ///
     /// If already subscribed to this channel, automatically adds the new markets
@@ -678,6 +753,32 @@ impl KalshiStreamHandle {
         subs.get(&channel).map(|s| s.sid)
     }
 
+    /// Drop local state for a subscription the server no longer knows about.
+    ///
+    /// Only removes the entry if `sid` still matches, so a concurrent
+    /// resubscribe is left untouched. Returns whether an entry was removed.
+    pub(crate) fn forget_subscription(&self, channel: Channel, sid: i64) -> bool {
+        let mut subs = self
+            .subscriptions
+            .write()
+            .expect("subscription lock poisoned");
+        if subs.get(&channel).is_some_and(|s| s.sid == sid) {
+            subs.remove(&channel);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Subscription IDs currently tracked locally.
+    pub(crate) fn local_sids(&self) -> HashSet<i64> {
+        let subs = self
+            .subscriptions
+            .read()
+            .expect("subscription lock poisoned");
+        subs.values().map(|s| s.sid).collect()
+    }
+
     /// Update an existing subscription by adding or removing markets.
     ///
     /// This is a lower-level method that operates directly on a subscription ID.
@@ -853,7 +954,7 @@ impl KalshiStreamHandle {
     }
 
     /// Raw unsubscribe without local state management.
-    async fn unsubscribe_raw(&self, sids: &[i64]) -> Result<UnsubscribeResult> {
+    pub(crate) async fn unsubscribe_raw(&self, sids: &[i64]) -> Result<UnsubscribeResult> {
         let (tx, rx) = oneshot::channel();
 
         let cmd = StreamCommand::Unsubscribe {
@@ -916,7 +1017,9 @@ mod tests {
             cmd_sender,
             update_sender: update_sender.clone(),
             update_receiver: update_sender.subscribe(),
+            raw_sender: broadcast::channel(16).0,
             subscriptions,
+            stats: Arc::new(StatsRecorder::new()),
         };
 
         (handle, cmd_receiver)
@@ -1367,7 +1470,9 @@ mod tests {
             cmd_sender: cmd_sender.clone(),
             update_sender: update_sender.clone(),
             update_receiver: update_sender.subscribe(),
+            raw_sender: broadcast::channel(16).0,
             subscriptions: subscriptions.clone(),
+            stats: Arc::new(StatsRecorder::new()),
         };
 
         let handle2 = handle1.clone();
@@ -1406,7 +1511,9 @@ mod tests {
             cmd_sender: cmd_sender.clone(),
             update_sender: update_sender.clone(),
             update_receiver: update_sender.subscribe(),
+            raw_sender: broadcast::channel(16).0,
             subscriptions,
+            stats: Arc::new(StatsRecorder::new()),
         };
 
         let handle2 = handle1.clone();
@@ -1450,4 +1557,191 @@ mod tests {
         assert_eq!(received1.sid, 1);
         assert_eq!(received2.sid, 1);
     }
+
+    // ========== Mock Server Tests ==========
+
+    #[tokio::test]
+    async fn test_subscribe_then_add_markets_against_mock_server() {
+        use super::super::mock_server::MockKalshiServer;
+
+        let mut server = MockKalshiServer::start().await;
+        let client = server.connect_client().await;
+        let mut conn = server.accept().await;
+        let mut handle = client.handle();
+
+        let subscribe = tokio::spawn(async move {
+            handle.subscribe(Channel::Ticker, &["MARKET-A"]).await?;
+            handle.subscribe(Channel::Ticker, &["MARKET-B"]).await?;
+            Ok::<_, Error>(handle)
+        });
+
+        let (_, sids) = conn.expect_subscribe().await;
+        let update = conn.expect_command("update_subscription").await;
+        assert_eq!(update["params"]["sids"][0], sids[0]);
+        assert_eq!(update["params"]["market_ticker"], "MARKET-B");
+        conn.ack_update_subscription(&update, &["MARKET-A", "MARKET-B"])
+            .await;
+
+        let handle = subscribe.await.unwrap().unwrap();
+        assert_eq!(handle.sid(Channel::Ticker), Some(sids[0]));
+        let mut markets = handle.markets(Channel::Ticker);
+        markets.sort();
+        assert_eq!(markets, vec!["MARKET-A", "MARKET-B"]);
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_error_against_mock_server() {
+        use super::super::mock_server::MockKalshiServer;
+
+        let mut server = MockKalshiServer::start().await;
+        let client = server.connect_client().await;
+        let mut conn = server.accept().await;
+        let mut handle = client.handle();
+
+        let subscribe =
+            tokio::spawn(async move { handle.subscribe(Channel::Fill, &[]).await.map(|_| handle) });
+
+        let command = conn.expect_command("subscribe").await;
+        conn.send_error(
+            command["id"].as_u64().unwrap(),
+            9,
+            "Authentication required",
+        )
+        .await;
+
+        match subscribe.await.unwrap() {
+            Err(Error::Api(msg)) => assert_eq!(msg, "9: Authentication required"),
+            other => panic!("expected Api error, got {:?}", other.map(|_| ())),
+        }
+        assert!(!client.handle().is_subscribed(Channel::Fill));
+    }
+
+    #[tokio::test]
+    async fn test_list_subscriptions_remote_against_mock_server() {
+        use super::super::mock_server::MockKalshiServer;
+
+        let mut server = MockKalshiServer::start().await;
+        let client = server.connect_client().await;
+        let mut conn = server.accept().await;
+        let handle = client.handle();
+
+        let list = tokio::spawn(async move { handle.list_subscriptions_remote().await });
+
+        let command = conn.expect_command("list_subscriptions").await;
+        conn.ack_list_subscriptions(&command, &[("orderbook_delta", 4)])
+            .await;
+
+        let subs = list.await.unwrap().unwrap();
+        assert_eq!(subs.len(), 1);
+        assert_eq!(subs[0].channel, "orderbook_delta");
+        assert_eq!(subs[0].sid, 4);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_against_mock_server() {
+        use super::super::mock_server::MockKalshiServer;
+
+        let mut server = MockKalshiServer::start().await;
+        let client = server.connect_client().await;
+        let mut conn = server.accept().await;
+        assert!(client.handle().is_alive());
+
+        let shutdown = tokio::spawn(client.shutdown());
+        assert!(matches!(
+            conn.recv_message().await,
+            Some(tokio_tungstenite::tungstenite::Message::Close(_))
+        ));
+        drop(conn);
+
+        shutdown.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_stats_against_mock_server() {
+        use super::super::mock_server::MockKalshiServer;
+
+        let mut server = MockKalshiServer::start().await;
+        let client = server.connect_client().await;
+        let mut conn = server.accept().await;
+        let mut handle = client.handle();
+
+        conn.push(
+            "trade",
+            1,
+            Some(1),
+            serde_json::json!({
+                "trade_id": "t1",
+                "market_ticker": "TEST",
+                "yes_price_dollars": "0.50",
+                "no_price_dollars": "0.50",
+                "count_fp": "1.00",
+                "taker_side": "yes",
+                "ts": chrono::Utc::now().timestamp()
+            }),
+        )
+        .await;
+        conn.push("ticker", 2, Some(1), serde_json::json!({ "bogus": true }))
+            .await;
+        conn.send_text("not json").await;
+        conn.push_orderbook_delta(3, 1, "TEST", "yes", "0.45", "10.00")
+            .await;
+
+        // The delta arrives last, so every frame before it has been recorded
+        loop {
+            let update = timeout(Duration::from_secs(5), handle.update_receiver.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            if update.channel == "orderbook_delta" {
+                break;
+            }
+        }
+
+        let stats = handle.stats();
+        assert_eq!(stats.messages_received, 2);
+        assert_eq!(stats.parse_failures, 2);
+        assert!(stats.bytes_received > 0);
+        assert_eq!(stats.connect_retries, 0);
+        assert_eq!(stats.channels["trade"].messages, 1);
+        assert_eq!(stats.channels["trade"].latency.count(), 1);
+        assert_eq!(stats.channels["orderbook_delta"].messages, 1);
+        assert_eq!(stats.channels["ticker"].parse_failures, 1);
+        assert_eq!(stats.channels["ticker"].messages, 0);
+    }
+
+    #[tokio::test]
+    async fn test_raw_frames_against_mock_server() {
+        use super::super::mock_server::MockKalshiServer;
+
+        let mut server = MockKalshiServer::start().await;
+        let client = server.connect_client().await;
+        let mut conn = server.accept().await;
+        let handle = client.handle();
+        let mut frames = handle.raw_frames();
+
+        conn.push_orderbook_delta(1, 1, "TEST", "yes", "0.45", "10.00")
+            .await;
+        conn.send_text("not json").await;
+
+        let frame = timeout(Duration::from_secs(5), frames.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        let raw: serde_json::Value = serde_json::from_str(&frame.text).unwrap();
+        assert_eq!(raw["type"], "orderbook_delta");
+        assert_eq!(raw["msg"]["market_ticker"], "TEST");
+        let update = frame.update.expect("delta should produce an update");
+        assert_eq!(update.seq, Some(1));
+        assert!(matches!(
+            update.msg,
+            super::super::message::StreamMessage::OrderbookDelta(_)
+        ));
+
+        let frame = timeout(Duration::from_secs(5), frames.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(frame.text, "not json");
+        assert!(frame.update.is_none());
+    }
 }
@@ -89,7 +89,7 @@ impl UnsubscribeResult {
 ///
 /// When subscribing to the communications channel for RFQ/quote updates,
 /// sharding can be used to distribute traffic across multiple connections.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct CommunicationsSharding {
     /// Number of shards to distribute traffic across.
     /// Each shard receives approximately 1/shard_factor of the traffic.
@@ -127,7 +127,7 @@ impl CommunicationsSharding {
 }
 
 /// Options for subscribe operations.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct SubscribeOptions {
     /// Optional sharding config for communications channel.
     pub sharding: Option<CommunicationsSharding>,
@@ -0,0 +1,756 @@
+//! Declarative subscription management.
+//!
+//! [`SubscriptionManager`] lets callers describe the subscriptions they want
+//! instead of issuing subscribe/unsubscribe calls one at a time. A background
+//! task diffs the desired state against the handle's local subscription state,
+//! issues the minimal set of commands to converge, and periodically checks the
+//! result against the server.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use tokio::{
+    sync::{Notify, mpsc, oneshot},
+    task::JoinHandle,
+    time::{MissedTickBehavior, interval, sleep},
+};
+use tracing::{debug, warn};
+
+use super::{Channel, KalshiStreamHandle, SubscribeOptions};
+use crate::error::{DisconnectReason, Error, Result};
+
+/// Configuration for [`SubscriptionManager`].
+#[derive(Debug, Clone)]
+pub struct SubscriptionManagerConfig {
+    /// How long to wait after a change before reconciling.
+    ///
+    /// Changes made within this window are applied together, so a burst of
+    /// `add_markets` calls results in a single `update_subscription` per channel.
+    ///
+    /// Default: 50 milliseconds.
+    pub coalesce_window: Duration,
+
+    /// Interval between checks against the server's subscription list.
+    ///
+    /// Default: 60 seconds.
+    pub verify_interval: Duration,
+}
+
+impl Default for SubscriptionManagerConfig {
+    fn default() -> Self {
+        Self {
+            coalesce_window: Duration::from_millis(50),
+            verify_interval: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Outcome of a reconciliation or verification pass.
+#[derive(Debug, Default)]
+pub struct SyncReport {
+    /// Channels that were newly subscribed.
+    pub subscribed: Vec<Channel>,
+    /// Channels that were unsubscribed and subscribed again because their
+    /// options or market scope changed.
+    pub resubscribed: Vec<Channel>,
+    /// Channels that were fully unsubscribed.
+    pub unsubscribed: Vec<Channel>,
+    /// Number of markets added to existing subscriptions.
+    pub markets_added: usize,
+    /// Number of markets removed from existing subscriptions.
+    pub markets_removed: usize,
+    /// Managed channels tracked locally whose subscription the server no
+    /// longer reported. These are resubscribed in the same pass.
+    pub missing_on_server: Vec<Channel>,
+    /// Server subscriptions with no local owner that were unsubscribed.
+    pub orphaned_sids: Vec<i64>,
+    /// Channels that could not be reconciled, with the error encountered.
+    ///
+    /// Failed channels are retried on the next pass.
+    pub failures: Vec<(Channel, Error)>,
+}
+
+impl SyncReport {
+    /// Returns true if every managed channel was reconciled successfully.
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// Returns true if the pass sent any commands to the server.
+    pub fn has_changes(&self) -> bool {
+        !self.subscribed.is_empty()
+            || !self.resubscribed.is_empty()
+            || !self.unsubscribed.is_empty()
+            || self.markets_added > 0
+            || self.markets_removed > 0
+            || !self.orphaned_sids.is_empty()
+    }
+
+    fn merge(&mut self, other: SyncReport) {
+        self.subscribed.extend(other.subscribed);
+        self.resubscribed.extend(other.resubscribed);
+        self.unsubscribed.extend(other.unsubscribed);
+        self.markets_added += other.markets_added;
+        self.markets_removed += other.markets_removed;
+        self.missing_on_server.extend(other.missing_on_server);
+        self.orphaned_sids.extend(other.orphaned_sids);
+        self.failures.extend(other.failures);
+    }
+}
+
+/// Desired configuration for one channel.
+#[derive(Debug, Clone)]
+struct ChannelTarget {
+    markets: HashSet<String>,
+    options: SubscribeOptions,
+}
+
+#[derive(Debug, Default)]
+struct DesiredState {
+    channels: HashMap<Channel, ChannelTarget>,
+    /// Every channel the manager has been told about, including removed ones,
+    /// so that removals are carried out. Channels never mentioned are left alone.
+    managed: HashSet<Channel>,
+}
+
+type SharedDesired = Arc<Mutex<DesiredState>>;
+
+enum ManagerRequest {
+    Sync(oneshot::Sender<SyncReport>),
+    Verify(oneshot::Sender<Result<SyncReport>>),
+}
+
+/// Keeps a stream's subscriptions converged on a declared target.
+///
+/// Callers declare which channels and markets they want with [`set`](Self::set),
+/// [`add_markets`](Self::add_markets), [`remove_markets`](Self::remove_markets)
+/// and [`remove_channel`](Self::remove_channel). These only record the target
+/// and return immediately; a background task applies them after a short
+/// coalescing window using the fewest commands it can:
+///
+/// - a channel not yet subscribed gets a `subscribe`
+/// - markets added to or removed from a subscribed channel become
+///   `update_subscription` calls
+/// - a removed channel, or one with no markets left, gets an `unsubscribe`
+/// - a channel whose [`SubscribeOptions`] changed is resubscribed
+///
+/// Only channels the manager has been told about are touched. Other
+/// subscriptions made directly on the handle are left as they are.
+///
+/// # Drift Repair
+///
+/// Every [`verify_interval`](SubscriptionManagerConfig::verify_interval) the
+/// manager compares local state with
+/// [`list_subscriptions_remote`](KalshiStreamHandle::list_subscriptions_remote).
+/// A managed subscription the server no longer reports is dropped locally and
+/// subscribed again. A server subscription that no handle knows about is
+/// unsubscribed once it has been seen on two consecutive checks, which avoids
+/// racing a subscribe that is still in flight. The server only reports channel
+/// and sid, so market-level drift within a subscription cannot be detected.
+///
+/// # Example
+///
+/// ```no_run
+/// use kalshi_trade_rs::ws::{Channel, KalshiStreamHandle, SubscriptionManager};
+///
+/// # async fn example(handle: KalshiStreamHandle) -> Result<(), Box<dyn std::error::Error>> {
+/// let manager = SubscriptionManager::new(handle);
+///
+/// manager.set(Channel::OrderbookDelta, &["MARKET-A", "MARKET-B"]);
+/// manager.set(Channel::Fill, &[]);
+///
+/// // Later: swap one market for another. Only the difference is sent.
+/// manager.remove_markets(Channel::OrderbookDelta, &["MARKET-A"]);
+/// manager.add_markets(Channel::OrderbookDelta, &["MARKET-C"]);
+///
+/// // Optionally wait for the changes to reach the server.
+/// let report = manager.sync().await?;
+/// assert!(report.is_ok());
+/// # Ok(())
+/// # }
+/// ```
+///
+/// The manager must be created inside a Tokio runtime. Dropping it stops the
+/// background task but leaves existing subscriptions in place.
+pub struct SubscriptionManager {
+    desired: SharedDesired,
+    changed: Arc<Notify>,
+    request_sender: mpsc::Sender<ManagerRequest>,
+    task: JoinHandle<()>,
+}
+
+impl SubscriptionManager {
+    /// Create a manager with the default configuration.
+    pub fn new(handle: KalshiStreamHandle) -> Self {
+        Self::with_config(handle, SubscriptionManagerConfig::default())
+    }
+
+    /// Create a manager with a custom configuration.
+    pub fn with_config(handle: KalshiStreamHandle, config: SubscriptionManagerConfig) -> Self {
+        let desired = SharedDesired::default();
+        let changed = Arc::new(Notify::new());
+        let (request_sender, request_receiver) = mpsc::channel(16);
+
+        let worker = Worker {
+            handle,
+            config,
+            desired: desired.clone(),
+            changed: changed.clone(),
+            requests: request_receiver,
+            applied: HashMap::new(),
+            suspected_orphans: HashSet::new(),
+        };
+        let task = tokio::spawn(worker.run());
+
+        Self {
+            desired,
+            changed,
+            request_sender,
+            task,
+        }
+    }
+
+    /// Declare the full market set for a channel with default options.
+    ///
+    /// An empty slice subscribes to all markets on channels that allow it.
+    /// For [`Channel::OrderbookDelta`], which requires tickers, an empty slice
+    /// removes the channel.
+    pub fn set(&self, channel: Channel, markets: &[&str]) {
+        self.set_with_options(channel, markets, SubscribeOptions::default());
+    }
+
+    /// Declare the full market set and subscribe options for a channel.
+    pub fn set_with_options(&self, channel: Channel, markets: &[&str], options: SubscribeOptions) {
+        self.update(|desired| {
+            desired.channels.insert(
+                channel,
+                ChannelTarget {
+                    markets: markets.iter().map(|m| m.to_string()).collect(),
+                    options,
+                },
+            );
+            desired.managed.insert(channel);
+        });
+    }
+
+    /// Add markets to a channel's target, creating it if needed.
+    pub fn add_markets(&self, channel: Channel, markets: &[&str]) {
+        if markets.is_empty() {
+            return;
+        }
+        self.update(|desired| {
+            desired
+                .channels
+                .entry(channel)
+                .or_insert_with(|| ChannelTarget {
+                    markets: HashSet::new(),
+                    options: SubscribeOptions::default(),
+                })
+                .markets
+                .extend(markets.iter().map(|m| m.to_string()));
+            desired.managed.insert(channel);
+        });
+    }
+
+    /// Remove markets from a channel's target.
+    ///
+    /// Removing the last market removes the channel rather than widening it to
+    /// all markets. Use [`set`](Self::set) with an empty slice for that.
+    pub fn remove_markets(&self, channel: Channel, markets: &[&str]) {
+        if markets.is_empty() {
+            return;
+        }
+        self.update(|desired| {
+            let Some(target) = desired.channels.get_mut(&channel) else {
+                return;
+            };
+            if target.markets.is_empty() {
+                return;
+            }
+            for market in markets {
+                target.markets.remove(*market);
+            }
+            if target.markets.is_empty() {
+                desired.channels.remove(&channel);
+            }
+        });
+    }
+
+    /// Remove a channel from the target, unsubscribing it.
+    pub fn remove_channel(&self, channel: Channel) {
+        self.update(|desired| {
+            desired.channels.remove(&channel);
+            desired.managed.insert(channel);
+        });
+    }
+
+    /// Remove every managed channel from the target.
+    pub fn clear(&self) {
+        self.update(|desired| desired.channels.clear());
+    }
+
+    /// Get the declared target as a map of channel to markets.
+    pub fn desired(&self) -> HashMap<Channel, Vec<String>> {
+        let desired = self.desired.lock().expect("desired state lock poisoned");
+        desired
+            .channels
+            .iter()
+            .map(|(channel, target)| (*channel, target.markets.iter().cloned().collect()))
+            .collect()
+    }
+
+    /// Reconcile immediately and wait for the result.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error only if the background task has stopped. Per-channel
+    /// failures are reported in [`SyncReport::failures`].
+    pub async fn sync(&self) -> Result<SyncReport> {
+        let (tx, rx) = oneshot::channel();
+        self.request(ManagerRequest::Sync(tx)).await?;
+        rx.await
+            .map_err(|_| Error::Disconnected(DisconnectReason::SessionDied))
+    }
+
+    /// Check against the server, repair any drift, and reconcile.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the background task has stopped or the server's
+    /// subscription list could not be fetched.
+    pub async fn verify(&self) -> Result<SyncReport> {
+        let (tx, rx) = oneshot::channel();
+        self.request(ManagerRequest::Verify(tx)).await?;
+        rx.await
+            .map_err(|_| Error::Disconnected(DisconnectReason::SessionDied))?
+    }
+
+    fn update(&self, apply: impl FnOnce(&mut DesiredState)) {
+        {
+            let mut desired = self.desired.lock().expect("desired state lock poisoned");
+            apply(&mut desired);
+        }
+        self.changed.notify_one();
+    }
+
+    async fn request(&self, request: ManagerRequest) -> Result<()> {
+        self.request_sender
+            .send(request)
+            .await
+            .map_err(|_| Error::Disconnected(DisconnectReason::SessionDied))
+    }
+}
+
+impl Drop for SubscriptionManager {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl std::fmt::Debug for SubscriptionManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SubscriptionManager")
+            .field("desired", &self.desired())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Background task that owns the handle and serializes all reconciliation.
+struct Worker {
+    handle: KalshiStreamHandle,
+    config: SubscriptionManagerConfig,
+    desired: SharedDesired,
+    changed: Arc<Notify>,
+    requests: mpsc::Receiver<ManagerRequest>,
+    /// Options each channel was last subscribed with by this manager.
+    applied: HashMap<Channel, SubscribeOptions>,
+    /// Unknown server sids seen on the previous verification.
+    suspected_orphans: HashSet<i64>,
+}
+
+impl Worker {
+    async fn run(mut self) {
+        let mut verify_timer = interval(self.config.verify_interval);
+        verify_timer.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        // The first tick completes immediately; nothing to verify yet.
+        verify_timer.tick().await;
+
+        loop {
+            tokio::select! {
+                _ = self.changed.notified() => {
+                    sleep(self.config.coalesce_window).await;
+                    let report = self.reconcile().await;
+                    log_report("reconcile", &report);
+                }
+                request = self.requests.recv() => match request {
+                    Some(ManagerRequest::Sync(response)) => {
+                        let _ = response.send(self.reconcile().await);
+                    }
+                    Some(ManagerRequest::Verify(response)) => {
+                        let _ = response.send(self.verify().await);
+                    }
+                    None => break,
+                },
+                _ = verify_timer.tick() => {
+                    if !self.handle.is_alive() {
+                        debug!("Stream session ended, stopping subscription manager");
+                        break;
+                    }
+                    match self.verify().await {
+                        Ok(report) => log_report("verify", &report),
+                        Err(e) => warn!("Subscription verification failed: {}", e),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Bring every managed channel in line with the desired state.
+    async fn reconcile(&mut self) -> SyncReport {
+        let (targets, mut channels) = {
+            let desired = self.desired.lock().expect("desired state lock poisoned");
+            let channels: Vec<Channel> = desired.managed.iter().copied().collect();
+            (desired.channels.clone(), channels)
+        };
+        channels.sort_by_key(|c| c.as_str());
+
+        let mut report = SyncReport::default();
+        for channel in channels {
+            if let Err(e) = self
+                .reconcile_channel(channel, targets.get(&channel), &mut report)
+                .await
+            {
+                report.failures.push((channel, e));
+            }
+        }
+        report
+    }
+
+    async fn reconcile_channel(
+        &mut self,
+        channel: Channel,
+        target: Option<&ChannelTarget>,
+        report: &mut SyncReport,
+    ) -> Result<()> {
+        // A ticker-required channel with no markets cannot be subscribed.
+        let target = target.filter(|t| !(t.markets.is_empty() && channel.requires_market_ticker()));
+        let current: Option<HashSet<String>> = self
+            .handle
+            .is_subscribed(channel)
+            .then(|| self.handle.markets(channel).into_iter().collect());
+
+        match (target, current) {
+            (None, None) => {}
+            (None, Some(_)) => {
+                self.handle.unsubscribe_all(channel).await?;
+                self.applied.remove(&channel);
+                report.unsubscribed.push(channel);
+            }
+            (Some(target), None) => {
+                self.subscribe(channel, target).await?;
+                report.subscribed.push(channel);
+            }
+            (Some(target), Some(current)) => {
+                // Switching between "all markets" and an explicit list, or
+                // changing options, needs a fresh subscription.
+                let options_changed = self
+                    .applied
+                    .get(&channel)
+                    .is_some_and(|applied| *applied != target.options);
+                let scope_changed = target.markets.is_empty() != current.is_empty();
+
+                if options_changed || scope_changed {
+                    self.handle.unsubscribe_all(channel).await?;
+                    self.applied.remove(&channel);
+                    self.subscribe(channel, target).await?;
+                    report.resubscribed.push(channel);
+                    return Ok(());
+                }
+
+                let mut added: Vec<&str> = target
+                    .markets
+                    .iter()
+                    .filter(|m| !current.contains(*m))
+                    .map(String::as_str)
+                    .collect();
+                let mut removed: Vec<&str> = current
+                    .iter()
+                    .filter(|m| !target.markets.contains(*m))
+                    .map(String::as_str)
+                    .collect();
+                added.sort_unstable();
+                removed.sort_unstable();
+
+                // Add before removing so the subscription never empties out.
+                if !added.is_empty() {
+                    self.handle.subscribe(channel, &added).await?;
+                    report.markets_added += added.len();
+                }
+                if !removed.is_empty() {
+                    self.handle.unsubscribe(channel, &removed).await?;
+                    report.markets_removed += removed.len();
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn subscribe(&mut self, channel: Channel, target: &ChannelTarget) -> Result<()> {
+        let mut markets: Vec<&str> = target.markets.iter().map(String::as_str).collect();
+        markets.sort_unstable();
+        self.handle
+            .subscribe_with_options(channel, &markets, target.options.clone())
+            .await?;
+        self.applied.insert(channel, target.options.clone());
+        Ok(())
+    }
+
+    /// Compare local state with the server, repair drift, then reconcile.
+    async fn verify(&mut self) -> Result<SyncReport> {
+        let remote: HashSet<i64> = self
+            .handle
+            .list_subscriptions_remote()
+            .await?
+            .into_iter()
+            .map(|s| s.sid)
+            .collect();
+
+        let mut report = SyncReport::default();
+
+        let mut managed: Vec<Channel> = {
+            let desired = self.desired.lock().expect("desired state lock poisoned");
+            desired.managed.iter().copied().collect()
+        };
+        managed.sort_by_key(|c| c.as_str());
+
+        for channel in managed {
+            let Some(sid) = self.handle.sid(channel) else {
+                continue;
+            };
+            if !remote.contains(&sid) && self.handle.forget_subscription(channel, sid) {
+                warn!(
+                    "Subscription {} (sid={}) missing on server, resubscribing",
+                    channel.as_str(),
+                    sid
+                );
+                self.applied.remove(&channel);
+                report.missing_on_server.push(channel);
+            }
+        }
+
+        let local = self.handle.local_sids();
+        let unknown: HashSet<i64> = remote.difference(&local).copied().collect();
+        let mut orphans: Vec<i64> = unknown
+            .intersection(&self.suspected_orphans)
+            .copied()
+            .collect();
+        orphans.sort_unstable();
+        self.suspected_orphans = unknown;
+
+        if !orphans.is_empty() {
+            warn!("Unsubscribing orphaned server subscriptions: {:?}", orphans);
+            match self.handle.unsubscribe_raw(&orphans).await {
+                Ok(_) => {
+                    for sid in &orphans {
+                        self.suspected_orphans.remove(sid);
+                    }
+                    report.orphaned_sids = orphans;
+                }
+                // Still suspected, so the next check retries.
+                Err(e) => warn!("Failed to unsubscribe orphaned subscriptions: {}", e),
+            }
+        }
+
+        report.merge(self.reconcile().await);
+        Ok(report)
+    }
+}
+
+fn log_report(pass: &str, report: &SyncReport) {
+    if report.has_changes() {
+        debug!("Subscription {} applied changes: {:?}", pass, report);
+    }
+    for (channel, error) in &report.failures {
+        warn!(
+            "Subscription {} failed for {}: {}",
+            pass,
+            channel.as_str(),
+            error
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value as JsonValue;
+
+    use super::*;
+    use crate::ws::{
+        KalshiStreamClient,
+        mock_server::{MockConnection, MockKalshiServer},
+    };
+
+    fn test_manager_config() -> SubscriptionManagerConfig {
+        SubscriptionManagerConfig {
+            coalesce_window: Duration::from_millis(20),
+            verify_interval: Duration::from_secs(3600),
+        }
+    }
+
+    async fn start() -> (
+        MockKalshiServer,
+        KalshiStreamClient,
+        MockConnection,
+        SubscriptionManager,
+    ) {
+        let mut server = MockKalshiServer::start().await;
+        let client = server.connect_client().await;
+        let conn = server.accept().await;
+        let manager = SubscriptionManager::with_config(client.handle(), test_manager_config());
+        (server, client, conn, manager)
+    }
+
+    fn tickers(command: &JsonValue) -> Vec<String> {
+        let params = &command["params"];
+        let mut markets: Vec<String> = match params["market_tickers"].as_array() {
+            Some(list) => list
+                .iter()
+                .filter_map(|m| m.as_str().map(String::from))
+                .collect(),
+            None => params["market_ticker"]
+                .as_str()
+                .map(|m| vec![m.to_string()])
+                .unwrap_or_default(),
+        };
+        markets.sort();
+        markets
+    }
+
+    #[tokio::test]
+    async fn test_rapid_changes_are_coalesced_into_one_subscribe() {
+        let (_server, client, mut conn, manager) = start().await;
+
+        manager.add_markets(Channel::Ticker, &["MARKET-A"]);
+        manager.add_markets(Channel::Ticker, &["MARKET-B"]);
+        manager.add_markets(Channel::Ticker, &["MARKET-C"]);
+        manager.remove_markets(Channel::Ticker, &["MARKET-B"]);
+
+        let (command, sids) = conn.expect_subscribe().await;
+        assert_eq!(tickers(&command), vec!["MARKET-A", "MARKET-C"]);
+
+        let report = manager.sync().await.unwrap();
+        assert!(report.is_ok());
+        assert!(!report.has_changes());
+        assert_eq!(client.handle().sid(Channel::Ticker), Some(sids[0]));
+    }
+
+    #[tokio::test]
+    async fn test_market_diff_sends_add_then_delete() {
+        let (_server, client, mut conn, manager) = start().await;
+
+        manager.set(Channel::OrderbookDelta, &["MARKET-A", "MARKET-B"]);
+        let (_, sids) = conn.expect_subscribe().await;
+        manager.sync().await.unwrap();
+
+        manager.set(Channel::OrderbookDelta, &["MARKET-B", "MARKET-C"]);
+        let sync = tokio::spawn(async move { manager.sync().await });
+
+        let add = conn.expect_command("update_subscription").await;
+        assert_eq!(add["params"]["action"], "add_markets");
+        assert_eq!(tickers(&add), vec!["MARKET-C"]);
+        conn.ack_update_subscription(&add, &["MARKET-A", "MARKET-B", "MARKET-C"])
+            .await;
+
+        let delete = conn.expect_command("update_subscription").await;
+        assert_eq!(delete["params"]["action"], "delete_markets");
+        assert_eq!(tickers(&delete), vec!["MARKET-A"]);
+        conn.ack_update_subscription(&delete, &["MARKET-B", "MARKET-C"])
+            .await;
+
+        let report = sync.await.unwrap().unwrap();
+        // The coalesced background pass may have applied the change first.
+        assert!(report.is_ok());
+
+        let handle = client.handle();
+        assert_eq!(handle.sid(Channel::OrderbookDelta), Some(sids[0]));
+        let mut markets = handle.markets(Channel::OrderbookDelta);
+        markets.sort();
+        assert_eq!(markets, vec!["MARKET-B", "MARKET-C"]);
+    }
+
+    #[tokio::test]
+    async fn test_remove_channel_unsubscribes_only_managed_channels() {
+        let (_server, client, mut conn, manager) = start().await;
+
+        // Subscribed directly on the handle, so not managed.
+        let mut handle = client.handle();
+        let direct = tokio::spawn(async move { handle.subscribe(Channel::Trade, &[]).await });
+        conn.expect_subscribe().await;
+        direct.await.unwrap().unwrap();
+
+        manager.set(Channel::Fill, &[]);
+        conn.expect_subscribe().await;
+        manager.sync().await.unwrap();
+
+        manager.remove_channel(Channel::Fill);
+        let sync = tokio::spawn(async move {
+            let report = manager.sync().await;
+            (manager, report)
+        });
+        let unsubscribe = conn.expect_command("unsubscribe").await;
+        conn.ack_unsubscribe(&unsubscribe).await;
+        let (manager, report) = sync.await.unwrap();
+        assert!(report.unwrap().is_ok());
+
+        let handle = client.handle();
+        assert!(!handle.is_subscribed(Channel::Fill));
+        assert!(handle.is_subscribed(Channel::Trade));
+        assert!(manager.desired().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_verify_resubscribes_missing_and_drops_orphans() {
+        let (_server, client, mut conn, manager) = start().await;
+
+        manager.set(Channel::Ticker, &["MARKET-A"]);
+        let (_, sids) = conn.expect_subscribe().await;
+        manager.sync().await.unwrap();
+        let old_sid = sids[0];
+
+        // First check: our sid is gone and an unknown sid 99 appears.
+        let manager = Arc::new(manager);
+        let verifier = manager.clone();
+        let verify = tokio::spawn(async move { verifier.verify().await });
+        let list = conn.expect_command("list_subscriptions").await;
+        conn.ack_list_subscriptions(&list, &[("trade", 99)]).await;
+        let (command, sids) = conn.expect_subscribe().await;
+        assert_eq!(tickers(&command), vec!["MARKET-A"]);
+
+        let report = verify.await.unwrap().unwrap();
+        assert_eq!(report.missing_on_server, vec![Channel::Ticker]);
+        assert_eq!(report.subscribed, vec![Channel::Ticker]);
+        assert!(report.orphaned_sids.is_empty());
+        assert_ne!(sids[0], old_sid);
+        assert_eq!(client.handle().sid(Channel::Ticker), Some(sids[0]));
+
+        // Second check: sid 99 is still unknown, so it is unsubscribed.
+        let new_sid = sids[0];
+        let verifier = manager.clone();
+        let verify = tokio::spawn(async move { verifier.verify().await });
+        let list = conn.expect_command("list_subscriptions").await;
+        conn.ack_list_subscriptions(&list, &[("ticker", new_sid), ("trade", 99)])
+            .await;
+        let unsubscribe = conn.expect_command("unsubscribe").await;
+        assert_eq!(unsubscribe["params"]["sids"], serde_json::json!([99]));
+        conn.ack_unsubscribe(&unsubscribe).await;
+
+        let report = verify.await.unwrap().unwrap();
+        assert_eq!(report.orphaned_sids, vec![99]);
+        assert!(report.missing_on_server.is_empty());
+        assert!(report.subscribed.is_empty());
+    }
+}
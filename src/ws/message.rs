@@ -198,6 +198,30 @@ pub enum StreamMessage {
     ///
     /// Confirms that a specific subscription ID (sid) has been unsubscribed.
     Unsubscribed,
+    /// Data message that could not be parsed into a known variant.
+    ///
+    /// Emitted instead of dropping the message when Kalshi sends a new message
+    /// type, or a known type whose payload no longer matches its struct.
+    #[serde(skip_deserializing)]
+    Unknown {
+        /// The message type as sent by the server.
+        channel: String,
+        /// The `msg` payload as received.
+        raw: serde_json::Value,
+    },
+}
+
+/// A WebSocket text frame as received, paired with the update it produced.
+///
+/// Delivered by [`KalshiStreamHandle::raw_frames`](super::KalshiStreamHandle::raw_frames).
+#[derive(Debug, Clone)]
+pub struct RawFrame {
+    /// The original JSON text of the frame.
+    pub text: String,
+    /// The update broadcast for this frame, if any.
+    ///
+    /// `None` for command responses, errors, and frames that are not valid JSON.
+    pub update: Option<StreamUpdate>,
 }
 
 /// Orderbook snapshot data containing the full orderbook state.
@@ -0,0 +1,360 @@
+//! In-process mock Kalshi WebSocket server for tests.
+//!
+//! [`MockKalshiServer`] listens on a local TCP port (plain `ws://`, no TLS) and
+//! hands each accepted connection to the test as a [`MockConnection`]. The test
+//! then scripts the server side of the conversation: it reads the commands the
+//! session sends, acknowledges them with sids, pushes snapshots and deltas with
+//! sequence numbers, injects errors, and closes or drops the connection.
+//!
+//! Message shapes follow the Kalshi wire format parsed in [`super::protocol`].
+//!
+//! # Example
+//!
+//! ```ignore
+//! let mut server = MockKalshiServer::start().await;
+//! let client = server.connect_client().await;
+//! let mut conn = server.accept().await;
+//!
+//! let mut handle = client.handle();
+//! let subscribe = tokio::spawn(async move {
+//!     handle.subscribe(Channel::Ticker, &["MARKET-A"]).await
+//! });
+//! conn.expect_subscribe().await;
+//! subscribe.await.unwrap().unwrap();
+//! ```
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{Value as JsonValue, json};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+    task::JoinHandle,
+    time::timeout,
+};
+use tokio_tungstenite::{
+    WebSocketStream,
+    tungstenite::{
+        Message,
+        protocol::{CloseFrame, frame::coding::CloseCode},
+    },
+};
+
+use super::{ConnectStrategy, HealthConfig, KalshiStreamClient};
+use crate::{
+    auth::{KalshiConfig, TEST_PRIVATE_KEY_PEM},
+    client::Environment,
+};
+
+/// How long the mock waits for the client before failing a test.
+const RECV_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Build a config signed with the crate's test key.
+pub(crate) fn test_config() -> KalshiConfig {
+    KalshiConfig::new(Environment::Demo, "mock-key-id", TEST_PRIVATE_KEY_PEM)
+        .expect("test key should parse")
+}
+
+/// A local WebSocket server speaking the Kalshi streaming protocol.
+pub(crate) struct MockKalshiServer {
+    addr: SocketAddr,
+    connections: mpsc::Receiver<MockConnection>,
+    accept_task: JoinHandle<()>,
+}
+
+impl MockKalshiServer {
+    /// Bind to an ephemeral local port and start accepting connections.
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind mock server");
+        let addr = listener.local_addr().expect("mock server address");
+        let (conn_tx, connections) = mpsc::channel(8);
+
+        let accept_task = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let Ok(ws) = tokio_tungstenite::accept_async(stream).await else {
+                    continue;
+                };
+                let conn = MockConnection { ws, next_sid: 1 };
+                if conn_tx.send(conn).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            addr,
+            connections,
+            accept_task,
+        }
+    }
+
+    /// The `ws://` URL clients should connect to.
+    pub fn url(&self) -> String {
+        format!("ws://{}", self.addr)
+    }
+
+    /// Connect a [`KalshiStreamClient`] to this server with default health settings.
+    pub async fn connect_client(&self) -> KalshiStreamClient {
+        KalshiStreamClient::connect_to_url(
+            &test_config(),
+            &self.url(),
+            ConnectStrategy::Simple,
+            HealthConfig::default(),
+            64,
+        )
+        .await
+        .expect("connect to mock server")
+    }
+
+    /// Wait for the next client connection.
+    pub async fn accept(&mut self) -> MockConnection {
+        timeout(RECV_TIMEOUT, self.connections.recv())
+            .await
+            .expect("timed out waiting for client connection")
+            .expect("mock server stopped accepting")
+    }
+}
+
+impl Drop for MockKalshiServer {
+    fn drop(&mut self) {
+        self.accept_task.abort();
+    }
+}
+
+/// The server side of one accepted client connection.
+pub(crate) struct MockConnection {
+    ws: WebSocketStream<TcpStream>,
+    next_sid: i64,
+}
+
+impl MockConnection {
+    /// Receive the next raw frame from the client.
+    ///
+    /// Returns `None` once the client has closed the connection.
+    pub async fn recv_message(&mut self) -> Option<Message> {
+        match timeout(RECV_TIMEOUT, self.ws.next()).await {
+            Ok(Some(Ok(msg))) => Some(msg),
+            Ok(Some(Err(_))) | Ok(None) => None,
+            Err(_) => panic!("timed out waiting for client message"),
+        }
+    }
+
+    /// Receive the next JSON command, skipping control frames.
+    pub async fn recv_command(&mut self) -> JsonValue {
+        loop {
+            match self.recv_message().await {
+                Some(Message::Text(text)) => {
+                    return serde_json::from_str(&text).expect("client sent invalid JSON");
+                }
+                Some(Message::Close(_)) | None => panic!("client closed while awaiting command"),
+                Some(_) => continue,
+            }
+        }
+    }
+
+    /// Receive a command and assert its `cmd` field.
+    pub async fn expect_command(&mut self, cmd: &str) -> JsonValue {
+        let command = self.recv_command().await;
+        assert_eq!(command["cmd"], cmd, "unexpected command: {}", command);
+        command
+    }
+
+    /// Receive a `subscribe` command and acknowledge every channel in it.
+    ///
+    /// Returns the command and the sids assigned, one per channel in order.
+    pub async fn expect_subscribe(&mut self) -> (JsonValue, Vec<i64>) {
+        let command = self.expect_command("subscribe").await;
+        let sids = self.ack_subscribe(&command).await;
+        (command, sids)
+    }
+
+    /// Acknowledge every channel in a `subscribe` command with a fresh sid.
+    pub async fn ack_subscribe(&mut self, command: &JsonValue) -> Vec<i64> {
+        let id = command["id"].as_u64().expect("subscribe id");
+        let channels: Vec<String> = command["params"]["channels"]
+            .as_array()
+            .expect("subscribe channels")
+            .iter()
+            .filter_map(|c| c.as_str().map(String::from))
+            .collect();
+
+        let mut sids = Vec::with_capacity(channels.len());
+        for channel in channels {
+            let sid = self.next_sid;
+            self.next_sid += 1;
+            self.send_json(json!({
+                "id": id,
+                "type": "subscribed",
+                "msg": { "channel": channel, "sid": sid }
+            }))
+            .await;
+            sids.push(sid);
+        }
+        sids
+    }
+
+    /// Acknowledge a single channel of a subscribe request with an explicit sid.
+    pub async fn send_subscribed(&mut self, id: u64, channel: &str, sid: i64) {
+        self.send_json(json!({
+            "id": id,
+            "type": "subscribed",
+            "msg": { "channel": channel, "sid": sid }
+        }))
+        .await;
+    }
+
+    /// Acknowledge every sid in an `unsubscribe` command.
+    pub async fn ack_unsubscribe(&mut self, command: &JsonValue) {
+        let id = command["id"].as_u64().expect("unsubscribe id");
+        let sids: Vec<i64> = command["params"]["sids"]
+            .as_array()
+            .expect("unsubscribe sids")
+            .iter()
+            .filter_map(|s| s.as_i64())
+            .collect();
+
+        for sid in sids {
+            self.send_json(json!({
+                "id": id,
+                "sid": sid,
+                "seq": 1,
+                "type": "unsubscribed"
+            }))
+            .await;
+        }
+    }
+
+    /// Reply `ok` to an `update_subscription` command with the resulting market list.
+    pub async fn ack_update_subscription(&mut self, command: &JsonValue, markets: &[&str]) {
+        let id = command["id"].as_u64().expect("update_subscription id");
+        let sid = command["params"]["sids"][0].as_i64().unwrap_or_default();
+        self.send_json(json!({
+            "id": id,
+            "sid": sid,
+            "type": "ok",
+            "msg": { "market_tickers": markets }
+        }))
+        .await;
+    }
+
+    /// Reply `ok` to a `list_subscriptions` command.
+    pub async fn ack_list_subscriptions(&mut self, command: &JsonValue, subs: &[(&str, i64)]) {
+        let id = command["id"].as_u64().expect("list_subscriptions id");
+        let entries: Vec<JsonValue> = subs
+            .iter()
+            .map(|(channel, sid)| json!({ "channel": channel, "sid": sid }))
+            .collect();
+        self.send_json(json!({ "id": id, "type": "ok", "msg": entries }))
+            .await;
+    }
+
+    /// Send a spec-format error response for request `id`.
+    pub async fn send_error(&mut self, id: u64, code: i64, message: &str) {
+        self.send_json(json!({
+            "id": id,
+            "type": "error",
+            "msg": { "code": code, "msg": message }
+        }))
+        .await;
+    }
+
+    /// Push a data message on a subscription.
+    pub async fn push(&mut self, msg_type: &str, sid: i64, seq: Option<i64>, msg: JsonValue) {
+        let mut frame = json!({ "type": msg_type, "sid": sid, "msg": msg });
+        if let Some(seq) = seq {
+            frame["seq"] = json!(seq);
+        }
+        self.send_json(frame).await;
+    }
+
+    /// Push an `orderbook_snapshot` with `(price_dollars, count_fp)` levels.
+    pub async fn push_orderbook_snapshot(
+        &mut self,
+        sid: i64,
+        seq: i64,
+        ticker: &str,
+        yes: &[(&str, &str)],
+        no: &[(&str, &str)],
+    ) {
+        self.push(
+            "orderbook_snapshot",
+            sid,
+            Some(seq),
+            json!({
+                "market_ticker": ticker,
+                "market_id": format!("{}-id", ticker),
+                "yes_dollars_fp": yes,
+                "no_dollars_fp": no,
+            }),
+        )
+        .await;
+    }
+
+    /// Push an `orderbook_delta` for one price level.
+    pub async fn push_orderbook_delta(
+        &mut self,
+        sid: i64,
+        seq: i64,
+        ticker: &str,
+        side: &str,
+        price_dollars: &str,
+        delta_fp: &str,
+    ) {
+        self.push(
+            "orderbook_delta",
+            sid,
+            Some(seq),
+            json!({
+                "market_ticker": ticker,
+                "market_id": format!("{}-id", ticker),
+                "price_dollars": price_dollars,
+                "delta_fp": delta_fp,
+                "side": side,
+            }),
+        )
+        .await;
+    }
+
+    /// Send a JSON value as a text frame.
+    pub async fn send_json(&mut self, value: JsonValue) {
+        self.send_text(&value.to_string()).await;
+    }
+
+    /// Send a raw text frame.
+    pub async fn send_text(&mut self, text: &str) {
+        self.ws
+            .send(Message::Text(text.to_string().into()))
+            .await
+            .expect("mock send");
+    }
+
+    /// Send a ping frame (Kalshi sends `heartbeat` pings on idle connections).
+    pub async fn send_ping(&mut self, data: &[u8]) {
+        self.ws
+            .send(Message::Ping(data.to_vec().into()))
+            .await
+            .expect("mock ping");
+    }
+
+    /// Close the connection cleanly with a close frame.
+    pub async fn close(mut self) {
+        let _ = self
+            .ws
+            .send(Message::Close(Some(CloseFrame {
+                code: CloseCode::Normal,
+                reason: "mock close".into(),
+            })))
+            .await;
+        // Drain until the client acknowledges the close.
+        while let Ok(Some(Ok(_))) = timeout(RECV_TIMEOUT, self.ws.next()).await {}
+    }
+
+    /// Drop the TCP connection without a closing handshake.
+    pub fn drop_connection(self) {
+        drop(self.ws);
+    }
+}
@@ -12,11 +12,13 @@ use std::{
     time::{Instant, SystemTime, UNIX_EPOCH},
 };
 
+use chrono::Utc;
 use futures_util::{
     SinkExt, StreamExt,
     stream::{SplitSink, SplitStream},
 };
 
+use serde_json::Value as JsonValue;
 use tokio::{
     net::TcpStream,
     sync::{broadcast, mpsc, oneshot},
@@ -35,8 +37,9 @@ use super::{
         ChannelError, ChannelSubscription, ServerSubscription, StreamCommand, SubscribeResult,
         UnsubscribeResult,
     },
-    message::{StreamMessage, StreamUpdate},
+    message::{RawFrame, StreamMessage, StreamUpdate},
     protocol::{self, IncomingMessage},
+    stats::SharedStats,
 };
 
 use crate::{
@@ -206,9 +209,13 @@ pub struct KalshiStreamSession {
     ready_sender: Option<oneshot::Sender<()>>,
     /// Sender for broadcasting updates to subscribers.
     update_sender: broadcast::Sender<StreamUpdate>,
+    /// Sender for the raw frame tap. Frames are only built while a receiver exists.
+    raw_sender: broadcast::Sender<RawFrame>,
     /// Shared subscription state with client handles.
     /// Used to capture and clear subscriptions on disconnect.
     subscriptions: SharedSubscriptions,
+    /// Throughput and latency statistics shared with client handles.
+    stats: SharedStats,
     /// WebSocket reader half.
     ws_reader: SplitStream<WsStream>,
     /// WebSocket writer half.
@@ -216,7 +223,7 @@ pub struct KalshiStreamSession {
 }
 
 impl KalshiStreamSession {
-    /// Connect to the Kalshi WebSocket API.
+    /// Connect to a Kalshi WebSocket endpoint.
     ///
     /// The `ready_sender` will be notified when the session enters its main loop,
     /// allowing the caller to confirm the session is running before returning.
@@ -224,43 +231,30 @@ impl KalshiStreamSession {
     /// # Arguments
     ///
     /// * `config` - The Kalshi configuration with API credentials.
+    /// * `ws_url` - WebSocket URL to connect to (normally `config.environment.ws_url()`).
     /// * `strategy` - Connection strategy (Simple or Retry).
+    /// * `health_config` - Health monitoring configuration.
     /// * `cmd_receiver` - Receiver for commands from client handles.
     /// * `update_sender` - Sender for broadcasting updates to subscribers.
+    /// * `raw_sender` - Sender for the raw frame tap.
     /// * `subscriptions` - Shared subscription state with client handles.
+    /// * `stats` - Statistics recorder shared with client handles.
     /// * `ready_sender` - One-shot sender to signal session readiness.
+    #[allow(clippy::too_many_arguments)]
     pub async fn connect(
         config: &KalshiConfig,
-        strategy: ConnectStrategy,
-        cmd_receiver: mpsc::Receiver<StreamCommand>,
-        update_sender: broadcast::Sender<StreamUpdate>,
-        subscriptions: SharedSubscriptions,
-        ready_sender: oneshot::Sender<()>,
-    ) -> Result<Self> {
-        Self::connect_full(
-            config,
-            strategy,
-            HealthConfig::default(),
-            cmd_receiver,
-            update_sender,
-            subscriptions,
-            Some(ready_sender),
-        )
-        .await
-    }
-
-    /// Full connect with all options.
-    async fn connect_full(
-        config: &KalshiConfig,
+        ws_url: &str,
         strategy: ConnectStrategy,
         health_config: HealthConfig,
         cmd_receiver: mpsc::Receiver<StreamCommand>,
         update_sender: broadcast::Sender<StreamUpdate>,
+        raw_sender: broadcast::Sender<RawFrame>,
         subscriptions: SharedSubscriptions,
-        ready_sender: Option<oneshot::Sender<()>>,
+        stats: SharedStats,
+        ready_sender: oneshot::Sender<()>,
     ) -> Result<Self> {
-        let ws_url = config.environment.ws_url();
-        let ws_stream = Self::connect_with_strategy(config, ws_url, strategy).await?;
+        let ws_stream = Self::connect_with_strategy(config, ws_url, strategy, &stats).await?;
+        stats.mark_connected();
 
         let (ws_writer, ws_reader) = ws_stream.split();
 
@@ -276,9 +270,11 @@ impl KalshiStreamSession {
             pending_updates: HashMap::new(),
             pending_list_subscriptions: HashMap::new(),
             ping_pending: false,
-            ready_sender,
+            ready_sender: Some(ready_sender),
             subscriptions,
+            stats,
             update_sender,
+            raw_sender,
             ws_reader,
             ws_writer,
         })
@@ -289,25 +285,36 @@ impl KalshiStreamSession {
         config: &KalshiConfig,
         ws_url: &str,
         strategy: ConnectStrategy,
+        stats: &SharedStats,
     ) -> Result<WsStream> {
         match strategy {
             ConnectStrategy::Simple => Self::connect_with_auth(config, ws_url).await,
-            ConnectStrategy::Retry => Self::connect_with_retry(config, ws_url).await,
+            ConnectStrategy::Retry => Self::connect_with_retry(config, ws_url, stats).await,
         }
     }
 
     /// Connect with exponential backoff retry.
-    async fn connect_with_retry(config: &KalshiConfig, ws_url: &str) -> Result<WsStream> {
+    async fn connect_with_retry(
+        config: &KalshiConfig,
+        ws_url: &str,
+        stats: &SharedStats,
+    ) -> Result<WsStream> {
         let mut attempt: u64 = 1;
 
         loop {
             info!("Connection attempt {} to {}", attempt, ws_url);
 
             match timeout(CONNECT_TIMEOUT, Self::connect_with_auth(config, ws_url)).await {
-                Ok(Ok(ws_stream)) => return Ok(ws_stream),
+                Ok(Ok(ws_stream)) => {
+                    if attempt > 1 {
+                        stats.record_reconnect();
+                    }
+                    return Ok(ws_stream);
+                }
                 Ok(Err(e)) => warn!("Connection failed: {}", e),
                 Err(_) => warn!("Connection timed out after {:?}", CONNECT_TIMEOUT),
             }
+            stats.record_connect_retry();
 
             let backoff = (BACKOFF_BASE * attempt as u32).min(MAX_BACKOFF);
             info!("Retrying in {:?}", backoff);
@@ -715,7 +722,13 @@ impl KalshiStreamSession {
 
         match message {
             Ok(Message::Text(text)) => {
-                self.handle_text_message(&text).await;
+                let update = self.handle_text_message(&text).await;
+                if self.raw_sender.receiver_count() > 0 {
+                    let _ = self.raw_sender.send(RawFrame {
+                        text: text.to_string(),
+                        update,
+                    });
+                }
                 Ok(false)
             }
 
@@ -776,8 +789,13 @@ impl KalshiStreamSession {
     }
 
     /// Handle an incoming text message from the WebSocket.
-    async fn handle_text_message(&mut self, text: &str) {
+    ///
+    /// Returns a copy of the update broadcast for this frame while the raw
+    /// frame tap has receivers, so it can be paired with the frame text.
+    async fn handle_text_message(&mut self, text: &str) -> Option<StreamUpdate> {
         debug!("Received message: {}", text);
+        let received_at = Utc::now();
+        self.stats.record_frame(text.len());
 
         match protocol::parse_incoming(text) {
             Ok(IncomingMessage::Response {
@@ -813,24 +831,20 @@ impl KalshiStreamSession {
                             collector.finish();
                         }
                     }
-                    return;
+                    return None;
                 }
 
                 if msg_type == "unsubscribed" {
                     // Use top-level sid/seq (per spec), fall back to msg.sid for compat
                     let sid = top_sid.or_else(|| msg.get("sid").and_then(|s| s.as_i64()));
-                    if let Some(sid) = sid {
-                        let update = StreamUpdate {
+                    let published = sid.and_then(|sid| {
+                        self.publish(StreamUpdate {
                             channel: msg_type.clone(),
                             sid,
                             seq: top_seq,
                             msg: StreamMessage::Unsubscribed,
-                        };
-
-                        if let Err(e) = self.update_sender.send(update) {
-                            debug!("No update receivers for unsubscribed event: {}", e);
-                        }
-                    }
+                        })
+                    });
 
                     // Check for pending unsubscribe collector
                     if let Some(collector) = self.pending_unsubscriptions.get_mut(&id) {
@@ -843,7 +857,7 @@ impl KalshiStreamSession {
                             }
                         }
                     }
-                    return;
+                    return published;
                 }
 
                 // Check if this is a response to a pending update_subscription
@@ -866,7 +880,7 @@ impl KalshiStreamSession {
                     );
 
                     let _ = response.send(Ok(markets));
-                    return;
+                    return None;
                 }
 
                 // Check if this is a response to a pending list_subscriptions
@@ -894,11 +908,12 @@ impl KalshiStreamSession {
                     );
 
                     let _ = response.send(Ok(subscriptions));
-                    return;
+                    return None;
                 }
 
                 // Unexpected response type with no handler
                 warn!("Unexpected response id {} type {}", id, msg_type);
+                None
             }
 
             Ok(IncomingMessage::Update {
@@ -919,37 +934,51 @@ impl KalshiStreamSession {
                 // Handle "unsubscribed" updates specially
                 if msg_type == "unsubscribed" {
                     // This is a confirmation of unsubscription for this sid
-                    let update = StreamUpdate {
+                    return self.publish(StreamUpdate {
                         channel: msg_type,
                         sid,
                         seq,
                         msg: StreamMessage::Unsubscribed,
-                    };
-
-                    if let Err(e) = self.update_sender.send(update) {
-                        debug!("No update receivers for unsubscribed event: {}", e);
-                    }
-                    return;
+                    });
                 }
 
                 // Parse the message using type-based routing
-                match StreamMessage::from_type_and_value(&msg_type, msg) {
+                let stream_msg = match StreamMessage::from_type_and_value(&msg_type, msg) {
                     Ok(stream_msg) => {
-                        let update = StreamUpdate {
-                            channel: msg_type,
-                            sid,
-                            seq,
-                            msg: stream_msg,
-                        };
-                        if let Err(e) = self.update_sender.send(update) {
-                            // No receivers - this is okay, they might subscribe later
-                            debug!("No update receivers: {}", e);
-                        }
+                        self.stats
+                            .record_message(&msg_type, text.len(), &stream_msg, received_at);
+                        stream_msg
                     }
                     Err(e) => {
-                        warn!("Failed to parse update: {}", e);
+                        warn!(
+                            "Failed to parse {} update: {} (payload: {})",
+                            msg_type,
+                            e,
+                            preview(text)
+                        );
+                        self.stats.record_parse_failure(Some(&msg_type));
+
+                        // The payload was consumed by the failed parse; recover
+                        // it from the frame text rather than cloning every message.
+                        let raw = serde_json::from_str::<JsonValue>(text)
+                            .ok()
+                            .and_then(|mut frame| frame.get_mut("msg").map(JsonValue::take))
+                            .unwrap_or_default();
+                        StreamMessage::Unknown {
+                            channel: msg_type.clone(),
+                            raw,
+                        }
                     }
-                }
+                };
+
+                let published = self.publish(StreamUpdate {
+                    channel: msg_type,
+                    sid,
+                    seq,
+                    msg: stream_msg,
+                });
+                self.stats.record_pending_updates(self.update_sender.len());
+                published
             }
 
             Ok(IncomingMessage::Error {
@@ -973,7 +1002,7 @@ impl KalshiStreamSession {
                                 collector.finish();
                             }
                         }
-                        return;
+                        return None;
                     }
 
                     // Check if this is an error for a pending unsubscribe
@@ -985,7 +1014,7 @@ impl KalshiStreamSession {
                         );
                         // Remove the collector and let the sender error out
                         self.pending_unsubscriptions.remove(&request_id);
-                        return;
+                        return None;
                     }
 
                     // Check if this is an error for a pending update_subscription
@@ -995,7 +1024,7 @@ impl KalshiStreamSession {
                             request_id, code, message
                         );
                         let _ = response.send(Err(format!("{}: {}", code, message)));
-                        return;
+                        return None;
                     }
 
                     warn!(
@@ -1003,13 +1032,43 @@ impl KalshiStreamSession {
                         request_id, code, message
                     );
                 }
+                None
             }
 
             Err(e) => {
-                error!("Failed to parse incoming message: {}", e);
+                error!(
+                    "Failed to parse incoming message: {} (payload: {})",
+                    e,
+                    preview(text)
+                );
+                self.stats.record_parse_failure(None);
+                None
             }
         }
     }
+
+    /// Broadcast an update to subscribers.
+    ///
+    /// Returns a copy of the update if the raw frame tap has receivers.
+    fn publish(&self, update: StreamUpdate) -> Option<StreamUpdate> {
+        let tapped = (self.raw_sender.receiver_count() > 0).then(|| update.clone());
+        if let Err(e) = self.update_sender.send(update) {
+            // No receivers - this is okay, they might subscribe later
+            debug!("No update receivers: {}", e);
+        }
+        tapped
+    }
+}
+
+/// Maximum number of characters of a payload included in parse error logs.
+const PAYLOAD_PREVIEW_CHARS: usize = 200;
+
+/// Truncate a frame for logging, respecting UTF-8 boundaries.
+fn preview(text: &str) -> &str {
+    match text.char_indices().nth(PAYLOAD_PREVIEW_CHARS) {
+        Some((idx, _)) => &text[..idx],
+        None => text,
+    }
 }
 
 impl std::fmt::Debug for KalshiStreamSession {
@@ -1029,7 +1088,17 @@ impl std::fmt::Debug for KalshiStreamSession {
 
 #[cfg(test)]
 mod tests {
-    use serde_json::Value as JsonValue;
+    use std::time::Duration;
+
+    use serde_json::json;
+    use tokio::task::JoinHandle;
+
+    use super::*;
+    use crate::ws::{
+        UpdateAction,
+        mock_server::{MockKalshiServer, test_config},
+        stats::StatsRecorder,
+    };
 
     /// Extract subscription IDs from a subscribe response.
     fn extract_sids(response: &JsonValue) -> Vec<i64> {
@@ -1108,4 +1177,414 @@ mod tests {
         let sids = extract_sids(&response);
         assert!(sids.is_empty());
     }
+
+    // ========== Mock Server Tests ==========
+
+    /// A session running against the mock server, driven through raw commands.
+    struct TestSession {
+        cmd_sender: mpsc::Sender<StreamCommand>,
+        updates: broadcast::Receiver<StreamUpdate>,
+        subscriptions: SharedSubscriptions,
+        task: JoinHandle<()>,
+    }
+
+    async fn start_session(server: &MockKalshiServer, health_config: HealthConfig) -> TestSession {
+        let (cmd_sender, cmd_receiver) = mpsc::channel(8);
+        let (update_sender, updates) = broadcast::channel(64);
+        let (raw_sender, _) = broadcast::channel(64);
+        let (ready_tx, ready_rx) = oneshot::channel();
+        let subscriptions: SharedSubscriptions = Arc::new(RwLock::new(HashMap::new()));
+        let stats: SharedStats = Arc::new(StatsRecorder::new());
+
+        let session = KalshiStreamSession::connect(
+            &test_config(),
+            &server.url(),
+            ConnectStrategy::Simple,
+            health_config,
+            cmd_receiver,
+            update_sender,
+            raw_sender,
+            subscriptions.clone(),
+            stats.clone(),
+            ready_tx,
+        )
+        .await
+        .expect("connect to mock server");
+        let task = tokio::spawn(session.run());
+        ready_rx.await.expect("session ready");
+
+        TestSession {
+            cmd_sender,
+            updates,
+            subscriptions,
+            task,
+        }
+    }
+
+    async fn next_update(updates: &mut broadcast::Receiver<StreamUpdate>) -> StreamUpdate {
+        tokio::time::timeout(Duration::from_secs(5), updates.recv())
+            .await
+            .expect("timed out waiting for update")
+            .expect("update channel closed")
+    }
+
+    fn subscribe_command(
+        channels: &[&str],
+        markets: &[&str],
+    ) -> (
+        StreamCommand,
+        oneshot::Receiver<std::result::Result<SubscribeResult, String>>,
+    ) {
+        let (tx, rx) = oneshot::channel();
+        let cmd = StreamCommand::Subscribe {
+            channels: channels.iter().map(|s| s.to_string()).collect(),
+            market_tickers: markets.iter().map(|s| s.to_string()).collect(),
+            sharding: None,
+            skip_ticker_ack: None,
+            send_initial_snapshot: None,
+            response: tx,
+        };
+        (cmd, rx)
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_collects_one_ack_per_channel() {
+        let mut server = MockKalshiServer::start().await;
+        let session = start_session(&server, HealthConfig::default()).await;
+        let mut conn = server.accept().await;
+
+        let (cmd, rx) = subscribe_command(&["ticker", "trade"], &["MARKET-A"]);
+        session.cmd_sender.send(cmd).await.unwrap();
+
+        let (command, sids) = conn.expect_subscribe().await;
+        assert_eq!(command["params"]["channels"], json!(["ticker", "trade"]));
+        assert_eq!(command["params"]["market_ticker"], "MARKET-A");
+
+        let result = rx.await.unwrap().unwrap();
+        assert!(result.is_complete_success());
+        assert_eq!(result.sids(), sids);
+        assert_eq!(result.successful[0].channel, "ticker");
+        assert_eq!(result.successful[1].channel, "trade");
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_collects_partial_failure() {
+        let mut server = MockKalshiServer::start().await;
+        let session = start_session(&server, HealthConfig::default()).await;
+        let mut conn = server.accept().await;
+
+        let (cmd, rx) = subscribe_command(&["ticker", "fill"], &[]);
+        session.cmd_sender.send(cmd).await.unwrap();
+
+        let command = conn.expect_command("subscribe").await;
+        let id = command["id"].as_u64().unwrap();
+        conn.send_subscribed(id, "ticker", 7).await;
+        conn.send_error(id, 9, "Authentication required").await;
+
+        let result = rx.await.unwrap().unwrap();
+        assert!(result.has_any_success());
+        assert!(!result.is_complete_success());
+        assert_eq!(result.sids(), vec![7]);
+        assert_eq!(result.failed[0].code, 9);
+        assert_eq!(result.failed[0].message, "Authentication required");
+    }
+
+    #[tokio::test]
+    async fn test_unsubscribe_collects_every_sid() {
+        let mut server = MockKalshiServer::start().await;
+        let mut session = start_session(&server, HealthConfig::default()).await;
+        let mut conn = server.accept().await;
+
+        let (tx, rx) = oneshot::channel();
+        session
+            .cmd_sender
+            .send(StreamCommand::Unsubscribe {
+                sids: vec![3, 4],
+                response: tx,
+            })
+            .await
+            .unwrap();
+
+        let command = conn.expect_command("unsubscribe").await;
+        assert_eq!(command["params"]["sids"], json!([3, 4]));
+        conn.ack_unsubscribe(&command).await;
+
+        let result = rx.await.unwrap().unwrap();
+        assert_eq!(result.sids(), &[3, 4]);
+
+        // Each confirmation is also broadcast to subscribers
+        for expected in [3, 4] {
+            let update = next_update(&mut session.updates).await;
+            assert!(matches!(update.msg, StreamMessage::Unsubscribed));
+            assert_eq!(update.sid, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_update_subscription_returns_server_markets() {
+        let mut server = MockKalshiServer::start().await;
+        let session = start_session(&server, HealthConfig::default()).await;
+        let mut conn = server.accept().await;
+
+        let (tx, rx) = oneshot::channel();
+        session
+            .cmd_sender
+            .send(StreamCommand::UpdateSubscription {
+                sid: 5,
+                markets: vec!["MARKET-B".to_string()],
+                action: UpdateAction::AddMarkets,
+                send_initial_snapshot: None,
+                response: tx,
+            })
+            .await
+            .unwrap();
+
+        let command = conn.expect_command("update_subscription").await;
+        assert_eq!(command["params"]["action"], "add_markets");
+        assert_eq!(command["params"]["sids"], json!([5]));
+        conn.ack_update_subscription(&command, &["MARKET-A", "MARKET-B"])
+            .await;
+
+        let markets = rx.await.unwrap().unwrap();
+        assert_eq!(markets, vec!["MARKET-A", "MARKET-B"]);
+    }
+
+    #[tokio::test]
+    async fn test_update_subscription_error_is_returned() {
+        let mut server = MockKalshiServer::start().await;
+        let session = start_session(&server, HealthConfig::default()).await;
+        let mut conn = server.accept().await;
+
+        let (tx, rx) = oneshot::channel();
+        session
+            .cmd_sender
+            .send(StreamCommand::UpdateSubscription {
+                sid: 5,
+                markets: vec!["UNKNOWN".to_string()],
+                action: UpdateAction::AddMarkets,
+                send_initial_snapshot: None,
+                response: tx,
+            })
+            .await
+            .unwrap();
+
+        let command = conn.expect_command("update_subscription").await;
+        conn.send_error(command["id"].as_u64().unwrap(), 16, "Market not found")
+            .await;
+
+        let err = rx.await.unwrap().unwrap_err();
+        assert_eq!(err, "16: Market not found");
+    }
+
+    #[tokio::test]
+    async fn test_list_subscriptions_parses_server_view() {
+        let mut server = MockKalshiServer::start().await;
+        let session = start_session(&server, HealthConfig::default()).await;
+        let mut conn = server.accept().await;
+
+        let (tx, rx) = oneshot::channel();
+        session
+            .cmd_sender
+            .send(StreamCommand::ListSubscriptions { response: tx })
+            .await
+            .unwrap();
+
+        let command = conn.expect_command("list_subscriptions").await;
+        conn.ack_list_subscriptions(&command, &[("ticker", 1), ("fill", 2)])
+            .await;
+
+        let subs = rx.await.unwrap().unwrap();
+        assert_eq!(subs.len(), 2);
+        assert_eq!(subs[0].channel, "ticker");
+        assert_eq!(subs[0].sid, 1);
+        assert_eq!(subs[1].channel, "fill");
+        assert_eq!(subs[1].sid, 2);
+    }
+
+    #[tokio::test]
+    async fn test_pushed_updates_are_broadcast_in_order() {
+        let mut server = MockKalshiServer::start().await;
+        let mut session = start_session(&server, HealthConfig::default()).await;
+        let mut conn = server.accept().await;
+
+        conn.push_orderbook_snapshot(
+            1,
+            1,
+            "MARKET-A",
+            &[("0.45", "100.00")],
+            &[("0.53", "50.00")],
+        )
+        .await;
+        conn.push_orderbook_delta(1, 2, "MARKET-A", "yes", "0.46", "25.00")
+            .await;
+        // Malformed payloads are passed through as Unknown without killing the session
+        conn.push("ticker", 2, Some(1), json!({ "bogus": true }))
+            .await;
+        conn.push_orderbook_delta(1, 3, "MARKET-A", "no", "0.53", "-50.00")
+            .await;
+
+        let snapshot = next_update(&mut session.updates).await;
+        assert_eq!(snapshot.seq, Some(1));
+        let StreamMessage::OrderbookSnapshot(data) = snapshot.msg else {
+            panic!("expected snapshot, got {:?}", snapshot.msg);
+        };
+        assert_eq!(data.market_ticker, "MARKET-A");
+
+        let delta = next_update(&mut session.updates).await;
+        assert_eq!(delta.seq, Some(2));
+        assert!(matches!(delta.msg, StreamMessage::OrderbookDelta(_)));
+
+        let unknown = next_update(&mut session.updates).await;
+        assert_eq!(unknown.channel, "ticker");
+        assert_eq!(unknown.sid, 2);
+        match unknown.msg {
+            StreamMessage::Unknown { channel, raw } => {
+                assert_eq!(channel, "ticker");
+                assert_eq!(raw, json!({ "bogus": true }));
+            }
+            other => panic!("expected Unknown, got {:?}", other),
+        }
+
+        let delta = next_update(&mut session.updates).await;
+        assert_eq!(delta.sid, 1);
+        assert_eq!(delta.seq, Some(3));
+        assert!(matches!(delta.msg, StreamMessage::OrderbookDelta(_)));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_message_type_is_passed_through() {
+        let mut server = MockKalshiServer::start().await;
+        let mut session = start_session(&server, HealthConfig::default()).await;
+        let mut conn = server.accept().await;
+
+        conn.push("brand_new_channel", 9, Some(1), json!({ "field": [1, 2] }))
+            .await;
+
+        let update = next_update(&mut session.updates).await;
+        assert_eq!(update.channel, "brand_new_channel");
+        match update.msg {
+            StreamMessage::Unknown { channel, raw } => {
+                assert_eq!(channel, "brand_new_channel");
+                assert_eq!(raw["field"], json!([1, 2]));
+            }
+            other => panic!("expected Unknown, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_preview_truncates_on_char_boundary() {
+        let long = "é".repeat(PAYLOAD_PREVIEW_CHARS + 10);
+        assert_eq!(preview(&long).chars().count(), PAYLOAD_PREVIEW_CHARS);
+        assert_eq!(preview("short"), "short");
+    }
+
+    #[tokio::test]
+    async fn test_replies_to_server_heartbeat_ping() {
+        let mut server = MockKalshiServer::start().await;
+        let _session = start_session(&server, HealthConfig::default()).await;
+        let mut conn = server.accept().await;
+
+        conn.send_ping(b"heartbeat").await;
+
+        match conn.recv_message().await {
+            Some(Message::Pong(data)) => assert_eq!(&data[..], b"heartbeat"),
+            other => panic!("expected pong, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ping_timeout_reports_connection_lost() {
+        let mut server = MockKalshiServer::start().await;
+        let health = HealthConfig {
+            ping_interval: Duration::from_millis(50),
+            ping_timeout: Duration::from_millis(60),
+            server_ping_timeout: Duration::from_secs(30),
+        };
+        let mut session = start_session(&server, health).await;
+        // Hold the connection but never read, so our pings are never answered
+        let _conn = server.accept().await;
+
+        let update = next_update(&mut session.updates).await;
+        match update.msg {
+            StreamMessage::ConnectionLost { reason, .. } => {
+                assert_eq!(reason, DisconnectReason::PingTimeout);
+            }
+            other => panic!("expected ConnectionLost, got {:?}", other),
+        }
+        session.task.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_server_close_frame_reports_closed() {
+        let mut server = MockKalshiServer::start().await;
+        let mut session = start_session(&server, HealthConfig::default()).await;
+        let conn = server.accept().await;
+
+        conn.close().await;
+
+        let update = next_update(&mut session.updates).await;
+        match update.msg {
+            StreamMessage::Closed { reason } => assert_eq!(reason, DisconnectReason::ServerClosed),
+            other => panic!("expected Closed, got {:?}", other),
+        }
+        session.task.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_dropped_connection_reports_lost_subscriptions() {
+        let mut server = MockKalshiServer::start().await;
+        let mut session = start_session(&server, HealthConfig::default()).await;
+        let conn = server.accept().await;
+
+        session.subscriptions.write().unwrap().insert(
+            Channel::Ticker,
+            SubscriptionState {
+                sid: 1,
+                markets: HashSet::from(["MARKET-A".to_string()]),
+            },
+        );
+
+        conn.drop_connection();
+
+        let update = next_update(&mut session.updates).await;
+        match update.msg {
+            StreamMessage::ConnectionLost {
+                reason,
+                subscriptions,
+            } => {
+                assert!(
+                    matches!(
+                        reason,
+                        DisconnectReason::ProtocolError(_) | DisconnectReason::IoError(_)
+                    ),
+                    "unexpected reason: {:?}",
+                    reason
+                );
+                assert_eq!(
+                    subscriptions,
+                    vec![(Channel::Ticker, vec!["MARKET-A".to_string()])]
+                );
+            }
+            other => panic!("expected ConnectionLost, got {:?}", other),
+        }
+        session.task.await.unwrap();
+        assert!(session.subscriptions.read().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_close_command_reports_client_closed() {
+        let mut server = MockKalshiServer::start().await;
+        let mut session = start_session(&server, HealthConfig::default()).await;
+        let mut conn = server.accept().await;
+
+        session.cmd_sender.send(StreamCommand::Close).await.unwrap();
+
+        let update = next_update(&mut session.updates).await;
+        match update.msg {
+            StreamMessage::Closed { reason } => assert_eq!(reason, DisconnectReason::ClientClosed),
+            other => panic!("expected Closed, got {:?}", other),
+        }
+        assert!(matches!(conn.recv_message().await, Some(Message::Close(_))));
+        session.task.await.unwrap();
+    }
 }
@@ -0,0 +1,491 @@
+//! Stream throughput and latency statistics.
+//!
+//! The session records every frame it reads into a [`StatsRecorder`] shared
+//! with client handles, which read it back as a [`StreamStats`] snapshot via
+//! [`KalshiStreamHandle::stats`](super::KalshiStreamHandle::stats).
+//!
+//! With the `metrics` feature enabled the same measurements are also emitted
+//! through the [`metrics`](https://docs.rs/metrics) facade:
+//!
+//! | Metric | Type | Labels |
+//! |--------|------|--------|
+//! | `kalshi_ws_messages_received_total` | counter | `channel` |
+//! | `kalshi_ws_bytes_received_total` | counter | `channel` |
+//! | `kalshi_ws_parse_failures_total` | counter | `channel` |
+//! | `kalshi_ws_exchange_latency_seconds` | histogram | `channel` |
+//! | `kalshi_ws_pending_updates` | gauge | |
+//! | `kalshi_ws_connect_retries_total` | counter | |
+//! | `kalshi_ws_reconnects_total` | counter | |
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+
+use super::message::StreamMessage;
+
+/// Upper bounds of the latency histogram buckets, in milliseconds.
+///
+/// Latencies above the last bound land in a final overflow bucket.
+const LATENCY_BUCKETS_MS: [u64; 13] = [1, 2, 5, 10, 20, 50, 100, 200, 500, 1000, 2000, 5000, 10000];
+
+/// Snapshot of stream statistics since the connection was established.
+///
+/// Counters are monotonic for the lifetime of a connection, so rates over an
+/// arbitrary window can be computed by diffing two snapshots.
+#[derive(Debug, Clone)]
+pub struct StreamStats {
+    /// Time since the connection was established.
+    pub uptime: Duration,
+    /// Data messages whose payload parsed; the rest count as `parse_failures`.
+    pub messages_received: u64,
+    /// Bytes of text frames received, including command responses.
+    pub bytes_received: u64,
+    /// Frames that could not be parsed.
+    ///
+    /// A data message whose payload does not match its channel is still
+    /// broadcast as [`StreamMessage::Unknown`]; only frames that are not a
+    /// valid message at all are dropped.
+    pub parse_failures: u64,
+    /// Failed connection attempts before the connection was established.
+    ///
+    /// Only non-zero with [`ConnectStrategy::Retry`](super::ConnectStrategy::Retry).
+    pub connect_retries: u64,
+    /// Times the connection was established after failed attempts.
+    ///
+    /// Only non-zero with [`ConnectStrategy::Retry`](super::ConnectStrategy::Retry).
+    pub reconnects: u64,
+    /// Updates broadcast but not yet received by the slowest handle.
+    ///
+    /// When this reaches the channel's buffer size, the slowest handle starts
+    /// missing messages.
+    pub pending_updates: usize,
+    /// Highest value of `pending_updates` seen so far.
+    pub max_pending_updates: usize,
+    /// Per-channel breakdown, keyed by channel name (e.g. `"ticker"`).
+    pub channels: HashMap<String, ChannelStats>,
+}
+
+/// Statistics for a single channel.
+#[derive(Debug, Clone, Default)]
+pub struct ChannelStats {
+    /// Messages received on this channel.
+    pub messages: u64,
+    /// Bytes received on this channel.
+    pub bytes: u64,
+    /// Messages on this channel that failed to parse.
+    pub parse_failures: u64,
+    /// Average messages per second since the connection was established.
+    pub messages_per_second: f64,
+    /// Exchange-to-receive latency for messages carrying an exchange timestamp.
+    pub latency: LatencyHistogram,
+}
+
+/// Histogram of exchange-to-receive latencies.
+///
+/// Latency is measured from the exchange timestamp in the message to the
+/// moment the session read the frame, so it includes clock skew between the
+/// exchange and this host. Negative latencies (local clock behind) are
+/// counted as zero.
+///
+/// Timestamps come from `ticker.time`, `orderbook_delta.ts` and
+/// `user_order.last_update_time`, which have sub-second precision, and from
+/// `trade.ts` and `fill.ts`, which are whole seconds and so overstate latency
+/// by up to one second.
+#[derive(Debug, Clone)]
+pub struct LatencyHistogram {
+    counts: [u64; LATENCY_BUCKETS_MS.len() + 1],
+    count: u64,
+    sum: Duration,
+    min: Option<Duration>,
+    max: Option<Duration>,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self {
+            counts: [0; LATENCY_BUCKETS_MS.len() + 1],
+            count: 0,
+            sum: Duration::ZERO,
+            min: None,
+            max: None,
+        }
+    }
+}
+
+impl LatencyHistogram {
+    /// Record one latency observation.
+    pub(crate) fn record(&mut self, latency: Duration) {
+        let ms = latency.as_millis() as u64;
+        let bucket = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|&bound| ms <= bound)
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+
+        self.counts[bucket] += 1;
+        self.count += 1;
+        self.sum += latency;
+        self.min = Some(self.min.map_or(latency, |m| m.min(latency)));
+        self.max = Some(self.max.map_or(latency, |m| m.max(latency)));
+    }
+
+    /// Number of observations.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Mean latency, or `None` if nothing has been recorded.
+    pub fn mean(&self) -> Option<Duration> {
+        (self.count > 0)
+            .then(|| Duration::from_secs_f64(self.sum.as_secs_f64() / self.count as f64))
+    }
+
+    /// Smallest latency observed.
+    pub fn min(&self) -> Option<Duration> {
+        self.min
+    }
+
+    /// Largest latency observed.
+    pub fn max(&self) -> Option<Duration> {
+        self.max
+    }
+
+    /// Approximate latency at quantile `q` (0.0 to 1.0).
+    ///
+    /// Returns the upper bound of the bucket containing the quantile, or the
+    /// observed maximum if it falls in the overflow bucket.
+    pub fn quantile(&self, q: f64) -> Option<Duration> {
+        if self.count == 0 {
+            return None;
+        }
+
+        let target = ((q.clamp(0.0, 1.0) * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (i, &n) in self.counts.iter().enumerate() {
+            seen += n;
+            if seen >= target {
+                return match LATENCY_BUCKETS_MS.get(i) {
+                    Some(&bound) => Some(Duration::from_millis(bound)),
+                    None => self.max,
+                };
+            }
+        }
+        self.max
+    }
+
+    /// Bucket upper bounds and their counts.
+    ///
+    /// The final entry has no upper bound and counts everything above the
+    /// largest bound.
+    pub fn buckets(&self) -> Vec<(Option<Duration>, u64)> {
+        LATENCY_BUCKETS_MS
+            .iter()
+            .map(|&ms| Some(Duration::from_millis(ms)))
+            .chain(std::iter::once(None))
+            .zip(self.counts.iter().copied())
+            .collect()
+    }
+}
+
+/// Statistics shared between the session (writer) and handles (readers).
+pub(crate) type SharedStats = Arc<StatsRecorder>;
+
+/// Mutable statistics state recorded by the session.
+#[derive(Debug)]
+pub(crate) struct StatsRecorder {
+    inner: Mutex<StatsState>,
+}
+
+#[derive(Debug)]
+struct StatsState {
+    connected_at: Instant,
+    messages_received: u64,
+    bytes_received: u64,
+    parse_failures: u64,
+    connect_retries: u64,
+    reconnects: u64,
+    pending_updates: usize,
+    max_pending_updates: usize,
+    channels: HashMap<String, ChannelStats>,
+}
+
+impl StatsRecorder {
+    pub fn new() -> Self {
+        Self {
+            inner: Mutex::new(StatsState {
+                connected_at: Instant::now(),
+                messages_received: 0,
+                bytes_received: 0,
+                parse_failures: 0,
+                connect_retries: 0,
+                reconnects: 0,
+                pending_updates: 0,
+                max_pending_updates: 0,
+                channels: HashMap::new(),
+            }),
+        }
+    }
+
+    /// Restart the uptime clock once the connection is established.
+    pub fn mark_connected(&self) {
+        self.lock().connected_at = Instant::now();
+    }
+
+    /// Record a failed connection attempt.
+    pub fn record_connect_retry(&self) {
+        self.lock().connect_retries += 1;
+
+        #[cfg(feature = "metrics")]
+        metrics::counter!("kalshi_ws_connect_retries_total").increment(1);
+    }
+
+    /// Record a connection established after failed attempts.
+    pub fn record_reconnect(&self) {
+        self.lock().reconnects += 1;
+
+        #[cfg(feature = "metrics")]
+        metrics::counter!("kalshi_ws_reconnects_total").increment(1);
+    }
+
+    /// Record a text frame of `bytes` length.
+    pub fn record_frame(&self, bytes: usize) {
+        self.lock().bytes_received += bytes as u64;
+    }
+
+    /// Record a parsed data message and its frame size.
+    pub fn record_message(
+        &self,
+        channel: &str,
+        bytes: usize,
+        msg: &StreamMessage,
+        received_at: DateTime<Utc>,
+    ) {
+        let latency =
+            exchange_time(msg).map(|sent| (received_at - sent).to_std().unwrap_or(Duration::ZERO));
+
+        {
+            let mut state = self.lock();
+            state.messages_received += 1;
+            let channel_stats = state.channels.entry(channel.to_string()).or_default();
+            channel_stats.messages += 1;
+            channel_stats.bytes += bytes as u64;
+            if let Some(latency) = latency {
+                channel_stats.latency.record(latency);
+            }
+        }
+
+        #[cfg(feature = "metrics")]
+        {
+            let channel = channel.to_string();
+            metrics::counter!("kalshi_ws_messages_received_total", "channel" => channel.clone())
+                .increment(1);
+            metrics::counter!("kalshi_ws_bytes_received_total", "channel" => channel.clone())
+                .increment(bytes as u64);
+            if let Some(latency) = latency {
+                metrics::histogram!("kalshi_ws_exchange_latency_seconds", "channel" => channel)
+                    .record(latency.as_secs_f64());
+            }
+        }
+    }
+
+    /// Record a frame that failed to parse, on `channel` if it could be identified.
+    pub fn record_parse_failure(&self, channel: Option<&str>) {
+        {
+            let mut state = self.lock();
+            state.parse_failures += 1;
+            if let Some(channel) = channel {
+                state
+                    .channels
+                    .entry(channel.to_string())
+                    .or_default()
+                    .parse_failures += 1;
+            }
+        }
+
+        #[cfg(feature = "metrics")]
+        metrics::counter!(
+            "kalshi_ws_parse_failures_total",
+            "channel" => channel.unwrap_or("unknown").to_string()
+        )
+        .increment(1);
+    }
+
+    /// Record the broadcast backlog after sending an update.
+    pub fn record_pending_updates(&self, pending: usize) {
+        {
+            let mut state = self.lock();
+            state.pending_updates = pending;
+            state.max_pending_updates = state.max_pending_updates.max(pending);
+        }
+
+        #[cfg(feature = "metrics")]
+        metrics::gauge!("kalshi_ws_pending_updates").set(pending as f64);
+    }
+
+    /// Take a snapshot of the current statistics.
+    pub fn snapshot(&self) -> StreamStats {
+        let state = self.lock();
+        let uptime = state.connected_at.elapsed();
+        let secs = uptime.as_secs_f64();
+
+        let channels = state
+            .channels
+            .iter()
+            .map(|(name, stats)| {
+                let mut stats = stats.clone();
+                if secs > 0.0 {
+                    stats.messages_per_second = stats.messages as f64 / secs;
+                }
+                (name.clone(), stats)
+            })
+            .collect();
+
+        StreamStats {
+            uptime,
+            messages_received: state.messages_received,
+            bytes_received: state.bytes_received,
+            parse_failures: state.parse_failures,
+            connect_retries: state.connect_retries,
+            reconnects: state.reconnects,
+            pending_updates: state.pending_updates,
+            max_pending_updates: state.max_pending_updates,
+            channels,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, StatsState> {
+        self.inner.lock().expect("stats lock poisoned")
+    }
+}
+
+/// Extract the exchange timestamp from a message, if it carries one.
+fn exchange_time(msg: &StreamMessage) -> Option<DateTime<Utc>> {
+    match msg {
+        StreamMessage::Ticker(data) => {
+            parse_rfc3339(&data.time).or_else(|| DateTime::from_timestamp(data.ts, 0))
+        }
+        StreamMessage::OrderbookDelta(data) => data.ts.as_deref().and_then(parse_rfc3339),
+        StreamMessage::UserOrder(data) => data.last_update_time.as_deref().and_then(parse_rfc3339),
+        StreamMessage::Trade(data) => DateTime::from_timestamp(data.ts, 0),
+        StreamMessage::Fill(data) => DateTime::from_timestamp(data.ts, 0),
+        _ => None,
+    }
+}
+
+fn parse_rfc3339(s: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(s)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_buckets_and_quantiles() {
+        let mut hist = LatencyHistogram::default();
+        assert_eq!(hist.mean(), None);
+        assert_eq!(hist.quantile(0.5), None);
+
+        for ms in [3, 4, 8, 40, 15_000] {
+            hist.record(Duration::from_millis(ms));
+        }
+
+        assert_eq!(hist.count(), 5);
+        assert_eq!(hist.min(), Some(Duration::from_millis(3)));
+        assert_eq!(hist.max(), Some(Duration::from_millis(15_000)));
+        assert_eq!(hist.mean(), Some(Duration::from_millis(3011)));
+
+        // 3ms and 4ms fall in the 5ms bucket
+        assert_eq!(hist.quantile(0.4), Some(Duration::from_millis(5)));
+        assert_eq!(hist.quantile(0.6), Some(Duration::from_millis(10)));
+        assert_eq!(hist.quantile(0.8), Some(Duration::from_millis(50)));
+        // Overflow bucket reports the observed maximum
+        assert_eq!(hist.quantile(1.0), Some(Duration::from_millis(15_000)));
+
+        let buckets = hist.buckets();
+        assert_eq!(buckets.len(), LATENCY_BUCKETS_MS.len() + 1);
+        assert_eq!(buckets[2], (Some(Duration::from_millis(5)), 2));
+        assert_eq!(buckets.last(), Some(&(None, 1)));
+    }
+
+    #[test]
+    fn test_histogram_mean_with_large_count() {
+        let mut hist = LatencyHistogram {
+            count: u64::from(u32::MAX) + 1,
+            sum: Duration::from_secs(u64::from(u32::MAX) + 1),
+            ..LatencyHistogram::default()
+        };
+        assert_eq!(hist.mean(), Some(Duration::from_secs(1)));
+
+        hist.record(Duration::from_secs(1));
+        assert_eq!(hist.mean(), Some(Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn test_exchange_time_prefers_precise_timestamp() {
+        let msg = StreamMessage::from_type_and_value(
+            "ticker",
+            serde_json::json!({
+                "market_ticker": "TEST",
+                "market_id": "id",
+                "price_dollars": "0.50",
+                "yes_bid_dollars": "0.49",
+                "yes_ask_dollars": "0.51",
+                "volume_fp": "0",
+                "open_interest_fp": "0",
+                "ts": 1700000000,
+                "time": "2023-11-14T22:13:20.250Z"
+            }),
+        )
+        .unwrap();
+
+        let time = exchange_time(&msg).unwrap();
+        assert_eq!(time.timestamp_millis(), 1_700_000_000_250);
+    }
+
+    #[test]
+    fn test_recorder_snapshot() {
+        let recorder = StatsRecorder::new();
+        let msg = StreamMessage::from_type_and_value(
+            "trade",
+            serde_json::json!({
+                "trade_id": "t1",
+                "market_ticker": "TEST",
+                "yes_price_dollars": "0.50",
+                "no_price_dollars": "0.50",
+                "count_fp": "1.00",
+                "taker_side": "yes",
+                "ts": 1700000000
+            }),
+        )
+        .unwrap();
+        let received_at = DateTime::from_timestamp(1_700_000_000, 100_000_000).unwrap();
+
+        recorder.record_frame(120);
+        recorder.record_message("trade", 120, &msg, received_at);
+        recorder.record_parse_failure(Some("ticker"));
+        recorder.record_parse_failure(None);
+        recorder.record_pending_updates(3);
+        recorder.record_pending_updates(1);
+        recorder.record_connect_retry();
+        recorder.record_reconnect();
+
+        let stats = recorder.snapshot();
+        assert_eq!(stats.messages_received, 1);
+        assert_eq!(stats.bytes_received, 120);
+        assert_eq!(stats.parse_failures, 2);
+        assert_eq!(stats.connect_retries, 1);
+        assert_eq!(stats.reconnects, 1);
+        assert_eq!(stats.pending_updates, 1);
+        assert_eq!(stats.max_pending_updates, 3);
+
+        let trade = &stats.channels["trade"];
+        assert_eq!(trade.messages, 1);
+        assert_eq!(trade.bytes, 120);
+        assert_eq!(trade.latency.max(), Some(Duration::from_millis(100)));
+        assert_eq!(stats.channels["ticker"].parse_failures, 1);
+    }
+}